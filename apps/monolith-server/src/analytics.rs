@@ -0,0 +1,297 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::roles::{has_any_role, ROLE_ADMIN};
+
+pub const DEFAULT_STATS_DAYS: i64 = 30;
+pub const MAX_STATS_DAYS: i64 = 365;
+
+/// Engagement events counted per product and day. An inquiry is a rental request; a booking
+/// is a request the owner confirmed. Views are deduplicated per viewer, so they are recorded
+/// with `record_view` rather than `record_event`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProductEvent {
+    View,
+    WishlistAdd,
+    Inquiry,
+    Booking,
+}
+
+impl ProductEvent {
+    /// Per-counter increments, in `EngagementCounts` field order.
+    pub fn increments(self) -> (i64, i64, i64, i64) {
+        match self {
+            ProductEvent::View => (1, 0, 0, 0),
+            ProductEvent::WishlistAdd => (0, 1, 0, 0),
+            ProductEvent::Inquiry => (0, 0, 1, 0),
+            ProductEvent::Booking => (0, 0, 0, 1),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EngagementCounts {
+    pub views: i64,
+    pub wishlist_adds: i64,
+    pub inquiries: i64,
+    pub bookings: i64,
+}
+
+impl EngagementCounts {
+    pub fn add(&mut self, other: &EngagementCounts) {
+        self.views += other.views;
+        self.wishlist_adds += other.wishlist_adds;
+        self.inquiries += other.inquiries;
+        self.bookings += other.bookings;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyStats {
+    pub day: NaiveDate,
+    #[serde(flatten)]
+    pub counts: EngagementCounts,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct FunnelConversion {
+    pub view_to_wishlist: f64,
+    pub view_to_inquiry: f64,
+    pub inquiry_to_booking: f64,
+    pub view_to_booking: f64,
+}
+
+impl FunnelConversion {
+    pub fn from_counts(counts: &EngagementCounts) -> Self {
+        FunnelConversion {
+            view_to_wishlist: conversion_rate(counts.wishlist_adds, counts.views),
+            view_to_inquiry: conversion_rate(counts.inquiries, counts.views),
+            inquiry_to_booking: conversion_rate(counts.bookings, counts.inquiries),
+            view_to_booking: conversion_rate(counts.bookings, counts.views),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProductStatsResponse {
+    pub product_id: Uuid,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub totals: EngagementCounts,
+    pub funnel: FunnelConversion,
+    pub daily: Vec<DailyStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatsQuery {
+    pub days: Option<i64>,
+}
+
+/// Share of `from` that converted into `to`, rounded to four decimals. Zero when nothing
+/// entered the step.
+pub fn conversion_rate(to: i64, from: i64) -> f64 {
+    if from <= 0 {
+        return 0.0;
+    }
+    (to as f64 / from as f64 * 10000.0).round() / 10000.0
+}
+
+/// One entry per day from `from` to `to` inclusive, with zeroes for days without events.
+pub fn fill_daily_series(from: NaiveDate, to: NaiveDate, rows: &[DailyStats]) -> Vec<DailyStats> {
+    let mut series = Vec::new();
+    let mut day = from;
+    while day <= to {
+        let counts = rows
+            .iter()
+            .find(|r| r.day == day)
+            .map(|r| r.counts)
+            .unwrap_or_default();
+        series.push(DailyStats { day, counts });
+        day += Duration::days(1);
+    }
+    series
+}
+
+/// Adds one event to today's rollup row for the product. Days are UTC, whatever the
+/// database session's time zone.
+pub async fn record_event(db: &PgPool, product_id: Uuid, event: ProductEvent) -> Result<(), sqlx::Error> {
+    let (views, wishlist_adds, inquiries, bookings) = event.increments();
+    sqlx::query!(
+        r#"
+        INSERT INTO product_schema.product_daily_stats (product_id, day, views, wishlist_adds, inquiries, bookings)
+        VALUES ($1, $6, $2, $3, $4, $5)
+        ON CONFLICT (product_id, day) DO UPDATE SET
+            views = product_daily_stats.views + EXCLUDED.views,
+            wishlist_adds = product_daily_stats.wishlist_adds + EXCLUDED.wishlist_adds,
+            inquiries = product_daily_stats.inquiries + EXCLUDED.inquiries,
+            bookings = product_daily_stats.bookings + EXCLUDED.bookings
+        "#,
+        product_id,
+        views,
+        wishlist_adds,
+        inquiries,
+        bookings,
+        Utc::now().date_naive()
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Counts a view at most once per viewer, product and UTC day. Owners looking at their own
+/// listing are skipped. Anonymous views are not counted, as they cannot be deduplicated.
+pub async fn record_view(db: &PgPool, product_id: Uuid, viewer_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        WITH fresh AS (
+            INSERT INTO product_schema.product_view_log (product_id, viewer_id, day)
+            SELECT product_id, $2, $3 FROM product_schema.products
+            WHERE product_id = $1 AND owner_id <> $2
+            ON CONFLICT DO NOTHING
+            RETURNING product_id
+        )
+        INSERT INTO product_schema.product_daily_stats (product_id, day, views)
+        SELECT product_id, $3, 1 FROM fresh
+        ON CONFLICT (product_id, day) DO UPDATE SET views = product_daily_stats.views + 1
+        "#,
+        product_id,
+        viewer_id,
+        Utc::now().date_naive()
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Records the event off the request path; analytics must never fail or slow a user action.
+pub fn spawn_record_event(db: &PgPool, product_id: Uuid, event: ProductEvent) {
+    let db = db.clone();
+    tokio::spawn(async move {
+        let _ = record_event(&db, product_id, event).await;
+    });
+}
+
+pub fn spawn_record_view(db: &PgPool, product_id: Uuid, viewer_id: Uuid) {
+    let db = db.clone();
+    tokio::spawn(async move {
+        let _ = record_view(&db, product_id, viewer_id).await;
+    });
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+pub async fn get_product_stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(product_id): Path<Uuid>,
+    Query(query): Query<StatsQuery>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let product = sqlx::query!(
+        "SELECT owner_id FROM product_schema.products WHERE product_id = $1",
+        product_id
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    let product = match product {
+        Ok(Some(p)) => p,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Product not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch product stats"),
+    };
+
+    if product.owner_id != user_id {
+        match has_any_role(&state.db, user_id, &[ROLE_ADMIN]).await {
+            Ok(true) => {}
+            Ok(false) => return err(StatusCode::FORBIDDEN, "Not authorized to view stats for this product"),
+            Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch product stats"),
+        }
+    }
+
+    let days = query.days.unwrap_or(DEFAULT_STATS_DAYS).clamp(1, MAX_STATS_DAYS);
+    let to = Utc::now().date_naive();
+    let from = to - Duration::days(days - 1);
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT day, views, wishlist_adds, inquiries, bookings
+        FROM product_schema.product_daily_stats
+        WHERE product_id = $1 AND day BETWEEN $2 AND $3
+        ORDER BY day
+        "#,
+        product_id,
+        from,
+        to
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    let rows: Vec<DailyStats> = match rows {
+        Ok(rows) => rows
+            .into_iter()
+            .map(|r| DailyStats {
+                day: r.day,
+                counts: EngagementCounts {
+                    views: r.views,
+                    wishlist_adds: r.wishlist_adds,
+                    inquiries: r.inquiries,
+                    bookings: r.bookings,
+                },
+            })
+            .collect(),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch product stats"),
+    };
+
+    let daily = fill_daily_series(from, to, &rows);
+    let mut totals = EngagementCounts::default();
+    for d in &daily {
+        totals.add(&d.counts);
+    }
+
+    ok(ProductStatsResponse {
+        product_id,
+        from,
+        to,
+        funnel: FunnelConversion::from_counts(&totals),
+        totals,
+        daily,
+    })
+}
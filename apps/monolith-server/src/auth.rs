@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::state::{ok, err, AppState};
+use crate::etag::{check_if_match, with_etag, PreconditionError};
 use crate::jwt::{create_access_token, create_refresh_token, verify_token, hash_password, verify_password};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -263,7 +264,7 @@ pub async fn get_profile(
     // Get user and profile
     let user_data = sqlx::query!(
         r#"
-        SELECT u.email, up.first_name, up.last_name, up.avg_rating, up.total_reviews
+        SELECT u.email, up.first_name, up.last_name, up.avg_rating, up.total_reviews, up.version
         FROM user_schema.users u
         JOIN user_schema.user_profiles up ON u.user_id = up.user_id
         WHERE u.user_id = $1
@@ -287,7 +288,7 @@ pub async fn get_profile(
         "total_reviews": user_data.total_reviews
     });
 
-    ok(response)
+    with_etag(ok(response), user_data.version)
 }
 
 pub async fn update_profile(
@@ -318,6 +319,23 @@ pub async fn update_profile(
         Err(_) => return err(StatusCode::UNAUTHORIZED, "Invalid user ID in token"),
     };
 
+    let current = sqlx::query!(
+        "SELECT version FROM user_schema.user_profiles WHERE user_id = $1",
+        user_id
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    let current_version = match current {
+        Ok(Some(p)) => p.version,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "User not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update profile"),
+    };
+
+    if let Err(e) = check_if_match(&headers, current_version) {
+        return e.into_response();
+    }
+
     // Update profile
     let result = sqlx::query!(
         r#"
@@ -326,18 +344,22 @@ pub async fn update_profile(
             first_name = COALESCE($2, first_name),
             last_name = COALESCE($3, last_name),
             updated_at = NOW()
-        WHERE user_id = $1
+        WHERE user_id = $1 AND version = $4
+        RETURNING version
         "#,
         user_id,
         req.first_name,
-        req.last_name
+        req.last_name,
+        current_version
     )
-    .execute(&state.db)
+    .fetch_optional(&state.db)
     .await;
 
-    if result.is_err() {
-        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update profile");
-    }
+    let version = match result {
+        Ok(Some(p)) => p.version,
+        Ok(None) => return PreconditionError::Stale.into_response(),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update profile"),
+    };
 
-    ok(serde_json::json!({ "message": "Profile updated successfully" }))
+    with_etag(ok(serde_json::json!({ "message": "Profile updated successfully" })), version)
 }
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+#[derive(Debug, thiserror::Error)]
+pub enum BlobError {
+    #[error("Blob not found")]
+    NotFound,
+    #[error("Invalid blob key '{0}'")]
+    InvalidKey(String),
+    #[error("Blob store I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Storage for uploaded files, addressed by slash-separated keys chosen by the server.
+/// Implementations are synchronous; call them from `spawn_blocking` in request handlers.
+pub trait BlobStore: Send + Sync {
+    fn put(&self, key: &str, bytes: &[u8]) -> Result<(), BlobError>;
+    fn get(&self, key: &str) -> Result<Vec<u8>, BlobError>;
+    fn delete(&self, key: &str) -> Result<(), BlobError>;
+}
+
+/// Keys are relative paths of `[A-Za-z0-9._-]` segments, so they can never escape the store.
+pub fn validate_key(key: &str) -> Result<(), BlobError> {
+    let valid = !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(BlobError::InvalidKey(key.to_string()))
+    }
+}
+
+/// Files under a local directory.
+pub struct FileBlobStore {
+    root: PathBuf,
+}
+
+impl FileBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FileBlobStore { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, BlobError> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+impl BlobStore for FileBlobStore {
+    fn put(&self, key: &str, bytes: &[u8]) -> Result<(), BlobError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>, BlobError> {
+        match std::fs::read(self.path(key)?) {
+            Ok(bytes) => Ok(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(BlobError::NotFound),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn delete(&self, key: &str) -> Result<(), BlobError> {
+        match std::fs::remove_file(self.path(key)?) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Process-local store for tests.
+#[derive(Default)]
+pub struct MemoryBlobStore {
+    blobs: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl BlobStore for MemoryBlobStore {
+    fn put(&self, key: &str, bytes: &[u8]) -> Result<(), BlobError> {
+        validate_key(key)?;
+        self.blobs.lock().unwrap().insert(key.to_string(), bytes.to_vec());
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>, BlobError> {
+        self.blobs.lock().unwrap().get(key).cloned().ok_or(BlobError::NotFound)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), BlobError> {
+        self.blobs.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// Shared store for the server: files under `BLOB_STORE_DIR`, `./data/blobs` by default.
+pub fn default_blob_store() -> &'static dyn BlobStore {
+    static STORE: OnceLock<FileBlobStore> = OnceLock::new();
+    STORE.get_or_init(|| {
+        FileBlobStore::new(std::env::var("BLOB_STORE_DIR").unwrap_or_else(|_| "data/blobs".into()))
+    })
+}
@@ -0,0 +1,1147 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, OnceLock};
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::inventory::OVERDUE_HOLD_HOURS;
+
+pub const CALENDAR_IMPORT_JOB_INTERVAL_SECS: u64 = 60;
+/// How often each imported calendar is fetched again.
+pub const CALENDAR_SYNC_INTERVAL_MINUTES: i32 = 15;
+pub const MAX_CALENDAR_BYTES: usize = 2 * 1024 * 1024;
+pub const MAX_IMPORTED_EVENTS: usize = 2000;
+pub const MAX_IMPORTS_PER_PRODUCT: i64 = 10;
+pub const MAX_IMPORT_URL_LEN: usize = 2000;
+pub const MAX_IMPORT_LABEL_LEN: usize = 100;
+/// Where the feed handler is mounted; feed URLs are this plus `/<token>.ics`.
+pub const FEED_PATH_PREFIX: &str = "/api/v1/product/calendar";
+
+const PRODID: &str = "-//OneSociety//Rental Calendar//EN";
+const UID_DOMAIN: &str = "onesociety";
+const FETCH_TIMEOUT_SECS: u64 = 20;
+const IMPORT_BATCH_SIZE: i64 = 50;
+/// Past bookings older than this are left out of feeds.
+const FEED_HISTORY_DAYS: i64 = 90;
+const MAX_SUMMARY_LEN: usize = 255;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CalendarError {
+    #[error("{0}")]
+    InvalidUrl(String),
+    #[error("Calendar could not be fetched: {0}")]
+    Fetch(String),
+    #[error("Calendar is larger than {} bytes", MAX_CALENDAR_BYTES)]
+    TooLarge,
+    #[error("Calendar has more than {} events", MAX_IMPORTED_EVENTS)]
+    TooManyEvents,
+    #[error("Not a valid iCalendar file: {0}")]
+    Parse(String),
+    #[error("Failed to save calendar")]
+    Database(#[from] sqlx::Error),
+}
+
+/// A booked or blocked period published in a feed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub summary: String,
+}
+
+/// A busy period read from an external calendar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedEvent {
+    pub uid: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub summary: Option<String>,
+}
+
+fn escape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(escaped) => out.push(escaped),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Splits a content line into lines of at most 75 octets, continuations starting with a space.
+fn fold_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + line.len() / 70 * 3);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out
+}
+
+fn format_utc(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Renders an RFC 5545 calendar. Every event is opaque, so subscribers treat it as busy time.
+pub fn render_calendar(name: &str, events: &[CalendarEvent], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(name)),
+    ];
+    for event in events {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", escape_text(&event.uid)),
+            format!("DTSTAMP:{}", format_utc(now)),
+            format!("DTSTART:{}", format_utc(event.start)),
+            format!("DTEND:{}", format_utc(event.end)),
+            format!("SUMMARY:{}", escape_text(&event.summary)),
+            "TRANSP:OPAQUE".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = lines.iter().map(|l| fold_line(l)).collect::<Vec<_>>().join("\r\n");
+    out.push_str("\r\n");
+    out
+}
+
+/// Joins folded lines back together; accepts bare `\n` line endings as well as CRLF.
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in ics.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        if raw.starts_with(' ') || raw.starts_with('\t') {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&raw[1..]);
+                continue;
+            }
+        }
+        if !raw.is_empty() {
+            lines.push(raw.to_string());
+        }
+    }
+    lines
+}
+
+struct Property<'a> {
+    name: String,
+    params: Vec<(String, String)>,
+    value: &'a str,
+}
+
+impl Property<'_> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+}
+
+/// `NAME;PARAM=value;PARAM="quoted:value":VALUE`
+fn parse_property(line: &str) -> Option<Property<'_>> {
+    let mut in_quotes = false;
+    let split = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+
+    let mut head = line[..split].split(';');
+    let name = head.next()?.trim().to_ascii_uppercase();
+    let params = head
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.trim().to_ascii_uppercase(), v.trim_matches('"').to_string()))
+        .collect();
+    Some(Property { name, params, value: &line[split + 1..] })
+}
+
+/// A DTSTART/DTEND value and whether it is a whole day. Times with a `TZID=` are converted from
+/// that IANA zone; floating times and zones we do not know are read as UTC.
+fn parse_time(prop: &Property) -> Option<(DateTime<Utc>, bool)> {
+    let value = prop.value.trim();
+    if prop.param("VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE")) || value.len() == 8 {
+        let day = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0)?), true));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let at = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Utc.from_utc_datetime(&at), false));
+    }
+
+    let at = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let zone = prop.param("TZID").and_then(|tz| tz.trim_start_matches('/').parse::<Tz>().ok());
+    match zone {
+        // A time skipped by a DST change is read as the moment after the gap
+        Some(tz) => {
+            let local = tz
+                .from_local_datetime(&at)
+                .earliest()
+                .or_else(|| tz.from_local_datetime(&(at + Duration::hours(1))).earliest())?;
+            Some((local.with_timezone(&Utc), false))
+        }
+        None => Some((Utc.from_utc_datetime(&at), false)),
+    }
+}
+
+#[derive(Default)]
+struct EventFields {
+    uid: Option<String>,
+    start: Option<(DateTime<Utc>, bool)>,
+    end: Option<DateTime<Utc>>,
+    summary: Option<String>,
+    cancelled: bool,
+}
+
+impl EventFields {
+    fn set(&mut self, prop: &Property) {
+        match prop.name.as_str() {
+            "UID" => self.uid = Some(unescape_text(prop.value)),
+            "DTSTART" => self.start = parse_time(prop),
+            "DTEND" => self.end = parse_time(prop).map(|(at, _)| at),
+            "SUMMARY" => self.summary = Some(unescape_text(prop.value)),
+            "STATUS" => self.cancelled = prop.value.trim().eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+
+    /// All-day events without an end last one day; other events need one.
+    fn finish(self) -> Option<ImportedEvent> {
+        let (start, all_day) = self.start?;
+        let end = self.end.or_else(|| all_day.then(|| start + Duration::days(1)))?;
+        if self.cancelled || end <= start {
+            return None;
+        }
+        Some(ImportedEvent { uid: self.uid, start, end, summary: self.summary })
+    }
+}
+
+/// Busy periods from an iCalendar file. Cancelled events, events without a usable start or end
+/// and everything outside VEVENTs (alarms, time zone definitions) are skipped.
+pub fn parse_calendar(ics: &str) -> Result<Vec<ImportedEvent>, CalendarError> {
+    let lines = unfold(ics.trim_start_matches('\u{feff}'));
+    if !lines.first().is_some_and(|l| l.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err(CalendarError::Parse("missing BEGIN:VCALENDAR".to_string()));
+    }
+
+    let mut events = Vec::new();
+    let mut components: Vec<String> = Vec::new();
+    let mut current: Option<EventFields> = None;
+
+    for line in &lines {
+        let Some(prop) = parse_property(line) else { continue };
+        match prop.name.as_str() {
+            "BEGIN" => {
+                let component = prop.value.trim().to_ascii_uppercase();
+                if component == "VEVENT" {
+                    current = Some(EventFields::default());
+                }
+                components.push(component);
+            }
+            "END" => {
+                if components.pop().as_deref() != Some("VEVENT") {
+                    continue;
+                }
+                if let Some(event) = current.take().and_then(EventFields::finish) {
+                    if events.len() == MAX_IMPORTED_EVENTS {
+                        return Err(CalendarError::TooManyEvents);
+                    }
+                    events.push(event);
+                }
+            }
+            _ if components.last().map(String::as_str) == Some("VEVENT") => {
+                if let Some(fields) = current.as_mut() {
+                    fields.set(&prop);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(events)
+}
+
+/// Checks an owner-supplied calendar URL and rewrites `webcal://` to `https://`. Only public
+/// http(s) hosts are accepted so imports cannot be pointed at internal services.
+pub fn normalize_import_url(url: &str) -> Result<String, CalendarError> {
+    let invalid = |message: &str| CalendarError::InvalidUrl(message.to_string());
+
+    let url = url.trim();
+    if url.is_empty() || url.len() > MAX_IMPORT_URL_LEN {
+        return Err(invalid(&format!("Calendar URL must be between 1 and {} characters", MAX_IMPORT_URL_LEN)));
+    }
+    let url = match url.get(..9) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("webcal://") => format!("https://{}", &url[9..]),
+        _ => url.to_string(),
+    };
+
+    let parsed = reqwest::Url::parse(&url).map_err(|_| invalid("Calendar URL is not a valid URL"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(invalid("Calendar URL must use http, https or webcal"));
+    }
+
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| invalid("Calendar URL has no host"))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_ascii_lowercase();
+    let internal = match host.parse::<IpAddr>() {
+        Ok(ip) => !is_public_ip(ip),
+        Err(_) => {
+            host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local") || host.ends_with(".internal")
+        }
+    };
+    if internal {
+        return Err(invalid("Calendar URL must point to a public host"));
+    }
+
+    Ok(parsed.to_string())
+}
+
+/// Whether an address is on the public internet. IPv4 addresses mapped into IPv6 are judged by
+/// the IPv4 address, and carrier-grade NAT space (100.64.0.0/10) counts as internal.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || a == 0
+                || (a == 100 && b & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public_ip(IpAddr::V4(v4)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Resolves calendar hosts for the import client and refuses names with any internal address.
+/// The check runs when the connection is made, so a public name that resolves to an internal
+/// address is caught, on the first request and on every redirect.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if addrs.is_empty() || !addrs.iter().all(|addr| is_public_ip(addr.ip())) {
+                let refused: Box<dyn std::error::Error + Send + Sync> =
+                    format!("{} does not resolve to a public address", name.as_str()).into();
+                return Err(refused);
+            }
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Downloads a calendar, giving up past `MAX_CALENDAR_BYTES`.
+pub async fn fetch_calendar(client: &reqwest::Client, url: &str) -> Result<String, CalendarError> {
+    let fetch_failed = |e: reqwest::Error| CalendarError::Fetch(e.to_string());
+
+    let mut response = client.get(url).send().await.map_err(fetch_failed)?;
+    if !response.status().is_success() {
+        return Err(CalendarError::Fetch(format!("server answered {}", response.status())));
+    }
+    if response.content_length().is_some_and(|len| len > MAX_CALENDAR_BYTES as u64) {
+        return Err(CalendarError::TooLarge);
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(fetch_failed)? {
+        body.extend_from_slice(&chunk);
+        if body.len() > MAX_CALENDAR_BYTES {
+            return Err(CalendarError::TooLarge);
+        }
+    }
+
+    String::from_utf8(body).map_err(|_| CalendarError::Parse("calendar is not UTF-8 text".to_string()))
+}
+
+/// Shared client for calendar imports. Redirects are checked like the URL itself, and every
+/// host is resolved through `PublicResolver`. Proxies are not used, since they would resolve
+/// the host themselves.
+pub fn calendar_http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        let redirects = reqwest::redirect::Policy::custom(|attempt| {
+            if attempt.previous().len() >= 3 {
+                attempt.error("too many redirects")
+            } else if normalize_import_url(attempt.url().as_str()).is_err() {
+                attempt.error("redirected to a non-public host")
+            } else {
+                attempt.follow()
+            }
+        });
+        reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(FETCH_TIMEOUT_SECS))
+            .redirect(redirects)
+            .dns_resolver(Arc::new(PublicResolver))
+            .no_proxy()
+            .user_agent("OneSociety calendar import")
+            .build()
+            .expect("calendar HTTP client settings are valid")
+    })
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
+
+/// Fetches one imported calendar and replaces its blackouts with the events that have not ended
+/// yet. A failed fetch is recorded on the import and keeps the blackouts of the last good sync.
+pub async fn sync_import(db: &PgPool, client: &reqwest::Client, import_id: Uuid) -> Result<usize, CalendarError> {
+    let import = sqlx::query!(
+        "SELECT product_id, url FROM product_schema.calendar_imports WHERE import_id = $1",
+        import_id
+    )
+    .fetch_optional(db)
+    .await?;
+
+    let import = match import {
+        Some(i) => i,
+        None => return Ok(0),
+    };
+
+    let fetched = fetch_calendar(client, &import.url).await.and_then(|body| parse_calendar(&body));
+    let events = match fetched {
+        Ok(events) => events,
+        Err(e) => {
+            sqlx::query!(
+                r#"
+                UPDATE product_schema.calendar_imports SET last_attempted_at = NOW(), last_error = $2
+                WHERE import_id = $1
+                "#,
+                import_id,
+                e.to_string()
+            )
+            .execute(db)
+            .await?;
+            return Err(e);
+        }
+    };
+
+    let now = Utc::now();
+    let events: Vec<ImportedEvent> = events.into_iter().filter(|e| e.end > now).collect();
+    let starts: Vec<DateTime<Utc>> = events.iter().map(|e| e.start).collect();
+    let ends: Vec<DateTime<Utc>> = events.iter().map(|e| e.end).collect();
+    let summaries: Vec<Option<String>> =
+        events.iter().map(|e| e.summary.as_deref().map(|s| truncate(s, MAX_SUMMARY_LEN))).collect();
+    let uids: Vec<Option<String>> =
+        events.iter().map(|e| e.uid.as_deref().map(|u| truncate(u, MAX_SUMMARY_LEN))).collect();
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query!("DELETE FROM product_schema.product_blackouts WHERE import_id = $1", import_id)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO product_schema.product_blackouts (product_id, import_id, starts_at, ends_at, summary, external_uid)
+        SELECT $1, $2, * FROM UNNEST($3::timestamptz[], $4::timestamptz[], $5::varchar[], $6::varchar[])
+        "#,
+        import.product_id,
+        import_id,
+        &starts,
+        &ends,
+        &summaries as &[Option<String>],
+        &uids as &[Option<String>]
+    )
+    .execute(&mut tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE product_schema.calendar_imports
+        SET last_attempted_at = NOW(), last_synced_at = NOW(), last_error = NULL
+        WHERE import_id = $1
+        "#,
+        import_id
+    )
+    .execute(&mut tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(events.len())
+}
+
+/// One pass of the import job: claims the imports due for a sync and fetches them. Returns
+/// `(synced, failed)`; failures are recorded on each import.
+pub async fn process_calendar_imports(db: &PgPool) -> Result<(usize, usize), sqlx::Error> {
+    // Claiming stamps the attempt, so other instances skip these imports
+    let due = sqlx::query!(
+        r#"
+        UPDATE product_schema.calendar_imports SET last_attempted_at = NOW()
+        WHERE import_id IN (
+            SELECT import_id FROM product_schema.calendar_imports
+            WHERE last_attempted_at IS NULL OR last_attempted_at < NOW() - make_interval(mins => $1)
+            ORDER BY last_attempted_at NULLS FIRST
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING import_id
+        "#,
+        CALENDAR_SYNC_INTERVAL_MINUTES,
+        IMPORT_BATCH_SIZE
+    )
+    .fetch_all(db)
+    .await?;
+
+    let (mut synced, mut failed) = (0, 0);
+    for import in due {
+        match sync_import(db, calendar_http_client(), import.import_id).await {
+            Ok(_) => synced += 1,
+            Err(CalendarError::Database(e)) => return Err(e),
+            Err(e) => {
+                tracing::warn!(import_id = %import.import_id, error = %e, "calendar import failed");
+                failed += 1;
+            }
+        }
+    }
+
+    Ok((synced, failed))
+}
+
+/// Background loop started by the server.
+pub async fn run_calendar_import_job(db: PgPool) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(CALENDAR_IMPORT_JOB_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        match process_calendar_imports(&db).await {
+            Ok((0, 0)) => {}
+            Ok((synced, failed)) => tracing::info!(synced, failed, "synced imported calendars"),
+            Err(e) => tracing::warn!(error = %e, "calendar import pass failed"),
+        }
+    }
+}
+
+/// Bookings that hold a unit and the owner's own blackouts, for one product or all of the
+/// owner's. Imported blackouts are left out so calendars that import each other do not echo
+/// events back.
+async fn feed_events(db: &PgPool, owner_id: Uuid, product_id: Option<Uuid>) -> Result<Vec<CalendarEvent>, sqlx::Error> {
+    let now = Utc::now();
+    let since = now - Duration::days(FEED_HISTORY_DAYS);
+
+    let rentals = sqlx::query!(
+        r#"
+        SELECT r.rental_id, r.rental_period_start, r.rental_period_end, r.status, p.name
+        FROM rental_schema.rentals r
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE p.owner_id = $1 AND ($2::uuid IS NULL OR p.product_id = $2)
+        AND r.status IN ('pending_payment', 'requested', 'confirmed', 'active', 'overdue')
+        AND (r.rental_period_end > $3 OR r.status = 'overdue')
+        "#,
+        owner_id,
+        product_id,
+        since
+    )
+    .fetch_all(db)
+    .await?;
+
+    let blackouts = sqlx::query!(
+        r#"
+        SELECT b.blackout_id, b.starts_at, b.ends_at, p.name
+        FROM product_schema.product_blackouts b
+        JOIN product_schema.products p ON b.product_id = p.product_id
+        WHERE p.owner_id = $1 AND ($2::uuid IS NULL OR p.product_id = $2)
+        AND b.import_id IS NULL AND b.ends_at > $3
+        "#,
+        owner_id,
+        product_id,
+        since
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut events: Vec<CalendarEvent> = rentals
+        .into_iter()
+        .map(|r| CalendarEvent {
+            uid: format!("rental-{}@{}", r.rental_id, UID_DOMAIN),
+            start: r.rental_period_start,
+            // An overdue item is held the way booking checks hold it
+            end: if r.status == "overdue" {
+                r.rental_period_end.max(now) + Duration::hours(i64::from(OVERDUE_HOLD_HOURS))
+            } else {
+                r.rental_period_end
+            },
+            summary: if r.status == "requested" {
+                format!("{}: request pending", r.name)
+            } else {
+                format!("{}: booked", r.name)
+            },
+        })
+        .chain(blackouts.into_iter().map(|b| CalendarEvent {
+            uid: format!("blackout-{}@{}", b.blackout_id, UID_DOMAIN),
+            start: b.starts_at,
+            end: b.ends_at,
+            summary: format!("{}: blocked", b.name),
+        }))
+        .collect();
+    events.sort_by_key(|e| e.start);
+
+    Ok(events)
+}
+
+/// A 64 hex character secret from two random UUIDs.
+pub fn new_feed_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalendarFeedResponse {
+    pub token: String,
+    /// Path of the `.ics` feed; anyone with it can read the calendar.
+    pub path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalendarImportRequest {
+    pub url: String,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalendarImportResponse {
+    pub import_id: Uuid,
+    pub product_id: Uuid,
+    pub url: String,
+    pub label: Option<String>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlackoutRequest {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub summary: Option<String>,
+}
+
+impl BlackoutRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ends_at <= self.starts_at {
+            return Err("ends_at must be after starts_at".to_string());
+        }
+        if self.summary.as_ref().is_some_and(|s| s.chars().count() > MAX_SUMMARY_LEN) {
+            return Err(format!("summary must be at most {} characters", MAX_SUMMARY_LEN));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlackoutResponse {
+    pub blackout_id: Uuid,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub summary: Option<String>,
+    /// Set for blackouts copied from an imported calendar.
+    pub import_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// Only the owner manages a product's calendar.
+async fn check_owner(db: &PgPool, product_id: Uuid, user_id: Uuid) -> Result<(), (StatusCode, &'static str)> {
+    let product = sqlx::query!("SELECT owner_id FROM product_schema.products WHERE product_id = $1", product_id)
+        .fetch_optional(db)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load product"))?;
+
+    match product {
+        Some(p) if p.owner_id == user_id => Ok(()),
+        Some(_) => Err((StatusCode::FORBIDDEN, "Not authorized to manage this product's calendar")),
+        None => Err((StatusCode::NOT_FOUND, "Product not found")),
+    }
+}
+
+/// The feed token for a product, or for all of the owner's products when `product_id` is
+/// `None`, created on first use. Rotating replaces it so the old URL stops working.
+async fn feed_token(db: &PgPool, owner_id: Uuid, product_id: Option<Uuid>, rotate: bool) -> Result<String, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    if rotate {
+        sqlx::query!(
+            "DELETE FROM product_schema.calendar_feeds WHERE owner_id = $1 AND product_id IS NOT DISTINCT FROM $2",
+            owner_id,
+            product_id
+        )
+        .execute(&mut tx)
+        .await?;
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO product_schema.calendar_feeds (owner_id, product_id, token)
+        VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING
+        "#,
+        owner_id,
+        product_id,
+        new_feed_token()
+    )
+    .execute(&mut tx)
+    .await?;
+
+    let feed = sqlx::query!(
+        "SELECT token FROM product_schema.calendar_feeds WHERE owner_id = $1 AND product_id IS NOT DISTINCT FROM $2",
+        owner_id,
+        product_id
+    )
+    .fetch_one(&mut tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(feed.token)
+}
+
+/// Shared body of the feed token endpoints.
+async fn feed_response(state: AppState, headers: HeaderMap, product_id: Option<Uuid>, rotate: bool) -> Response {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized").into_response(),
+    };
+
+    if let Some(product_id) = product_id {
+        if let Err((status, message)) = check_owner(&state.db, product_id, user_id).await {
+            return err(status, message).into_response();
+        }
+    }
+
+    match feed_token(&state.db, user_id, product_id, rotate).await {
+        Ok(token) => {
+            let path = format!("{}/{}.ics", FEED_PATH_PREFIX, token);
+            ok(CalendarFeedResponse { token, path }).into_response()
+        }
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load calendar feed").into_response(),
+    }
+}
+
+pub async fn get_product_calendar_feed(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(product_id): Path<Uuid>,
+) -> Response {
+    feed_response(state, headers, Some(product_id), false).await
+}
+
+pub async fn rotate_product_calendar_feed(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(product_id): Path<Uuid>,
+) -> Response {
+    feed_response(state, headers, Some(product_id), true).await
+}
+
+pub async fn get_owner_calendar_feed(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    feed_response(state, headers, None, false).await
+}
+
+pub async fn rotate_owner_calendar_feed(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    feed_response(state, headers, None, true).await
+}
+
+/// The `.ics` feed behind a secret token. No login: calendar apps only have the URL.
+pub async fn serve_calendar_feed(State(state): State<AppState>, Path(token): Path<String>) -> Response {
+    let token = token.strip_suffix(".ics").unwrap_or(&token);
+
+    let feed = sqlx::query!(
+        r#"
+        SELECT f.owner_id, f.product_id, p.name AS "product_name?"
+        FROM product_schema.calendar_feeds f
+        LEFT JOIN product_schema.products p ON f.product_id = p.product_id
+        WHERE f.token = $1
+        "#,
+        token
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    let feed = match feed {
+        Ok(Some(f)) => f,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Calendar not found").into_response(),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load calendar").into_response(),
+    };
+
+    let events = match feed_events(&state.db, feed.owner_id, feed.product_id).await {
+        Ok(events) => events,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load calendar").into_response(),
+    };
+
+    let name = feed.product_name.unwrap_or_else(|| "Rentals".to_string());
+    (
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        render_calendar(&name, &events, Utc::now()),
+    )
+        .into_response()
+}
+
+async fn load_import(db: &PgPool, import_id: Uuid) -> Result<CalendarImportResponse, sqlx::Error> {
+    sqlx::query_as!(
+        CalendarImportResponse,
+        r#"
+        SELECT import_id, product_id, url, label, last_synced_at, last_error, created_at
+        FROM product_schema.calendar_imports
+        WHERE import_id = $1
+        "#,
+        import_id
+    )
+    .fetch_one(db)
+    .await
+}
+
+pub async fn list_calendar_imports(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(product_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    if let Err((status, message)) = check_owner(&state.db, product_id, user_id).await {
+        return err(status, message);
+    }
+
+    let imports = sqlx::query_as!(
+        CalendarImportResponse,
+        r#"
+        SELECT import_id, product_id, url, label, last_synced_at, last_error, created_at
+        FROM product_schema.calendar_imports
+        WHERE product_id = $1
+        ORDER BY created_at
+        "#,
+        product_id
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    match imports {
+        Ok(imports) => ok(imports),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch calendar imports"),
+    }
+}
+
+/// Adds an external calendar and syncs it straight away. A calendar that cannot be fetched yet
+/// is still added; the error shows on the import and the job keeps retrying.
+pub async fn add_calendar_import(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(product_id): Path<Uuid>,
+    Json(req): Json<CalendarImportRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let url = match normalize_import_url(&req.url) {
+        Ok(url) => url,
+        Err(e) => return err(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
+    let label = req.label.as_deref().map(str::trim).filter(|l| !l.is_empty());
+    if label.is_some_and(|l| l.chars().count() > MAX_IMPORT_LABEL_LEN) {
+        return err(StatusCode::BAD_REQUEST, &format!("label must be at most {} characters", MAX_IMPORT_LABEL_LEN));
+    }
+
+    if let Err((status, message)) = check_owner(&state.db, product_id, user_id).await {
+        return err(status, message);
+    }
+
+    let count = sqlx::query!(
+        r#"SELECT COUNT(*) AS "count!" FROM product_schema.calendar_imports WHERE product_id = $1"#,
+        product_id
+    )
+    .fetch_one(&state.db)
+    .await;
+
+    match count {
+        Ok(c) if c.count >= MAX_IMPORTS_PER_PRODUCT => {
+            let message = format!("A product can import at most {} calendars", MAX_IMPORTS_PER_PRODUCT);
+            return err(StatusCode::CONFLICT, &message);
+        }
+        Ok(_) => {}
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to add calendar import"),
+    }
+
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO product_schema.calendar_imports (product_id, url, label)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (product_id, url) DO NOTHING
+        RETURNING import_id
+        "#,
+        product_id,
+        url,
+        label
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    let import_id = match inserted {
+        Ok(Some(row)) => row.import_id,
+        Ok(None) => return err(StatusCode::CONFLICT, "This calendar is already imported"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to add calendar import"),
+    };
+
+    if let Err(CalendarError::Database(_)) = sync_import(&state.db, calendar_http_client(), import_id).await {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to sync calendar");
+    }
+
+    match load_import(&state.db, import_id).await {
+        Ok(import) => ok(import),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to add calendar import"),
+    }
+}
+
+pub async fn sync_calendar_import(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((product_id, import_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    if let Err((status, message)) = check_owner(&state.db, product_id, user_id).await {
+        return err(status, message);
+    }
+
+    match load_import(&state.db, import_id).await {
+        Ok(import) if import.product_id == product_id => {}
+        Ok(_) | Err(sqlx::Error::RowNotFound) => return err(StatusCode::NOT_FOUND, "Calendar import not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to sync calendar"),
+    }
+
+    match sync_import(&state.db, calendar_http_client(), import_id).await {
+        Ok(count) => ok(serde_json::json!({ "import_id": import_id, "blackouts": count })),
+        Err(CalendarError::Database(_)) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to sync calendar"),
+        Err(e) => err(StatusCode::BAD_GATEWAY, &e.to_string()),
+    }
+}
+
+/// Stops importing a calendar; its blackouts go with it.
+pub async fn delete_calendar_import(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((product_id, import_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    if let Err((status, message)) = check_owner(&state.db, product_id, user_id).await {
+        return err(status, message);
+    }
+
+    let deleted = sqlx::query!(
+        "DELETE FROM product_schema.calendar_imports WHERE import_id = $1 AND product_id = $2",
+        import_id,
+        product_id
+    )
+    .execute(&state.db)
+    .await;
+
+    match deleted {
+        Ok(r) if r.rows_affected() > 0 => ok(serde_json::json!({ "message": "Calendar import removed" })),
+        Ok(_) => err(StatusCode::NOT_FOUND, "Calendar import not found"),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to remove calendar import"),
+    }
+}
+
+/// Current and upcoming blackouts, entered or imported.
+pub async fn list_blackouts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(product_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    if let Err((status, message)) = check_owner(&state.db, product_id, user_id).await {
+        return err(status, message);
+    }
+
+    let blackouts = sqlx::query_as!(
+        BlackoutResponse,
+        r#"
+        SELECT blackout_id, starts_at, ends_at, summary, import_id, created_at
+        FROM product_schema.product_blackouts
+        WHERE product_id = $1 AND ends_at > NOW()
+        ORDER BY starts_at
+        "#,
+        product_id
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    match blackouts {
+        Ok(blackouts) => ok(blackouts),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch blackouts"),
+    }
+}
+
+/// Blocks the product for a period. Bookings that already overlap it are kept.
+pub async fn add_blackout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(product_id): Path<Uuid>,
+    Json(req): Json<BlackoutRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    if let Err(message) = req.validate() {
+        return err(StatusCode::BAD_REQUEST, &message);
+    }
+
+    if let Err((status, message)) = check_owner(&state.db, product_id, user_id).await {
+        return err(status, message);
+    }
+
+    let blackout = sqlx::query_as!(
+        BlackoutResponse,
+        r#"
+        INSERT INTO product_schema.product_blackouts (product_id, starts_at, ends_at, summary)
+        VALUES ($1, $2, $3, $4)
+        RETURNING blackout_id, starts_at, ends_at, summary, import_id, created_at
+        "#,
+        product_id,
+        req.starts_at,
+        req.ends_at,
+        req.summary.as_deref().map(str::trim).filter(|s| !s.is_empty())
+    )
+    .fetch_one(&state.db)
+    .await;
+
+    match blackout {
+        Ok(blackout) => ok(blackout),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to add blackout"),
+    }
+}
+
+/// Removes a blackout the owner entered. Imported ones follow their calendar.
+pub async fn delete_blackout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((product_id, blackout_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    if let Err((status, message)) = check_owner(&state.db, product_id, user_id).await {
+        return err(status, message);
+    }
+
+    let deleted = sqlx::query!(
+        r#"
+        DELETE FROM product_schema.product_blackouts
+        WHERE blackout_id = $1 AND product_id = $2 AND import_id IS NULL
+        "#,
+        blackout_id,
+        product_id
+    )
+    .execute(&state.db)
+    .await;
+
+    match deleted {
+        Ok(r) if r.rows_affected() > 0 => ok(serde_json::json!({ "message": "Blackout removed" })),
+        Ok(_) => err(StatusCode::NOT_FOUND, "Blackout not found; imported blackouts are removed with their calendar"),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to remove blackout"),
+    }
+}
@@ -0,0 +1,379 @@
+use std::fmt;
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::payment::{record_pending_charge, refund_rental_payments, rental_refundable_cents, rental_refundable_charges};
+use crate::pricing::PriceQuote;
+use crate::rental_status::{acting_role, RentalRole, RentalStatus};
+use crate::roles::{has_any_role, ROLE_ADMIN};
+
+/// Cancellation terms an owner offers on a listing; each rental keeps the terms it was booked under.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancellationPolicy {
+    Flexible,
+    #[default]
+    Moderate,
+    Strict,
+}
+
+impl CancellationPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CancellationPolicy::Flexible => "flexible",
+            CancellationPolicy::Moderate => "moderate",
+            CancellationPolicy::Strict => "strict",
+        }
+    }
+
+    /// `(minimum hours before start, percent of the rental charge refunded)`, most generous first.
+    /// Anything later than the last tier refunds nothing.
+    pub fn renter_refund_tiers(&self) -> &'static [(i64, i64)] {
+        match self {
+            CancellationPolicy::Flexible => &[(24, 100), (0, 50)],
+            CancellationPolicy::Moderate => &[(5 * 24, 100), (24, 50)],
+            CancellationPolicy::Strict => &[(7 * 24, 50)],
+        }
+    }
+}
+
+impl fmt::Display for CancellationPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CancellationPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flexible" => Ok(CancellationPolicy::Flexible),
+            "moderate" => Ok(CancellationPolicy::Moderate),
+            "strict" => Ok(CancellationPolicy::Strict),
+            other => Err(format!("Unknown cancellation policy '{}'", other)),
+        }
+    }
+}
+
+/// Owner cancellations of a confirmed rental: `(minimum hours before start, penalty percent of
+/// the rental subtotal)`. Later than the last tier costs `OWNER_LATE_PENALTY_PERCENT`.
+pub const OWNER_PENALTY_TIERS: &[(i64, i64)] = &[(7 * 24, 0), (24, 10)];
+pub const OWNER_LATE_PENALTY_PERCENT: i64 = 25;
+
+/// Whole hours until the rental starts, rounded down; negative once it has started.
+pub fn hours_before_start(start: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    (start - now).num_minutes().div_euclid(60)
+}
+
+pub fn renter_refund_percent(policy: CancellationPolicy, hours_before_start: i64) -> i64 {
+    policy
+        .renter_refund_tiers()
+        .iter()
+        .find(|(min_hours, _)| hours_before_start >= *min_hours)
+        .map_or(0, |(_, percent)| *percent)
+}
+
+pub fn owner_penalty_percent(hours_before_start: i64) -> i64 {
+    OWNER_PENALTY_TIERS
+        .iter()
+        .find(|(min_hours, _)| hours_before_start >= *min_hours)
+        .map_or(OWNER_LATE_PENALTY_PERCENT, |(_, percent)| *percent)
+}
+
+/// Money involved in a rental, in cents. `paid_cents` is what was captured and not yet refunded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RentalCharges {
+    pub paid_cents: i64,
+    pub deposit_cents: i64,
+    pub subtotal_cents: i64,
+    /// Insurance premium; the policy is cancelled with the rental, so it is refunded in full.
+    pub insurance_cents: i64,
+}
+
+impl RentalCharges {
+    pub fn from_quote(quote: Option<&PriceQuote>, paid_cents: i64) -> Self {
+        let cents = |amount: f64| (amount * 100.0).round() as i64;
+        RentalCharges {
+            paid_cents,
+            deposit_cents: quote.map_or(0, |q| cents(q.deposit)),
+            subtotal_cents: quote.map_or(0, |q| cents(q.subtotal)),
+            insurance_cents: quote.map_or(0, |q| cents(q.insurance_premium)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CancellationTerms {
+    pub policy: CancellationPolicy,
+    pub cancelled_by: RentalRole,
+    pub hours_before_start: i64,
+    /// Share of the rental charge (excluding deposit and insurance) returned to the renter.
+    pub refund_percent: i64,
+    pub refund_cents: i64,
+    pub owner_penalty_cents: i64,
+}
+
+/// Refund and penalty for cancelling a rental in state `from`. The deposit and any insurance
+/// premium are always returned.
+/// Renters get their policy's tier once the owner has confirmed, and everything before that.
+/// Owners and admins refund in full; owners also owe a penalty for dropping a confirmed rental.
+pub fn cancellation_terms(
+    policy: CancellationPolicy,
+    cancelled_by: RentalRole,
+    from: RentalStatus,
+    hours_before_start: i64,
+    charges: &RentalCharges,
+) -> CancellationTerms {
+    let refund_percent = match (cancelled_by, from) {
+        (RentalRole::Renter, RentalStatus::Requested) => 100,
+        (RentalRole::Renter, _) => renter_refund_percent(policy, hours_before_start),
+        (RentalRole::Owner, _) | (RentalRole::Admin, _) => 100,
+    };
+
+    let paid = charges.paid_cents.max(0);
+    let deposit = charges.deposit_cents.clamp(0, paid);
+    let premium = charges.insurance_cents.clamp(0, paid - deposit);
+    let refund_cents = deposit + premium + (paid - deposit - premium) * refund_percent / 100;
+
+    let owner_penalty_cents = if cancelled_by == RentalRole::Owner && from != RentalStatus::Requested {
+        charges.subtotal_cents.max(0) * owner_penalty_percent(hours_before_start) / 100
+    } else {
+        0
+    };
+
+    CancellationTerms {
+        policy,
+        cancelled_by,
+        hours_before_start,
+        refund_percent,
+        refund_cents,
+        owner_penalty_cents,
+    }
+}
+
+/// Terms for cancelling a rental the renter has `paid_cents` left to refund on.
+async fn load_terms(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+    from: RentalStatus,
+    cancelled_by: RentalRole,
+    paid_cents: i64,
+) -> Result<CancellationTerms, (StatusCode, String)> {
+    let failed = || (StatusCode::INTERNAL_SERVER_ERROR, "Failed to compute refund".to_string());
+
+    let rental = sqlx::query!(
+        "SELECT cancellation_policy, rental_period_start, price_quote FROM rental_schema.rentals WHERE rental_id = $1",
+        rental_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|_| failed())?;
+
+    let policy = rental.cancellation_policy.parse::<CancellationPolicy>().unwrap_or_default();
+    let quote: Option<PriceQuote> = rental.price_quote.and_then(|q| serde_json::from_value(q).ok());
+
+    Ok(cancellation_terms(
+        policy,
+        cancelled_by,
+        from,
+        hours_before_start(rental.rental_period_start, Utc::now()),
+        &RentalCharges::from_quote(quote.as_ref(), paid_cents),
+    ))
+}
+
+/// Settles a cancellation or decline inside the transition's transaction: records the renter's
+/// refund, the owner's penalty (as owed when there is no saved payment method to charge) and the
+/// terms applied. The caller sends the payments to the
+/// provider with `settle_rental_payments` once it has committed.
+pub async fn settle_cancellation(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+    from: RentalStatus,
+    cancelled_by: RentalRole,
+    reason: Option<&str>,
+) -> Result<CancellationTerms, (StatusCode, String)> {
+    // Locks the charges the refund is taken from
+    let paid_cents = rental_refundable_charges(tx, rental_id)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to compute refund".to_string()))?
+        .iter()
+        .map(|c| c.refundable_cents)
+        .sum();
+    let terms = load_terms(tx, rental_id, from, cancelled_by, paid_cents).await?;
+
+    if terms.refund_cents > 0 {
+        refund_rental_payments(tx, rental_id, terms.refund_cents, reason.unwrap_or("rental cancelled")).await?;
+    }
+
+    let mut owner_penalty_owed_cents = 0;
+    if terms.owner_penalty_cents > 0 {
+        let rental = sqlx::query!(
+            r#"
+            SELECT p.owner_id, COALESCE(r.price_quote->>'currency', 'USD') AS "currency!"
+            FROM rental_schema.rentals r
+            JOIN product_schema.products p ON r.product_id = p.product_id
+            WHERE r.rental_id = $1
+            "#,
+            rental_id
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to record cancellation".to_string()))?;
+
+        // A rental is cancelled at most once, so its id keys the penalty charge. An owner without
+        // a saved payment method can still cancel; the penalty is then recorded as owed.
+        let charged = record_pending_charge(
+            tx,
+            rental.owner_id,
+            Some(rental_id),
+            terms.owner_penalty_cents,
+            &rental.currency,
+            &format!("owner-cancellation-penalty-{}", rental_id),
+            "owner cancellation penalty",
+        )
+        .await;
+        match charged {
+            Ok(_) => {}
+            Err((StatusCode::PAYMENT_REQUIRED, _)) => owner_penalty_owed_cents = terms.owner_penalty_cents,
+            Err(e) => return Err(e),
+        }
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO rental_schema.rental_cancellations
+        (rental_id, cancelled_by, policy, hours_before_start, refund_percent, refund_cents, owner_penalty_cents,
+         owner_penalty_owed_cents)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+        rental_id,
+        terms.cancelled_by.as_str(),
+        terms.policy.as_str(),
+        terms.hours_before_start,
+        terms.refund_percent,
+        terms.refund_cents,
+        terms.owner_penalty_cents,
+        owner_penalty_owed_cents
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to record cancellation".to_string()))?;
+
+    Ok(terms)
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// What cancelling now would refund and cost the caller, without cancelling.
+pub async fn preview_cancellation(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let is_admin = match has_any_role(&state.db, user_id, &[ROLE_ADMIN]).await {
+        Ok(is_admin) => is_admin,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to compute refund"),
+    };
+
+    // Read-only and takes no locks, so a preview never holds up a cancellation or settlement
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to compute refund"),
+    };
+
+    let rental = sqlx::query!(
+        r#"
+        SELECT r.status, r.renter_id, p.owner_id
+        FROM rental_schema.rentals r
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE r.rental_id = $1
+        "#,
+        rental_id
+    )
+    .fetch_optional(&mut tx)
+    .await;
+
+    let rental = match rental {
+        Ok(Some(r)) => r,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Rental not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to compute refund"),
+    };
+
+    let mut roles = Vec::new();
+    if rental.renter_id == user_id {
+        roles.push(RentalRole::Renter);
+    }
+    if rental.owner_id == user_id {
+        roles.push(RentalRole::Owner);
+    }
+    if is_admin {
+        roles.push(RentalRole::Admin);
+    }
+    if roles.is_empty() {
+        return err(StatusCode::NOT_FOUND, "Rental not found");
+    }
+
+    let from = match rental.status.parse::<RentalStatus>() {
+        Ok(s) => s,
+        Err(message) => return err(StatusCode::INTERNAL_SERVER_ERROR, &message),
+    };
+
+    if !from.can_transition_to(RentalStatus::Cancelled) {
+        return err(StatusCode::CONFLICT, &format!("A {} rental cannot be cancelled", from));
+    }
+
+    let role = match acting_role(&roles, from, RentalStatus::Cancelled) {
+        Some(role) => role,
+        None => return err(StatusCode::FORBIDDEN, "Not allowed to cancel this rental"),
+    };
+
+    let paid_cents = match rental_refundable_cents(&mut tx, rental_id).await {
+        Ok(cents) => cents,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to compute refund"),
+    };
+
+    match load_terms(&mut tx, rental_id, from, role, paid_cents).await {
+        Ok(terms) => ok(terms),
+        Err((status, message)) => err(status, &message),
+    }
+}
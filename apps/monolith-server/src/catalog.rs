@@ -0,0 +1,838 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use calamine::{Reader, Xlsx};
+use rust_xlsxwriter::Workbook;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::category::slugify;
+use crate::product::{insert_product, CreateProductRequest};
+
+pub const MAX_IMPORT_ROWS: usize = 5000;
+pub const MAX_REPORTED_ERRORS: usize = 1000;
+/// Jobs that have not reported progress for this long lost their worker (e.g. a restart).
+pub const STALE_IMPORT_MINUTES: i32 = 15;
+const IMPORT_RECLAIM_INTERVAL_SECS: u64 = 300;
+
+/// Column order shared by import templates and exports.
+pub const CATALOG_COLUMNS: &[&str] = &[
+    "name",
+    "description",
+    "category",
+    "daily_price",
+    "deposit_amount",
+    "insurance_required",
+    "quantity",
+    "tags",
+    "city",
+    "country",
+    "specifications",
+];
+
+const REQUIRED_COLUMNS: &[&str] = &["name", "category", "daily_price"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CatalogFormat {
+    Csv,
+    Xlsx,
+}
+
+impl CatalogFormat {
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let lower = name.to_lowercase();
+        if lower.ends_with(".csv") {
+            Some(CatalogFormat::Csv)
+        } else if lower.ends_with(".xlsx") {
+            Some(CatalogFormat::Xlsx)
+        } else {
+            None
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            CatalogFormat::Csv => "text/csv; charset=utf-8",
+            CatalogFormat::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CatalogFormat::Csv => "csv",
+            CatalogFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RowError {
+    /// Spreadsheet row number; the header is row 1.
+    pub row: usize,
+    pub column: Option<String>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl RowError {
+    fn new(row: usize, column: Option<&str>, message: String) -> Self {
+        RowError { row, column: column.map(str::to_string), message, details: None }
+    }
+}
+
+/// A validated data row; `category` is still the raw slug, name or id from the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    pub row: usize,
+    pub name: String,
+    pub description: Option<String>,
+    pub category: String,
+    pub daily_price: f64,
+    pub deposit_amount: Option<f64>,
+    pub insurance_required: Option<bool>,
+    pub quantity: Option<i32>,
+    pub tags: Vec<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub specifications: Option<serde_json::Value>,
+}
+
+impl ImportRow {
+    pub fn into_request(self, category_id: Uuid) -> CreateProductRequest {
+        let address = if self.city.is_some() || self.country.is_some() {
+            Some(serde_json::json!({ "city": self.city, "country": self.country }))
+        } else {
+            None
+        };
+        CreateProductRequest {
+            name: self.name,
+            description: self.description,
+            category_id,
+            daily_price: self.daily_price,
+            deposit_amount: self.deposit_amount,
+            insurance_required: self.insurance_required,
+            specifications: self.specifications,
+            address,
+            tags: Some(self.tags),
+            quantity: self.quantity,
+            cancellation_policy: None,
+            instant_book: None,
+            instant_book_requirements: None,
+            late_fee_policy: None,
+        }
+    }
+}
+
+/// One exported listing, in `CATALOG_COLUMNS` order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportRow {
+    pub name: String,
+    pub description: Option<String>,
+    pub category: String,
+    pub daily_price: f64,
+    pub deposit_amount: Option<f64>,
+    pub insurance_required: bool,
+    pub quantity: i64,
+    pub tags: Vec<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub specifications: Option<serde_json::Value>,
+}
+
+impl ExportRow {
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.description.clone().unwrap_or_default(),
+            self.category.clone(),
+            self.daily_price.to_string(),
+            self.deposit_amount.map(|d| d.to_string()).unwrap_or_default(),
+            self.insurance_required.to_string(),
+            self.quantity.to_string(),
+            self.tags.join(", "),
+            self.city.clone().unwrap_or_default(),
+            self.country.clone().unwrap_or_default(),
+            self.specifications.as_ref().map(|s| s.to_string()).unwrap_or_default(),
+        ]
+    }
+}
+
+/// Maps each catalog column to its index in `headers`. `mapping` renames columns
+/// (catalog column -> header in the file); unmapped columns are matched by their own name.
+pub fn resolve_columns(
+    headers: &[String],
+    mapping: &HashMap<String, String>,
+) -> Result<HashMap<&'static str, usize>, String> {
+    if let Some(unknown) = mapping.keys().find(|k| !CATALOG_COLUMNS.contains(&k.as_str())) {
+        return Err(format!("Unknown column '{}' in mapping", unknown));
+    }
+
+    let normalized: Vec<String> = headers.iter().map(|h| h.trim().to_lowercase()).collect();
+    let mut columns = HashMap::new();
+    for column in CATALOG_COLUMNS {
+        let source = mapping.get(*column).map(|s| s.as_str()).unwrap_or(column);
+        if let Some(index) = normalized.iter().position(|h| *h == source.trim().to_lowercase()) {
+            columns.insert(*column, index);
+        } else if mapping.contains_key(*column) {
+            return Err(format!("Mapped header '{}' for '{}' not found in file", source, column));
+        }
+    }
+
+    let missing: Vec<&str> = REQUIRED_COLUMNS.iter().copied().filter(|c| !columns.contains_key(c)).collect();
+    if !missing.is_empty() {
+        return Err(format!("Missing required columns: {}", missing.join(", ")));
+    }
+
+    Ok(columns)
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" => Some(true),
+        "false" | "no" | "n" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Validates one data row, collecting every problem instead of stopping at the first.
+pub fn parse_row(row: usize, cells: &[String], columns: &HashMap<&'static str, usize>) -> Result<ImportRow, Vec<RowError>> {
+    let cell = |column: &str| {
+        columns
+            .get(column)
+            .and_then(|i| cells.get(*i))
+            .map(|c| c.trim())
+            .filter(|c| !c.is_empty())
+    };
+    let mut errors = Vec::new();
+
+    let name = cell("name").unwrap_or_default().to_string();
+    if name.is_empty() {
+        errors.push(RowError::new(row, Some("name"), "name is required".to_string()));
+    } else if name.chars().count() > 255 {
+        errors.push(RowError::new(row, Some("name"), "name is longer than 255 characters".to_string()));
+    }
+
+    let category = cell("category").unwrap_or_default().to_string();
+    if category.is_empty() {
+        errors.push(RowError::new(row, Some("category"), "category is required".to_string()));
+    }
+
+    let daily_price = match cell("daily_price").map(|v| v.parse::<f64>()) {
+        Some(Ok(p)) if p.is_finite() && p > 0.0 => p,
+        Some(_) => {
+            errors.push(RowError::new(row, Some("daily_price"), "daily_price must be a positive number".to_string()));
+            0.0
+        }
+        None => {
+            errors.push(RowError::new(row, Some("daily_price"), "daily_price is required".to_string()));
+            0.0
+        }
+    };
+
+    let deposit_amount = match cell("deposit_amount").map(|v| v.parse::<f64>()) {
+        Some(Ok(d)) if d.is_finite() && d >= 0.0 => Some(d),
+        Some(_) => {
+            errors.push(RowError::new(row, Some("deposit_amount"), "deposit_amount must be a non-negative number".to_string()));
+            None
+        }
+        None => None,
+    };
+
+    let insurance_required = match cell("insurance_required") {
+        Some(v) => match parse_bool(v) {
+            Some(b) => Some(b),
+            None => {
+                errors.push(RowError::new(row, Some("insurance_required"), format!("'{}' is not a yes/no value", v)));
+                None
+            }
+        },
+        None => None,
+    };
+
+    let quantity = match cell("quantity").map(|v| v.parse::<i32>()) {
+        Some(Ok(q)) if q >= 1 => Some(q),
+        Some(_) => {
+            errors.push(RowError::new(row, Some("quantity"), "quantity must be a whole number of at least 1".to_string()));
+            None
+        }
+        None => None,
+    };
+
+    let tags = cell("tags")
+        .map(|v| {
+            v.split([',', ';'])
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let specifications = match cell("specifications").map(serde_json::from_str::<serde_json::Value>) {
+        Some(Ok(v)) if v.is_object() => Some(v),
+        Some(_) => {
+            errors.push(RowError::new(row, Some("specifications"), "specifications must be a JSON object".to_string()));
+            None
+        }
+        None => None,
+    };
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(ImportRow {
+        row,
+        name,
+        description: cell("description").map(str::to_string),
+        category,
+        daily_price,
+        deposit_amount,
+        insurance_required,
+        quantity,
+        tags,
+        city: cell("city").map(str::to_string),
+        country: cell("country").map(str::to_string),
+        specifications,
+    })
+}
+
+pub fn read_csv(bytes: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(bytes);
+    reader
+        .records()
+        .map(|record| {
+            record
+                .map(|r| r.iter().map(str::to_string).collect())
+                .map_err(|e| format!("Invalid CSV: {}", e))
+        })
+        .collect()
+}
+
+/// Reads the first worksheet of an XLSX workbook.
+pub fn read_xlsx(bytes: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let mut workbook: Xlsx<_> = Xlsx::new(Cursor::new(bytes.to_vec())).map_err(|e| format!("Invalid XLSX: {}", e))?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or("Workbook has no worksheets")?
+        .map_err(|e| format!("Invalid XLSX: {}", e))?;
+    Ok(range
+        .rows()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+        .collect())
+}
+
+/// Spreadsheet apps evaluate CSV cells starting with these as formulas.
+fn escape_formula(cell: String) -> String {
+    if cell.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", cell)
+    } else {
+        cell
+    }
+}
+
+pub fn write_csv(rows: &[ExportRow]) -> Result<Vec<u8>, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(CATALOG_COLUMNS).map_err(|e| e.to_string())?;
+    for row in rows {
+        writer
+            .write_record(row.cells().into_iter().map(escape_formula))
+            .map_err(|e| e.to_string())?;
+    }
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+pub fn write_xlsx(rows: &[ExportRow]) -> Result<Vec<u8>, String> {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    for (col, header) in CATALOG_COLUMNS.iter().enumerate() {
+        sheet.write_string(0, col as u16, *header).map_err(|e| e.to_string())?;
+    }
+    for (i, row) in rows.iter().enumerate() {
+        for (col, value) in row.cells().iter().enumerate() {
+            sheet.write_string(i as u32 + 1, col as u16, value).map_err(|e| e.to_string())?;
+        }
+    }
+    workbook.save_to_buffer().map_err(|e| e.to_string())
+}
+
+/// Finds a category by id, slug or name. Imports never create categories.
+async fn resolve_category(db: &PgPool, raw: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    if let Ok(id) = Uuid::parse_str(raw) {
+        let row = sqlx::query!("SELECT category_id FROM product_schema.categories WHERE category_id = $1", id)
+            .fetch_optional(db)
+            .await?;
+        if let Some(row) = row {
+            return Ok(Some(row.category_id));
+        }
+    }
+
+    let slug = slugify(raw);
+    let existing = sqlx::query!(
+        r#"
+        SELECT category_id FROM product_schema.categories
+        WHERE slug = $1 OR lower(name) = lower($2)
+        ORDER BY (slug = $1) DESC
+        LIMIT 1
+        "#,
+        slug,
+        raw
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(existing.map(|row| row.category_id))
+}
+
+async fn fail_job(db: &PgPool, job_id: Uuid, reason: &str) {
+    let _ = sqlx::query!(
+        r#"
+        UPDATE product_schema.import_jobs
+        SET status = 'failed', failure_reason = $2, finished_at = NOW(), updated_at = NOW()
+        WHERE job_id = $1
+        "#,
+        job_id,
+        reason
+    )
+    .execute(db)
+    .await;
+}
+
+async fn record_progress(db: &PgPool, job_id: Uuid, processed: usize, created: usize, errors: &[RowError]) {
+    let _ = sqlx::query!(
+        r#"
+        UPDATE product_schema.import_jobs
+        SET processed_rows = $2, created_count = $3, error_count = $4, updated_at = NOW()
+        WHERE job_id = $1
+        "#,
+        job_id,
+        processed as i32,
+        created as i32,
+        errors.len() as i32
+    )
+    .execute(db)
+    .await;
+}
+
+/// Background worker for one import job. Every row is validated and created independently,
+/// so one bad row never blocks the rest of the file.
+pub async fn run_import(
+    db: PgPool,
+    job_id: Uuid,
+    owner_id: Uuid,
+    format: CatalogFormat,
+    bytes: Vec<u8>,
+    mapping: HashMap<String, String>,
+) {
+    let _ = sqlx::query!(
+        "UPDATE product_schema.import_jobs SET status = 'running', started_at = NOW(), updated_at = NOW() WHERE job_id = $1",
+        job_id
+    )
+    .execute(&db)
+    .await;
+
+    let rows = match format {
+        CatalogFormat::Csv => read_csv(&bytes),
+        CatalogFormat::Xlsx => read_xlsx(&bytes),
+    };
+    let rows = match rows {
+        Ok(r) => r,
+        Err(message) => return fail_job(&db, job_id, &message).await,
+    };
+
+    let Some((headers, data)) = rows.split_first() else {
+        return fail_job(&db, job_id, "File is empty").await;
+    };
+
+    let columns = match resolve_columns(headers, &mapping) {
+        Ok(c) => c,
+        Err(message) => return fail_job(&db, job_id, &message).await,
+    };
+
+    // Keep spreadsheet row numbers while skipping blank lines
+    let data: Vec<(usize, &Vec<String>)> = data
+        .iter()
+        .enumerate()
+        .map(|(i, cells)| (i + 2, cells))
+        .filter(|(_, cells)| cells.iter().any(|c| !c.trim().is_empty()))
+        .collect();
+
+    if data.len() > MAX_IMPORT_ROWS {
+        return fail_job(&db, job_id, "File has more than 5000 rows").await;
+    }
+
+    let _ = sqlx::query!(
+        "UPDATE product_schema.import_jobs SET total_rows = $2, updated_at = NOW() WHERE job_id = $1",
+        job_id,
+        data.len() as i32
+    )
+    .execute(&db)
+    .await;
+
+    // Misses are cached too, so a file full of one unknown category costs one lookup
+    let mut categories: HashMap<String, Option<Uuid>> = HashMap::new();
+    let mut errors: Vec<RowError> = Vec::new();
+    let mut created = 0;
+
+    for (processed, (row_number, cells)) in data.iter().enumerate() {
+        match parse_row(*row_number, cells, &columns) {
+            Err(row_errors) => errors.extend(row_errors),
+            Ok(row) => {
+                let key = row.category.to_lowercase();
+                let category_id = match categories.get(&key) {
+                    Some(id) => Ok(*id),
+                    None => resolve_category(&db, &row.category).await.map(|id| {
+                        categories.insert(key, id);
+                        id
+                    }),
+                };
+
+                match category_id {
+                    Err(_) => errors.push(RowError::new(*row_number, Some("category"), "Failed to resolve category".to_string())),
+                    Ok(None) => errors.push(RowError::new(
+                        *row_number,
+                        Some("category"),
+                        format!("Unknown category '{}'", row.category),
+                    )),
+                    Ok(Some(category_id)) => match insert_product(&db, owner_id, &row.into_request(category_id)).await {
+                        Ok(_) => created += 1,
+                        Err(e) => errors.push(RowError {
+                            row: *row_number,
+                            column: None,
+                            message: e.message,
+                            details: e.details,
+                        }),
+                    },
+                }
+            }
+        }
+
+        if (processed + 1) % 50 == 0 {
+            record_progress(&db, job_id, processed + 1, created, &errors).await;
+        }
+    }
+
+    let error_count = errors.len();
+    errors.truncate(MAX_REPORTED_ERRORS);
+
+    let _ = sqlx::query!(
+        r#"
+        UPDATE product_schema.import_jobs
+        SET status = 'completed', processed_rows = $2, created_count = $3, error_count = $4,
+            errors = $5, finished_at = NOW(), updated_at = NOW()
+        WHERE job_id = $1
+        "#,
+        job_id,
+        data.len() as i32,
+        created as i32,
+        error_count as i32,
+        serde_json::to_value(&errors).unwrap_or_default()
+    )
+    .execute(&db)
+    .await;
+}
+
+/// Fails jobs whose worker died without finishing. The upload only lived in that worker's
+/// memory, so the owner has to upload the file again.
+pub async fn reclaim_stale_imports(db: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE product_schema.import_jobs
+        SET status = 'failed', failure_reason = 'Import was interrupted; please upload the file again',
+            finished_at = NOW(), updated_at = NOW()
+        WHERE status IN ('queued', 'running') AND updated_at < NOW() - make_interval(mins => $1)
+        "#,
+        STALE_IMPORT_MINUTES
+    )
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Background loop started by the server; a failed pass is retried on the next tick.
+pub async fn run_import_reclaim_job(db: PgPool) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(IMPORT_RECLAIM_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        match reclaim_stale_imports(&db).await {
+            Ok(0) => {}
+            Ok(reclaimed) => tracing::info!(reclaimed, "failed stale import jobs"),
+            Err(e) => tracing::warn!(error = %e, "import reclaim pass failed"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportJobResponse {
+    pub job_id: Uuid,
+    pub status: String,
+    pub file_name: Option<String>,
+    pub total_rows: i32,
+    pub processed_rows: i32,
+    pub created_count: i32,
+    pub error_count: i32,
+    pub errors: serde_json::Value,
+    pub failure_reason: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportParams {
+    pub format: Option<CatalogFormat>,
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// Multipart fields: `file` (CSV or XLSX), optional `mapping` (JSON object of catalog column
+/// to file header) and optional `format` when the file name has no usable extension.
+pub async fn import_products(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let mut file: Option<(Option<String>, Vec<u8>)> = None;
+    let mut mapping: HashMap<String, String> = HashMap::new();
+    let mut format: Option<CatalogFormat> = None;
+
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(_) => return err(StatusCode::BAD_REQUEST, "Invalid multipart body"),
+        };
+
+        match field.name() {
+            Some("file") => {
+                let file_name = field.file_name().map(str::to_string);
+                match field.bytes().await {
+                    Ok(bytes) => file = Some((file_name, bytes.to_vec())),
+                    Err(_) => return err(StatusCode::BAD_REQUEST, "Failed to read uploaded file"),
+                }
+            }
+            Some("mapping") => {
+                let text = field.text().await.unwrap_or_default();
+                mapping = match serde_json::from_str(&text) {
+                    Ok(m) => m,
+                    Err(_) => return err(StatusCode::BAD_REQUEST, "mapping must be a JSON object of strings"),
+                };
+            }
+            Some("format") => {
+                let text = field.text().await.unwrap_or_default();
+                format = match serde_json::from_value(serde_json::Value::String(text.trim().to_lowercase())) {
+                    Ok(f) => Some(f),
+                    Err(_) => return err(StatusCode::BAD_REQUEST, "format must be csv or xlsx"),
+                };
+            }
+            _ => {}
+        }
+    }
+
+    let (file_name, bytes) = match file {
+        Some(f) => f,
+        None => return err(StatusCode::BAD_REQUEST, "file is required"),
+    };
+
+    let format = match format.or_else(|| file_name.as_deref().and_then(CatalogFormat::from_file_name)) {
+        Some(f) => f,
+        None => return err(StatusCode::BAD_REQUEST, "Unsupported file type; upload a .csv or .xlsx file"),
+    };
+
+    let job = sqlx::query!(
+        r#"
+        INSERT INTO product_schema.import_jobs (owner_id, file_name, format)
+        VALUES ($1, $2, $3)
+        RETURNING job_id
+        "#,
+        user_id,
+        file_name,
+        format.extension()
+    )
+    .fetch_one(&state.db)
+    .await;
+
+    let job_id = match job {
+        Ok(j) => j.job_id,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to start import"),
+    };
+
+    tokio::spawn(run_import(state.db.clone(), job_id, user_id, format, bytes, mapping));
+
+    let response = ok(serde_json::json!({
+        "job_id": job_id,
+        "status": "queued",
+        "message": "Import started"
+    }));
+
+    (StatusCode::ACCEPTED, response).into_response()
+}
+
+pub async fn get_import_job(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(job_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let job = sqlx::query!(
+        r#"
+        SELECT job_id, status, file_name, total_rows, processed_rows, created_count, error_count,
+               errors, failure_reason, created_at, finished_at
+        FROM product_schema.import_jobs
+        WHERE job_id = $1 AND owner_id = $2
+        "#,
+        job_id,
+        user_id
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    match job {
+        Ok(Some(j)) => ok(ImportJobResponse {
+            job_id: j.job_id,
+            status: j.status,
+            file_name: j.file_name,
+            total_rows: j.total_rows,
+            processed_rows: j.processed_rows,
+            created_count: j.created_count,
+            error_count: j.error_count,
+            errors: j.errors,
+            failure_reason: j.failure_reason,
+            created_at: j.created_at,
+            finished_at: j.finished_at,
+        }),
+        Ok(None) => err(StatusCode::NOT_FOUND, "Import job not found"),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch import job"),
+    }
+}
+
+pub async fn export_products(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<ExportParams>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let products = sqlx::query!(
+        r#"
+        SELECT p.name, p.description, c.slug as category, p.daily_price, p.deposit_amount,
+               p.insurance_required, p.address, p.specifications,
+               ARRAY(
+                   SELECT t.name FROM product_schema.product_tags pt
+                   JOIN product_schema.tags t ON t.tag_id = pt.tag_id
+                   WHERE pt.product_id = p.product_id
+                   ORDER BY t.name
+               ) as "tags!",
+               (SELECT COUNT(*) FROM product_schema.product_units u
+                WHERE u.product_id = p.product_id AND u.status = 'active') as "quantity!"
+        FROM product_schema.products p
+        JOIN product_schema.categories c ON c.category_id = p.category_id
+        WHERE p.owner_id = $1 AND p.status <> 'archived'
+        ORDER BY p.created_at
+        "#,
+        user_id
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    let products = match products {
+        Ok(p) => p,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to export products"),
+    };
+
+    let address_field = |address: &Option<serde_json::Value>, field: &str| {
+        address
+            .as_ref()
+            .and_then(|a| a.get(field))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+
+    let rows: Vec<ExportRow> = products
+        .into_iter()
+        .map(|p| ExportRow {
+            city: address_field(&p.address, "city"),
+            country: address_field(&p.address, "country"),
+            name: p.name,
+            description: p.description,
+            category: p.category,
+            daily_price: p.daily_price,
+            deposit_amount: p.deposit_amount,
+            insurance_required: p.insurance_required,
+            quantity: p.quantity,
+            tags: p.tags,
+            specifications: p.specifications,
+        })
+        .collect();
+
+    let format = params.format.unwrap_or(CatalogFormat::Csv);
+    let body = match format {
+        CatalogFormat::Csv => write_csv(&rows),
+        CatalogFormat::Xlsx => write_xlsx(&rows),
+    };
+
+    let body = match body {
+        Ok(b) => b,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to export products"),
+    };
+
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"listings.{}\"", format.extension()),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
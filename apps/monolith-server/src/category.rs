@@ -0,0 +1,549 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::roles::{has_any_role, ROLE_ADMIN};
+use crate::specs::check_spec_schema;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateCategoryRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub parent_category_id: Option<Uuid>,
+    pub slug: Option<String>,
+    pub icon: Option<String>,
+    pub sort_order: Option<i32>,
+    pub spec_schema: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateCategoryRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub slug: Option<String>,
+    pub icon: Option<String>,
+    pub sort_order: Option<i32>,
+    pub spec_schema: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveCategoryRequest {
+    pub parent_category_id: Option<Uuid>,
+    pub sort_order: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteCategoryParams {
+    pub reassign_to: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryResponse {
+    pub category_id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub icon: Option<String>,
+    pub description: Option<String>,
+    pub parent_category_id: Option<Uuid>,
+    pub sort_order: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CategoryTreeNode {
+    pub category_id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub icon: Option<String>,
+    pub description: Option<String>,
+    pub sort_order: i32,
+    pub children: Vec<CategoryTreeNode>,
+}
+
+/// Lowercase ASCII slug: runs of anything that is not a letter or digit become a single dash.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.trim().chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-').to_string();
+    if slug.is_empty() {
+        "category".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Appends `-2`, `-3`, ... until the slug no longer collides with `existing`.
+pub fn unique_slug(base: &str, existing: &[String]) -> String {
+    if !existing.iter().any(|s| s == base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|candidate| !existing.iter().any(|s| s == candidate))
+        .unwrap()
+}
+
+/// True when `candidate` is `ancestor` itself or sits anywhere below it.
+pub fn is_in_subtree(parents: &HashMap<Uuid, Option<Uuid>>, ancestor: Uuid, candidate: Uuid) -> bool {
+    let mut current = Some(candidate);
+    let mut steps = 0;
+    while let Some(id) = current {
+        if id == ancestor {
+            return true;
+        }
+        // Guard against a pre-existing cycle in the data
+        steps += 1;
+        if steps > parents.len() {
+            return false;
+        }
+        current = parents.get(&id).copied().flatten();
+    }
+    false
+}
+
+/// Nests a flat category list. Siblings are ordered by `sort_order`, then name.
+pub fn build_category_tree(categories: Vec<CategoryResponse>) -> Vec<CategoryTreeNode> {
+    let known: Vec<Uuid> = categories.iter().map(|c| c.category_id).collect();
+    let mut by_parent: HashMap<Option<Uuid>, Vec<CategoryResponse>> = HashMap::new();
+    for category in categories {
+        // Categories pointing at a missing parent are surfaced as roots
+        let parent = category.parent_category_id.filter(|p| known.contains(p));
+        by_parent.entry(parent).or_default().push(category);
+    }
+
+    fn attach(parent: Option<Uuid>, by_parent: &mut HashMap<Option<Uuid>, Vec<CategoryResponse>>) -> Vec<CategoryTreeNode> {
+        let mut children = by_parent.remove(&parent).unwrap_or_default();
+        children.sort_by(|a, b| a.sort_order.cmp(&b.sort_order).then_with(|| a.name.cmp(&b.name)));
+        children
+            .into_iter()
+            .map(|c| CategoryTreeNode {
+                children: attach(Some(c.category_id), by_parent),
+                category_id: c.category_id,
+                name: c.name,
+                slug: c.slug,
+                icon: c.icon,
+                description: c.description,
+                sort_order: c.sort_order,
+            })
+            .collect()
+    }
+
+    attach(None, &mut by_parent)
+}
+
+async fn fetch_categories(db: &sqlx::PgPool) -> Result<Vec<CategoryResponse>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT category_id, name, slug, icon, description, parent_category_id, sort_order, created_at
+        FROM product_schema.categories
+        ORDER BY sort_order, name
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|c| CategoryResponse {
+            category_id: c.category_id,
+            name: c.name,
+            slug: c.slug,
+            icon: c.icon,
+            description: c.description,
+            parent_category_id: c.parent_category_id,
+            sort_order: c.sort_order,
+            created_at: c.created_at,
+        })
+        .collect())
+}
+
+/// Parent links of every category, locked for the rest of the transaction so concurrent moves
+/// and deletes check for cycles against the tree the other one leaves behind. `NO KEY` keeps
+/// products that reference a category writable.
+async fn lock_category_parents(
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<HashMap<Uuid, Option<Uuid>>, sqlx::Error> {
+    let rows = sqlx::query!(
+        "SELECT category_id, parent_category_id FROM product_schema.categories FOR NO KEY UPDATE"
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.category_id, r.parent_category_id)).collect())
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// The category tree is shared by every listing, so only admins change it.
+async fn require_admin(state: &AppState, headers: &HeaderMap) -> Result<Uuid, axum::response::Response> {
+    let user_id = match extract_user_id_from_token(headers) {
+        Ok(id) => id,
+        Err(status) => return Err(err(status, "Unauthorized")),
+    };
+
+    match has_any_role(&state.db, user_id, &[ROLE_ADMIN]).await {
+        Ok(true) => Ok(user_id),
+        Ok(false) => Err(err(StatusCode::FORBIDDEN, "Admin access required")),
+        Err(_) => Err(err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to check permissions")),
+    }
+}
+
+async fn slug_taken(db: &sqlx::PgPool, slug: &str, except: Option<Uuid>) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        "SELECT category_id FROM product_schema.categories WHERE slug = $1 AND category_id IS DISTINCT FROM $2",
+        slug,
+        except
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+pub async fn create_category(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateCategoryRequest>,
+) -> impl IntoResponse {
+    if let Err(response) = require_admin(&state, &headers).await {
+        return response;
+    }
+
+    if let Some(Err(message)) = req.spec_schema.as_ref().map(check_spec_schema) {
+        return err(StatusCode::BAD_REQUEST, &message);
+    }
+
+    if let Some(parent_id) = req.parent_category_id {
+        let parent = sqlx::query!(
+            "SELECT category_id FROM product_schema.categories WHERE category_id = $1",
+            parent_id
+        )
+        .fetch_optional(&state.db)
+        .await;
+
+        match parent {
+            Ok(Some(_)) => {}
+            Ok(None) => return err(StatusCode::BAD_REQUEST, "Invalid parent_category_id"),
+            Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create category"),
+        }
+    }
+
+    // An explicit slug must be free; a generated one gets a numeric suffix instead
+    let slug = match req.slug.as_deref() {
+        Some(requested) => {
+            let slug = slugify(requested);
+            match slug_taken(&state.db, &slug, None).await {
+                Ok(false) => slug,
+                Ok(true) => return err(StatusCode::CONFLICT, "Category slug already exists"),
+                Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create category"),
+            }
+        }
+        None => {
+            let base = slugify(&req.name);
+            let existing = sqlx::query!(
+                "SELECT slug FROM product_schema.categories WHERE slug = $1 OR slug LIKE $1 || '-%'",
+                base
+            )
+            .fetch_all(&state.db)
+            .await;
+
+            match existing {
+                Ok(rows) => {
+                    let existing: Vec<String> = rows.into_iter().map(|r| r.slug).collect();
+                    unique_slug(&base, &existing)
+                }
+                Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create category"),
+            }
+        }
+    };
+
+    let category_id = Uuid::new_v4();
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO product_schema.categories
+        (category_id, name, slug, icon, description, parent_category_id, sort_order, spec_schema)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+        category_id,
+        req.name,
+        slug,
+        req.icon,
+        req.description,
+        req.parent_category_id,
+        req.sort_order.unwrap_or(0),
+        req.spec_schema
+    )
+    .execute(&state.db)
+    .await;
+
+    if result.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create category");
+    }
+
+    let response = serde_json::json!({
+        "category_id": category_id,
+        "slug": slug,
+        "message": "Category created successfully"
+    });
+
+    ok(response)
+}
+
+pub async fn list_categories(
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    match fetch_categories(&state.db).await {
+        Ok(categories) => ok(categories),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch categories"),
+    }
+}
+
+pub async fn get_category_tree(
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    match fetch_categories(&state.db).await {
+        Ok(categories) => ok(build_category_tree(categories)),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch categories"),
+    }
+}
+
+pub async fn update_category(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(category_id): Path<Uuid>,
+    Json(req): Json<UpdateCategoryRequest>,
+) -> impl IntoResponse {
+    if let Err(response) = require_admin(&state, &headers).await {
+        return response;
+    }
+
+    if let Some(Err(message)) = req.spec_schema.as_ref().map(check_spec_schema) {
+        return err(StatusCode::BAD_REQUEST, &message);
+    }
+
+    let slug = req.slug.as_deref().map(slugify);
+    if let Some(slug) = &slug {
+        match slug_taken(&state.db, slug, Some(category_id)).await {
+            Ok(false) => {}
+            Ok(true) => return err(StatusCode::CONFLICT, "Category slug already exists"),
+            Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update category"),
+        }
+    }
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE product_schema.categories
+        SET
+            name = COALESCE($2, name),
+            description = COALESCE($3, description),
+            slug = COALESCE($4, slug),
+            icon = COALESCE($5, icon),
+            sort_order = COALESCE($6, sort_order),
+            spec_schema = COALESCE($7, spec_schema),
+            updated_at = NOW()
+        WHERE category_id = $1
+        "#,
+        category_id,
+        req.name,
+        req.description,
+        slug,
+        req.icon,
+        req.sort_order,
+        req.spec_schema
+    )
+    .execute(&state.db)
+    .await;
+
+    match result {
+        Ok(r) if r.rows_affected() == 0 => err(StatusCode::NOT_FOUND, "Category not found"),
+        Ok(_) => ok(serde_json::json!({ "message": "Category updated successfully" })),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update category"),
+    }
+}
+
+pub async fn move_category(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(category_id): Path<Uuid>,
+    Json(req): Json<MoveCategoryRequest>,
+) -> impl IntoResponse {
+    if let Err(response) = require_admin(&state, &headers).await {
+        return response;
+    }
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to move category"),
+    };
+
+    let parents = match lock_category_parents(&mut tx).await {
+        Ok(p) => p,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to move category"),
+    };
+
+    if !parents.contains_key(&category_id) {
+        return err(StatusCode::NOT_FOUND, "Category not found");
+    }
+
+    if let Some(new_parent) = req.parent_category_id {
+        if !parents.contains_key(&new_parent) {
+            return err(StatusCode::BAD_REQUEST, "Invalid parent_category_id");
+        }
+        // Moving a category under itself or one of its descendants would create a cycle
+        if is_in_subtree(&parents, category_id, new_parent) {
+            return err(StatusCode::CONFLICT, "Cannot move a category into its own subtree");
+        }
+    }
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE product_schema.categories
+        SET parent_category_id = $2, sort_order = COALESCE($3, sort_order), updated_at = NOW()
+        WHERE category_id = $1
+        "#,
+        category_id,
+        req.parent_category_id,
+        req.sort_order
+    )
+    .execute(&mut tx)
+    .await;
+
+    if result.is_err() || tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to move category");
+    }
+
+    ok(serde_json::json!({ "message": "Category moved successfully" }))
+}
+
+pub async fn delete_category(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(category_id): Path<Uuid>,
+    Query(params): Query<DeleteCategoryParams>,
+) -> impl IntoResponse {
+    if let Err(response) = require_admin(&state, &headers).await {
+        return response;
+    }
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete category"),
+    };
+
+    let parents = match lock_category_parents(&mut tx).await {
+        Ok(p) => p,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete category"),
+    };
+
+    if !parents.contains_key(&category_id) {
+        return err(StatusCode::NOT_FOUND, "Category not found");
+    }
+
+    if let Some(target) = params.reassign_to {
+        if !parents.contains_key(&target) {
+            return err(StatusCode::BAD_REQUEST, "Invalid reassign_to category");
+        }
+        if is_in_subtree(&parents, category_id, target) {
+            return err(StatusCode::BAD_REQUEST, "Cannot reassign into the category being deleted");
+        }
+    }
+
+    let usage = sqlx::query!(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM product_schema.products WHERE category_id = $1) as product_count,
+            (SELECT COUNT(*) FROM product_schema.categories WHERE parent_category_id = $1) as child_count
+        "#,
+        category_id
+    )
+    .fetch_one(&mut tx)
+    .await;
+
+    let (product_count, child_count) = match usage {
+        Ok(u) => (u.product_count.unwrap_or(0), u.child_count.unwrap_or(0)),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete category"),
+    };
+
+    if product_count + child_count > 0 {
+        let target = match params.reassign_to {
+            Some(t) => t,
+            None => return err(StatusCode::CONFLICT, "Category still has products or subcategories; pass reassign_to"),
+        };
+
+        let moved_products = sqlx::query!(
+            "UPDATE product_schema.products SET category_id = $2, updated_at = NOW() WHERE category_id = $1",
+            category_id,
+            target
+        )
+        .execute(&mut tx)
+        .await;
+
+        let moved_children = sqlx::query!(
+            "UPDATE product_schema.categories SET parent_category_id = $2, updated_at = NOW() WHERE parent_category_id = $1",
+            category_id,
+            target
+        )
+        .execute(&mut tx)
+        .await;
+
+        if moved_products.is_err() || moved_children.is_err() {
+            return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to reassign category contents");
+        }
+    }
+
+    let result = sqlx::query!(
+        "DELETE FROM product_schema.categories WHERE category_id = $1",
+        category_id
+    )
+    .execute(&mut tx)
+    .await;
+
+    if result.is_err() || tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete category");
+    }
+
+    ok(serde_json::json!({
+        "message": "Category deleted successfully",
+        "reassigned_products": product_count,
+        "reassigned_subcategories": child_count
+    }))
+}
@@ -0,0 +1,760 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use axum::{
+    extract::{Multipart, Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::blob_store::{default_blob_store, BlobError};
+use crate::rental_status::{RentalRole, RentalStatus};
+use crate::roles::{has_any_role, ROLE_ADMIN};
+use crate::etag::check_if_match;
+
+pub const MAX_CHECKLIST_ITEMS: usize = 50;
+pub const MAX_METER_READINGS: usize = 20;
+pub const MAX_REPORT_NOTES_LEN: usize = 5000;
+pub const MAX_CHECKLIST_ITEM_LEN: usize = 100;
+pub const MAX_CHECKLIST_NOTE_LEN: usize = 500;
+pub const MAX_METER_NAME_LEN: usize = 50;
+pub const MAX_PHOTOS_PER_REPORT: i64 = 30;
+pub const MAX_PHOTO_BYTES: usize = 10 * 1024 * 1024;
+pub const MAX_PHOTO_CAPTION_LEN: usize = 200;
+
+/// Content types accepted for report photos, with the extension used in blob keys.
+pub const PHOTO_CONTENT_TYPES: &[(&str, &str)] =
+    &[("image/jpeg", "jpg"), ("image/png", "png"), ("image/webp", "webp"), ("image/heic", "heic")];
+
+/// When in the rental a handover report is taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandoverStage {
+    Pickup,
+    Return,
+}
+
+impl HandoverStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HandoverStage::Pickup => "pickup",
+            HandoverStage::Return => "return",
+        }
+    }
+
+    /// Rental states in which the report for this stage can still be written.
+    pub fn open_during(&self) -> &'static [RentalStatus] {
+        match self {
+            HandoverStage::Pickup => &[RentalStatus::Confirmed, RentalStatus::Active],
+            HandoverStage::Return => &[RentalStatus::Active, RentalStatus::Overdue, RentalStatus::Returned],
+        }
+    }
+}
+
+impl fmt::Display for HandoverStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for HandoverStage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pickup" => Ok(HandoverStage::Pickup),
+            "return" => Ok(HandoverStage::Return),
+            other => Err(format!("Unknown handover stage '{}'", other)),
+        }
+    }
+}
+
+/// Condition of one checklist item, best first, so a higher value is worse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemCondition {
+    Excellent,
+    Good,
+    Fair,
+    Damaged,
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub item: String,
+    pub condition: ItemCondition,
+    pub note: Option<String>,
+}
+
+/// What the parties record at a handover; the body of `PUT .../condition-reports/:stage`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConditionReportContent {
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    pub notes: Option<String>,
+    /// e.g. `{"odometer_km": 10452, "fuel_percent": 75}`
+    #[serde(default)]
+    pub meter_readings: BTreeMap<String, f64>,
+}
+
+impl ConditionReportContent {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.checklist.len() > MAX_CHECKLIST_ITEMS {
+            return Err(format!("checklist can have at most {} items", MAX_CHECKLIST_ITEMS));
+        }
+        let mut seen = HashSet::new();
+        for entry in &self.checklist {
+            let name = entry.item.trim();
+            if name.is_empty() || name.chars().count() > MAX_CHECKLIST_ITEM_LEN {
+                return Err(format!("checklist item names must be 1 to {} characters", MAX_CHECKLIST_ITEM_LEN));
+            }
+            if !seen.insert(item_key(name)) {
+                return Err(format!("checklist item '{}' appears twice", name));
+            }
+            if entry.note.as_deref().is_some_and(|n| n.chars().count() > MAX_CHECKLIST_NOTE_LEN) {
+                return Err(format!("checklist notes must be at most {} characters", MAX_CHECKLIST_NOTE_LEN));
+            }
+        }
+        if self.notes.as_deref().is_some_and(|n| n.chars().count() > MAX_REPORT_NOTES_LEN) {
+            return Err(format!("notes must be at most {} characters", MAX_REPORT_NOTES_LEN));
+        }
+        if self.meter_readings.len() > MAX_METER_READINGS {
+            return Err(format!("at most {} meter readings are allowed", MAX_METER_READINGS));
+        }
+        for (name, value) in &self.meter_readings {
+            if name.trim().is_empty() || name.chars().count() > MAX_METER_NAME_LEN {
+                return Err(format!("meter names must be 1 to {} characters", MAX_METER_NAME_LEN));
+            }
+            if !value.is_finite() || *value < 0.0 {
+                return Err(format!("meter reading '{}' must be a non-negative number", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn item_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// One checklist item at pickup and at return.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChecklistComparison {
+    pub item: String,
+    pub pickup: Option<ItemCondition>,
+    #[serde(rename = "return")]
+    pub return_condition: Option<ItemCondition>,
+    /// Recorded at both handovers and in worse condition at return.
+    pub worsened: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeterComparison {
+    pub name: String,
+    pub pickup: Option<f64>,
+    #[serde(rename = "return")]
+    pub return_value: Option<f64>,
+    pub delta: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReportComparison {
+    pub checklist: Vec<ChecklistComparison>,
+    pub meter_readings: Vec<MeterComparison>,
+}
+
+/// Lines the pickup and return reports up side by side. Items match by name, ignoring case;
+/// pickup order comes first, then anything only listed at return.
+pub fn compare_reports(
+    pickup: Option<&ConditionReportContent>,
+    returned: Option<&ConditionReportContent>,
+) -> ReportComparison {
+    let empty = ConditionReportContent::default();
+    let (pickup, returned) = (pickup.unwrap_or(&empty), returned.unwrap_or(&empty));
+
+    let find = |content: &ConditionReportContent, name: &str| {
+        content.checklist.iter().find(|c| item_key(&c.item) == item_key(name)).map(|c| c.condition)
+    };
+
+    let mut checklist: Vec<ChecklistComparison> = pickup
+        .checklist
+        .iter()
+        .map(|entry| {
+            let return_condition = find(returned, &entry.item);
+            ChecklistComparison {
+                item: entry.item.trim().to_string(),
+                pickup: Some(entry.condition),
+                return_condition,
+                worsened: return_condition.is_some_and(|r| r > entry.condition),
+            }
+        })
+        .collect();
+    checklist.extend(
+        returned
+            .checklist
+            .iter()
+            .filter(|entry| find(pickup, &entry.item).is_none())
+            .map(|entry| ChecklistComparison {
+                item: entry.item.trim().to_string(),
+                pickup: None,
+                return_condition: Some(entry.condition),
+                worsened: false,
+            }),
+    );
+
+    let names: BTreeSet<&String> =
+        pickup.meter_readings.keys().chain(returned.meter_readings.keys()).collect();
+    let meter_readings = names
+        .into_iter()
+        .map(|name| {
+            let (before, after) = (pickup.meter_readings.get(name).copied(), returned.meter_readings.get(name).copied());
+            MeterComparison {
+                name: name.clone(),
+                pickup: before,
+                return_value: after,
+                delta: before.zip(after).map(|(b, a)| a - b),
+            }
+        })
+        .collect();
+
+    ReportComparison { checklist, meter_readings }
+}
+
+pub fn photo_extension(content_type: &str) -> Option<&'static str> {
+    PHOTO_CONTENT_TYPES.iter().find(|(ct, _)| *ct == content_type).map(|(_, ext)| *ext)
+}
+
+pub fn photo_blob_key(rental_id: Uuid, report_id: Uuid, photo_id: Uuid, extension: &str) -> String {
+    format!("condition-reports/{}/{}/{}.{}", rental_id, report_id, photo_id, extension)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConditionPhotoResponse {
+    pub photo_id: Uuid,
+    pub url: String,
+    pub content_type: String,
+    pub caption: Option<String>,
+    pub uploaded_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConditionReportResponse {
+    pub report_id: Uuid,
+    pub stage: HandoverStage,
+    #[serde(flatten)]
+    pub content: ConditionReportContent,
+    pub photos: Vec<ConditionPhotoResponse>,
+    pub updated_by: Uuid,
+    pub renter_signed_at: Option<DateTime<Utc>>,
+    pub owner_signed_at: Option<DateTime<Utc>>,
+    /// Signed by both parties; the report can no longer change.
+    pub signed_by_both: bool,
+    /// Bumped whenever the content or photos change. Signing requires it as `If-Match`, so a
+    /// party only signs the version they reviewed.
+    pub version: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConditionReportsResponse {
+    pub pickup: Option<ConditionReportResponse>,
+    #[serde(rename = "return")]
+    pub return_report: Option<ConditionReportResponse>,
+    pub comparison: ReportComparison,
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// The caller's role on the rental and its status. Admins can read every report but only the
+/// renter and owner write and sign.
+async fn rental_access(
+    db: &PgPool,
+    rental_id: Uuid,
+    user_id: Uuid,
+) -> Result<(RentalRole, RentalStatus), (StatusCode, String)> {
+    let failed = || (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load rental".to_string());
+    let not_found = || (StatusCode::NOT_FOUND, "Rental not found".to_string());
+
+    let rental = sqlx::query!(
+        r#"
+        SELECT r.status, r.renter_id, p.owner_id
+        FROM rental_schema.rentals r
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE r.rental_id = $1
+        "#,
+        rental_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|_| failed())?
+    .ok_or_else(not_found)?;
+
+    let status = rental.status.parse::<RentalStatus>().map_err(|m| (StatusCode::INTERNAL_SERVER_ERROR, m))?;
+
+    let role = if rental.renter_id == user_id {
+        RentalRole::Renter
+    } else if rental.owner_id == user_id {
+        RentalRole::Owner
+    } else if has_any_role(db, user_id, &[ROLE_ADMIN]).await.map_err(|_| failed())? {
+        RentalRole::Admin
+    } else {
+        return Err(not_found());
+    };
+
+    Ok((role, status))
+}
+
+/// Write access for one of the parties while the stage is open.
+async fn writable_stage(
+    db: &PgPool,
+    rental_id: Uuid,
+    user_id: Uuid,
+    stage: HandoverStage,
+) -> Result<RentalRole, (StatusCode, String)> {
+    let (role, status) = rental_access(db, rental_id, user_id).await?;
+    if role == RentalRole::Admin {
+        return Err((StatusCode::FORBIDDEN, "Only the renter and owner can edit condition reports".to_string()));
+    }
+    if !stage.open_during().contains(&status) {
+        return Err((StatusCode::CONFLICT, format!("The {} report cannot be changed on a {} rental", stage, status)));
+    }
+    Ok(role)
+}
+
+async fn load_reports(db: &PgPool, rental_id: Uuid) -> Result<Vec<ConditionReportResponse>, sqlx::Error> {
+    let reports = sqlx::query!(
+        r#"
+        SELECT report_id, stage, checklist, notes, meter_readings, updated_by, renter_signed_at,
+               owner_signed_at, version, updated_at
+        FROM rental_schema.condition_reports
+        WHERE rental_id = $1
+        "#,
+        rental_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    let photos = sqlx::query!(
+        r#"
+        SELECT p.photo_id, p.report_id, p.content_type, p.caption, p.uploaded_by, p.created_at
+        FROM rental_schema.condition_report_photos p
+        JOIN rental_schema.condition_reports r ON p.report_id = r.report_id
+        WHERE r.rental_id = $1
+        ORDER BY p.created_at, p.photo_id
+        "#,
+        rental_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(reports
+        .into_iter()
+        .filter_map(|r| {
+            let stage = r.stage.parse::<HandoverStage>().ok()?;
+            let photos = photos
+                .iter()
+                .filter(|p| p.report_id == r.report_id)
+                .map(|p| ConditionPhotoResponse {
+                    photo_id: p.photo_id,
+                    url: format!("/api/v1/rental/rentals/{}/condition-reports/photos/{}", rental_id, p.photo_id),
+                    content_type: p.content_type.clone(),
+                    caption: p.caption.clone(),
+                    uploaded_by: p.uploaded_by,
+                    created_at: p.created_at,
+                })
+                .collect();
+            Some(ConditionReportResponse {
+                report_id: r.report_id,
+                stage,
+                content: ConditionReportContent {
+                    checklist: serde_json::from_value(r.checklist).unwrap_or_default(),
+                    notes: r.notes,
+                    meter_readings: serde_json::from_value(r.meter_readings).unwrap_or_default(),
+                },
+                photos,
+                updated_by: r.updated_by,
+                renter_signed_at: r.renter_signed_at,
+                owner_signed_at: r.owner_signed_at,
+                signed_by_both: r.renter_signed_at.is_some() && r.owner_signed_at.is_some(),
+                version: r.version,
+                updated_at: r.updated_at,
+            })
+        })
+        .collect())
+}
+
+async fn reports_response(db: &PgPool, rental_id: Uuid) -> Response {
+    let mut reports = match load_reports(db, rental_id).await {
+        Ok(r) => r,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch condition reports"),
+    };
+
+    let mut take = |stage: HandoverStage| reports.iter().position(|r| r.stage == stage).map(|i| reports.remove(i));
+    let pickup = take(HandoverStage::Pickup);
+    let return_report = take(HandoverStage::Return);
+    let comparison = compare_reports(pickup.as_ref().map(|r| &r.content), return_report.as_ref().map(|r| &r.content));
+
+    ok(ConditionReportsResponse { pickup, return_report, comparison })
+}
+
+/// Both handover reports side by side, with the checklist and meters compared.
+pub async fn get_condition_reports(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    if let Err((status, message)) = rental_access(&state.db, rental_id, user_id).await {
+        return err(status, &message);
+    }
+
+    reports_response(&state.db, rental_id).await
+}
+
+/// Creates or replaces the report for a stage. Any change clears both signatures; a report
+/// signed by both parties is final.
+pub async fn put_condition_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((rental_id, stage)): Path<(Uuid, HandoverStage)>,
+    Json(content): Json<ConditionReportContent>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    if let Err(message) = content.validate() {
+        return err(StatusCode::BAD_REQUEST, &message);
+    }
+
+    if let Err((status, message)) = writable_stage(&state.db, rental_id, user_id, stage).await {
+        return err(status, &message);
+    }
+
+    let saved = sqlx::query!(
+        r#"
+        INSERT INTO rental_schema.condition_reports
+        (rental_id, stage, checklist, notes, meter_readings, created_by, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $6)
+        ON CONFLICT (rental_id, stage) DO UPDATE
+        SET checklist = EXCLUDED.checklist,
+            notes = EXCLUDED.notes,
+            meter_readings = EXCLUDED.meter_readings,
+            updated_by = EXCLUDED.updated_by,
+            renter_signed_at = NULL,
+            owner_signed_at = NULL,
+            version = condition_reports.version + 1,
+            updated_at = NOW()
+        WHERE condition_reports.renter_signed_at IS NULL OR condition_reports.owner_signed_at IS NULL
+        RETURNING report_id
+        "#,
+        rental_id,
+        stage.as_str(),
+        serde_json::to_value(&content.checklist).unwrap_or_default(),
+        content.notes.as_deref().map(str::trim).filter(|n| !n.is_empty()),
+        serde_json::to_value(&content.meter_readings).unwrap_or_default(),
+        user_id
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    match saved {
+        Ok(Some(_)) => reports_response(&state.db, rental_id).await,
+        Ok(None) => err(StatusCode::CONFLICT, "This report was signed by both parties and can no longer change"),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save condition report"),
+    }
+}
+
+/// Multipart fields: `photo` (JPEG, PNG, WebP or HEIC, up to 10 MB) and an optional `caption`.
+pub async fn upload_condition_photo(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((rental_id, stage)): Path<(Uuid, HandoverStage)>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    if let Err((status, message)) = writable_stage(&state.db, rental_id, user_id, stage).await {
+        return err(status, &message);
+    }
+
+    let mut photo: Option<(String, Vec<u8>)> = None;
+    let mut caption: Option<String> = None;
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(_) => return err(StatusCode::BAD_REQUEST, "Invalid multipart body"),
+        };
+
+        match field.name() {
+            Some("photo") => {
+                let content_type = field.content_type().unwrap_or_default().to_string();
+                match field.bytes().await {
+                    Ok(bytes) => photo = Some((content_type, bytes.to_vec())),
+                    Err(_) => return err(StatusCode::BAD_REQUEST, "Failed to read uploaded photo"),
+                }
+            }
+            Some("caption") => {
+                let text = field.text().await.unwrap_or_default();
+                caption = Some(text.trim().to_string()).filter(|c| !c.is_empty());
+            }
+            _ => {}
+        }
+    }
+
+    let (content_type, bytes) = match photo {
+        Some(p) => p,
+        None => return err(StatusCode::BAD_REQUEST, "photo is required"),
+    };
+    let extension = match photo_extension(&content_type) {
+        Some(ext) => ext,
+        None => return err(StatusCode::UNSUPPORTED_MEDIA_TYPE, "Photos must be JPEG, PNG, WebP or HEIC"),
+    };
+    if bytes.is_empty() || bytes.len() > MAX_PHOTO_BYTES {
+        return err(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            &format!("Photos must be at most {} MB", MAX_PHOTO_BYTES / (1024 * 1024)),
+        );
+    }
+    if caption.as_deref().is_some_and(|c| c.chars().count() > MAX_PHOTO_CAPTION_LEN) {
+        return err(
+            StatusCode::BAD_REQUEST,
+            &format!("caption must be at most {} characters", MAX_PHOTO_CAPTION_LEN),
+        );
+    }
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to upload photo"),
+    };
+
+    let report = sqlx::query!(
+        r#"
+        SELECT report_id, renter_signed_at, owner_signed_at,
+               (SELECT COUNT(*) FROM rental_schema.condition_report_photos p
+                WHERE p.report_id = r.report_id) AS "photo_count!"
+        FROM rental_schema.condition_reports r
+        WHERE rental_id = $1 AND stage = $2
+        FOR UPDATE
+        "#,
+        rental_id,
+        stage.as_str()
+    )
+    .fetch_optional(&mut tx)
+    .await;
+
+    let report = match report {
+        Ok(Some(r)) => r,
+        Ok(None) => return err(StatusCode::NOT_FOUND, &format!("Create the {} report before adding photos", stage)),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to upload photo"),
+    };
+    if report.renter_signed_at.is_some() && report.owner_signed_at.is_some() {
+        return err(StatusCode::CONFLICT, "This report was signed by both parties and can no longer change");
+    }
+    if report.photo_count >= MAX_PHOTOS_PER_REPORT {
+        return err(StatusCode::CONFLICT, "This report already has the maximum number of photos");
+    }
+
+    let photo_id = Uuid::new_v4();
+    let size_bytes = bytes.len() as i64;
+    let key = photo_blob_key(rental_id, report.report_id, photo_id, extension);
+    let stored = {
+        let key = key.clone();
+        tokio::task::spawn_blocking(move || default_blob_store().put(&key, &bytes)).await
+    };
+    if !matches!(stored, Ok(Ok(()))) {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to store photo");
+    }
+
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO rental_schema.condition_report_photos
+        (photo_id, report_id, blob_key, content_type, size_bytes, caption, uploaded_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        photo_id,
+        report.report_id,
+        key,
+        content_type,
+        size_bytes,
+        caption,
+        user_id
+    )
+    .execute(&mut tx)
+    .await;
+
+    // New evidence needs a fresh sign-off from both parties
+    let cleared = sqlx::query!(
+        r#"
+        UPDATE rental_schema.condition_reports
+        SET renter_signed_at = NULL, owner_signed_at = NULL, updated_by = $2, version = version + 1,
+            updated_at = NOW()
+        WHERE report_id = $1
+        "#,
+        report.report_id,
+        user_id
+    )
+    .execute(&mut tx)
+    .await;
+
+    if inserted.is_err() || cleared.is_err() || tx.commit().await.is_err() {
+        // Best effort: an orphaned blob is harmless but wastes space
+        let _ = tokio::task::spawn_blocking(move || default_blob_store().delete(&key)).await;
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to upload photo");
+    }
+
+    reports_response(&state.db, rental_id).await
+}
+
+/// Signs the caller's side of a report. `If-Match` must carry the report's `version`, so a
+/// change made after the caller reviewed it is never signed. Signing twice keeps the first
+/// signature.
+pub async fn sign_condition_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((rental_id, stage)): Path<(Uuid, HandoverStage)>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let role = match writable_stage(&state.db, rental_id, user_id, stage).await {
+        Ok(role) => role,
+        Err((status, message)) => return err(status, &message),
+    };
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to sign condition report"),
+    };
+
+    let report = sqlx::query!(
+        r#"
+        SELECT report_id, version FROM rental_schema.condition_reports
+        WHERE rental_id = $1 AND stage = $2
+        FOR UPDATE
+        "#,
+        rental_id,
+        stage.as_str()
+    )
+    .fetch_optional(&mut tx)
+    .await;
+
+    let report = match report {
+        Ok(Some(r)) => r,
+        Ok(None) => return err(StatusCode::NOT_FOUND, &format!("No {} report to sign", stage)),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to sign condition report"),
+    };
+
+    if let Err(e) = check_if_match(&headers, report.version) {
+        return e.into_response();
+    }
+
+    let signed = sqlx::query!(
+        r#"
+        UPDATE rental_schema.condition_reports
+        SET renter_signed_at = CASE WHEN $2 = 'renter' THEN COALESCE(renter_signed_at, NOW()) ELSE renter_signed_at END,
+            owner_signed_at = CASE WHEN $2 = 'owner' THEN COALESCE(owner_signed_at, NOW()) ELSE owner_signed_at END
+        WHERE report_id = $1
+        "#,
+        report.report_id,
+        role.as_str()
+    )
+    .execute(&mut tx)
+    .await;
+
+    if signed.is_err() || tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to sign condition report");
+    }
+
+    reports_response(&state.db, rental_id).await
+}
+
+pub async fn get_condition_photo(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((rental_id, photo_id)): Path<(Uuid, Uuid)>,
+) -> Response {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    if let Err((status, message)) = rental_access(&state.db, rental_id, user_id).await {
+        return err(status, &message);
+    }
+
+    let photo = sqlx::query!(
+        r#"
+        SELECT p.blob_key, p.content_type
+        FROM rental_schema.condition_report_photos p
+        JOIN rental_schema.condition_reports r ON p.report_id = r.report_id
+        WHERE p.photo_id = $1 AND r.rental_id = $2
+        "#,
+        photo_id,
+        rental_id
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    let photo = match photo {
+        Ok(Some(p)) => p,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Photo not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch photo"),
+    };
+
+    let key = photo.blob_key;
+    match tokio::task::spawn_blocking(move || default_blob_store().get(&key)).await {
+        // Stored bytes are only checked by declared type, so browsers must not sniff them
+        Ok(Ok(bytes)) => (
+            [(header::CONTENT_TYPE, photo.content_type), (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string())],
+            bytes,
+        )
+            .into_response(),
+        Ok(Err(BlobError::NotFound)) => err(StatusCode::NOT_FOUND, "Photo not found"),
+        _ => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch photo"),
+    }
+}
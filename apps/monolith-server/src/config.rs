@@ -6,13 +6,27 @@ use sqlx::{postgres::PgPoolOptions, PgPool};
 pub struct AppConfig {
     pub bind_addr: String,
     pub database_url: String,
+    /// Hours an owner has to answer a rental request before it expires; `RENTAL_REQUEST_TTL_HOURS`, default 48.
+    pub rental_request_ttl_hours: i64,
+    /// Key signing pagination cursors; `CURSOR_SECRET`, optional. Without it each process signs
+    /// with a random key, so cursors stop working after a restart and across replicas.
+    pub cursor_secret: Option<String>,
 }
 
 impl AppConfig {
     pub fn from_env() -> anyhow::Result<Self> {
         let bind_addr = std::env::var("BIND_ADDR").unwrap_or_else(|_| "0.0.0.0:8000".into());
         let database_url = std::env::var("DATABASE_URL").context("DATABASE_URL missing")?;
-        Ok(Self { bind_addr, database_url })
+        let rental_request_ttl_hours = match std::env::var("RENTAL_REQUEST_TTL_HOURS") {
+            Ok(v) => v
+                .parse::<i64>()
+                .ok()
+                .filter(|h| *h > 0)
+                .context("RENTAL_REQUEST_TTL_HOURS must be a positive number of hours")?,
+            Err(_) => 48,
+        };
+        let cursor_secret = std::env::var("CURSOR_SECRET").ok().filter(|s| !s.is_empty());
+        Ok(Self { bind_addr, database_url, rental_request_ttl_hours, cursor_secret })
     }
 
     pub async fn make_db_pool(&self) -> anyhow::Result<PgPool> {
@@ -0,0 +1,860 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::escrow::{held_deposit_cents, settle_deposit, DepositSettlement};
+use crate::notification::{send_notification, NewNotification, KIND_DAMAGE_CLAIM_OPENED, KIND_DAMAGE_CLAIM_RESOLVED};
+use crate::pagination::{page_size, DEFAULT_PAGE_SIZE};
+use crate::payment::settle_rental_payments;
+use crate::rental_status::{record_rental_history, transition_rental, RentalRole, RentalStatus};
+use crate::roles::{has_any_role, ROLE_ADMIN, ROLE_SUPPORT};
+
+/// How long after the return an owner can open a claim.
+pub const CLAIM_WINDOW_HOURS: i64 = 72;
+/// How long the renter has to answer before support may resolve without them.
+pub const RESPONSE_WINDOW_HOURS: i64 = 72;
+pub const MAX_CLAIM_TEXT_LEN: usize = 5000;
+pub const MAX_EVIDENCE_PHOTOS: usize = 20;
+pub const DEPOSIT_RELEASE_INTERVAL_SECS: u64 = 900;
+
+const UNIQUE_VIOLATION: &str = "23505";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimStatus {
+    Open,
+    /// The renter has answered; waiting on support.
+    Responded,
+    Resolved,
+}
+
+impl ClaimStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClaimStatus::Open => "open",
+            ClaimStatus::Responded => "responded",
+            ClaimStatus::Resolved => "resolved",
+        }
+    }
+}
+
+impl fmt::Display for ClaimStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ClaimStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(ClaimStatus::Open),
+            "responded" => Ok(ClaimStatus::Responded),
+            "resolved" => Ok(ClaimStatus::Resolved),
+            other => Err(format!("Unknown claim status '{}'", other)),
+        }
+    }
+}
+
+/// Support's decision: capture the claimed amount from the deposit, part of it, or nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimOutcome {
+    Full,
+    Partial,
+    Denied,
+}
+
+impl ClaimOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClaimOutcome::Full => "full",
+            ClaimOutcome::Partial => "partial",
+            ClaimOutcome::Denied => "denied",
+        }
+    }
+}
+
+impl fmt::Display for ClaimOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenClaimRequest {
+    pub amount_cents: i64,
+    pub description: String,
+    /// Condition report photos of this rental backing the claim.
+    #[serde(default)]
+    pub evidence_photo_ids: Vec<Uuid>,
+}
+
+impl OpenClaimRequest {
+    /// Claims are paid out of the deposit, so they cannot ask for more than is held.
+    pub fn validate(&self, held_cents: i64) -> Result<(), String> {
+        if held_cents <= 0 {
+            return Err("No deposit is held for this rental".to_string());
+        }
+        if self.amount_cents <= 0 || self.amount_cents > held_cents {
+            return Err(format!("amount_cents must be between 1 and the {} cents held as deposit", held_cents));
+        }
+        let description = self.description.trim();
+        if description.is_empty() || description.chars().count() > MAX_CLAIM_TEXT_LEN {
+            return Err(format!("description must be 1 to {} characters", MAX_CLAIM_TEXT_LEN));
+        }
+        if self.evidence_photo_ids.len() > MAX_EVIDENCE_PHOTOS {
+            return Err(format!("At most {} evidence photos can be attached", MAX_EVIDENCE_PHOTOS));
+        }
+        if self.evidence_photo_ids.iter().collect::<HashSet<_>>().len() != self.evidence_photo_ids.len() {
+            return Err("evidence_photo_ids must not repeat".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RespondToClaimRequest {
+    /// Whether the renter agrees to pay the claimed amount.
+    pub accepts: bool,
+    pub response: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveClaimRequest {
+    pub outcome: ClaimOutcome,
+    /// Required for a partial capture; less than the claimed amount.
+    pub captured_cents: Option<i64>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClaimFilters {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DamageClaimResponse {
+    pub claim_id: Uuid,
+    pub rental_id: Uuid,
+    pub opened_by: Uuid,
+    pub amount_cents: i64,
+    pub description: String,
+    pub evidence_photo_ids: Vec<Uuid>,
+    pub status: String,
+    pub renter_accepts: Option<bool>,
+    pub renter_response: Option<String>,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub outcome: Option<String>,
+    pub captured_cents: Option<i64>,
+    pub released_cents: Option<i64>,
+    pub resolution_note: Option<String>,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Policy the rental was booked with, if the renter bought one.
+    pub insurance_policy_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub fn claim_window_open(returned_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    now < returned_at + Duration::hours(CLAIM_WINDOW_HOURS)
+}
+
+/// Support resolves once the renter has answered, or once they have had their chance to.
+pub fn resolvable(status: ClaimStatus, opened_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    match status {
+        ClaimStatus::Responded => true,
+        ClaimStatus::Open => now >= opened_at + Duration::hours(RESPONSE_WINDOW_HOURS),
+        ClaimStatus::Resolved => false,
+    }
+}
+
+/// Cents captured from a deposit of `held_cents` for a claim of `claim_cents`.
+pub fn capture_for(
+    outcome: ClaimOutcome,
+    captured_cents: Option<i64>,
+    claim_cents: i64,
+    held_cents: i64,
+) -> Result<i64, String> {
+    let captured = match outcome {
+        ClaimOutcome::Full => claim_cents,
+        ClaimOutcome::Denied => 0,
+        ClaimOutcome::Partial => match captured_cents {
+            Some(cents) if cents > 0 && cents < claim_cents => cents,
+            _ => return Err(format!("captured_cents must be between 1 and {} for a partial capture", claim_cents - 1)),
+        },
+    };
+    Ok(captured.clamp(0, held_cents.max(0)))
+}
+
+/// Whether the rental has a claim support has not resolved yet. Such rentals are closed
+/// through the claim rather than the plain status endpoints.
+pub async fn has_unresolved_claim(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        "SELECT claim_id FROM rental_schema.damage_claims WHERE rental_id = $1 AND status <> 'resolved'",
+        rental_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    Ok(row.is_some())
+}
+
+async fn fetch_claim(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+) -> Result<Option<DamageClaimResponse>, sqlx::Error> {
+    sqlx::query_as!(
+        DamageClaimResponse,
+        r#"
+        SELECT claim_id, rental_id, opened_by, amount_cents, description, evidence_photo_ids, status,
+               renter_accepts, renter_response, responded_at, outcome, captured_cents, released_cents,
+               resolution_note, resolved_by, resolved_at, insurance_policy_id, created_at
+        FROM rental_schema.damage_claims
+        WHERE rental_id = $1
+        "#,
+        rental_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+}
+
+/// Completes a returned rental nobody claimed against within `CLAIM_WINDOW_HOURS` and releases
+/// its deposit. Returns `false` when a claim was opened or another instance holds the rental.
+async fn release_unclaimed_deposit(db: &PgPool, rental_id: Uuid) -> Result<bool, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let rental = sqlx::query!(
+        r#"
+        SELECT rental_id FROM rental_schema.rentals
+        WHERE rental_id = $1 AND status = 'returned'
+        FOR UPDATE SKIP LOCKED
+        "#,
+        rental_id
+    )
+    .fetch_optional(&mut tx)
+    .await?;
+
+    if rental.is_none() || has_unresolved_claim(&mut tx, rental_id).await? {
+        return Ok(false);
+    }
+
+    sqlx::query!(
+        "UPDATE rental_schema.rentals SET status = $2, updated_at = NOW() WHERE rental_id = $1",
+        rental_id,
+        RentalStatus::Completed.as_str()
+    )
+    .execute(&mut tx)
+    .await?;
+
+    record_rental_history(
+        &mut tx,
+        rental_id,
+        Some(RentalStatus::Returned),
+        RentalStatus::Completed,
+        None,
+        None,
+        Some("No damage claim within the claim window"),
+    )
+    .await?;
+
+    if let Err((_, message)) = settle_deposit(&mut tx, rental_id, 0, None, "deposit released").await {
+        tracing::warn!(%rental_id, error = %message, "could not release deposit");
+        return Ok(false);
+    }
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// One pass of the release job: completes every returned rental whose claim window has closed
+/// and pays out its deposit. Returns how many were released.
+pub async fn release_unclaimed_deposits(db: &PgPool) -> Result<usize, sqlx::Error> {
+    let due = sqlx::query!(
+        r#"
+        SELECT r.rental_id
+        FROM rental_schema.rentals r
+        WHERE r.status = 'returned'
+          AND (SELECT MAX(h.created_at) FROM rental_schema.rental_status_history h
+               WHERE h.rental_id = r.rental_id AND h.to_status = 'returned') <= NOW() - make_interval(hours => $1::int)
+        "#,
+        CLAIM_WINDOW_HOURS as i32
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut released = 0;
+    for rental in due {
+        match release_unclaimed_deposit(db, rental.rental_id).await {
+            Ok(true) => {
+                released += 1;
+                if let Err(e) = settle_rental_payments(db, Some(rental.rental_id)).await {
+                    tracing::warn!(rental_id = %rental.rental_id, error = %e, "settling rental payments failed");
+                }
+            }
+            Ok(false) => {}
+            // One rental failing does not hold up the rest
+            Err(e) => tracing::warn!(rental_id = %rental.rental_id, error = %e, "releasing deposit failed"),
+        }
+    }
+
+    Ok(released)
+}
+
+/// Background loop started by the server. Every instance may run it; row locks keep them from
+/// releasing the same deposit twice.
+pub async fn run_deposit_release_job(db: PgPool) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(DEPOSIT_RELEASE_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        match release_unclaimed_deposits(&db).await {
+            Ok(0) => {}
+            Ok(released) => tracing::info!(released, "released unclaimed deposits"),
+            Err(e) => tracing::warn!(error = %e, "deposit release pass failed"),
+        }
+    }
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+struct ClaimRental {
+    role: Option<RentalRole>,
+    status: RentalStatus,
+    renter_id: Uuid,
+    owner_id: Uuid,
+    product_name: String,
+}
+
+/// Locks the rental and works out the caller's part in it; `role` is `None` for support staff
+/// and admins who are not a party. Anyone else gets a 404.
+async fn lock_claim_rental(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+    user_id: Uuid,
+    is_staff: bool,
+) -> Result<ClaimRental, (StatusCode, String)> {
+    let rental = sqlx::query!(
+        r#"
+        SELECT r.status, r.renter_id, p.owner_id, p.name
+        FROM rental_schema.rentals r
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE r.rental_id = $1
+        FOR UPDATE OF r
+        "#,
+        rental_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load rental".to_string()))?
+    .ok_or((StatusCode::NOT_FOUND, "Rental not found".to_string()))?;
+
+    let role = if rental.renter_id == user_id {
+        Some(RentalRole::Renter)
+    } else if rental.owner_id == user_id {
+        Some(RentalRole::Owner)
+    } else if is_staff {
+        None
+    } else {
+        return Err((StatusCode::NOT_FOUND, "Rental not found".to_string()));
+    };
+
+    let status = rental
+        .status
+        .parse::<RentalStatus>()
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+
+    Ok(ClaimRental {
+        role,
+        status,
+        renter_id: rental.renter_id,
+        owner_id: rental.owner_id,
+        product_name: rental.name,
+    })
+}
+
+/// Owners open one claim per rental within `CLAIM_WINDOW_HOURS` of the return. The rental moves
+/// to disputed and its deposit stays in escrow until support resolves the claim.
+pub async fn open_damage_claim(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+    Json(req): Json<OpenClaimRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to open claim"),
+    };
+
+    let rental = match lock_claim_rental(&mut tx, rental_id, user_id, false).await {
+        Ok(r) => r,
+        Err((status, message)) => return err(status, &message),
+    };
+    if rental.role != Some(RentalRole::Owner) {
+        return err(StatusCode::FORBIDDEN, "Only the owner can open a damage claim");
+    }
+    if !matches!(rental.status, RentalStatus::Returned | RentalStatus::Disputed) {
+        return err(StatusCode::CONFLICT, &format!("Claims cannot be opened on a {} rental", rental.status));
+    }
+
+    let returned = sqlx::query!(
+        r#"
+        SELECT MAX(created_at) AS returned_at FROM rental_schema.rental_status_history
+        WHERE rental_id = $1 AND to_status = 'returned'
+        "#,
+        rental_id
+    )
+    .fetch_one(&mut tx)
+    .await;
+
+    let returned_at = match returned {
+        Ok(r) => r.returned_at,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to open claim"),
+    };
+    match returned_at {
+        Some(at) if claim_window_open(at, Utc::now()) => {}
+        Some(_) => return err(StatusCode::CONFLICT, "The window for damage claims on this rental has closed"),
+        None => return err(StatusCode::CONFLICT, "Claims can only be opened after the item is returned"),
+    }
+
+    let held_cents = match held_deposit_cents(&mut tx, rental_id).await {
+        Ok(cents) => cents,
+        Err((status, message)) => return err(status, &message),
+    };
+    if let Err(message) = req.validate(held_cents) {
+        return err(StatusCode::BAD_REQUEST, &message);
+    }
+
+    if !req.evidence_photo_ids.is_empty() {
+        let found = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM rental_schema.condition_report_photos p
+            JOIN rental_schema.condition_reports r ON p.report_id = r.report_id
+            WHERE r.rental_id = $1 AND p.photo_id = ANY($2)
+            "#,
+            rental_id,
+            &req.evidence_photo_ids
+        )
+        .fetch_one(&mut tx)
+        .await;
+
+        match found {
+            Ok(r) if r.count == req.evidence_photo_ids.len() as i64 => {}
+            Ok(_) => return err(StatusCode::BAD_REQUEST, "Evidence must be photos from this rental's condition reports"),
+            Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to open claim"),
+        }
+    }
+
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO rental_schema.damage_claims
+        (rental_id, opened_by, amount_cents, description, evidence_photo_ids, insurance_policy_id)
+        VALUES ($1, $2, $3, $4, $5,
+                (SELECT insurance_policy_id FROM rental_schema.rentals WHERE rental_id = $1))
+        "#,
+        rental_id,
+        user_id,
+        req.amount_cents,
+        req.description.trim(),
+        &req.evidence_photo_ids
+    )
+    .execute(&mut tx)
+    .await;
+
+    match inserted {
+        Ok(_) => {}
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some(UNIQUE_VIOLATION) => {
+            return err(StatusCode::CONFLICT, "A damage claim already exists for this rental");
+        }
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to open claim"),
+    }
+
+    if rental.status == RentalStatus::Returned {
+        if let Err((status, message)) = transition_rental(
+            &mut tx,
+            rental_id,
+            RentalStatus::Returned,
+            RentalStatus::Disputed,
+            user_id,
+            &[RentalRole::Owner],
+            Some("Damage claim opened"),
+        )
+        .await
+        {
+            return err(status, &message);
+        }
+    }
+
+    let notified = send_notification(
+        &mut tx,
+        &NewNotification {
+            user_id: rental.renter_id,
+            kind: KIND_DAMAGE_CLAIM_OPENED.to_string(),
+            title: format!("Damage claim on your rental of {}", rental.product_name),
+            body: format!(
+                "The owner is claiming {} cents from your deposit. You have {} hours to respond.",
+                req.amount_cents, RESPONSE_WINDOW_HOURS
+            ),
+            data: serde_json::json!({ "rental_id": rental_id, "amount_cents": req.amount_cents }),
+        },
+    )
+    .await;
+    if notified.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to open claim");
+    }
+
+    let claim = match fetch_claim(&mut tx, rental_id).await {
+        Ok(Some(claim)) => claim,
+        _ => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to open claim"),
+    };
+
+    if tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to open claim");
+    }
+
+    ok(claim)
+}
+
+/// The claim on a rental, for its parties and support staff.
+pub async fn get_damage_claim(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let is_staff = match has_any_role(&state.db, user_id, &[ROLE_ADMIN, ROLE_SUPPORT]).await {
+        Ok(is_staff) => is_staff,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch claim"),
+    };
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch claim"),
+    };
+
+    // A plain read; only the writes below lock the rental
+    let party = sqlx::query!(
+        r#"
+        SELECT r.rental_id FROM rental_schema.rentals r
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE r.rental_id = $1 AND ($3 OR r.renter_id = $2 OR p.owner_id = $2)
+        "#,
+        rental_id,
+        user_id,
+        is_staff
+    )
+    .fetch_optional(&mut tx)
+    .await;
+
+    match party {
+        Ok(Some(_)) => {}
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Rental not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch claim"),
+    }
+
+    match fetch_claim(&mut tx, rental_id).await {
+        Ok(Some(claim)) => ok(claim),
+        Ok(None) => err(StatusCode::NOT_FOUND, "No damage claim on this rental"),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch claim"),
+    }
+}
+
+/// The renter's answer. It can be given once, while the claim is open.
+pub async fn respond_to_damage_claim(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+    Json(req): Json<RespondToClaimRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let response = req.response.trim();
+    if response.is_empty() || response.chars().count() > MAX_CLAIM_TEXT_LEN {
+        return err(StatusCode::BAD_REQUEST, &format!("response must be 1 to {} characters", MAX_CLAIM_TEXT_LEN));
+    }
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to respond to claim"),
+    };
+
+    match lock_claim_rental(&mut tx, rental_id, user_id, false).await {
+        Ok(rental) if rental.role == Some(RentalRole::Renter) => {}
+        Ok(_) => return err(StatusCode::FORBIDDEN, "Only the renter can respond to a damage claim"),
+        Err((status, message)) => return err(status, &message),
+    }
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE rental_schema.damage_claims
+        SET status = 'responded', renter_accepts = $2, renter_response = $3, responded_at = NOW()
+        WHERE rental_id = $1 AND status = 'open'
+        RETURNING claim_id
+        "#,
+        rental_id,
+        req.accepts,
+        response
+    )
+    .fetch_optional(&mut tx)
+    .await;
+
+    match updated {
+        Ok(Some(_)) => {}
+        Ok(None) => return err(StatusCode::CONFLICT, "There is no open claim to respond to"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to respond to claim"),
+    }
+
+    let claim = match fetch_claim(&mut tx, rental_id).await {
+        Ok(Some(claim)) => claim,
+        _ => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to respond to claim"),
+    };
+
+    if tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to respond to claim");
+    }
+
+    ok(claim)
+}
+
+/// Support's decision. Captures the decided amount from the escrowed deposit, refunds the rest
+/// to the renter and completes the rental, all in one transaction.
+pub async fn resolve_damage_claim(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+    Json(req): Json<ResolveClaimRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    match has_any_role(&state.db, user_id, &[ROLE_ADMIN, ROLE_SUPPORT]).await {
+        Ok(true) => {}
+        Ok(false) => return err(StatusCode::FORBIDDEN, "Only support staff can resolve damage claims"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve claim"),
+    }
+
+    let note = req.note.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    if note.is_some_and(|n| n.chars().count() > MAX_CLAIM_TEXT_LEN) {
+        return err(StatusCode::BAD_REQUEST, &format!("note must be at most {} characters", MAX_CLAIM_TEXT_LEN));
+    }
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve claim"),
+    };
+
+    let rental = match lock_claim_rental(&mut tx, rental_id, user_id, true).await {
+        Ok(r) => r,
+        Err((status, message)) => return err(status, &message),
+    };
+    if rental.role.is_some() {
+        return err(StatusCode::FORBIDDEN, "You cannot resolve a claim on your own rental");
+    }
+
+    let claim = match fetch_claim(&mut tx, rental_id).await {
+        Ok(Some(claim)) => claim,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "No damage claim on this rental"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve claim"),
+    };
+
+    let status = match claim.status.parse::<ClaimStatus>() {
+        Ok(s) => s,
+        Err(message) => return err(StatusCode::INTERNAL_SERVER_ERROR, &message),
+    };
+    if status == ClaimStatus::Resolved {
+        return err(StatusCode::CONFLICT, "This claim is already resolved");
+    }
+    if !resolvable(status, claim.created_at, Utc::now()) {
+        let deadline = claim.created_at + Duration::hours(RESPONSE_WINDOW_HOURS);
+        return err(StatusCode::CONFLICT, &format!("The renter has until {} to respond", deadline.to_rfc3339()));
+    }
+
+    let held_cents = match held_deposit_cents(&mut tx, rental_id).await {
+        Ok(cents) => cents,
+        Err((status, message)) => return err(status, &message),
+    };
+    let capture_cents = match capture_for(req.outcome, req.captured_cents, claim.amount_cents, held_cents) {
+        Ok(cents) => cents,
+        Err(message) => return err(StatusCode::BAD_REQUEST, &message),
+    };
+
+    let settlement: DepositSettlement =
+        match settle_deposit(&mut tx, rental_id, capture_cents, Some(claim.claim_id), "damage claim resolved").await {
+            Ok(s) => s,
+            Err((status, message)) => return err(status, &message),
+        };
+
+    let resolved = sqlx::query!(
+        r#"
+        UPDATE rental_schema.damage_claims
+        SET status = 'resolved', outcome = $2, captured_cents = $3, released_cents = $4,
+            resolution_note = $5, resolved_by = $6, resolved_at = NOW()
+        WHERE claim_id = $1
+        "#,
+        claim.claim_id,
+        req.outcome.as_str(),
+        settlement.captured_cents,
+        settlement.released_cents,
+        note,
+        user_id
+    )
+    .execute(&mut tx)
+    .await;
+    if resolved.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve claim");
+    }
+
+    // Support staff close disputes with the same rights admins have in the state machine
+    if rental.status == RentalStatus::Disputed {
+        let reason = format!("Damage claim resolved: {}", req.outcome);
+        if let Err((status, message)) = transition_rental(
+            &mut tx,
+            rental_id,
+            RentalStatus::Disputed,
+            RentalStatus::Completed,
+            user_id,
+            &[RentalRole::Admin],
+            Some(&reason),
+        )
+        .await
+        {
+            return err(status, &message);
+        }
+    }
+
+    for (recipient, body) in [
+        (rental.renter_id, format!("{} cents of your deposit was kept and {} cents refunded.", settlement.captured_cents, settlement.released_cents)),
+        (rental.owner_id, format!("{} cents of the deposit was awarded to you.", settlement.captured_cents)),
+    ] {
+        let notified = send_notification(
+            &mut tx,
+            &NewNotification {
+                user_id: recipient,
+                kind: KIND_DAMAGE_CLAIM_RESOLVED.to_string(),
+                title: format!("Damage claim on {} resolved", rental.product_name),
+                body,
+                data: serde_json::json!({ "rental_id": rental_id, "outcome": req.outcome, "deposit": settlement }),
+            },
+        )
+        .await;
+        if notified.is_err() {
+            return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve claim");
+        }
+    }
+
+    let claim = match fetch_claim(&mut tx, rental_id).await {
+        Ok(Some(claim)) => claim,
+        _ => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve claim"),
+    };
+
+    if tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve claim");
+    }
+
+    // The settlement job retries anything the provider does not take now
+    if let Err(e) = settle_rental_payments(&state.db, Some(rental_id)).await {
+        tracing::warn!(%rental_id, error = %e, "settling rental payments failed");
+    }
+
+    ok(claim)
+}
+
+/// Support queue: unresolved claims oldest first, or those with `status`.
+pub async fn list_damage_claims(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(filters): Query<ClaimFilters>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    match has_any_role(&state.db, user_id, &[ROLE_ADMIN, ROLE_SUPPORT]).await {
+        Ok(true) => {}
+        Ok(false) => return err(StatusCode::FORBIDDEN, "Only support staff can list damage claims"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch claims"),
+    }
+
+    let status = match filters.status.as_deref().map(str::parse::<ClaimStatus>).transpose() {
+        Ok(s) => s,
+        Err(message) => return err(StatusCode::BAD_REQUEST, &message),
+    };
+
+    let claims = sqlx::query_as!(
+        DamageClaimResponse,
+        r#"
+        SELECT claim_id, rental_id, opened_by, amount_cents, description, evidence_photo_ids, status,
+               renter_accepts, renter_response, responded_at, outcome, captured_cents, released_cents,
+               resolution_note, resolved_by, resolved_at, insurance_policy_id, created_at
+        FROM rental_schema.damage_claims
+        WHERE CASE WHEN $1::text IS NULL THEN status <> 'resolved' ELSE status = $1 END
+        ORDER BY created_at, claim_id
+        LIMIT $2
+        "#,
+        status.map(|s| s.as_str()),
+        page_size(filters.limit, DEFAULT_PAGE_SIZE)
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    match claims {
+        Ok(claims) => ok(claims),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch claims"),
+    }
+}
@@ -0,0 +1,104 @@
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::cancellation::RentalCharges;
+use crate::payment::{refund_rental_payments, rental_refundable_charges};
+use crate::pricing::PriceQuote;
+
+/// How a rental's deposit left escrow: `captured_cents` stays with the owner, the rest goes
+/// back to the renter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepositSettlement {
+    pub held_cents: i64,
+    pub captured_cents: i64,
+    pub released_cents: i64,
+}
+
+/// Captures up to `capture_cents` of what is held and releases the remainder.
+pub fn split_deposit(held_cents: i64, capture_cents: i64) -> DepositSettlement {
+    let held_cents = held_cents.max(0);
+    let captured_cents = capture_cents.clamp(0, held_cents);
+    DepositSettlement { held_cents, captured_cents, released_cents: held_cents - captured_cents }
+}
+
+/// Deposit held for a rental: the quoted deposit less late fees taken from it, limited to what
+/// was paid and not refunded. Zero once the deposit has been settled.
+pub async fn held_deposit_cents(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+) -> Result<i64, (StatusCode, String)> {
+    let failed = || (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load deposit".to_string());
+
+    let rental = sqlx::query!(
+        r#"
+        SELECT r.price_quote,
+               EXISTS (SELECT 1 FROM rental_schema.rental_deposit_settlements s
+                       WHERE s.rental_id = r.rental_id) AS "settled!",
+               (SELECT COALESCE(SUM(f.from_deposit_cents), 0)::int8 FROM rental_schema.rental_late_fees f
+                WHERE f.rental_id = r.rental_id) AS "late_fees_cents!"
+        FROM rental_schema.rentals r
+        WHERE r.rental_id = $1
+        "#,
+        rental_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|_| failed())?;
+
+    if rental.settled {
+        return Ok(0);
+    }
+
+    let quote: Option<PriceQuote> = rental.price_quote.and_then(|q| serde_json::from_value(q).ok());
+    let paid_cents = rental_refundable_charges(tx, rental_id)
+        .await
+        .map_err(|_| failed())?
+        .iter()
+        .map(|c| c.refundable_cents)
+        .sum();
+
+    let charges = RentalCharges::from_quote(quote.as_ref(), paid_cents);
+    Ok((charges.deposit_cents - rental.late_fees_cents).clamp(0, paid_cents))
+}
+
+/// Takes the deposit out of escrow inside the caller's transaction: keeps `capture_cents` of
+/// it, records a pending refund of the rest and records the split. The caller pays the refund
+/// out with `settle_rental_payments` once it commits. A deposit is settled once.
+pub async fn settle_deposit(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+    capture_cents: i64,
+    claim_id: Option<Uuid>,
+    reason: &str,
+) -> Result<DepositSettlement, (StatusCode, String)> {
+    let settlement = split_deposit(held_deposit_cents(tx, rental_id).await?, capture_cents);
+
+    if settlement.released_cents > 0 {
+        refund_rental_payments(tx, rental_id, settlement.released_cents, reason).await?;
+    }
+
+    let recorded = sqlx::query!(
+        r#"
+        INSERT INTO rental_schema.rental_deposit_settlements
+        (rental_id, claim_id, held_cents, captured_cents, released_cents)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (rental_id) DO NOTHING
+        "#,
+        rental_id,
+        claim_id,
+        settlement.held_cents,
+        settlement.captured_cents,
+        settlement.released_cents
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to settle deposit".to_string()))?;
+
+    if recorded.rows_affected() == 0 {
+        return Err((StatusCode::CONFLICT, "The deposit for this rental was already settled".to_string()));
+    }
+
+    Ok(settlement)
+}
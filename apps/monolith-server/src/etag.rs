@@ -0,0 +1,79 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::Response,
+};
+
+use crate::state::err;
+
+/// Strong entity tag for a row version. Versions are bumped by a database trigger on every
+/// update, so any change to the row invalidates earlier tags.
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum PreconditionError {
+    #[error("If-Match header is required; fetch the resource first to get its ETag")]
+    Missing,
+    #[error("If-Match header is malformed")]
+    Malformed,
+    #[error("Resource was modified by someone else; reload it and retry")]
+    Stale,
+}
+
+impl PreconditionError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            PreconditionError::Missing => StatusCode::PRECONDITION_REQUIRED,
+            PreconditionError::Malformed => StatusCode::BAD_REQUEST,
+            PreconditionError::Stale => StatusCode::PRECONDITION_FAILED,
+        }
+    }
+
+    pub fn into_response(self) -> Response {
+        err(self.status(), &self.to_string())
+    }
+}
+
+/// Whether an `If-Match` value matches the current version. `*` matches anything; weak tags
+/// never match because `If-Match` uses strong comparison.
+pub fn if_match_allows(value: &str, current_version: i32) -> Result<bool, PreconditionError> {
+    let value = value.trim();
+    if value == "*" {
+        return Ok(true);
+    }
+
+    let current = etag(current_version);
+    let mut matched = false;
+    for tag in value.split(',').map(str::trim) {
+        let opaque = tag.strip_prefix("W/").unwrap_or(tag);
+        if opaque.len() < 2 || !opaque.starts_with('"') || !opaque.ends_with('"') {
+            return Err(PreconditionError::Malformed);
+        }
+        if !tag.starts_with("W/") && tag == current {
+            matched = true;
+        }
+    }
+    Ok(matched)
+}
+
+/// Enforces `If-Match` for a write against the version the handler just read.
+pub fn check_if_match(headers: &HeaderMap, current_version: i32) -> Result<(), PreconditionError> {
+    let value = match headers.get(header::IF_MATCH) {
+        Some(v) => v.to_str().map_err(|_| PreconditionError::Malformed)?,
+        None => return Err(PreconditionError::Missing),
+    };
+
+    if if_match_allows(value, current_version)? {
+        Ok(())
+    } else {
+        Err(PreconditionError::Stale)
+    }
+}
+
+pub fn with_etag(mut response: Response, version: i32) -> Response {
+    if let Ok(value) = HeaderValue::from_str(&etag(version)) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    response
+}
@@ -0,0 +1,304 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::insurance::cancel_policy;
+use crate::notification::{send_notification, NewNotification, KIND_RENTAL_REQUEST_EXPIRED};
+use crate::payment::{refund_rental_payments, rental_refundable_charges, settle_charge, settle_rental_payments};
+use crate::rental_status::{record_rental_history, RentalStatus};
+
+pub const REQUEST_EXPIRY_INTERVAL_SECS: u64 = 300;
+/// How long an instant booking may wait on its charge before the expiry job settles it.
+pub const PENDING_PAYMENT_GRACE_MINUTES: i32 = 5;
+
+/// What a renter needs to skip the owner's approval on an instant-book listing. Renters who
+/// fall short can still book; their rental waits for the owner like any request.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstantBookRequirements {
+    #[serde(default)]
+    pub require_verified_id: bool,
+    /// Minimum average rating as a renter, 1 to 5.
+    pub min_rating: Option<f64>,
+    pub min_completed_rentals: Option<i64>,
+}
+
+/// The renter's track record, as checked against `InstantBookRequirements`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenterStanding {
+    pub identity_verified: bool,
+    pub avg_rating: Option<f64>,
+    pub completed_rentals: i64,
+}
+
+impl InstantBookRequirements {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_rating.is_some_and(|r| !(1.0..=5.0).contains(&r)) {
+            return Err("min_rating must be between 1 and 5".to_string());
+        }
+        if self.min_completed_rentals.is_some_and(|n| n < 0) {
+            return Err("min_completed_rentals cannot be negative".to_string());
+        }
+        Ok(())
+    }
+
+    /// Requirements the renter does not meet; empty when they may book instantly. Renters
+    /// without any rating never meet a rating requirement.
+    pub fn unmet(&self, standing: &RenterStanding) -> Vec<&'static str> {
+        let mut unmet = Vec::new();
+        if self.require_verified_id && !standing.identity_verified {
+            unmet.push("verified_id");
+        }
+        if let Some(min_rating) = self.min_rating {
+            if !standing.avg_rating.is_some_and(|r| r >= min_rating) {
+                unmet.push("min_rating");
+            }
+        }
+        if self.min_completed_rentals.is_some_and(|min| standing.completed_rentals < min) {
+            unmet.push("min_completed_rentals");
+        }
+        unmet
+    }
+}
+
+/// The renter's standing, or `None` if there is no such user. The rating is the one owners
+/// gave them as a renter, not their rating as an owner.
+pub async fn load_renter_standing(db: &PgPool, renter_id: Uuid) -> Result<Option<RenterStanding>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT COALESCE(up.identity_verified, false) AS "identity_verified!",
+               (SELECT AVG(ur.rating)::float8 FROM review_schema.user_reviews ur
+                WHERE ur.reviewed_user_id = u.user_id AND ur.review_type = 'renter'
+                AND ur.status = 'active') AS avg_rating,
+               (SELECT COUNT(*) FROM rental_schema.rentals r
+                WHERE r.renter_id = u.user_id AND r.status = 'completed') AS "completed_rentals!"
+        FROM user_schema.users u
+        LEFT JOIN user_schema.user_profiles up ON up.user_id = u.user_id
+        WHERE u.user_id = $1
+        LIMIT 1
+        "#,
+        renter_id
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|row| RenterStanding {
+        identity_verified: row.identity_verified,
+        avg_rating: row.avg_rating,
+        completed_rentals: row.completed_rentals,
+    }))
+}
+
+/// Confirms or cancels an instant booking once its charge has settled. Returns the new status,
+/// or `None` while the charge is still pending or when the booking was finished already.
+pub async fn complete_instant_booking(db: &PgPool, rental_id: Uuid) -> Result<Option<RentalStatus>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let rental = sqlx::query!(
+        r#"
+        SELECT pi.status AS "charge_status?"
+        FROM rental_schema.rentals r
+        LEFT JOIN payment_schema.payment_intents pi
+            ON pi.rental_id = r.rental_id AND pi.metadata->>'idempotency_key' = r.rental_id::text
+        WHERE r.rental_id = $1 AND r.status = 'pending_payment'
+        FOR UPDATE OF r
+        "#,
+        rental_id
+    )
+    .fetch_optional(&mut tx)
+    .await?;
+
+    let rental = match rental {
+        Some(r) => r,
+        None => return Ok(None),
+    };
+
+    let (to, reason) = match rental.charge_status.as_deref() {
+        Some("succeeded") => (RentalStatus::Confirmed, "Instant book"),
+        Some("pending") => return Ok(None),
+        _ => (RentalStatus::Cancelled, "Payment declined"),
+    };
+
+    sqlx::query!(
+        "UPDATE rental_schema.rentals SET status = $2, updated_at = NOW() WHERE rental_id = $1",
+        rental_id,
+        to.as_str()
+    )
+    .execute(&mut tx)
+    .await?;
+
+    record_rental_history(&mut tx, rental_id, Some(RentalStatus::PendingPayment), to, None, None, Some(reason)).await?;
+    if to == RentalStatus::Cancelled {
+        cancel_policy(&mut tx, rental_id).await?;
+    }
+
+    tx.commit().await?;
+    Ok(Some(to))
+}
+
+/// Finishes instant bookings whose handler stopped between committing and charging: the charge
+/// is sent again under the same key, then the booking is confirmed or cancelled. Returns how
+/// many were finished.
+pub async fn settle_pending_bookings(db: &PgPool) -> Result<usize, sqlx::Error> {
+    let stale = sqlx::query!(
+        r#"
+        SELECT r.rental_id, pi.id AS "payment_intent_id?"
+        FROM rental_schema.rentals r
+        LEFT JOIN payment_schema.payment_intents pi
+            ON pi.rental_id = r.rental_id AND pi.status = 'pending'
+            AND pi.metadata->>'idempotency_key' = r.rental_id::text
+        WHERE r.status = 'pending_payment' AND r.updated_at < NOW() - make_interval(mins => $1)
+        "#,
+        PENDING_PAYMENT_GRACE_MINUTES
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut finished = 0;
+    for rental in stale {
+        if let Some(payment_intent_id) = rental.payment_intent_id {
+            if let Err((_, message)) = settle_charge(db, payment_intent_id).await {
+                tracing::warn!(rental_id = %rental.rental_id, error = %message, "charge for instant booking failed");
+            }
+        }
+        if complete_instant_booking(db, rental.rental_id).await?.is_some() {
+            finished += 1;
+        }
+    }
+    Ok(finished)
+}
+
+/// A request lapses when the owner has not answered within `ttl`, or once the rental would
+/// already have started.
+pub fn request_expired(created_at: DateTime<Utc>, starts_at: DateTime<Utc>, now: DateTime<Utc>, ttl: Duration) -> bool {
+    now >= created_at + ttl || now >= starts_at
+}
+
+/// Expires one request, refunding anything paid and telling the renter. Returns `false` when
+/// the owner answered in the meantime or the row is busy.
+async fn expire_request(db: &PgPool, rental_id: Uuid) -> Result<bool, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    // Rows locked by an owner answering right now are left for the next pass
+    let rental = sqlx::query!(
+        r#"
+        SELECT r.renter_id, p.name
+        FROM rental_schema.rentals r
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE r.rental_id = $1 AND r.status = 'requested'
+        FOR UPDATE OF r SKIP LOCKED
+        "#,
+        rental_id
+    )
+    .fetch_optional(&mut tx)
+    .await?;
+
+    let rental = match rental {
+        Some(r) => r,
+        None => return Ok(false),
+    };
+
+    sqlx::query!(
+        "UPDATE rental_schema.rentals SET status = $2, updated_at = NOW() WHERE rental_id = $1",
+        rental_id,
+        RentalStatus::Expired.as_str()
+    )
+    .execute(&mut tx)
+    .await?;
+
+    record_rental_history(
+        &mut tx,
+        rental_id,
+        Some(RentalStatus::Requested),
+        RentalStatus::Expired,
+        None,
+        None,
+        Some("Owner did not respond in time"),
+    )
+    .await?;
+    cancel_policy(&mut tx, rental_id).await?;
+
+    let paid: i64 = rental_refundable_charges(&mut tx, rental_id)
+        .await?
+        .iter()
+        .map(|c| c.refundable_cents)
+        .sum();
+    if paid > 0 {
+        if let Err((_, message)) = refund_rental_payments(&mut tx, rental_id, paid, "rental request expired").await {
+            tracing::warn!(%rental_id, error = %message, "refund for expired request failed");
+            return Ok(false);
+        }
+    }
+
+    send_notification(
+        &mut tx,
+        &NewNotification {
+            user_id: rental.renter_id,
+            kind: KIND_RENTAL_REQUEST_EXPIRED.to_string(),
+            title: format!("Your request for {} expired", rental.name),
+            body: "The owner did not respond in time, so no booking was made.".to_string(),
+            data: serde_json::json!({ "rental_id": rental_id, "refund_cents": paid }),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    // The refund is on record; the settlement job retries it if the provider is down
+    if paid > 0 {
+        if let Err(e) = settle_rental_payments(db, Some(rental_id)).await {
+            tracing::warn!(%rental_id, error = %e, "refund for expired request left to the settlement job");
+        }
+    }
+    Ok(true)
+}
+
+/// Expires every request that has lapsed under `ttl`. Returns how many were expired.
+pub async fn expire_stale_requests(db: &PgPool, ttl: Duration) -> Result<usize, sqlx::Error> {
+    let now = Utc::now();
+    let stale = sqlx::query!(
+        r#"
+        SELECT rental_id, created_at AS "created_at!", rental_period_start
+        FROM rental_schema.rentals
+        WHERE status = 'requested' AND (created_at <= $1 OR rental_period_start <= $2)
+        "#,
+        now - ttl,
+        now
+    )
+    .fetch_all(db)
+    .await?;
+
+    // One request failing does not hold up the rest
+    let mut expired = 0;
+    for rental in stale {
+        if !request_expired(rental.created_at, rental.rental_period_start, now, ttl) {
+            continue;
+        }
+        match expire_request(db, rental.rental_id).await {
+            Ok(true) => expired += 1,
+            Ok(false) => {}
+            Err(e) => tracing::warn!(rental_id = %rental.rental_id, error = %e, "expiring rental request failed"),
+        }
+    }
+    Ok(expired)
+}
+
+/// Background loop started by the server; a failed pass is retried on the next tick. It also
+/// finishes instant bookings left waiting on their charge.
+pub async fn run_request_expiry(db: PgPool, ttl_hours: i64) {
+    let ttl = Duration::hours(ttl_hours);
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(REQUEST_EXPIRY_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        match expire_stale_requests(&db, ttl).await {
+            Ok(0) => {}
+            Ok(expired) => tracing::info!(expired, "expired unanswered rental requests"),
+            Err(e) => tracing::warn!(error = %e, "rental request expiry pass failed"),
+        }
+        match settle_pending_bookings(&db).await {
+            Ok(0) => {}
+            Ok(finished) => tracing::info!(finished, "finished instant bookings pending payment"),
+            Err(e) => tracing::warn!(error = %e, "pending booking pass failed"),
+        }
+    }
+}
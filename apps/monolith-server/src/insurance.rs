@@ -0,0 +1,374 @@
+use std::sync::OnceLock;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::pricing::{quote_for_product, round_cents, PriceQuote, QuoteRequest};
+use crate::roles::{has_any_role, ROLE_ADMIN, ROLE_SUPPORT};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum InsuranceError {
+    #[error("Unknown coverage option '{0}'")]
+    UnknownOption(String),
+    #[error("Insurance is not available for this rental: {0}")]
+    Unavailable(String),
+}
+
+impl InsuranceError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            InsuranceError::UnknownOption(_) => StatusCode::BAD_REQUEST,
+            InsuranceError::Unavailable(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+}
+
+/// A policy the renter can buy for a quoted rental. Amounts are in the quote's currency.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoverageOption {
+    pub option_id: String,
+    pub name: String,
+    pub coverage_amount: f64,
+    pub deductible: f64,
+    pub premium: f64,
+}
+
+/// The insurer's record of a bound policy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IssuedPolicy {
+    pub policy_number: String,
+    pub terms: serde_json::Value,
+}
+
+/// An insurer that prices and binds rental coverage. Providers are synchronous and must not
+/// touch the database; the booking records what they return.
+pub trait InsuranceProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn coverage_options(&self, quote: &PriceQuote) -> Result<Vec<CoverageOption>, InsuranceError>;
+    fn issue_policy(
+        &self,
+        rental_id: Uuid,
+        quote: &PriceQuote,
+        option: &CoverageOption,
+    ) -> Result<IssuedPolicy, InsuranceError>;
+}
+
+/// The option `option_id` among what the provider offers for `quote`.
+pub fn select_coverage(
+    provider: &dyn InsuranceProvider,
+    quote: &PriceQuote,
+    option_id: &str,
+) -> Result<CoverageOption, InsuranceError> {
+    provider
+        .coverage_options(quote)?
+        .into_iter()
+        .find(|o| o.option_id == option_id)
+        .ok_or_else(|| InsuranceError::UnknownOption(option_id.to_string()))
+}
+
+/// (id, name, days of the average daily rate covered, deductible share, premium share of the
+/// rental subtotal, minimum premium)
+const MOCK_PLANS: &[(&str, &str, f64, f64, f64, f64)] = &[
+    ("basic", "Basic damage cover", 30.0, 0.10, 0.05, 2.0),
+    ("premium", "Full damage and theft cover", 60.0, 0.0, 0.12, 5.0),
+];
+
+/// Deterministic insurer for development and tests: coverage scales with the daily rate and
+/// the premium with the rental subtotal.
+#[derive(Debug, Default)]
+pub struct MockInsuranceProvider;
+
+impl InsuranceProvider for MockInsuranceProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn coverage_options(&self, quote: &PriceQuote) -> Result<Vec<CoverageOption>, InsuranceError> {
+        if quote.days <= 0 {
+            return Err(InsuranceError::Unavailable("the rental has no billable days".to_string()));
+        }
+        let daily_rate = quote.base_amount / quote.days as f64;
+
+        Ok(MOCK_PLANS
+            .iter()
+            .map(|(option_id, name, days_covered, deductible_share, premium_share, min_premium)| {
+                let coverage_amount = round_cents(daily_rate * days_covered);
+                CoverageOption {
+                    option_id: option_id.to_string(),
+                    name: name.to_string(),
+                    coverage_amount,
+                    deductible: round_cents(coverage_amount * deductible_share),
+                    premium: round_cents((quote.subtotal * premium_share).max(*min_premium)),
+                }
+            })
+            .collect())
+    }
+
+    fn issue_policy(
+        &self,
+        rental_id: Uuid,
+        quote: &PriceQuote,
+        option: &CoverageOption,
+    ) -> Result<IssuedPolicy, InsuranceError> {
+        let reference = rental_id.simple().to_string()[..12].to_uppercase();
+        Ok(IssuedPolicy {
+            policy_number: format!("MOCK-{}-{}", option.option_id.to_uppercase(), reference),
+            terms: serde_json::json!({
+                "option": option,
+                "currency": quote.currency,
+                "covers": "accidental damage to the rented item during the rental period",
+            }),
+        })
+    }
+}
+
+/// Shared insurer for the server. Only the mock provider exists so far.
+pub fn default_insurance_provider() -> &'static dyn InsuranceProvider {
+    static PROVIDER: OnceLock<MockInsuranceProvider> = OnceLock::new();
+    PROVIDER.get_or_init(MockInsuranceProvider::default)
+}
+
+/// Stores a policy bound for a committed booking and links it to the rental, inside the caller's
+/// transaction. Coverage runs for the rental period.
+pub async fn record_policy(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+    provider: &str,
+    quote: &PriceQuote,
+    option: &CoverageOption,
+    issued: &IssuedPolicy,
+) -> Result<Uuid, sqlx::Error> {
+    let policy = sqlx::query!(
+        r#"
+        INSERT INTO rental_schema.insurance_policies
+        (rental_id, provider, policy_number, coverage_option, coverage_amount, deductible, premium,
+         status, valid_from, valid_until, terms)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 'active', $8, $9, $10)
+        RETURNING policy_id
+        "#,
+        rental_id,
+        provider,
+        issued.policy_number,
+        option.option_id,
+        option.coverage_amount,
+        option.deductible,
+        option.premium,
+        quote.rental_period_start,
+        quote.rental_period_end,
+        issued.terms
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE rental_schema.rentals SET insurance_policy_id = $2 WHERE rental_id = $1",
+        rental_id,
+        policy.policy_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    Ok(policy.policy_id)
+}
+
+/// Coverage option of the rental's active policy, so it can be priced again for new dates.
+pub async fn active_coverage_option(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+) -> Result<Option<String>, sqlx::Error> {
+    let policy = sqlx::query!(
+        "SELECT coverage_option FROM rental_schema.insurance_policies WHERE rental_id = $1 AND status = 'active'",
+        rental_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    Ok(policy.and_then(|p| p.coverage_option))
+}
+
+/// Moves an active policy to the rental's new dates at the premium quoted for them.
+pub async fn reschedule_policy(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+    valid_from: DateTime<Utc>,
+    valid_until: DateTime<Utc>,
+    premium: f64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE rental_schema.insurance_policies SET valid_from = $2, valid_until = $3, premium = $4
+        WHERE rental_id = $1 AND status = 'active'
+        "#,
+        rental_id,
+        valid_from,
+        valid_until,
+        premium
+    )
+    .execute(&mut *tx)
+    .await?;
+    Ok(())
+}
+
+/// Cancels the policy of a rental that will not take place.
+pub async fn cancel_policy(tx: &mut Transaction<'_, Postgres>, rental_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE rental_schema.insurance_policies SET status = 'cancelled' WHERE rental_id = $1 AND status = 'active'",
+        rental_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InsurancePolicyResponse {
+    pub policy_id: Uuid,
+    pub rental_id: Uuid,
+    pub provider: String,
+    pub policy_number: String,
+    pub coverage_option: Option<String>,
+    pub coverage_amount: f64,
+    pub deductible: f64,
+    pub premium: f64,
+    pub status: String,
+    pub valid_from: DateTime<Utc>,
+    pub valid_until: DateTime<Utc>,
+    pub terms: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// Coverage options for a quoted rental, so renters can pick one before booking.
+pub async fn quote_insurance(
+    State(state): State<AppState>,
+    Json(req): Json<QuoteRequest>,
+) -> impl IntoResponse {
+    let product = sqlx::query!(
+        "SELECT insurance_required FROM product_schema.products WHERE product_id = $1",
+        req.product_id
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    let insurance_required = match product {
+        Ok(Some(p)) => p.insurance_required,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Product not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to quote insurance"),
+    };
+
+    let quote = match quote_for_product(
+        &state.db,
+        req.product_id,
+        req.rental_period_start,
+        req.rental_period_end,
+        req.delivery_requested.unwrap_or(false),
+    )
+    .await
+    {
+        Ok(q) => q,
+        Err((status, message)) => return err(status, &message),
+    };
+
+    let provider = default_insurance_provider();
+    let options = match provider.coverage_options(&quote) {
+        Ok(options) => options,
+        Err(e) => return err(e.status(), &e.to_string()),
+    };
+
+    ok(serde_json::json!({
+        "provider": provider.name(),
+        "insurance_required": insurance_required,
+        "currency": quote.currency,
+        "options": options,
+    }))
+}
+
+/// The policy bought with a rental, for its parties and support staff.
+pub async fn get_rental_insurance(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let is_staff = match has_any_role(&state.db, user_id, &[ROLE_ADMIN, ROLE_SUPPORT]).await {
+        Ok(is_staff) => is_staff,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch insurance policy"),
+    };
+
+    let party = sqlx::query!(
+        r#"
+        SELECT r.rental_id FROM rental_schema.rentals r
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE r.rental_id = $1 AND ($3 OR r.renter_id = $2 OR p.owner_id = $2)
+        "#,
+        rental_id,
+        user_id,
+        is_staff
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    match party {
+        Ok(Some(_)) => {}
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Rental not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch insurance policy"),
+    }
+
+    let policy = sqlx::query_as!(
+        InsurancePolicyResponse,
+        r#"
+        SELECT policy_id, rental_id AS "rental_id!", provider AS "provider!", policy_number AS "policy_number!",
+               coverage_option, coverage_amount AS "coverage_amount!", deductible, premium, status,
+               valid_from AS "valid_from!", valid_until AS "valid_until!", terms, created_at AS "created_at!"
+        FROM rental_schema.insurance_policies
+        WHERE rental_id = $1
+        "#,
+        rental_id
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    match policy {
+        Ok(Some(p)) => ok(p),
+        Ok(None) => err(StatusCode::NOT_FOUND, "This rental has no insurance policy"),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch insurance policy"),
+    }
+}
@@ -0,0 +1,420 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+
+pub const MAX_UNITS_PER_PRODUCT: i32 = 100;
+/// How far past now an overdue rental keeps its unit booked. The hold moves forward on every
+/// check until the item is back, without blocking bookings further out.
+pub const OVERDUE_HOLD_HOURS: i32 = 24;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddUnitRequest {
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnitResponse {
+    pub unit_id: Uuid,
+    pub label: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Labels for `count` new units, continuing after the `existing` ones ("Unit 1", "Unit 2", ...).
+pub fn default_unit_labels(existing: usize, count: usize) -> Vec<String> {
+    (existing + 1..=existing + count).map(|n| format!("Unit {}", n)).collect()
+}
+
+/// First unit, in the given order, that is not busy.
+pub fn pick_free_unit(units: &[Uuid], busy: &[Uuid]) -> Option<Uuid> {
+    units.iter().copied().find(|u| !busy.contains(u))
+}
+
+/// Creates `count` units for a new product inside the caller's transaction.
+pub async fn create_units(
+    tx: &mut Transaction<'_, Postgres>,
+    product_id: Uuid,
+    count: i32,
+) -> Result<(), sqlx::Error> {
+    let labels = default_unit_labels(0, count.max(1) as usize);
+    sqlx::query!(
+        r#"
+        INSERT INTO product_schema.product_units (product_id, label)
+        SELECT $1, * FROM UNNEST($2::text[])
+        "#,
+        product_id,
+        &labels
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Active unit ids for a product, oldest first. Locks the rows so concurrent bookings of the
+/// same product serialize on unit assignment.
+pub async fn lock_active_units(
+    tx: &mut Transaction<'_, Postgres>,
+    product_id: Uuid,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT unit_id FROM product_schema.product_units
+        WHERE product_id = $1 AND status = 'active'
+        ORDER BY created_at, label
+        FOR UPDATE
+        "#,
+        product_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.unit_id).collect())
+}
+
+/// A rental holding one of a product's units, as returned by `overlapping_rentals`.
+#[derive(Debug, Clone)]
+pub struct UnitHold {
+    pub rental_id: Uuid,
+    pub unit_id: Option<Uuid>,
+    pub rental_period_start: DateTime<Utc>,
+    pub rental_period_end: DateTime<Utc>,
+    pub status: String,
+}
+
+/// Rentals of a product that overlap `start..end`, earliest first. Periods are half-open, so a
+/// booking that ends as this one starts does not count. An overdue rental is held from its start
+/// until `OVERDUE_HOLD_HOURS` from now.
+pub async fn overlapping_rentals(
+    tx: &mut Transaction<'_, Postgres>,
+    product_id: Uuid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<UnitHold>, sqlx::Error> {
+    sqlx::query_as!(
+        UnitHold,
+        r#"
+        SELECT rental_id, unit_id, rental_period_start, rental_period_end, status
+        FROM rental_schema.rentals
+        WHERE product_id = $1
+        AND ((status IN ('pending_payment', 'requested', 'confirmed', 'active')
+              AND rental_period && tstzrange($2, $3, '[)'))
+             OR (status = 'overdue'
+                 AND tstzrange(rental_period_start,
+                               GREATEST(rental_period_end, NOW()) + make_interval(hours => $4::int), '[)')
+                     && tstzrange($2, $3, '[)')))
+        ORDER BY rental_period_start
+        "#,
+        product_id,
+        start,
+        end,
+        OVERDUE_HOLD_HOURS
+    )
+    .fetch_all(&mut *tx)
+    .await
+}
+
+/// Units already booked for any part of `start..end`, by the rentals `overlapping_rentals`
+/// returns.
+pub async fn busy_units(
+    tx: &mut Transaction<'_, Postgres>,
+    product_id: Uuid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let mut units: Vec<Uuid> =
+        overlapping_rentals(tx, product_id, start, end).await?.into_iter().filter_map(|r| r.unit_id).collect();
+    units.sort();
+    units.dedup();
+    Ok(units)
+}
+
+/// Whether the owner blocked the product for any part of `start..end`, by hand or through an
+/// imported calendar. Blackouts apply to every unit.
+pub async fn blacked_out(
+    tx: &mut Transaction<'_, Postgres>,
+    product_id: Uuid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM product_schema.product_blackouts
+            WHERE product_id = $1 AND period && tstzrange($2, $3, '[)')
+        ) as "blocked!"
+        "#,
+        product_id,
+        start,
+        end
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    Ok(row.blocked)
+}
+
+/// Whether `unit_id` is free for all of `start..end`, ignoring `except_rental_id` so a rental
+/// can be checked against its own new dates. Blackouts on the unit's product count as busy, and
+/// overdue rentals hold the unit as in `busy_units`.
+pub async fn unit_free_for(
+    tx: &mut Transaction<'_, Postgres>,
+    unit_id: Uuid,
+    except_rental_id: Uuid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT NOT EXISTS (
+            SELECT 1 FROM rental_schema.rentals
+            WHERE unit_id = $1
+            AND rental_id <> $2
+            AND ((status IN ('pending_payment', 'requested', 'confirmed', 'active')
+                  AND rental_period && tstzrange($3, $4, '[)'))
+                 OR (status = 'overdue'
+                     AND tstzrange(rental_period_start,
+                                   GREATEST(rental_period_end, NOW()) + make_interval(hours => $5::int), '[)')
+                         && tstzrange($3, $4, '[)')))
+        ) AND NOT EXISTS (
+            SELECT 1 FROM product_schema.product_blackouts b
+            JOIN product_schema.product_units u ON u.product_id = b.product_id
+            WHERE u.unit_id = $1 AND b.period && tstzrange($3, $4, '[)')
+        ) as "free!"
+        "#,
+        unit_id,
+        except_rental_id,
+        start,
+        end,
+        OVERDUE_HOLD_HOURS
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    Ok(row.free)
+}
+
+/// Picks a unit that is free for the whole period, or `None` when every unit is booked or the
+/// product is blacked out.
+pub async fn assign_unit(
+    tx: &mut Transaction<'_, Postgres>,
+    product_id: Uuid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let units = lock_active_units(tx, product_id).await?;
+    if blacked_out(tx, product_id, start, end).await? {
+        return Ok(None);
+    }
+    let busy = busy_units(tx, product_id, start, end).await?;
+    Ok(pick_free_unit(&units, &busy))
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+async fn verify_owner(state: &AppState, headers: &HeaderMap, product_id: Uuid) -> Result<(), axum::response::Response> {
+    let user_id = match extract_user_id_from_token(headers) {
+        Ok(id) => id,
+        Err(status) => return Err(err(status, "Unauthorized")),
+    };
+
+    let product = sqlx::query!(
+        "SELECT owner_id FROM product_schema.products WHERE product_id = $1",
+        product_id
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    match product {
+        Ok(Some(p)) if p.owner_id == user_id => Ok(()),
+        Ok(Some(_)) => Err(err(StatusCode::FORBIDDEN, "Not authorized to manage this product")),
+        Ok(None) => Err(err(StatusCode::NOT_FOUND, "Product not found")),
+        Err(_) => Err(err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load product")),
+    }
+}
+
+pub async fn list_units(
+    State(state): State<AppState>,
+    Path(product_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let units = sqlx::query!(
+        r#"
+        SELECT unit_id, label, status, created_at
+        FROM product_schema.product_units
+        WHERE product_id = $1
+        ORDER BY created_at, label
+        "#,
+        product_id
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    let units = match units {
+        Ok(u) => u,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch units"),
+    };
+
+    let response: Vec<UnitResponse> = units
+        .into_iter()
+        .map(|u| UnitResponse {
+            unit_id: u.unit_id,
+            label: u.label,
+            status: u.status,
+            created_at: u.created_at,
+        })
+        .collect();
+
+    ok(response)
+}
+
+pub async fn add_unit(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(product_id): Path<Uuid>,
+    Json(req): Json<AddUnitRequest>,
+) -> impl IntoResponse {
+    if let Err(response) = verify_owner(&state, &headers, product_id).await {
+        return response;
+    }
+
+    let existing = sqlx::query!(
+        r#"SELECT COUNT(*) as "count!" FROM product_schema.product_units WHERE product_id = $1"#,
+        product_id
+    )
+    .fetch_one(&state.db)
+    .await;
+
+    let existing = match existing {
+        Ok(e) => e.count,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to add unit"),
+    };
+
+    if existing >= MAX_UNITS_PER_PRODUCT as i64 {
+        return err(
+            StatusCode::BAD_REQUEST,
+            &format!("A product can have at most {} units", MAX_UNITS_PER_PRODUCT),
+        );
+    }
+
+    let label = match req.label.as_deref().map(str::trim) {
+        Some(l) if !l.is_empty() => l.to_string(),
+        _ => default_unit_labels(existing as usize, 1).remove(0),
+    };
+
+    let unit = sqlx::query!(
+        r#"
+        INSERT INTO product_schema.product_units (product_id, label)
+        VALUES ($1, $2)
+        ON CONFLICT (product_id, label) DO NOTHING
+        RETURNING unit_id
+        "#,
+        product_id,
+        label
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    match unit {
+        Ok(Some(u)) => ok(serde_json::json!({
+            "unit_id": u.unit_id,
+            "label": label,
+            "message": "Unit added successfully"
+        })),
+        Ok(None) => err(StatusCode::CONFLICT, "A unit with this label already exists"),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to add unit"),
+    }
+}
+
+pub async fn retire_unit(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((product_id, unit_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    if let Err(response) = verify_owner(&state, &headers, product_id).await {
+        return response;
+    }
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to retire unit"),
+    };
+
+    let units = match lock_active_units(&mut tx, product_id).await {
+        Ok(u) => u,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to retire unit"),
+    };
+
+    if !units.contains(&unit_id) {
+        return err(StatusCode::NOT_FOUND, "Unit not found");
+    }
+
+    if units.len() == 1 {
+        return err(StatusCode::CONFLICT, "A product needs at least one active unit");
+    }
+
+    // Units with upcoming or running bookings have to stay in service. An overdue unit is still
+    // out, and its hold always runs past now.
+    let booked = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM rental_schema.rentals
+        WHERE unit_id = $1
+        AND (status = 'overdue' OR (status IN ('pending_payment', 'requested', 'confirmed', 'active') AND rental_period_end >= NOW()))
+        "#,
+        unit_id
+    )
+    .fetch_one(&mut tx)
+    .await;
+
+    match booked {
+        Ok(b) if b.count > 0 => return err(StatusCode::CONFLICT, "Unit has upcoming bookings"),
+        Ok(_) => {}
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to retire unit"),
+    }
+
+    let result = sqlx::query!(
+        "UPDATE product_schema.product_units SET status = 'retired' WHERE unit_id = $1",
+        unit_id
+    )
+    .execute(&mut tx)
+    .await;
+
+    if result.is_err() || tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to retire unit");
+    }
+
+    ok(serde_json::json!({ "message": "Unit retired successfully" }))
+}
@@ -3,8 +3,36 @@ pub mod state;
 pub mod auth;
 pub mod jwt;
 pub mod product;
+pub mod category;
 pub mod rental;
+pub mod rental_status;
+pub mod cancellation;
+pub mod instant_book;
+pub mod rental_modification;
+pub mod condition_report;
+pub mod escrow;
+pub mod damage_claim;
+pub mod overdue;
+pub mod insurance;
 pub mod messaging;
 pub mod review;
 pub mod payment;
 pub mod subscription;
+pub mod pricing;
+pub mod wishlist;
+pub mod roles;
+pub mod tag;
+pub mod specs;
+pub mod listing;
+pub mod moderation;
+pub mod inventory;
+pub mod calendar;
+pub mod catalog;
+pub mod promotion;
+pub mod analytics;
+pub mod pagination;
+pub mod etag;
+pub mod notification;
+pub mod saved_search;
+pub mod similar;
+pub mod blob_store;
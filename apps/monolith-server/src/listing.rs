@@ -0,0 +1,310 @@
+use std::fmt;
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::state::{ok, err, err_with_data, AppState};
+use crate::jwt::verify_token;
+use crate::roles::{has_any_role, ROLE_ADMIN, ROLE_MODERATOR};
+
+/// Lifecycle of a product listing. Mirrors the CHECK constraint on `product_schema.products.status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListingStatus {
+    Draft,
+    PendingReview,
+    Active,
+    Paused,
+    Archived,
+}
+
+impl ListingStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListingStatus::Draft => "draft",
+            ListingStatus::PendingReview => "pending_review",
+            ListingStatus::Active => "active",
+            ListingStatus::Paused => "paused",
+            ListingStatus::Archived => "archived",
+        }
+    }
+
+    /// States reachable from `self` in one step. `Archived` is terminal.
+    pub fn allowed_transitions(&self) -> &'static [ListingStatus] {
+        use ListingStatus::*;
+        match self {
+            Draft => &[PendingReview, Archived],
+            PendingReview => &[Active, Draft, Archived],
+            Active => &[Paused, Archived],
+            Paused => &[Active, Archived],
+            Archived => &[],
+        }
+    }
+
+    pub fn can_transition_to(&self, next: ListingStatus) -> bool {
+        self.allowed_transitions().contains(&next)
+    }
+
+    /// Entering these states puts the listing in front of renters or reviewers.
+    pub fn requires_complete_listing(&self) -> bool {
+        matches!(self, ListingStatus::PendingReview | ListingStatus::Active)
+    }
+}
+
+impl fmt::Display for ListingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ListingStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "draft" => Ok(ListingStatus::Draft),
+            "pending_review" => Ok(ListingStatus::PendingReview),
+            "active" => Ok(ListingStatus::Active),
+            "paused" => Ok(ListingStatus::Paused),
+            "archived" => Ok(ListingStatus::Archived),
+            other => Err(format!("Unknown listing status '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeListingStatusRequest {
+    pub status: ListingStatus,
+}
+
+/// Missing pieces that keep a listing from being published; empty when it is complete.
+pub fn publish_blockers(image_count: i64, daily_price: f64, address: Option<&serde_json::Value>) -> Vec<String> {
+    let mut missing = Vec::new();
+    if image_count < 1 {
+        missing.push("At least one image is required".to_string());
+    }
+    if daily_price <= 0.0 {
+        missing.push("daily_price must be greater than zero".to_string());
+    }
+    let has_field = |field: &str| {
+        address
+            .and_then(|a| a.get(field))
+            .and_then(|v| v.as_str())
+            .map(|v| !v.trim().is_empty())
+            .unwrap_or(false)
+    };
+    if !has_field("city") || !has_field("country") {
+        missing.push("address must include city and country".to_string());
+    }
+    missing
+}
+
+/// Moves a listing from `from` to `to` inside the caller's transaction, enforcing the state
+/// machine, reviewer approval, publish completeness and the no-open-rentals rule.
+/// Returns the resulting status: a submitted listing with no open moderation case goes live.
+pub async fn transition_listing(
+    tx: &mut Transaction<'_, Postgres>,
+    product_id: Uuid,
+    from: ListingStatus,
+    to: ListingStatus,
+    is_reviewer: bool,
+) -> Result<ListingStatus, (StatusCode, String, Vec<String>)> {
+    if from == to {
+        return Ok(to);
+    }
+
+    if !from.can_transition_to(to) {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Cannot move listing from {} to {}", from, to),
+            Vec::new(),
+        ));
+    }
+
+    // Only reviewers approve a submitted listing; owners may withdraw it back to draft
+    if from == ListingStatus::PendingReview && to == ListingStatus::Active && !is_reviewer {
+        return Err((StatusCode::FORBIDDEN, "Only moderators can approve listings".to_string(), Vec::new()));
+    }
+
+    // Pending requests and items not yet back count too, not just running rentals
+    let active_rentals = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM rental_schema.rentals
+        WHERE product_id = $1 AND status IN ('pending_payment', 'requested', 'confirmed', 'active', 'overdue')
+        "#,
+        product_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to check rentals".to_string(), Vec::new()))?;
+
+    if active_rentals.count > 0 {
+        return Err((
+            StatusCode::CONFLICT,
+            "Listing status cannot change while it has open rentals".to_string(),
+            Vec::new(),
+        ));
+    }
+
+    if to.requires_complete_listing() {
+        let listing = sqlx::query!(
+            r#"
+            SELECT p.daily_price, p.address,
+                   (SELECT COUNT(*) FROM product_schema.product_images pi WHERE pi.product_id = p.product_id) as "image_count!"
+            FROM product_schema.products p
+            WHERE p.product_id = $1
+            "#,
+            product_id
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load listing".to_string(), Vec::new()))?;
+
+        let missing = publish_blockers(listing.image_count, listing.daily_price, listing.address.as_ref());
+        if !missing.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, "Listing is incomplete".to_string(), missing));
+        }
+    }
+
+    // Listings the moderation pipeline did not flag skip the manual review queue
+    let mut next = to;
+    if to == ListingStatus::PendingReview {
+        let open_cases = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "count!"
+            FROM product_schema.moderation_cases
+            WHERE product_id = $1 AND status = 'open'
+            "#,
+            product_id
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to check moderation cases".to_string(), Vec::new()))?;
+
+        if open_cases.count == 0 {
+            next = ListingStatus::Active;
+        }
+    }
+
+    // activated_at marks new and reactivated listings for saved-search alerts
+    sqlx::query!(
+        r#"
+        UPDATE product_schema.products
+        SET status = $2,
+            activated_at = CASE WHEN $2 = 'active' THEN NOW() ELSE activated_at END,
+            updated_at = NOW()
+        WHERE product_id = $1
+        "#,
+        product_id,
+        next.as_str()
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to update listing status".to_string(), Vec::new()))?;
+
+    Ok(next)
+}
+
+/// Incomplete listings report what is missing alongside the error message.
+pub fn transition_error_response((status, message, missing): (StatusCode, String, Vec<String>)) -> axum::response::Response {
+    if missing.is_empty() {
+        err(status, &message)
+    } else {
+        err_with_data(status, &message, serde_json::json!({ "missing": missing }))
+    }
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+pub async fn change_listing_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(product_id): Path<Uuid>,
+    Json(req): Json<ChangeListingStatusRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let is_reviewer = match has_any_role(&state.db, user_id, &[ROLE_ADMIN, ROLE_MODERATOR]).await {
+        Ok(is_reviewer) => is_reviewer,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update listing status"),
+    };
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update listing status"),
+    };
+
+    // Lock the row so concurrent transitions see each other's result
+    let product = sqlx::query!(
+        "SELECT owner_id, status FROM product_schema.products WHERE product_id = $1 FOR UPDATE",
+        product_id
+    )
+    .fetch_optional(&mut tx)
+    .await;
+
+    let product = match product {
+        Ok(Some(p)) => p,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Product not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update listing status"),
+    };
+
+    if product.owner_id != user_id && !is_reviewer {
+        return err(StatusCode::FORBIDDEN, "Not authorized to update this product");
+    }
+
+    let current = match product.status.parse::<ListingStatus>() {
+        Ok(s) => s,
+        Err(message) => return err(StatusCode::INTERNAL_SERVER_ERROR, &message),
+    };
+
+    let status = match transition_listing(&mut tx, product_id, current, req.status, is_reviewer).await {
+        Ok(s) => s,
+        Err(e) => return transition_error_response(e),
+    };
+
+    if tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update listing status");
+    }
+
+    ok(serde_json::json!({
+        "product_id": product_id,
+        "previous_status": current,
+        "status": status,
+        "allowed_transitions": status.allowed_transitions(),
+    }))
+}
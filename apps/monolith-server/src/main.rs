@@ -22,8 +22,20 @@ async fn main() -> anyhow::Result<()> {
         .init();
 
     let config = config::AppConfig::from_env()?;
+    match config.cursor_secret.clone() {
+        Some(secret) => monolith_server::pagination::init_cursor_secret(secret),
+        None => tracing::warn!("CURSOR_SECRET not set; pagination cursors are only valid within this process"),
+    }
     let pool = config.make_db_pool().await?;
 
+    tokio::spawn(monolith_server::saved_search::run_matcher(pool.clone()));
+    tokio::spawn(monolith_server::instant_book::run_request_expiry(pool.clone(), config.rental_request_ttl_hours));
+    tokio::spawn(monolith_server::overdue::run_overdue_job(pool.clone()));
+    tokio::spawn(monolith_server::calendar::run_calendar_import_job(pool.clone()));
+    tokio::spawn(monolith_server::catalog::run_import_reclaim_job(pool.clone()));
+    tokio::spawn(monolith_server::payment::run_payment_settlement_job(pool.clone()));
+    tokio::spawn(monolith_server::damage_claim::run_deposit_release_job(pool.clone()));
+
     let state = AppState { db: pool };
 
     let app = Router::new()
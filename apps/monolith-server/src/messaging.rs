@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::state::{err, ok, AppState};
+use crate::pagination::{decode_cursor, page_size, CursorPage, DEFAULT_PAGE_SIZE};
 
 // Request/Response Models
 #[derive(Debug, Deserialize)]
@@ -25,8 +26,9 @@ pub struct SendMessageRequest {
 #[derive(Debug, Deserialize)]
 pub struct ConversationFilters {
     pub status: Option<String>,
+    pub cursor: Option<String>,
     pub limit: Option<i64>,
-    pub offset: Option<i64>,
+    pub include_total: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -53,22 +55,6 @@ pub struct MessageResponse {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct ConversationListResponse {
-    pub conversations: Vec<ConversationResponse>,
-    pub total: i64,
-    pub limit: i64,
-    pub offset: i64,
-}
-
-#[derive(Debug, Serialize)]
-pub struct MessageListResponse {
-    pub messages: Vec<MessageResponse>,
-    pub total: i64,
-    pub limit: i64,
-    pub offset: i64,
-}
-
 // Helper function to extract user ID from JWT token
 async fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, (axum::http::StatusCode, String)> {
     let auth_header = headers
@@ -194,8 +180,11 @@ pub async fn list_conversations(
         Err(e) => return err(e.0, e.1.as_str()),
     };
 
-    let limit = filters.limit.unwrap_or(20).min(100);
-    let offset = filters.offset.unwrap_or(0);
+    let limit = page_size(filters.limit, DEFAULT_PAGE_SIZE);
+    let cursor = match decode_cursor(filters.cursor.as_deref(), "conversations") {
+        Ok(c) => c,
+        Err(e) => return err(axum::http::StatusCode::BAD_REQUEST, &e.to_string()),
+    };
     let status = filters.status.unwrap_or_else(|| "active".to_string().to_string());
 
     let conversations = sqlx::query!(
@@ -216,29 +205,35 @@ pub async fn list_conversations(
         LEFT JOIN messaging_schema.messages msg ON msg.conversation_id = c.id
         WHERE (c.owner_id = $1 OR c.renter_id = $1)
         AND c.status = $2
+        AND ($4::timestamptz IS NULL OR (c.updated_at, c.id) < ($4, $5))
         GROUP BY c.id, c.rental_id, c.owner_id, c.renter_id, c.status, c.created_at, c.updated_at,
                  m.id, m.content, m.message_type, m.created_at, m.sender_id
-        ORDER BY c.updated_at DESC
-        LIMIT $3 OFFSET $4
+        ORDER BY c.updated_at DESC, c.id DESC
+        LIMIT $3
         "#,
         user_id,
         status,
-        limit,
-        offset
+        limit + 1,
+        cursor.as_ref().map(|c| c.sort_key),
+        cursor.as_ref().map(|c| c.id)
     )
     .fetch_all(&state.db)
     .await
     .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?;
 
-    let total = sqlx::query!(
-        "SELECT COUNT(*) as count FROM messaging_schema.conversations WHERE (owner_id = $1 OR renter_id = $1) AND status = $2",
-        user_id,
-        status
-    )
-    .fetch_one(&state.db)
-    .await
-    .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?
-    .count;
+    let total = if filters.include_total.unwrap_or(false) {
+        sqlx::query!(
+            "SELECT COUNT(*) as count FROM messaging_schema.conversations WHERE (owner_id = $1 OR renter_id = $1) AND status = $2",
+            user_id,
+            status
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?
+        .count
+    } else {
+        None
+    };
 
     let conversation_responses: Vec<ConversationResponse> = conversations
         .into_iter()
@@ -263,12 +258,8 @@ pub async fn list_conversations(
         })
         .collect();
 
-    let response = ConversationListResponse {
-        conversations: conversation_responses,
-        total,
-        limit,
-        offset,
-    };
+    let response = CursorPage::from_rows(conversation_responses, limit, "conversations", |c| (c.updated_at, c.id))
+        .with_total(total);
 
     ok(response)
 }
@@ -434,33 +425,42 @@ pub async fn list_messages(
         return err(axum::http::StatusCode::NOT_FOUND, "Conversation not found or access denied");
     }
 
-    let limit = filters.limit.unwrap_or(50).min(100);
-    let offset = filters.offset.unwrap_or(0);
+    let limit = page_size(filters.limit, 50);
+    let cursor = match decode_cursor(filters.cursor.as_deref(), "messages") {
+        Ok(c) => c,
+        Err(e) => return err(axum::http::StatusCode::BAD_REQUEST, &e.to_string()),
+    };
 
     let messages = sqlx::query!(
         r#"
         SELECT id, sender_id, content, message_type, is_read, created_at
         FROM messaging_schema.messages
         WHERE conversation_id = $1
-        ORDER BY created_at DESC
-        LIMIT $2 OFFSET $3
+        AND ($3::timestamptz IS NULL OR (created_at, id) < ($3, $4))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $2
         "#,
         conversation_id,
-        limit,
-        offset
+        limit + 1,
+        cursor.as_ref().map(|c| c.sort_key),
+        cursor.as_ref().map(|c| c.id)
     )
     .fetch_all(&state.db)
     .await
     .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?;
 
-    let total = sqlx::query!(
-        "SELECT COUNT(*) as count FROM messaging_schema.messages WHERE conversation_id = $1",
-        conversation_id
-    )
-    .fetch_one(&state.db)
-    .await
-    .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?
-    .count;
+    let total = if filters.include_total.unwrap_or(false) {
+        sqlx::query!(
+            "SELECT COUNT(*) as count FROM messaging_schema.messages WHERE conversation_id = $1",
+            conversation_id
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?
+        .count
+    } else {
+        None
+    };
 
     let message_responses: Vec<MessageResponse> = messages
         .into_iter()
@@ -475,12 +475,8 @@ pub async fn list_messages(
         })
         .collect();
 
-    let response = MessageListResponse {
-        messages: message_responses,
-        total,
-        limit,
-        offset,
-    };
+    let response = CursorPage::from_rows(message_responses, limit, "messages", |m| (m.created_at, m.id))
+        .with_total(total);
 
     ok(response)
 }
@@ -0,0 +1,608 @@
+use std::sync::OnceLock;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::listing::{transition_error_response, transition_listing, ListingStatus};
+use crate::tag::set_product_tags;
+use crate::roles::{has_any_role, ROLE_ADMIN, ROLE_MODERATOR};
+
+/// Outcome of scanning a listing, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationDecision {
+    Approve,
+    Flag,
+    Reject,
+}
+
+/// The user-supplied text of a listing that scanners look at.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ListingContent {
+    pub name: String,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl ListingContent {
+    pub fn text(&self) -> String {
+        let mut parts = vec![self.name.as_str()];
+        if let Some(description) = &self.description {
+            parts.push(description);
+        }
+        parts.extend(self.tags.iter().map(|t| t.as_str()));
+        parts.join("\n")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanFinding {
+    pub scanner: String,
+    pub rule: String,
+    pub decision: ModerationDecision,
+    pub excerpt: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationOutcome {
+    pub decision: ModerationDecision,
+    pub findings: Vec<ScanFinding>,
+}
+
+/// A single moderation check. Scanners are synchronous and must not touch the database.
+pub trait ContentScanner: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn scan(&self, content: &ListingContent) -> Vec<ScanFinding>;
+}
+
+pub struct KeywordRule {
+    pub keyword: String,
+    pub decision: ModerationDecision,
+}
+
+/// Whole-word, case-insensitive keyword matching.
+pub struct KeywordScanner {
+    rules: Vec<KeywordRule>,
+}
+
+impl KeywordScanner {
+    pub fn new(rules: Vec<KeywordRule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .map(|r| KeywordRule { keyword: words(&r.keyword), decision: r.decision })
+            .collect();
+        KeywordScanner { rules }
+    }
+}
+
+/// Lowercases and reduces text to single-space separated alphanumeric words.
+fn words(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl ContentScanner for KeywordScanner {
+    fn name(&self) -> &'static str {
+        "keyword"
+    }
+
+    fn scan(&self, content: &ListingContent) -> Vec<ScanFinding> {
+        let haystack = format!(" {} ", words(&content.text()));
+        self.rules
+            .iter()
+            .filter(|r| haystack.contains(&format!(" {} ", r.keyword)))
+            .map(|r| ScanFinding {
+                scanner: self.name().to_string(),
+                rule: r.keyword.clone(),
+                decision: r.decision,
+                excerpt: r.keyword.clone(),
+            })
+            .collect()
+    }
+}
+
+pub struct RegexRule {
+    pub label: String,
+    pub pattern: Regex,
+    pub decision: ModerationDecision,
+}
+
+pub struct RegexScanner {
+    rules: Vec<RegexRule>,
+}
+
+impl RegexScanner {
+    pub fn new(rules: &[(&str, &str, ModerationDecision)]) -> Result<Self, regex::Error> {
+        let rules = rules
+            .iter()
+            .map(|(label, pattern, decision)| {
+                Ok(RegexRule { label: label.to_string(), pattern: Regex::new(pattern)?, decision: *decision })
+            })
+            .collect::<Result<Vec<_>, regex::Error>>()?;
+        Ok(RegexScanner { rules })
+    }
+}
+
+impl ContentScanner for RegexScanner {
+    fn name(&self) -> &'static str {
+        "regex"
+    }
+
+    fn scan(&self, content: &ListingContent) -> Vec<ScanFinding> {
+        let text = content.text();
+        self.rules
+            .iter()
+            .filter_map(|r| {
+                r.pattern.find(&text).map(|m| ScanFinding {
+                    scanner: self.name().to_string(),
+                    rule: r.label.clone(),
+                    decision: r.decision,
+                    excerpt: m.as_str().to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Flags emails, phone numbers and links that would move the deal off-platform.
+pub struct ContactInfoScanner {
+    inner: RegexScanner,
+}
+
+impl ContactInfoScanner {
+    pub fn new() -> Self {
+        let inner = RegexScanner::new(&[
+            ("email", r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b", ModerationDecision::Flag),
+            // International, trunk-prefixed national and grouped 3-3-4 numbers; plain digit runs
+            // such as model numbers and measurements do not match
+            (
+                "phone",
+                r"\+\d[\d\s().-]{7,17}\d|\b0\d{2,4}[\s/.-]?\d{5,8}\b|\(?\b\d{3}\)?[\s.-]\d{3}[\s.-]\d{4}\b",
+                ModerationDecision::Flag,
+            ),
+            ("url", r"(?i)\b(?:https?://|www\.)\S+", ModerationDecision::Flag),
+        ])
+        .expect("built-in contact patterns are valid");
+        ContactInfoScanner { inner }
+    }
+}
+
+impl Default for ContactInfoScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContentScanner for ContactInfoScanner {
+    fn name(&self) -> &'static str {
+        "contact_info"
+    }
+
+    fn scan(&self, content: &ListingContent) -> Vec<ScanFinding> {
+        self.inner
+            .scan(content)
+            .into_iter()
+            .map(|f| ScanFinding { scanner: self.name().to_string(), ..f })
+            .collect()
+    }
+}
+
+/// Stand-in for a trained model: scores spam/scam signals locally so the pipeline has a
+/// classifier slot until a real one is wired in.
+pub struct ClassifierStub {
+    pub flag_threshold: f64,
+    pub reject_threshold: f64,
+}
+
+impl Default for ClassifierStub {
+    fn default() -> Self {
+        ClassifierStub { flag_threshold: 0.5, reject_threshold: 0.9 }
+    }
+}
+
+const SCAM_PHRASES: &[&str] = &["wire transfer", "western union", "gift card", "pay outside", "deposit first"];
+
+impl ClassifierStub {
+    /// Spam score in `[0, 1]`.
+    pub fn score(&self, content: &ListingContent) -> f64 {
+        let text = content.text();
+        let normalized = words(&text);
+        let mut score = 0.0;
+
+        score += SCAM_PHRASES.iter().filter(|p| normalized.contains(*p)).count() as f64 * 0.35;
+
+        let letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).collect();
+        if letters.len() >= 20 {
+            let upper = letters.iter().filter(|c| c.is_uppercase()).count() as f64;
+            if upper / letters.len() as f64 > 0.7 {
+                score += 0.3;
+            }
+        }
+
+        if text.matches('!').count() >= 5 {
+            score += 0.2;
+        }
+
+        f64::min(score, 1.0)
+    }
+}
+
+impl ContentScanner for ClassifierStub {
+    fn name(&self) -> &'static str {
+        "classifier"
+    }
+
+    fn scan(&self, content: &ListingContent) -> Vec<ScanFinding> {
+        let score = self.score(content);
+        let decision = if score >= self.reject_threshold {
+            ModerationDecision::Reject
+        } else if score >= self.flag_threshold {
+            ModerationDecision::Flag
+        } else {
+            return Vec::new();
+        };
+        vec![ScanFinding {
+            scanner: self.name().to_string(),
+            rule: "spam_score".to_string(),
+            decision,
+            excerpt: format!("{:.2}", score),
+        }]
+    }
+}
+
+pub struct ModerationPipeline {
+    scanners: Vec<Box<dyn ContentScanner>>,
+}
+
+impl ModerationPipeline {
+    pub fn new() -> Self {
+        ModerationPipeline { scanners: Vec::new() }
+    }
+
+    pub fn with_scanner<S: ContentScanner + 'static>(mut self, scanner: S) -> Self {
+        self.scanners.push(Box::new(scanner));
+        self
+    }
+
+    /// The strictest finding wins; no findings means the listing is approved.
+    pub fn evaluate(&self, content: &ListingContent) -> ModerationOutcome {
+        let findings: Vec<ScanFinding> = self.scanners.iter().flat_map(|s| s.scan(content)).collect();
+        let decision = findings
+            .iter()
+            .map(|f| f.decision)
+            .max()
+            .unwrap_or(ModerationDecision::Approve);
+        ModerationOutcome { decision, findings }
+    }
+}
+
+impl Default for ModerationPipeline {
+    /// Built-in rules for prohibited items, contact details and spam.
+    fn default() -> Self {
+        let keywords = [
+            ("firearm", ModerationDecision::Reject),
+            ("handgun", ModerationDecision::Reject),
+            ("ammunition", ModerationDecision::Reject),
+            ("explosives", ModerationDecision::Reject),
+            ("cocaine", ModerationDecision::Reject),
+            ("counterfeit", ModerationDecision::Reject),
+            ("replica", ModerationDecision::Flag),
+            ("drone jammer", ModerationDecision::Reject),
+            ("pepper spray", ModerationDecision::Flag),
+        ]
+        .into_iter()
+        .map(|(keyword, decision)| KeywordRule { keyword: keyword.to_string(), decision })
+        .collect();
+
+        let prohibited = RegexScanner::new(&[
+            ("fake_documents", r"(?i)\bfake\s+(?:id|passport|licen[cs]e)s?\b", ModerationDecision::Reject),
+            ("prescription_drugs", r"(?i)\bprescription\s+(?:drug|medication|pill)s?\b", ModerationDecision::Reject),
+            ("crypto_payment", r"(?i)\b(?:crypto|bitcoin|btc)\s+(?:only|payment)\b", ModerationDecision::Flag),
+        ])
+        .expect("built-in moderation patterns are valid");
+
+        ModerationPipeline::new()
+            .with_scanner(KeywordScanner::new(keywords))
+            .with_scanner(prohibited)
+            .with_scanner(ContactInfoScanner::new())
+            .with_scanner(ClassifierStub::default())
+    }
+}
+
+/// Shared pipeline so the built-in regexes are compiled once.
+pub fn default_pipeline() -> &'static ModerationPipeline {
+    static PIPELINE: OnceLock<ModerationPipeline> = OnceLock::new();
+    PIPELINE.get_or_init(ModerationPipeline::default)
+}
+
+/// Queues a flagged listing for moderators, refreshing the findings of an already open case.
+/// `pending` is edited text of a published listing, held back until a moderator approves it.
+pub async fn open_moderation_case(
+    tx: &mut Transaction<'_, Postgres>,
+    product_id: Uuid,
+    outcome: &ModerationOutcome,
+    pending: Option<&ListingContent>,
+) -> Result<(), sqlx::Error> {
+    let findings = serde_json::to_value(&outcome.findings).unwrap_or_default();
+    let pending = pending.and_then(|c| serde_json::to_value(c).ok());
+    sqlx::query!(
+        r#"
+        INSERT INTO product_schema.moderation_cases (product_id, findings, pending_content)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (product_id) WHERE status = 'open'
+        DO UPDATE SET findings = EXCLUDED.findings, pending_content = EXCLUDED.pending_content, updated_at = NOW()
+        "#,
+        product_id,
+        findings,
+        pending
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModerationCaseFilters {
+    pub status: Option<String>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResolveCaseRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModerationCaseResponse {
+    pub case_id: Uuid,
+    pub product_id: Uuid,
+    pub product_name: String,
+    pub product_status: String,
+    pub status: String,
+    pub findings: serde_json::Value,
+    pub resolved_by: Option<Uuid>,
+    pub resolution_note: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+async fn require_moderator(state: &AppState, headers: &HeaderMap) -> Result<Uuid, axum::response::Response> {
+    let user_id = match extract_user_id_from_token(headers) {
+        Ok(id) => id,
+        Err(status) => return Err(err(status, "Unauthorized")),
+    };
+
+    match has_any_role(&state.db, user_id, &[ROLE_ADMIN, ROLE_MODERATOR]).await {
+        Ok(true) => Ok(user_id),
+        Ok(false) => Err(err(StatusCode::FORBIDDEN, "Moderator access required")),
+        Err(_) => Err(err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to check permissions")),
+    }
+}
+
+pub async fn list_moderation_cases(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(filters): Query<ModerationCaseFilters>,
+) -> impl IntoResponse {
+    if let Err(response) = require_moderator(&state, &headers).await {
+        return response;
+    }
+
+    let status = filters.status.unwrap_or_else(|| "open".to_string());
+    let page = filters.page.unwrap_or(1).max(1);
+    let per_page = filters.per_page.unwrap_or(20).min(100);
+    let offset = (page - 1) * per_page;
+
+    let cases = sqlx::query!(
+        r#"
+        SELECT c.case_id, c.product_id, p.name as product_name, p.status as product_status,
+               c.status, c.findings, c.resolved_by, c.resolution_note, c.created_at, c.resolved_at
+        FROM product_schema.moderation_cases c
+        JOIN product_schema.products p ON p.product_id = c.product_id
+        WHERE c.status = $1
+        ORDER BY c.created_at
+        LIMIT $2 OFFSET $3
+        "#,
+        status,
+        per_page,
+        offset
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    let cases = match cases {
+        Ok(c) => c,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch moderation cases"),
+    };
+
+    let response: Vec<ModerationCaseResponse> = cases
+        .into_iter()
+        .map(|c| ModerationCaseResponse {
+            case_id: c.case_id,
+            product_id: c.product_id,
+            product_name: c.product_name,
+            product_status: c.product_status,
+            status: c.status,
+            findings: c.findings,
+            resolved_by: c.resolved_by,
+            resolution_note: c.resolution_note,
+            created_at: c.created_at,
+            resolved_at: c.resolved_at,
+        })
+        .collect();
+
+    ok(response)
+}
+
+/// Closes an open case and moves the listing accordingly: approval publishes a listing waiting
+/// for review, rejection sends it back to draft or pauses it if it is already live. A case
+/// holding back edits to a published listing only decides whether those edits go live.
+async fn resolve_case(
+    state: &AppState,
+    headers: &HeaderMap,
+    case_id: Uuid,
+    approve: bool,
+    note: Option<String>,
+) -> axum::response::Response {
+    let moderator_id = match require_moderator(state, headers).await {
+        Ok(id) => id,
+        Err(response) => return response,
+    };
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve moderation case"),
+    };
+
+    let case = sqlx::query!(
+        r#"
+        SELECT c.product_id, c.pending_content, p.status as product_status
+        FROM product_schema.moderation_cases c
+        JOIN product_schema.products p ON p.product_id = c.product_id
+        WHERE c.case_id = $1 AND c.status = 'open'
+        FOR UPDATE
+        "#,
+        case_id
+    )
+    .fetch_optional(&mut tx)
+    .await;
+
+    let case = match case {
+        Ok(Some(c)) => c,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Open moderation case not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve moderation case"),
+    };
+
+    let resolved = sqlx::query!(
+        r#"
+        UPDATE product_schema.moderation_cases
+        SET status = $2, resolved_by = $3, resolution_note = $4, resolved_at = NOW(), updated_at = NOW()
+        WHERE case_id = $1
+        "#,
+        case_id,
+        if approve { "approved" } else { "rejected" },
+        moderator_id,
+        note
+    )
+    .execute(&mut tx)
+    .await;
+
+    if resolved.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve moderation case");
+    }
+
+    let current = match case.product_status.parse::<ListingStatus>() {
+        Ok(s) => s,
+        Err(message) => return err(StatusCode::INTERNAL_SERVER_ERROR, &message),
+    };
+
+    let pending = case
+        .pending_content
+        .and_then(|c| serde_json::from_value::<ListingContent>(c).ok());
+
+    if let Some(content) = &pending {
+        if approve && apply_listing_content(&mut tx, case.product_id, content).await.is_err() {
+            return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve moderation case");
+        }
+    }
+
+    let next = match (approve, current) {
+        // Rejected edits are dropped; the approved text stays live
+        _ if pending.is_some() => current,
+        (true, ListingStatus::PendingReview) => ListingStatus::Active,
+        (false, ListingStatus::PendingReview) => ListingStatus::Draft,
+        (false, ListingStatus::Active) => ListingStatus::Paused,
+        (_, other) => other,
+    };
+
+    let status = match transition_listing(&mut tx, case.product_id, current, next, true).await {
+        Ok(s) => s,
+        Err(e) => return transition_error_response(e),
+    };
+
+    if tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to resolve moderation case");
+    }
+
+    ok(serde_json::json!({
+        "case_id": case_id,
+        "product_id": case.product_id,
+        "product_status": status,
+        "message": if approve { "Listing approved" } else { "Listing rejected" }
+    }))
+}
+
+async fn apply_listing_content(
+    tx: &mut Transaction<'_, Postgres>,
+    product_id: Uuid,
+    content: &ListingContent,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE product_schema.products SET name = $2, description = $3, updated_at = NOW() WHERE product_id = $1",
+        product_id,
+        content.name,
+        content.description
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    set_product_tags(tx, product_id, &content.tags).await
+}
+
+pub async fn approve_moderation_case(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(case_id): Path<Uuid>,
+    Json(req): Json<ResolveCaseRequest>,
+) -> impl IntoResponse {
+    resolve_case(&state, &headers, case_id, true, req.note).await
+}
+
+pub async fn reject_moderation_case(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(case_id): Path<Uuid>,
+    Json(req): Json<ResolveCaseRequest>,
+) -> impl IntoResponse {
+    resolve_case(&state, &headers, case_id, false, req.note).await
+}
@@ -0,0 +1,183 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::pagination::{decode_cursor, page_size, CursorPage, DEFAULT_PAGE_SIZE};
+
+pub const KIND_SAVED_SEARCH_MATCH: &str = "saved_search_match";
+pub const KIND_SAVED_SEARCH_DIGEST: &str = "saved_search_digest";
+pub const KIND_RENTAL_REQUEST_EXPIRED: &str = "rental_request_expired";
+pub const KIND_DAMAGE_CLAIM_OPENED: &str = "damage_claim_opened";
+pub const KIND_DAMAGE_CLAIM_RESOLVED: &str = "damage_claim_resolved";
+pub const KIND_RENTAL_OVERDUE: &str = "rental_overdue";
+pub const KIND_LATE_FEE_CHARGED: &str = "late_fee_charged";
+
+/// A message for a user. Notifications are written to the user's inbox; delivery over push or
+/// email reads from the same table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewNotification {
+    pub user_id: Uuid,
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationResponse {
+    pub notification_id: Uuid,
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+    pub data: serde_json::Value,
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationFilters {
+    pub unread_only: Option<bool>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Queues a notification inside the caller's transaction, so it is only sent if the change
+/// that caused it commits.
+pub async fn send_notification(
+    tx: &mut Transaction<'_, Postgres>,
+    notification: &NewNotification,
+) -> Result<Uuid, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO user_schema.notifications (user_id, kind, title, body, data)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING notification_id
+        "#,
+        notification.user_id,
+        notification.kind,
+        notification.title,
+        notification.body,
+        notification.data
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    Ok(row.notification_id)
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+pub async fn list_notifications(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(filters): Query<NotificationFilters>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let limit = page_size(filters.limit, DEFAULT_PAGE_SIZE);
+    let cursor = match decode_cursor(filters.cursor.as_deref(), "notifications") {
+        Ok(c) => c,
+        Err(e) => return err(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
+
+    let notifications = sqlx::query!(
+        r#"
+        SELECT notification_id, kind, title, body, data, read_at, created_at
+        FROM user_schema.notifications
+        WHERE user_id = $1
+        AND (NOT $2 OR read_at IS NULL)
+        AND ($4::timestamptz IS NULL OR (created_at, notification_id) < ($4, $5))
+        ORDER BY created_at DESC, notification_id DESC
+        LIMIT $3
+        "#,
+        user_id,
+        filters.unread_only.unwrap_or(false),
+        limit + 1,
+        cursor.as_ref().map(|c| c.sort_key),
+        cursor.as_ref().map(|c| c.id)
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    let notifications = match notifications {
+        Ok(n) => n,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch notifications"),
+    };
+
+    let responses: Vec<NotificationResponse> = notifications
+        .into_iter()
+        .map(|n| NotificationResponse {
+            notification_id: n.notification_id,
+            kind: n.kind,
+            title: n.title,
+            body: n.body,
+            data: n.data,
+            read_at: n.read_at,
+            created_at: n.created_at,
+        })
+        .collect();
+
+    ok(CursorPage::from_rows(responses, limit, "notifications", |n| (n.created_at, n.notification_id)))
+}
+
+pub async fn mark_notification_read(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(notification_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE user_schema.notifications SET read_at = COALESCE(read_at, NOW())
+        WHERE notification_id = $1 AND user_id = $2
+        "#,
+        notification_id,
+        user_id
+    )
+    .execute(&state.db)
+    .await;
+
+    match result {
+        Ok(r) if r.rows_affected() > 0 => ok(serde_json::json!({ "message": "Notification marked as read" })),
+        Ok(_) => err(StatusCode::NOT_FOUND, "Notification not found"),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update notification"),
+    }
+}
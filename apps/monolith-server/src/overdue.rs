@@ -0,0 +1,482 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::escrow::held_deposit_cents;
+use crate::notification::{send_notification, NewNotification, KIND_LATE_FEE_CHARGED, KIND_RENTAL_OVERDUE};
+use crate::payment::{record_pending_charge, settle_charge};
+use crate::pricing::{PriceQuote, DEFAULT_CURRENCY};
+use crate::rental_status::{record_rental_history, RentalStatus};
+use crate::roles::{has_any_role, ROLE_ADMIN, ROLE_SUPPORT};
+
+pub const OVERDUE_JOB_INTERVAL_SECS: u64 = 300;
+
+/// What an owner charges when an item comes back late.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LateFeePolicy {
+    /// Hours after the rental end before fees start.
+    pub grace_hours: i64,
+    /// Fee per started day late; the listing's daily price when unset.
+    pub daily_fee: Option<f64>,
+    /// No further fees accrue after this many days.
+    pub max_fee_days: i64,
+}
+
+impl Default for LateFeePolicy {
+    fn default() -> Self {
+        LateFeePolicy { grace_hours: 2, daily_fee: None, max_fee_days: 14 }
+    }
+}
+
+impl LateFeePolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0..=72).contains(&self.grace_hours) {
+            return Err("grace_hours must be between 0 and 72".to_string());
+        }
+        if self.daily_fee.is_some_and(|f| !f.is_finite() || f < 0.0) {
+            return Err("daily_fee must be a non-negative amount".to_string());
+        }
+        if !(1..=60).contains(&self.max_fee_days) {
+            return Err("max_fee_days must be between 1 and 60".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn daily_fee_cents(&self, daily_price: f64) -> i64 {
+        (self.daily_fee.unwrap_or(daily_price).max(0.0) * 100.0).round() as i64
+    }
+}
+
+/// Started days late once the grace period after `end` has passed, capped by the policy.
+pub fn fee_days_due(end: DateTime<Utc>, policy: &LateFeePolicy, now: DateTime<Utc>) -> i64 {
+    let late_seconds = (now - (end + Duration::hours(policy.grace_hours))).num_seconds();
+    if late_seconds <= 0 {
+        return 0;
+    }
+    ((late_seconds + 86_399) / 86_400).min(policy.max_fee_days)
+}
+
+/// One day's late fee and where the money comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LateFeeCharge {
+    pub fee_day: i64,
+    pub amount_cents: i64,
+    pub from_deposit_cents: i64,
+    pub from_payment_method_cents: i64,
+}
+
+/// Fees for the days after `charged_days` up to `due_days`. Each is taken from the deposit
+/// still held while it lasts, and from the renter's payment method after that.
+pub fn plan_late_fees(
+    charged_days: i64,
+    due_days: i64,
+    fee_cents: i64,
+    deposit_available_cents: i64,
+) -> Vec<LateFeeCharge> {
+    let fee_cents = fee_cents.max(0);
+    let mut deposit = deposit_available_cents.max(0);
+    (charged_days + 1..=due_days)
+        .map(|fee_day| {
+            let from_deposit_cents = fee_cents.min(deposit);
+            deposit -= from_deposit_cents;
+            LateFeeCharge {
+                fee_day,
+                amount_cents: fee_cents,
+                from_deposit_cents,
+                from_payment_method_cents: fee_cents - from_deposit_cents,
+            }
+        })
+        .collect()
+}
+
+/// Moves one active rental past its end to overdue and tells both parties. Returns `false`
+/// when it was returned in the meantime or another instance holds it.
+async fn mark_overdue(db: &PgPool, rental_id: Uuid) -> Result<bool, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let rental = sqlx::query!(
+        r#"
+        SELECT r.renter_id, p.owner_id, p.name
+        FROM rental_schema.rentals r
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE r.rental_id = $1 AND r.status = 'active' AND r.rental_period_end <= NOW()
+        FOR UPDATE OF r SKIP LOCKED
+        "#,
+        rental_id
+    )
+    .fetch_optional(&mut tx)
+    .await?;
+
+    let rental = match rental {
+        Some(r) => r,
+        None => return Ok(false),
+    };
+
+    sqlx::query!(
+        "UPDATE rental_schema.rentals SET status = $2, updated_at = NOW() WHERE rental_id = $1",
+        rental_id,
+        RentalStatus::Overdue.as_str()
+    )
+    .execute(&mut tx)
+    .await?;
+
+    record_rental_history(
+        &mut tx,
+        rental_id,
+        Some(RentalStatus::Active),
+        RentalStatus::Overdue,
+        None,
+        None,
+        Some("Not returned by the end of the rental"),
+    )
+    .await?;
+
+    for (user_id, body) in [
+        (rental.renter_id, "The rental period has ended. Please return the item; late fees may apply."),
+        (rental.owner_id, "The rental period has ended and the item has not been marked as returned."),
+    ] {
+        send_notification(
+            &mut tx,
+            &NewNotification {
+                user_id,
+                kind: KIND_RENTAL_OVERDUE.to_string(),
+                title: format!("{} is overdue", rental.name),
+                body: body.to_string(),
+                data: serde_json::json!({ "rental_id": rental_id }),
+            },
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Records the late fees an overdue rental has accrued since the last pass and charges the part
+/// the deposit does not cover. Each fee day is committed under its `(rental_id, fee_day)` key
+/// before the provider is called, and that key is the charge's idempotency key, so repeated or
+/// concurrent passes never charge a day twice. Returns the cents charged.
+async fn accrue_late_fees(db: &PgPool, rental_id: Uuid, now: DateTime<Utc>) -> Result<i64, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let rental = sqlx::query!(
+        r#"
+        SELECT r.renter_id, r.rental_period_end, r.price_quote, p.name, p.daily_price, p.late_fee_policy,
+               (SELECT COALESCE(MAX(f.fee_day), 0)::int8 FROM rental_schema.rental_late_fees f
+                WHERE f.rental_id = r.rental_id) AS "charged_days!"
+        FROM rental_schema.rentals r
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE r.rental_id = $1 AND r.status = 'overdue'
+        FOR UPDATE OF r SKIP LOCKED
+        "#,
+        rental_id
+    )
+    .fetch_optional(&mut tx)
+    .await?;
+
+    let rental = match rental {
+        Some(r) => r,
+        None => return Ok(0),
+    };
+
+    let policy: LateFeePolicy = serde_json::from_value(rental.late_fee_policy).unwrap_or_default();
+    let due_days = fee_days_due(rental.rental_period_end, &policy, now);
+    if due_days <= rental.charged_days {
+        return Ok(0);
+    }
+
+    let deposit_cents = match held_deposit_cents(&mut tx, rental_id).await {
+        Ok(cents) => cents,
+        Err((_, message)) => {
+            tracing::warn!(%rental_id, error = %message, "could not load deposit for late fees");
+            return Ok(0);
+        }
+    };
+    let currency = rental
+        .price_quote
+        .and_then(|q| serde_json::from_value::<PriceQuote>(q).ok())
+        .map_or_else(|| DEFAULT_CURRENCY.to_string(), |q| q.currency);
+
+    let plan = plan_late_fees(rental.charged_days, due_days, policy.daily_fee_cents(rental.daily_price), deposit_cents);
+
+    let mut charges = Vec::new();
+    let mut charged = 0;
+    let mut unpaid = 0;
+    for fee in &plan {
+        let recorded = sqlx::query!(
+            r#"
+            INSERT INTO rental_schema.rental_late_fees
+            (rental_id, fee_day, amount_cents, from_deposit_cents, payment_method_cents, unpaid_cents)
+            VALUES ($1, $2, $3, $4, $5, 0)
+            ON CONFLICT (rental_id, fee_day) DO NOTHING
+            "#,
+            rental_id,
+            fee.fee_day as i32,
+            fee.amount_cents,
+            fee.from_deposit_cents,
+            fee.from_payment_method_cents
+        )
+        .execute(&mut tx)
+        .await?;
+        if recorded.rows_affected() == 0 {
+            continue;
+        }
+
+        if fee.from_payment_method_cents > 0 {
+            let key = format!("late-fee-{}-{}", rental_id, fee.fee_day);
+            match record_pending_charge(
+                &mut tx,
+                rental.renter_id,
+                Some(rental_id),
+                fee.from_payment_method_cents,
+                &currency,
+                &key,
+                "late fee",
+            )
+            .await
+            {
+                Ok(payment_intent_id) => {
+                    sqlx::query!(
+                        r#"
+                        UPDATE rental_schema.rental_late_fees SET payment_intent_id = $3
+                        WHERE rental_id = $1 AND fee_day = $2
+                        "#,
+                        rental_id,
+                        fee.fee_day as i32,
+                        payment_intent_id
+                    )
+                    .execute(&mut tx)
+                    .await?;
+                    charges.push((fee.fee_day, payment_intent_id, fee.from_payment_method_cents));
+                }
+                // Left on the books for support to collect; the next day is still charged
+                Err((StatusCode::PAYMENT_REQUIRED, _)) => {
+                    mark_fee_unpaid(&mut tx, rental_id, fee.fee_day).await?;
+                    unpaid += fee.from_payment_method_cents;
+                    charged += fee.from_deposit_cents;
+                    continue;
+                }
+                Err((_, message)) => {
+                    tracing::warn!(%rental_id, error = %message, "late fee charge failed");
+                    return Ok(0);
+                }
+            }
+        }
+        charged += fee.amount_cents;
+    }
+
+    if charged + unpaid > 0 {
+        send_notification(
+            &mut tx,
+            &NewNotification {
+                user_id: rental.renter_id,
+                kind: KIND_LATE_FEE_CHARGED.to_string(),
+                title: format!("Late fee for {}", rental.name),
+                body: format!(
+                    "{} is {} day(s) late. {:.2} {} in late fees were charged.",
+                    rental.name,
+                    due_days,
+                    charged as f64 / 100.0,
+                    currency
+                ),
+                data: serde_json::json!({
+                    "rental_id": rental_id,
+                    "days_late": due_days,
+                    "charged_cents": charged,
+                    "unpaid_cents": unpaid,
+                    "fees": plan,
+                }),
+            },
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    // The fee rows are committed; the settlement job retries charges the provider does not take now
+    for (fee_day, payment_intent_id, cents) in charges {
+        match settle_charge(db, payment_intent_id).await {
+            Ok(()) => {}
+            Err((StatusCode::PAYMENT_REQUIRED, message)) => {
+                tracing::warn!(%rental_id, fee_day, error = %message, "late fee charge declined");
+                let mut tx = db.begin().await?;
+                mark_fee_unpaid(&mut tx, rental_id, fee_day).await?;
+                tx.commit().await?;
+                charged -= cents;
+            }
+            Err((_, message)) => tracing::warn!(%rental_id, fee_day, error = %message, "late fee charge failed"),
+        }
+    }
+
+    Ok(charged)
+}
+
+/// Moves what a fee day was to take from the payment method to unpaid.
+async fn mark_fee_unpaid(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+    fee_day: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE rental_schema.rental_late_fees
+        SET unpaid_cents = unpaid_cents + payment_method_cents, payment_method_cents = 0
+        WHERE rental_id = $1 AND fee_day = $2
+        "#,
+        rental_id,
+        fee_day as i32
+    )
+    .execute(&mut *tx)
+    .await?;
+    Ok(())
+}
+
+/// One pass of the overdue job: marks rentals past their end as overdue, then charges late fees
+/// on every overdue rental. Returns `(marked overdue, cents charged)`.
+pub async fn process_overdue_rentals(db: &PgPool) -> Result<(usize, i64), sqlx::Error> {
+    let now = Utc::now();
+
+    let ended = sqlx::query!(
+        "SELECT rental_id FROM rental_schema.rentals WHERE status = 'active' AND rental_period_end <= $1",
+        now
+    )
+    .fetch_all(db)
+    .await?;
+
+    // One rental failing does not hold up the rest
+    let mut marked = 0;
+    for rental in ended {
+        match mark_overdue(db, rental.rental_id).await {
+            Ok(true) => marked += 1,
+            Ok(false) => {}
+            Err(e) => tracing::warn!(rental_id = %rental.rental_id, error = %e, "marking rental overdue failed"),
+        }
+    }
+
+    let overdue = sqlx::query!("SELECT rental_id FROM rental_schema.rentals WHERE status = 'overdue'")
+        .fetch_all(db)
+        .await?;
+
+    let mut charged = 0;
+    for rental in overdue {
+        match accrue_late_fees(db, rental.rental_id, now).await {
+            Ok(cents) => charged += cents,
+            Err(e) => tracing::warn!(rental_id = %rental.rental_id, error = %e, "accruing late fees failed"),
+        }
+    }
+
+    Ok((marked, charged))
+}
+
+/// Background loop started by the server. Every instance may run it; row locks keep them from
+/// processing the same rental at once.
+pub async fn run_overdue_job(db: PgPool) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(OVERDUE_JOB_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        match process_overdue_rentals(&db).await {
+            Ok((0, 0)) => {}
+            Ok((marked, charged_cents)) => tracing::info!(marked, charged_cents, "processed overdue rentals"),
+            Err(e) => tracing::warn!(error = %e, "overdue rental pass failed"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LateFeeResponse {
+    pub fee_day: i32,
+    pub amount_cents: i64,
+    pub from_deposit_cents: i64,
+    pub payment_method_cents: i64,
+    pub unpaid_cents: i64,
+    pub payment_intent_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// Late fees accrued on a rental, for its parties and support staff.
+pub async fn list_late_fees(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let is_staff = match has_any_role(&state.db, user_id, &[ROLE_ADMIN, ROLE_SUPPORT]).await {
+        Ok(is_staff) => is_staff,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch late fees"),
+    };
+
+    let party = sqlx::query!(
+        r#"
+        SELECT r.rental_id FROM rental_schema.rentals r
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE r.rental_id = $1 AND ($3 OR r.renter_id = $2 OR p.owner_id = $2)
+        "#,
+        rental_id,
+        user_id,
+        is_staff
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    match party {
+        Ok(Some(_)) => {}
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Rental not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch late fees"),
+    }
+
+    let fees = sqlx::query_as!(
+        LateFeeResponse,
+        r#"
+        SELECT fee_day, amount_cents, from_deposit_cents, payment_method_cents, unpaid_cents, payment_intent_id, created_at
+        FROM rental_schema.rental_late_fees
+        WHERE rental_id = $1
+        ORDER BY fee_day
+        "#,
+        rental_id
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    match fees {
+        Ok(fees) => ok(fees),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch late fees"),
+    }
+}
@@ -0,0 +1,131 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+static CURSOR_SECRET: OnceLock<String> = OnceLock::new();
+
+/// Sets the key cursors are signed with (`CURSOR_SECRET`). Called once at startup; later
+/// calls are ignored.
+pub fn init_cursor_secret(secret: String) {
+    let _ = CURSOR_SECRET.set(secret);
+}
+
+/// Without a configured key (`CURSOR_SECRET` unset, or in tests) a random one is used, so
+/// cursors are still unforgeable but only valid within this process.
+fn cursor_secret() -> &'static [u8] {
+    CURSOR_SECRET
+        .get_or_init(|| format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()))
+        .as_bytes()
+}
+
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// Keyset position after the last item of a page. Every cursor-paginated list is sorted by a
+/// timestamp descending with the row id as tie-breaker, so the order is total and stable.
+/// `scope` names the list the cursor was issued for. `exclude` lists rows already shown out
+/// of order (e.g. promoted listings on the first page) that later pages must skip.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub scope: String,
+    pub sort_key: DateTime<Utc>,
+    pub id: Uuid,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<Uuid>,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum CursorError {
+    #[error("Invalid cursor")]
+    Invalid,
+    #[error("Cursor was issued for a different list")]
+    WrongScope,
+}
+
+impl Cursor {
+    pub fn new(scope: &str, sort_key: DateTime<Utc>, id: Uuid) -> Self {
+        Cursor { scope: scope.to_string(), sort_key, id, exclude: Vec::new() }
+    }
+
+    pub fn excluding(mut self, exclude: Vec<Uuid>) -> Self {
+        self.exclude = exclude;
+        self
+    }
+
+    /// Opaque, signed token handed to clients as `next_cursor`.
+    pub fn encode(&self) -> String {
+        encode(&Header::default(), self, &EncodingKey::from_secret(cursor_secret()))
+            .unwrap_or_default()
+    }
+
+    pub fn decode(token: &str, scope: &str) -> Result<Cursor, CursorError> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+
+        let cursor = decode::<Cursor>(token, &DecodingKey::from_secret(cursor_secret()), &validation)
+            .map(|data| data.claims)
+            .map_err(|_| CursorError::Invalid)?;
+
+        if cursor.scope != scope {
+            return Err(CursorError::WrongScope);
+        }
+        Ok(cursor)
+    }
+}
+
+/// Decodes the optional `cursor` query parameter; no cursor means the first page.
+pub fn decode_cursor(token: Option<&str>, scope: &str) -> Result<Option<Cursor>, CursorError> {
+    token.map(|t| Cursor::decode(t, scope)).transpose()
+}
+
+pub fn page_size(limit: Option<i64>, default: i64) -> i64 {
+    limit.unwrap_or(default).clamp(1, MAX_PAGE_SIZE)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    /// Only computed when the client asks for it with `include_total=true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+}
+
+impl<T> CursorPage<T> {
+    /// Builds a page from rows fetched with `LIMIT page_len + 1`; the extra row only tells
+    /// whether another page exists.
+    pub fn from_rows<F>(rows: Vec<T>, page_len: i64, scope: &str, key: F) -> Self
+    where
+        F: Fn(&T) -> (DateTime<Utc>, Uuid),
+    {
+        Self::from_rows_with(rows, page_len, |last| {
+            let (sort_key, id) = key(last);
+            Cursor::new(scope, sort_key, id)
+        })
+    }
+
+    /// Like `from_rows`, but the caller builds the whole cursor from the last row kept.
+    pub fn from_rows_with<F>(mut rows: Vec<T>, page_len: i64, cursor: F) -> Self
+    where
+        F: Fn(&T) -> Cursor,
+    {
+        let page_len = page_len.max(0) as usize;
+        let next_cursor = if rows.len() > page_len {
+            rows.truncate(page_len);
+            rows.last().map(|last| cursor(last).encode())
+        } else {
+            None
+        };
+
+        CursorPage { items: rows, next_cursor, total: None }
+    }
+
+    pub fn with_total(mut self, total: Option<i64>) -> Self {
+        self.total = total;
+        self
+    }
+}
@@ -5,9 +5,11 @@ use axum::{
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
 use crate::state::{err, ok, AppState};
+use crate::pagination::{decode_cursor, page_size, CursorPage, DEFAULT_PAGE_SIZE};
 
 // Request/Response Models
 #[derive(Debug, Deserialize)]
@@ -23,6 +25,7 @@ pub struct CreatePaymentMethodRequest {
 pub struct CreatePaymentIntentRequest {
     pub rental_id: Option<Uuid>,
     pub subscription_id: Option<Uuid>,
+    pub promotion_id: Option<Uuid>,
     pub amount_cents: i32,
     pub currency: Option<String>,
     pub payment_method_id: Option<Uuid>,
@@ -39,7 +42,10 @@ pub struct PaymentFilters {
     pub status: Option<String>,
     pub payment_type: Option<String>,
     pub limit: Option<i64>,
+    /// Offset paging for payment methods; payment intents page with `cursor`
     pub offset: Option<i64>,
+    pub cursor: Option<String>,
+    pub include_total: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -62,6 +68,7 @@ pub struct PaymentIntentResponse {
     pub user_id: Uuid,
     pub rental_id: Option<Uuid>,
     pub subscription_id: Option<Uuid>,
+    pub promotion_id: Option<Uuid>,
     pub amount_cents: i32,
     pub currency: String,
     pub status: String,
@@ -97,14 +104,6 @@ pub struct PaymentMethodListResponse {
     pub offset: i64,
 }
 
-#[derive(Debug, Serialize)]
-pub struct PaymentIntentListResponse {
-    pub payment_intents: Vec<PaymentIntentResponse>,
-    pub total: i64,
-    pub limit: i64,
-    pub offset: i64,
-}
-
 // Helper function to extract user ID from JWT token
 async fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, (axum::http::StatusCode, String)> {
     let auth_header = headers
@@ -140,6 +139,428 @@ async fn confirm_stripe_payment_intent(payment_intent_id: &str, payment_method_i
     Ok(format!("ch_mock_{}_{}", payment_intent_id, payment_method_id))
 }
 
+async fn refund_stripe_charge(charge_id: &str, amount_cents: i64, idempotency_key: Uuid) -> Result<String, String> {
+    // Mock implementation - in real app, this would call Stripe API with an Idempotency-Key header
+    Ok(format!("re_mock_{}_{}_{}", charge_id, amount_cents, idempotency_key))
+}
+
+async fn charge_stripe_payment_method(
+    provider_payment_method_id: &str,
+    amount_cents: i32,
+    currency: &str,
+    idempotency_key: &str,
+) -> Result<(String, String), String> {
+    // Mock implementation - in real app, this would create and confirm a Stripe payment intent in
+    // one call, so a retry with the same idempotency key returns the original charge
+    Ok((
+        format!("pi_mock_{}_{}_{}", amount_cents, currency, idempotency_key),
+        format!("ch_mock_{}_{}", idempotency_key, provider_payment_method_id),
+    ))
+}
+
+// Refunds
+/// How often the background job retries refunds and charges still waiting on the provider.
+pub const PAYMENT_SETTLEMENT_INTERVAL_SECS: u64 = 60;
+/// Pending refunds and charges sent to the provider per pass.
+const SETTLEMENT_BATCH_SIZE: i64 = 100;
+
+/// A captured charge on a rental and how much of it has not been refunded yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefundableCharge {
+    pub payment_intent_id: Uuid,
+    pub amount_cents: i64,
+    pub refundable_cents: i64,
+    pub currency: String,
+    pub provider_charge_id: String,
+}
+
+/// Splits a refund across charges, newest first, never exceeding what each charge has left.
+/// Returns `(payment_intent_id, cents)` pairs; anything beyond the total refundable is dropped.
+pub fn allocate_refund(charges: &[RefundableCharge], refund_cents: i64) -> Vec<(Uuid, i64)> {
+    let mut remaining = refund_cents.max(0);
+    let mut allocations = Vec::new();
+    for charge in charges.iter().rev() {
+        if remaining == 0 {
+            break;
+        }
+        let cents = remaining.min(charge.refundable_cents);
+        if cents > 0 {
+            allocations.push((charge.payment_intent_id, cents));
+            remaining -= cents;
+        }
+    }
+    allocations
+}
+
+/// The renter's succeeded charges for a rental, oldest first, locked for the rest of the
+/// transaction. Refunds still waiting on the provider count as refunded, and a charge without a
+/// provider charge id has nothing the provider could refund.
+pub async fn rental_refundable_charges(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+) -> Result<Vec<RefundableCharge>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT pi.id, pi.amount_cents, pi.currency, pi.provider_charge_id AS "provider_charge_id!",
+               COALESCE((
+                   SELECT SUM(t.amount_cents) FROM payment_schema.payment_transactions t
+                   WHERE t.payment_intent_id = pi.id AND t.transaction_type = 'refund'
+                   AND t.status IN ('pending', 'succeeded')
+               ), 0) AS "refunded_cents!"
+        FROM payment_schema.payment_intents pi
+        JOIN rental_schema.rentals r ON r.rental_id = pi.rental_id AND r.renter_id = pi.user_id
+        WHERE pi.rental_id = $1 AND pi.status IN ('succeeded', 'partially_refunded')
+        AND pi.provider_charge_id IS NOT NULL
+        ORDER BY pi.created_at, pi.id
+        FOR UPDATE OF pi
+        "#,
+        rental_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| RefundableCharge {
+            payment_intent_id: r.id,
+            amount_cents: r.amount_cents as i64,
+            refundable_cents: (r.amount_cents as i64 - r.refunded_cents).max(0),
+            currency: r.currency,
+            provider_charge_id: r.provider_charge_id,
+        })
+        .collect())
+}
+
+/// What is left to refund of the charges `rental_refundable_charges` returns, in cents, read
+/// without locking them; for showing terms, not for recording refunds.
+pub async fn rental_refundable_cents(tx: &mut Transaction<'_, Postgres>, rental_id: Uuid) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(SUM(GREATEST(pi.amount_cents - COALESCE((
+                   SELECT SUM(t.amount_cents) FROM payment_schema.payment_transactions t
+                   WHERE t.payment_intent_id = pi.id AND t.transaction_type = 'refund'
+                   AND t.status IN ('pending', 'succeeded')
+               ), 0), 0)), 0)::BIGINT AS "refundable_cents!"
+        FROM payment_schema.payment_intents pi
+        JOIN rental_schema.rentals r ON r.rental_id = pi.rental_id AND r.renter_id = pi.user_id
+        WHERE pi.rental_id = $1 AND pi.status IN ('succeeded', 'partially_refunded')
+        AND pi.provider_charge_id IS NOT NULL
+        "#,
+        rental_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+}
+
+/// Records refunds of up to `refund_cents` of what was paid for a rental, one pending refund
+/// transaction per charge touched, and returns the amount recorded. Nothing reaches the provider
+/// until `settle_rental_payments` runs after the caller commits.
+pub async fn refund_rental_payments(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+    refund_cents: i64,
+    reason: &str,
+) -> Result<i64, (axum::http::StatusCode, String)> {
+    let charges = rental_refundable_charges(tx, rental_id)
+        .await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut refunded = 0;
+    for (payment_intent_id, cents) in allocate_refund(&charges, refund_cents) {
+        let charge = charges.iter().find(|c| c.payment_intent_id == payment_intent_id).unwrap();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO payment_schema.payment_transactions
+            (payment_intent_id, transaction_type, amount_cents, currency, status, metadata)
+            VALUES ($1, 'refund', $2, $3, 'pending', $4)
+            "#,
+            payment_intent_id,
+            cents as i32,
+            charge.currency,
+            serde_json::json!({ "reason": reason })
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        refunded += cents;
+    }
+
+    Ok(refunded)
+}
+
+/// Sends one pending refund to the provider. The transaction id is the idempotency key, so a
+/// refund retried after a crash is not paid out twice. Returns false if it was already settled.
+async fn settle_refund(db: &PgPool, transaction_id: Uuid) -> Result<bool, String> {
+    let refund = sqlx::query!(
+        r#"
+        SELECT t.amount_cents, pi.provider_charge_id AS "provider_charge_id!"
+        FROM payment_schema.payment_transactions t
+        JOIN payment_schema.payment_intents pi ON pi.id = t.payment_intent_id
+        WHERE t.id = $1 AND t.transaction_type = 'refund' AND t.status = 'pending'
+        "#,
+        transaction_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let refund = match refund {
+        Some(r) => r,
+        None => return Ok(false),
+    };
+
+    let provider_refund_id =
+        refund_stripe_charge(&refund.provider_charge_id, refund.amount_cents as i64, transaction_id).await?;
+
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    let payment_intent_id = sqlx::query_scalar!(
+        r#"
+        UPDATE payment_schema.payment_transactions
+        SET status = 'succeeded', provider_transaction_id = $2, updated_at = NOW()
+        WHERE id = $1 AND status = 'pending'
+        RETURNING payment_intent_id
+        "#,
+        transaction_id,
+        provider_refund_id
+    )
+    .fetch_optional(&mut tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let payment_intent_id = match payment_intent_id {
+        Some(id) => id,
+        None => return Ok(false),
+    };
+
+    sqlx::query!(
+        r#"
+        UPDATE payment_schema.payment_intents pi
+        SET status = CASE
+                WHEN (
+                    SELECT COALESCE(SUM(t.amount_cents), 0) FROM payment_schema.payment_transactions t
+                    WHERE t.payment_intent_id = pi.id AND t.transaction_type = 'refund' AND t.status = 'succeeded'
+                ) >= pi.amount_cents THEN 'refunded'
+                ELSE 'partially_refunded'
+            END,
+            updated_at = NOW()
+        WHERE pi.id = $1
+        "#,
+        payment_intent_id
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Records a charge to the user's saved payment method without calling the provider: the
+/// default method, otherwise the most recently added active one. `idempotency_key` is kept with
+/// the intent so every attempt to collect it sends the same key. Returns the payment intent id.
+pub async fn record_pending_charge(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    rental_id: Option<Uuid>,
+    amount_cents: i64,
+    currency: &str,
+    idempotency_key: &str,
+    reason: &str,
+) -> Result<Uuid, (axum::http::StatusCode, String)> {
+    let amount_cents = i32::try_from(amount_cents)
+        .ok()
+        .filter(|a| *a > 0)
+        .ok_or((axum::http::StatusCode::BAD_REQUEST, "Invalid charge amount".to_string()))?;
+
+    let payment_method_id = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM payment_schema.payment_methods
+        WHERE user_id = $1 AND is_active = true
+        ORDER BY is_default DESC, created_at DESC
+        LIMIT 1
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .ok_or((axum::http::StatusCode::PAYMENT_REQUIRED, "A saved payment method is required".to_string()))?;
+
+    sqlx::query_scalar!(
+        r#"
+        INSERT INTO payment_schema.payment_intents
+        (user_id, rental_id, amount_cents, currency, status, payment_method_id, metadata)
+        VALUES ($1, $2, $3, $4, 'pending', $5, $6)
+        RETURNING id
+        "#,
+        user_id,
+        rental_id,
+        amount_cents,
+        currency,
+        payment_method_id,
+        serde_json::json!({ "idempotency_key": idempotency_key, "reason": reason })
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// Sends a charge recorded by `record_pending_charge` to the provider and records the outcome.
+/// Settling an intent that is no longer pending does nothing; a declined charge marks it failed.
+pub async fn settle_charge(db: &PgPool, payment_intent_id: Uuid) -> Result<(), (axum::http::StatusCode, String)> {
+    let failed = |e: sqlx::Error| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+
+    let intent = sqlx::query!(
+        r#"
+        SELECT pi.amount_cents, pi.currency, pi.metadata->>'idempotency_key' AS "idempotency_key!",
+               pm.provider_payment_method_id AS "provider_payment_method_id?"
+        FROM payment_schema.payment_intents pi
+        LEFT JOIN payment_schema.payment_methods pm ON pm.id = pi.payment_method_id AND pm.is_active = true
+        WHERE pi.id = $1 AND pi.status = 'pending' AND pi.metadata ? 'idempotency_key'
+        "#,
+        payment_intent_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(failed)?;
+
+    let intent = match intent {
+        Some(i) => i,
+        None => return Ok(()),
+    };
+
+    let charged = match intent.provider_payment_method_id {
+        Some(method) => {
+            charge_stripe_payment_method(&method, intent.amount_cents, &intent.currency, &intent.idempotency_key).await
+        }
+        None => Err("The saved payment method was removed".to_string()),
+    };
+
+    let (provider_payment_intent_id, provider_charge_id) = match charged {
+        Ok(ids) => ids,
+        Err(reason) => {
+            sqlx::query!(
+                r#"
+                UPDATE payment_schema.payment_intents
+                SET status = 'failed', failure_reason = $2, updated_at = NOW()
+                WHERE id = $1 AND status = 'pending'
+                "#,
+                payment_intent_id,
+                reason
+            )
+            .execute(db)
+            .await
+            .map_err(failed)?;
+            return Err((axum::http::StatusCode::PAYMENT_REQUIRED, reason));
+        }
+    };
+
+    let mut tx = db.begin().await.map_err(failed)?;
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE payment_schema.payment_intents
+        SET status = 'succeeded', provider_payment_intent_id = $2, provider_charge_id = $3, updated_at = NOW()
+        WHERE id = $1 AND status = 'pending'
+        "#,
+        payment_intent_id,
+        provider_payment_intent_id,
+        provider_charge_id
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(failed)?;
+
+    if updated.rows_affected() == 0 {
+        return Ok(());
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO payment_schema.payment_transactions
+        (payment_intent_id, transaction_type, amount_cents, currency, status, provider_transaction_id)
+        VALUES ($1, 'charge', $2, $3, 'succeeded', $4)
+        "#,
+        payment_intent_id,
+        intent.amount_cents,
+        intent.currency,
+        provider_charge_id
+    )
+    .execute(&mut tx)
+    .await
+    .map_err(failed)?;
+
+    tx.commit().await.map_err(failed)
+}
+
+/// Sends pending refunds and charges to the provider: those of one rental right after the change
+/// that recorded them commits, or every one left behind when `rental_id` is `None`. Failed
+/// refunds stay pending for the next pass. Returns how many were settled.
+pub async fn settle_rental_payments(db: &PgPool, rental_id: Option<Uuid>) -> Result<usize, sqlx::Error> {
+    let refunds = sqlx::query_scalar!(
+        r#"
+        SELECT t.id
+        FROM payment_schema.payment_transactions t
+        JOIN payment_schema.payment_intents pi ON pi.id = t.payment_intent_id
+        WHERE t.transaction_type = 'refund' AND t.status = 'pending'
+        AND ($1::uuid IS NULL OR pi.rental_id = $1)
+        ORDER BY t.created_at
+        LIMIT $2
+        "#,
+        rental_id,
+        SETTLEMENT_BATCH_SIZE
+    )
+    .fetch_all(db)
+    .await?;
+
+    let charges = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM payment_schema.payment_intents
+        WHERE status = 'pending' AND metadata ? 'idempotency_key'
+        AND ($1::uuid IS NULL OR rental_id = $1)
+        ORDER BY created_at
+        LIMIT $2
+        "#,
+        rental_id,
+        SETTLEMENT_BATCH_SIZE
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut settled = 0;
+    for transaction_id in refunds {
+        match settle_refund(db, transaction_id).await {
+            Ok(true) => settled += 1,
+            Ok(false) => {}
+            Err(e) => tracing::warn!(%transaction_id, error = %e, "refund failed"),
+        }
+    }
+    for payment_intent_id in charges {
+        match settle_charge(db, payment_intent_id).await {
+            Ok(()) => settled += 1,
+            Err((_, e)) => tracing::warn!(%payment_intent_id, error = %e, "charge failed"),
+        }
+    }
+    Ok(settled)
+}
+
+/// Background loop started by the server; picks up payments whose request handler died between
+/// committing and calling the provider.
+pub async fn run_payment_settlement_job(db: PgPool) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(PAYMENT_SETTLEMENT_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        match settle_rental_payments(&db, None).await {
+            Ok(0) => {}
+            Ok(settled) => tracing::info!(settled, "settled pending payments"),
+            Err(e) => tracing::warn!(error = %e, "payment settlement pass failed"),
+        }
+    }
+}
+
 // Handlers
 pub async fn create_payment_method(
     State(state): State<AppState>,
@@ -288,13 +709,18 @@ pub async fn create_payment_intent(
         return err(axum::http::StatusCode::BAD_REQUEST, "Amount must be greater than 0");
     }
 
-    // Validate that either rental_id or subscription_id is provided, but not both
-    if request.rental_id.is_some() && request.subscription_id.is_some() {
-        return err(axum::http::StatusCode::BAD_REQUEST, "Cannot specify both rental_id and subscription_id");
+    // Validate that exactly one of rental_id, subscription_id or promotion_id is provided
+    let targets = [request.rental_id, request.subscription_id, request.promotion_id]
+        .iter()
+        .filter(|t| t.is_some())
+        .count();
+
+    if targets > 1 {
+        return err(axum::http::StatusCode::BAD_REQUEST, "Specify only one of rental_id, subscription_id or promotion_id");
     }
 
-    if request.rental_id.is_none() && request.subscription_id.is_none() {
-        return err(axum::http::StatusCode::BAD_REQUEST, "Must specify either rental_id or subscription_id");
+    if targets == 0 {
+        return err(axum::http::StatusCode::BAD_REQUEST, "Must specify rental_id, subscription_id or promotion_id");
     }
 
     // Validate rental exists and user has access (if rental_id provided)
@@ -335,7 +761,43 @@ pub async fn create_payment_intent(
         }
     }
 
-    let currency = request.currency.unwrap_or_else(|| "USD".to_string());
+    // Validate promotion belongs to the user and is still unpaid (if promotion_id provided)
+    let mut promotion_currency = None;
+    if let Some(promotion_id) = request.promotion_id {
+        let promotion = sqlx::query!(
+            "SELECT owner_id, status, amount_cents, currency FROM product_schema.promotions WHERE promotion_id = $1",
+            promotion_id
+        )
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        let promotion = match promotion {
+            Some(p) => p,
+            None => return err(axum::http::StatusCode::NOT_FOUND, "Promotion not found"),
+        };
+
+        if promotion.owner_id != user_id {
+            return err(axum::http::StatusCode::FORBIDDEN, "You can only pay for your own promotions");
+        }
+
+        if promotion.status != "pending_payment" {
+            return err(axum::http::StatusCode::BAD_REQUEST, "Promotion is not awaiting payment");
+        }
+
+        if promotion.amount_cents != request.amount_cents
+            || request.currency.as_deref().is_some_and(|c| c != promotion.currency)
+        {
+            return err(axum::http::StatusCode::BAD_REQUEST, "Amount does not match the promotion price");
+        }
+
+        promotion_currency = Some(promotion.currency);
+    }
+
+    let currency = request
+        .currency
+        .or(promotion_currency)
+        .unwrap_or_else(|| "USD".to_string());
 
     // Create payment intent with provider
     let provider_payment_intent_id = create_stripe_payment_intent(request.amount_cents, &currency)
@@ -346,13 +808,14 @@ pub async fn create_payment_intent(
     let payment_intent = sqlx::query!(
         r#"
         INSERT INTO payment_schema.payment_intents 
-        (user_id, rental_id, subscription_id, amount_cents, currency, payment_method_id, provider_payment_intent_id, metadata)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        (user_id, rental_id, subscription_id, promotion_id, amount_cents, currency, payment_method_id, provider_payment_intent_id, metadata)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
         RETURNING id, created_at, updated_at
         "#,
         user_id,
         request.rental_id,
         request.subscription_id,
+        request.promotion_id,
         request.amount_cents,
         currency,
         request.payment_method_id,
@@ -368,6 +831,7 @@ pub async fn create_payment_intent(
         user_id,
         rental_id: request.rental_id,
         subscription_id: request.subscription_id,
+        promotion_id: request.promotion_id,
         amount_cents: request.amount_cents,
         currency,
         status: "pending".to_string(),
@@ -441,19 +905,24 @@ pub async fn confirm_payment_intent(
     .await
     .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?;
 
+    // The intent, its charge record and whatever it paid for change together
+    let mut tx = state.db.begin()
+        .await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
     // Update payment intent status
     let result = sqlx::query!(
         r#"
         UPDATE payment_schema.payment_intents 
         SET status = 'succeeded', provider_charge_id = $1, payment_method_id = $2, updated_at = NOW()
         WHERE id = $3
-        RETURNING id, user_id, rental_id, subscription_id, amount_cents, currency, status, metadata, created_at, updated_at
+        RETURNING id, user_id, rental_id, subscription_id, promotion_id, amount_cents, currency, status, metadata, created_at, updated_at
         "#,
         provider_charge_id,
         payment_method_id.unwrap(),
         payment_intent_id
     )
-    .fetch_one(&state.db)
+    .fetch_one(&mut tx)
     .await
     .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?;
 
@@ -469,15 +938,35 @@ pub async fn confirm_payment_intent(
         result.currency,
         provider_charge_id
     )
-    .execute(&state.db)
+    .execute(&mut tx)
     .await
     .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?;
 
+    // A paid promotion starts competing for boosted slots on its start date
+    if let Some(promotion_id) = result.promotion_id {
+        sqlx::query!(
+            r#"
+            UPDATE product_schema.promotions
+            SET status = 'paid', paid_at = NOW()
+            WHERE promotion_id = $1 AND status = 'pending_payment'
+            "#,
+            promotion_id
+        )
+        .execute(&mut tx)
+        .await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
     let response = PaymentIntentResponse {
         id: result.id,
         user_id: result.user_id,
         rental_id: result.rental_id,
         subscription_id: result.subscription_id,
+        promotion_id: result.promotion_id,
         amount_cents: result.amount_cents,
         currency: result.currency,
         status: result.status,
@@ -505,7 +994,7 @@ pub async fn get_payment_intent(
 
     let payment_intent = sqlx::query!(
         r#"
-        SELECT id, user_id, rental_id, subscription_id, amount_cents, currency, status, 
+        SELECT id, user_id, rental_id, subscription_id, promotion_id, amount_cents, currency, status, 
                payment_method_id, provider_payment_intent_id, provider_charge_id, failure_reason, 
                metadata, created_at, updated_at
         FROM payment_schema.payment_intents
@@ -525,6 +1014,7 @@ pub async fn get_payment_intent(
         user_id: payment_intent.user_id,
         rental_id: payment_intent.rental_id,
         subscription_id: payment_intent.subscription_id,
+        promotion_id: payment_intent.promotion_id,
         amount_cents: payment_intent.amount_cents,
         currency: payment_intent.currency,
         status: payment_intent.status,
@@ -550,35 +1040,44 @@ pub async fn list_payment_intents(
         Err(e) => return err(e.0, e.1.as_str()),
     };
 
-    let limit = filters.limit.unwrap_or(20).min(100);
-    let offset = filters.offset.unwrap_or(0);
+    let limit = page_size(filters.limit, DEFAULT_PAGE_SIZE);
+    let cursor = match decode_cursor(filters.cursor.as_deref(), "payment_intents") {
+        Ok(c) => c,
+        Err(e) => return err(axum::http::StatusCode::BAD_REQUEST, &e.to_string()),
+    };
 
     let payment_intents = sqlx::query!(
         r#"
-        SELECT id, rental_id, subscription_id, amount_cents, currency, status, 
+        SELECT id, rental_id, subscription_id, promotion_id, amount_cents, currency, status, 
                payment_method_id, provider_payment_intent_id, provider_charge_id, failure_reason, 
                metadata, created_at, updated_at
         FROM payment_schema.payment_intents
         WHERE user_id = $1
-        ORDER BY created_at DESC
-        LIMIT $2 OFFSET $3
+        AND ($3::timestamptz IS NULL OR (created_at, id) < ($3, $4))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $2
         "#,
         user_id,
-        limit,
-        offset
+        limit + 1,
+        cursor.as_ref().map(|c| c.sort_key),
+        cursor.as_ref().map(|c| c.id)
     )
     .fetch_all(&state.db)
     .await
     .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?;
 
-    let total = sqlx::query!(
-        "SELECT COUNT(*) as count FROM payment_schema.payment_intents WHERE user_id = $1",
-        user_id
-    )
-    .fetch_one(&state.db)
-    .await
-    .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?
-    .count;
+    let total = if filters.include_total.unwrap_or(false) {
+        sqlx::query!(
+            "SELECT COUNT(*) as count FROM payment_schema.payment_intents WHERE user_id = $1",
+            user_id
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?
+        .count
+    } else {
+        None
+    };
 
     let payment_intent_responses: Vec<PaymentIntentResponse> = payment_intents
         .into_iter()
@@ -587,6 +1086,7 @@ pub async fn list_payment_intents(
             user_id,
             rental_id: row.rental_id,
             subscription_id: row.subscription_id,
+            promotion_id: row.promotion_id,
             amount_cents: row.amount_cents,
             currency: row.currency,
             status: row.status,
@@ -600,12 +1100,8 @@ pub async fn list_payment_intents(
         })
         .collect();
 
-    let response = PaymentIntentListResponse {
-        payment_intents: payment_intent_responses,
-        total,
-        limit,
-        offset,
-    };
+    let response = CursorPage::from_rows(payment_intent_responses, limit, "payment_intents", |p| (p.created_at, p.id))
+        .with_total(total);
 
     ok(response)
 }
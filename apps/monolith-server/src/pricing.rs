@@ -0,0 +1,475 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+
+// Platform fee charged to the renter on every booking (business model: 1%-10%)
+pub const PLATFORM_COMMISSION_RATE: f64 = 0.10;
+// Flat VAT applied to the rental subtotal and platform fee
+pub const TAX_RATE: f64 = 0.19;
+pub const DEFAULT_CURRENCY: &str = "EUR";
+pub const DELIVERY_FEE_LABEL: &str = "Delivery fee";
+
+const DAYS_PER_WEEK: i64 = 7;
+const DAYS_PER_MONTH: i64 = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LengthOfStayDiscount {
+    pub min_days: i64,
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonalOverride {
+    pub label: Option<String>,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub daily_price: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PricingRules {
+    pub weekly_price: Option<f64>,
+    pub monthly_price: Option<f64>,
+    pub weekend_daily_price: Option<f64>,
+    #[serde(default)]
+    pub seasonal_overrides: Vec<SeasonalOverride>,
+    #[serde(default)]
+    pub length_of_stay_discounts: Vec<LengthOfStayDiscount>,
+    pub cleaning_fee: Option<f64>,
+    pub delivery_fee: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuoteRequest {
+    pub product_id: Uuid,
+    pub rental_period_start: DateTime<Utc>,
+    pub rental_period_end: DateTime<Utc>,
+    pub delivery_requested: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuoteLineItem {
+    pub label: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceQuote {
+    pub product_id: Uuid,
+    pub rental_period_start: DateTime<Utc>,
+    pub rental_period_end: DateTime<Utc>,
+    pub days: i64,
+    pub currency: String,
+    pub base_amount: f64,
+    pub discounts: Vec<QuoteLineItem>,
+    pub fees: Vec<QuoteLineItem>,
+    pub subtotal: f64,
+    pub platform_commission: f64,
+    pub tax: f64,
+    pub total: f64,
+    pub deposit: f64,
+    /// Insurance bought with the booking. Collected with the rental but not taxed or
+    /// commissioned; quotes saved before insurance existed read as zero.
+    #[serde(default)]
+    pub insurance_premium: f64,
+    pub total_due: f64,
+    pub quoted_at: DateTime<Utc>,
+}
+
+impl PriceQuote {
+    /// Amount due in whole cents, as charged through the payment module.
+    pub fn total_due_cents(&self) -> i64 {
+        (self.total_due * 100.0).round() as i64
+    }
+
+    /// Whether the quote was priced with delivery, so a re-quote can keep it.
+    pub fn includes_delivery(&self) -> bool {
+        self.fees.iter().any(|f| f.label == DELIVERY_FEE_LABEL)
+    }
+
+    /// The same quote with `premium` replacing any insurance premium in the amount due.
+    pub fn with_insurance_premium(mut self, premium: f64) -> Self {
+        let premium = round_cents(premium.max(0.0));
+        self.total_due = round_cents(self.total_due - self.insurance_premium + premium);
+        self.insurance_premium = premium;
+        self
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum PricingError {
+    #[error("Rental period end must be after start")]
+    InvalidPeriod,
+    #[error("Pricing rules are invalid: {0}")]
+    InvalidRules(String),
+}
+
+pub fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// Number of billable days; partial days are charged as full days.
+pub fn billable_days(start: DateTime<Utc>, end: DateTime<Utc>) -> Result<i64, PricingError> {
+    if end <= start {
+        return Err(PricingError::InvalidPeriod);
+    }
+    let minutes = (end - start).num_minutes();
+    Ok((minutes + 24 * 60 - 1) / (24 * 60))
+}
+
+impl PricingRules {
+    pub fn validate(&self) -> Result<(), PricingError> {
+        let amounts = [
+            self.weekly_price,
+            self.monthly_price,
+            self.weekend_daily_price,
+            self.cleaning_fee,
+            self.delivery_fee,
+        ];
+        if amounts.iter().flatten().any(|a| !a.is_finite() || *a < 0.0) {
+            return Err(PricingError::InvalidRules("amounts must be non-negative".into()));
+        }
+        // A package priced at zero would make every whole week or month of a rental free
+        if [self.weekly_price, self.monthly_price].iter().flatten().any(|p| *p <= 0.0) {
+            return Err(PricingError::InvalidRules("weekly and monthly prices must be above zero".into()));
+        }
+        for discount in &self.length_of_stay_discounts {
+            if discount.min_days < 1 || !(0.0..100.0).contains(&discount.percent) {
+                return Err(PricingError::InvalidRules(
+                    "discounts need min_days >= 1 and a percent between 0 and 100".into(),
+                ));
+            }
+        }
+        for season in &self.seasonal_overrides {
+            if season.end_date < season.start_date || !season.daily_price.is_finite() || season.daily_price <= 0.0 {
+                return Err(PricingError::InvalidRules(
+                    "seasonal overrides need start_date <= end_date and a positive price".into(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Nightly price for a single calendar day. Seasonal overrides win over the weekend rate.
+    pub fn price_for_day(&self, day: NaiveDate, daily_price: f64) -> f64 {
+        if let Some(season) = self
+            .seasonal_overrides
+            .iter()
+            .find(|s| s.start_date <= day && day <= s.end_date)
+        {
+            return season.daily_price;
+        }
+        match (day.weekday(), self.weekend_daily_price) {
+            (Weekday::Sat | Weekday::Sun, Some(weekend)) => weekend,
+            _ => daily_price,
+        }
+    }
+}
+
+/// Computes an itemized quote. Pure so it can be shared by `/rental/quote` and `create_rental`.
+pub fn compute_quote(
+    product_id: Uuid,
+    daily_price: f64,
+    deposit_amount: Option<f64>,
+    rules: &PricingRules,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    delivery_requested: bool,
+) -> Result<PriceQuote, PricingError> {
+    let days = billable_days(start, end)?;
+    let first_day = start.date_naive();
+
+    let base_amount: f64 = (0..days)
+        .map(|i| rules.price_for_day(first_day + Duration::days(i), daily_price))
+        .sum();
+
+    let mut discounts = Vec::new();
+
+    // Weekly/monthly packages replace the nightly total when cheaper
+    let mut remaining = days;
+    let mut package_amount = 0.0;
+    let mut package_label = None;
+    // A zero package price is treated as no package, never as free weeks or months
+    if let Some(monthly) = rules.monthly_price.filter(|p| *p > 0.0) {
+        if remaining >= DAYS_PER_MONTH {
+            package_amount += (remaining / DAYS_PER_MONTH) as f64 * monthly;
+            remaining %= DAYS_PER_MONTH;
+            package_label = Some("Monthly rate");
+        }
+    }
+    if let Some(weekly) = rules.weekly_price.filter(|p| *p > 0.0) {
+        if remaining >= DAYS_PER_WEEK {
+            package_amount += (remaining / DAYS_PER_WEEK) as f64 * weekly;
+            remaining %= DAYS_PER_WEEK;
+            package_label = package_label.or(Some("Weekly rate"));
+        }
+    }
+    let mut discounted = base_amount;
+    if let Some(label) = package_label {
+        // Days left over after the packages keep their weekend and seasonal rates
+        package_amount += (days - remaining..days)
+            .map(|i| rules.price_for_day(first_day + Duration::days(i), daily_price))
+            .sum::<f64>();
+        if package_amount < base_amount {
+            discounts.push(QuoteLineItem {
+                label: label.to_string(),
+                amount: round_cents(base_amount - package_amount),
+            });
+            discounted = package_amount;
+        }
+    }
+
+    // Only the best matching length-of-stay discount applies
+    if let Some(best) = rules
+        .length_of_stay_discounts
+        .iter()
+        .filter(|d| d.min_days <= days)
+        .max_by(|a, b| a.percent.total_cmp(&b.percent))
+    {
+        let amount = round_cents(discounted * best.percent / 100.0);
+        if amount > 0.0 {
+            discounts.push(QuoteLineItem {
+                label: format!("{}+ day discount ({}%)", best.min_days, best.percent),
+                amount,
+            });
+        }
+    }
+
+    let mut fees = Vec::new();
+    if let Some(cleaning) = rules.cleaning_fee.filter(|f| *f > 0.0) {
+        fees.push(QuoteLineItem { label: "Cleaning fee".into(), amount: round_cents(cleaning) });
+    }
+    if delivery_requested {
+        if let Some(delivery) = rules.delivery_fee.filter(|f| *f > 0.0) {
+            fees.push(QuoteLineItem { label: DELIVERY_FEE_LABEL.into(), amount: round_cents(delivery) });
+        }
+    }
+
+    let base_amount = round_cents(base_amount);
+    let discount_total: f64 = discounts.iter().map(|d| d.amount).sum();
+    let fee_total: f64 = fees.iter().map(|f| f.amount).sum();
+    let subtotal = round_cents(base_amount - discount_total + fee_total);
+    let platform_commission = round_cents(subtotal * PLATFORM_COMMISSION_RATE);
+    let tax = round_cents((subtotal + platform_commission) * TAX_RATE);
+    let total = round_cents(subtotal + platform_commission + tax);
+    let deposit = round_cents(deposit_amount.unwrap_or(0.0));
+
+    Ok(PriceQuote {
+        product_id,
+        rental_period_start: start,
+        rental_period_end: end,
+        days,
+        currency: DEFAULT_CURRENCY.to_string(),
+        base_amount,
+        discounts,
+        fees,
+        subtotal,
+        platform_commission,
+        tax,
+        total,
+        deposit,
+        insurance_premium: 0.0,
+        total_due: round_cents(total + deposit),
+        quoted_at: Utc::now(),
+    })
+}
+
+/// A product's pricing rules, or the defaults when it has none. Stored rules that no longer
+/// decode are an error rather than being dropped.
+pub async fn load_pricing_rules(db: &PgPool, product_id: Uuid) -> Result<PricingRules, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT weekly_price, monthly_price, weekend_daily_price, seasonal_overrides,
+               length_of_stay_discounts, cleaning_fee, delivery_fee
+        FROM product_schema.pricing_rules
+        WHERE product_id = $1
+        "#,
+        product_id
+    )
+    .fetch_optional(db)
+    .await?;
+
+    // Quoting without rules that fail to decode would silently change the price, so it fails
+    let malformed = |e: serde_json::Error| {
+        tracing::warn!(%product_id, error = %e, "stored pricing rules are malformed");
+        sqlx::Error::Decode(Box::new(e))
+    };
+    let rules = match row {
+        Some(r) => PricingRules {
+            weekly_price: r.weekly_price,
+            monthly_price: r.monthly_price,
+            weekend_daily_price: r.weekend_daily_price,
+            seasonal_overrides: serde_json::from_value(r.seasonal_overrides).map_err(malformed)?,
+            length_of_stay_discounts: serde_json::from_value(r.length_of_stay_discounts).map_err(malformed)?,
+            cleaning_fee: r.cleaning_fee,
+            delivery_fee: r.delivery_fee,
+        },
+        None => PricingRules::default(),
+    };
+
+    Ok(rules)
+}
+
+/// Looks up the product and its rules and prices the requested period.
+pub async fn quote_for_product(
+    db: &PgPool,
+    product_id: Uuid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    delivery_requested: bool,
+) -> Result<PriceQuote, (StatusCode, String)> {
+    let product = sqlx::query!(
+        "SELECT daily_price, deposit_amount FROM product_schema.products WHERE product_id = $1",
+        product_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load product".to_string()))?
+    .ok_or((StatusCode::NOT_FOUND, "Product not found".to_string()))?;
+
+    let rules = load_pricing_rules(db, product_id)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load pricing rules".to_string()))?;
+
+    compute_quote(
+        product_id,
+        product.daily_price,
+        product.deposit_amount,
+        &rules,
+        start,
+        end,
+        delivery_requested,
+    )
+    .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+pub async fn get_pricing_rules(
+    State(state): State<AppState>,
+    Path(product_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match load_pricing_rules(&state.db, product_id).await {
+        Ok(rules) => ok(rules),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch pricing rules"),
+    }
+}
+
+pub async fn upsert_pricing_rules(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(product_id): Path<Uuid>,
+    Json(rules): Json<PricingRules>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    if let Err(e) = rules.validate() {
+        return err(StatusCode::BAD_REQUEST, &e.to_string());
+    }
+
+    // Verify product ownership
+    let product = sqlx::query!(
+        "SELECT owner_id FROM product_schema.products WHERE product_id = $1",
+        product_id
+    )
+    .fetch_one(&state.db)
+    .await;
+
+    let product = match product {
+        Ok(p) => p,
+        Err(_) => return err(StatusCode::NOT_FOUND, "Product not found"),
+    };
+
+    if product.owner_id != user_id {
+        return err(StatusCode::FORBIDDEN, "Not authorized to update this product");
+    }
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO product_schema.pricing_rules
+        (product_id, weekly_price, monthly_price, weekend_daily_price, seasonal_overrides,
+         length_of_stay_discounts, cleaning_fee, delivery_fee)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (product_id) DO UPDATE SET
+            weekly_price = EXCLUDED.weekly_price,
+            monthly_price = EXCLUDED.monthly_price,
+            weekend_daily_price = EXCLUDED.weekend_daily_price,
+            seasonal_overrides = EXCLUDED.seasonal_overrides,
+            length_of_stay_discounts = EXCLUDED.length_of_stay_discounts,
+            cleaning_fee = EXCLUDED.cleaning_fee,
+            delivery_fee = EXCLUDED.delivery_fee,
+            updated_at = NOW()
+        "#,
+        product_id,
+        rules.weekly_price,
+        rules.monthly_price,
+        rules.weekend_daily_price,
+        serde_json::to_value(&rules.seasonal_overrides).unwrap_or_default(),
+        serde_json::to_value(&rules.length_of_stay_discounts).unwrap_or_default(),
+        rules.cleaning_fee,
+        rules.delivery_fee
+    )
+    .execute(&state.db)
+    .await;
+
+    if result.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save pricing rules");
+    }
+
+    ok(serde_json::json!({ "message": "Pricing rules updated successfully" }))
+}
+
+pub async fn quote_rental(
+    State(state): State<AppState>,
+    Json(req): Json<QuoteRequest>,
+) -> impl IntoResponse {
+    let quote = quote_for_product(
+        &state.db,
+        req.product_id,
+        req.rental_period_start,
+        req.rental_period_end,
+        req.delivery_requested.unwrap_or(false),
+    )
+    .await;
+
+    match quote {
+        Ok(q) => ok(q),
+        Err((status, message)) => err(status, &message),
+    }
+}
@@ -7,8 +7,21 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::state::{ok, err, AppState};
+use crate::state::{ok, err, err_with_data, AppState};
 use crate::jwt::verify_token;
+use crate::wishlist::wishlisted_product_ids;
+use crate::tag::{normalize_tags, set_product_tags};
+use crate::specs::{check_spec_filters, effective_spec_schema, validate_specifications};
+use crate::listing::{transition_error_response, transition_listing, ListingStatus};
+use crate::inventory::{create_units, MAX_UNITS_PER_PRODUCT};
+use crate::promotion::{record_promotion_impressions, select_promoted_products, MAX_PROMOTED_SLOTS};
+use crate::analytics::spawn_record_view;
+use crate::etag::{check_if_match, with_etag, PreconditionError};
+use crate::pagination::{decode_cursor, page_size, Cursor, CursorPage, DEFAULT_PAGE_SIZE};
+use crate::cancellation::CancellationPolicy;
+use crate::instant_book::InstantBookRequirements;
+use crate::overdue::LateFeePolicy;
+use crate::moderation::{default_pipeline, open_moderation_case, ListingContent, ModerationDecision, ModerationOutcome};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateProductRequest {
@@ -21,6 +34,15 @@ pub struct CreateProductRequest {
     pub specifications: Option<serde_json::Value>,
     pub address: Option<serde_json::Value>,
     pub tags: Option<Vec<String>>,
+    /// Number of identical units; defaults to one.
+    pub quantity: Option<i32>,
+    /// Defaults to moderate.
+    pub cancellation_policy: Option<CancellationPolicy>,
+    /// Confirm qualifying renters without waiting for the owner; defaults to off.
+    pub instant_book: Option<bool>,
+    pub instant_book_requirements: Option<InstantBookRequirements>,
+    /// Fees for late returns; two hours' grace, then the daily price per day, by default.
+    pub late_fee_policy: Option<LateFeePolicy>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -33,7 +55,13 @@ pub struct UpdateProductRequest {
     pub insurance_required: Option<bool>,
     pub specifications: Option<serde_json::Value>,
     pub address: Option<serde_json::Value>,
-    pub status: Option<String>,
+    pub status: Option<ListingStatus>,
+    pub tags: Option<Vec<String>>,
+    /// Applies to new bookings only; existing rentals keep the policy they were booked under.
+    pub cancellation_policy: Option<CancellationPolicy>,
+    pub instant_book: Option<bool>,
+    pub instant_book_requirements: Option<InstantBookRequirements>,
+    pub late_fee_policy: Option<LateFeePolicy>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -51,17 +79,16 @@ pub struct ProductResponse {
     pub avg_rating: Option<f64>,
     pub total_reviews: i32,
     pub status: String,
+    pub cancellation_policy: String,
+    pub instant_book: bool,
+    pub instant_book_requirements: InstantBookRequirements,
+    pub late_fee_policy: LateFeePolicy,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub tags: Vec<String>,
     pub images: Vec<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ProductListResponse {
-    pub products: Vec<ProductResponse>,
-    pub total: i64,
-    pub page: i64,
-    pub per_page: i64,
+    pub is_wishlisted: bool,
+    /// Set when the listing is shown in a paid promotion slot; report clicks against it.
+    pub promotion_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -70,24 +97,38 @@ pub struct ProductFilters {
     pub min_price: Option<f64>,
     pub max_price: Option<f64>,
     pub search: Option<String>,
-    pub page: Option<i64>,
-    pub per_page: Option<i64>,
+    /// JSON object of filterable specification values, e.g. `{"mount":"EF"}`
+    pub specs: Option<String>,
+    /// Search centre; listings match when their address has `lat`/`lng` within `radius_km`
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub radius_km: Option<f64>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+    pub include_total: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateCategoryRequest {
-    pub name: String,
-    pub description: Option<String>,
-    pub parent_category_id: Option<Uuid>,
-}
+pub const DEFAULT_RADIUS_KM: f64 = 25.0;
+pub const MAX_RADIUS_KM: f64 = 500.0;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CategoryResponse {
-    pub category_id: Uuid,
-    pub name: String,
-    pub description: Option<String>,
-    pub parent_category_id: Option<Uuid>,
-    pub created_at: chrono::DateTime<chrono::Utc>,
+/// Validated `(lat, lng, radius_km)` of a geo filter, or `None` when no centre was given.
+pub fn geo_filter(filters: &ProductFilters) -> Result<Option<(f64, f64, f64)>, String> {
+    let (lat, lng) = match (filters.lat, filters.lng) {
+        (None, None) if filters.radius_km.is_none() => return Ok(None),
+        (Some(lat), Some(lng)) => (lat, lng),
+        _ => return Err("lat and lng must be given together".to_string()),
+    };
+
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lng) {
+        return Err("lat/lng out of range".to_string());
+    }
+
+    let radius = filters.radius_km.unwrap_or(DEFAULT_RADIUS_KM);
+    if !(radius > 0.0 && radius <= MAX_RADIUS_KM) {
+        return Err(format!("radius_km must be between 0 and {}", MAX_RADIUS_KM));
+    }
+
+    Ok(Some((lat, lng, radius)))
 }
 
 fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
@@ -115,39 +156,127 @@ fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
     }
 }
 
-pub async fn create_product(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-    Json(req): Json<CreateProductRequest>,
-) -> impl IntoResponse {
-    let user_id = match extract_user_id_from_token(&headers) {
-        Ok(id) => id,
-        Err(status) => return err(status, "Unauthorized"),
+/// Validation or storage failure while writing a product; `details` carries field-level errors.
+#[derive(Debug)]
+pub struct ProductWriteError {
+    pub status: StatusCode,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+}
+
+impl ProductWriteError {
+    fn new(status: StatusCode, message: &str) -> Self {
+        ProductWriteError { status, message: message.to_string(), details: None }
+    }
+
+    pub fn into_response(self) -> axum::response::Response {
+        match self.details {
+            Some(details) => err_with_data(self.status, &self.message, details),
+            None => err(self.status, &self.message),
+        }
+    }
+}
+
+/// Checks specifications against the category's (possibly inherited) spec schema.
+async fn validate_product_specs(
+    db: &sqlx::PgPool,
+    category_id: Uuid,
+    specifications: Option<&serde_json::Value>,
+) -> Result<(), ProductWriteError> {
+    let schema = match effective_spec_schema(db, category_id).await {
+        Ok(s) => s,
+        Err(_) => return Err(ProductWriteError::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load spec schema")),
     };
 
+    if let Some((_, schema)) = schema {
+        let empty = serde_json::json!({});
+        if let Err(errors) = validate_specifications(&schema, specifications.unwrap_or(&empty)) {
+            return Err(ProductWriteError {
+                status: StatusCode::BAD_REQUEST,
+                message: "Specifications do not match the category schema".to_string(),
+                details: Some(serde_json::json!({ "field_errors": errors })),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs listing text through the moderation pipeline; rejected content never gets written.
+fn moderate_listing(content: &ListingContent) -> Result<ModerationOutcome, ProductWriteError> {
+    let outcome = default_pipeline().evaluate(content);
+    if outcome.decision == ModerationDecision::Reject {
+        return Err(ProductWriteError {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            message: "Listing violates the content policy".to_string(),
+            details: Some(serde_json::json!({ "findings": outcome.findings })),
+        });
+    }
+    Ok(outcome)
+}
+
+/// Validates and stores a new draft listing with its tags and units. Shared by `create_product`
+/// and bulk import so both apply the same rules.
+pub async fn insert_product(
+    db: &sqlx::PgPool,
+    owner_id: Uuid,
+    req: &CreateProductRequest,
+) -> Result<(Uuid, ModerationOutcome), ProductWriteError> {
     // Verify category exists
     let category = sqlx::query!(
         "SELECT category_id FROM product_schema.categories WHERE category_id = $1",
         req.category_id
     )
-    .fetch_optional(&state.db)
+    .fetch_optional(db)
     .await;
 
     if category.is_err() || category.unwrap().is_none() {
-        return err(StatusCode::BAD_REQUEST, "Invalid category_id");
+        return Err(ProductWriteError::new(StatusCode::BAD_REQUEST, "Invalid category_id"));
+    }
+
+    validate_product_specs(db, req.category_id, req.specifications.as_ref()).await?;
+
+    let quantity = req.quantity.unwrap_or(1);
+    if !(1..=MAX_UNITS_PER_PRODUCT).contains(&quantity) {
+        return Err(ProductWriteError::new(StatusCode::BAD_REQUEST, "quantity must be between 1 and 100"));
     }
 
+    let tags = normalize_tags(req.tags.as_deref().unwrap_or_default())
+        .map_err(|e| ProductWriteError::new(StatusCode::BAD_REQUEST, &e.to_string()))?;
+
+    let requirements = req.instant_book_requirements.clone().unwrap_or_default();
+    requirements
+        .validate()
+        .map_err(|e| ProductWriteError::new(StatusCode::BAD_REQUEST, &e))?;
+
+    let late_fee_policy = req.late_fee_policy.clone().unwrap_or_default();
+    late_fee_policy
+        .validate()
+        .map_err(|e| ProductWriteError::new(StatusCode::BAD_REQUEST, &e))?;
+
+    let moderation = moderate_listing(&ListingContent {
+        name: req.name.clone(),
+        description: req.description.clone(),
+        tags: tags.clone(),
+    })?;
+
+    let failed = |message: &str| ProductWriteError::new(StatusCode::INTERNAL_SERVER_ERROR, message);
+
+    // Product and tags are written together or not at all
+    let mut tx = db.begin().await.map_err(|_| failed("Failed to create product"))?;
+
     // Create product
     let product_id = Uuid::new_v4();
-    let result = sqlx::query!(
+    sqlx::query!(
         r#"
         INSERT INTO product_schema.products 
         (product_id, owner_id, category_id, name, description, daily_price, deposit_amount, 
-         insurance_required, specifications, address)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+         insurance_required, specifications, address, status, cancellation_policy,
+         instant_book, instant_book_requirements, late_fee_policy)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
         "#,
         product_id,
-        user_id,
+        owner_id,
         req.category_id,
         req.name,
         req.description,
@@ -155,41 +284,56 @@ pub async fn create_product(
         req.deposit_amount,
         req.insurance_required.unwrap_or(false),
         req.specifications,
-        req.address
+        req.address,
+        ListingStatus::Draft.as_str(),
+        req.cancellation_policy.unwrap_or_default().as_str(),
+        req.instant_book.unwrap_or(false),
+        serde_json::to_value(&requirements).unwrap_or_default(),
+        serde_json::to_value(&late_fee_policy).unwrap_or_default()
     )
-    .execute(&state.db)
-    .await;
-
-    if result.is_err() {
-        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create product");
+    .execute(&mut tx)
+    .await
+    .map_err(|_| failed("Failed to create product"))?;
+
+    set_product_tags(&mut tx, product_id, &tags)
+        .await
+        .map_err(|_| failed("Failed to save product tags"))?;
+
+    create_units(&mut tx, product_id, quantity)
+        .await
+        .map_err(|_| failed("Failed to create product units"))?;
+
+    if moderation.decision == ModerationDecision::Flag {
+        open_moderation_case(&mut tx, product_id, &moderation, None)
+            .await
+            .map_err(|_| failed("Failed to create product"))?;
     }
 
-    // Add tags if provided
-    if let Some(tags) = req.tags {
-        for tag_name in tags {
-            // Get or create tag
-            let tag_id = sqlx::query!(
-                "INSERT INTO product_schema.tags (name) VALUES ($1) ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name RETURNING tag_id",
-                tag_name
-            )
-            .fetch_one(&state.db)
-            .await;
-
-            if let Ok(tag) = tag_id {
-                // Link tag to product
-                let _ = sqlx::query!(
-                    "INSERT INTO product_schema.product_tags (product_id, tag_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
-                    product_id,
-                    tag.tag_id
-                )
-                .execute(&state.db)
-                .await;
-            }
-        }
-    }
+    tx.commit().await.map_err(|_| failed("Failed to create product"))?;
+
+    Ok((product_id, moderation))
+}
+
+pub async fn create_product(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateProductRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let (product_id, moderation) = match insert_product(&state.db, user_id, &req).await {
+        Ok(created) => created,
+        Err(e) => return e.into_response(),
+    };
 
     let response = serde_json::json!({
         "product_id": product_id,
+        "status": ListingStatus::Draft,
+        "quantity": req.quantity.unwrap_or(1),
+        "moderation": moderation.decision,
         "message": "Product created successfully"
     });
 
@@ -198,6 +342,7 @@ pub async fn create_product(
 
 pub async fn get_product(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(product_id): Path<Uuid>,
 ) -> impl IntoResponse {
     let product = sqlx::query!(
@@ -222,6 +367,23 @@ pub async fn get_product(
         Err(_) => return err(StatusCode::NOT_FOUND, "Product not found"),
     };
 
+    let viewer = extract_user_id_from_token(&headers).ok();
+
+    // Owners checking their own listing are skipped when the view is recorded
+    if let Some(viewer_id) = viewer {
+        spawn_record_view(&state.db, product_id, viewer_id);
+    }
+
+    // Anonymous callers simply never see a wishlisted product
+    let is_wishlisted = match viewer {
+        Some(user_id) => wishlisted_product_ids(&state.db, user_id, &[product_id])
+            .await
+            .map(|ids| !ids.is_empty())
+            .unwrap_or(false),
+        None => false,
+    };
+
+    let version = product.version;
     let response = ProductResponse {
         product_id: product.product_id,
         owner_id: product.owner_id,
@@ -236,76 +398,86 @@ pub async fn get_product(
         avg_rating: product.avg_rating,
         total_reviews: product.total_reviews,
         status: product.status,
+        cancellation_policy: product.cancellation_policy,
+        instant_book: product.instant_book,
+        instant_book_requirements: serde_json::from_value(product.instant_book_requirements).unwrap_or_default(),
+        late_fee_policy: serde_json::from_value(product.late_fee_policy).unwrap_or_default(),
         created_at: product.created_at,
         tags: product.tags.unwrap_or_default(),
         images: product.images.unwrap_or_default(),
+        is_wishlisted,
+        promotion_id: None,
     };
 
-    ok(response)
+    with_etag(ok(response), version)
 }
 
 pub async fn list_products(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(filters): Query<ProductFilters>,
 ) -> impl IntoResponse {
-    let page = filters.page.unwrap_or(1);
-    let per_page = filters.per_page.unwrap_or(20).min(100);
-    let offset = (page - 1) * per_page;
-
-    let mut query = String::from(
-        r#"
-        SELECT p.*, 
-               array_agg(DISTINCT t.name) FILTER (WHERE t.name IS NOT NULL) as tags,
-               array_agg(DISTINCT pi.image_url) FILTER (WHERE pi.image_url IS NOT NULL) as images
-        FROM product_schema.products p
-        LEFT JOIN product_schema.product_tags pt ON p.product_id = pt.product_id
-        LEFT JOIN product_schema.tags t ON pt.tag_id = t.tag_id
-        LEFT JOIN product_schema.product_images pi ON p.product_id = pi.product_id
-        WHERE p.status = 'active'
-        "#
-    );
-
-    let mut conditions = Vec::new();
-    let mut params: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send + Sync>> = Vec::new();
-    let mut param_count = 0;
-
-    if let Some(category_id) = filters.category_id {
-        param_count += 1;
-        conditions.push(format!("p.category_id = ${}", param_count));
-        params.push(Box::new(category_id));
-    }
-
-    if let Some(min_price) = filters.min_price {
-        param_count += 1;
-        conditions.push(format!("p.daily_price >= ${}", param_count));
-        params.push(Box::new(min_price));
-    }
-
-    if let Some(max_price) = filters.max_price {
-        param_count += 1;
-        conditions.push(format!("p.daily_price <= ${}", param_count));
-        params.push(Box::new(max_price));
-    }
-
-    if let Some(search) = filters.search {
-        param_count += 1;
-        conditions.push(format!("(p.name ILIKE ${} OR p.description ILIKE ${})", param_count, param_count));
-        params.push(Box::new(format!("%{}%", search)));
-    }
+    let limit = page_size(filters.limit, DEFAULT_PAGE_SIZE);
+    let cursor = match decode_cursor(filters.cursor.as_deref(), "products") {
+        Ok(c) => c,
+        Err(e) => return err(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
 
-    if !conditions.is_empty() {
-        query.push_str(" AND ");
-        query.push_str(&conditions.join(" AND "));
+    // Spec filters only make sense within a category whose schema marks them filterable
+    let mut spec_filters: Option<serde_json::Value> = None;
+    if let Some(raw) = filters.specs.as_deref() {
+        let category_id = match filters.category_id {
+            Some(c) => c,
+            None => return err(StatusCode::BAD_REQUEST, "specs filter requires category_id"),
+        };
+        let parsed: serde_json::Value = match serde_json::from_str(raw) {
+            Ok(v) => v,
+            Err(_) => return err(StatusCode::BAD_REQUEST, "specs must be a JSON object"),
+        };
+        let schema = match effective_spec_schema(&state.db, category_id).await {
+            Ok(s) => s.map(|(_, schema)| schema),
+            Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load spec schema"),
+        };
+        if let Err(message) = check_spec_filters(schema.as_ref(), &parsed) {
+            return err(StatusCode::BAD_REQUEST, &message);
+        }
+        spec_filters = Some(parsed);
     }
 
-    query.push_str(" GROUP BY p.product_id ORDER BY p.created_at DESC LIMIT ");
-    query.push_str(&per_page.to_string());
-    query.push_str(" OFFSET ");
-    query.push_str(&offset.to_string());
+    let geo = match geo_filter(&filters) {
+        Ok(g) => g,
+        Err(message) => return err(StatusCode::BAD_REQUEST, &message),
+    };
+    let search = filters.search.as_deref().map(str::trim).filter(|s| !s.is_empty());
+
+    // Promoted listings are matched with `listing_matches_filters`, the saved search predicate
+    let filters_json = serde_json::to_value(&filters).unwrap_or_default();
+
+    // Promoted listings lead the first page and take slots out of the page size. At least one
+    // slot stays organic so the next cursor always points into the organic order; the listings
+    // shown as promoted travel in the cursor and are left out of later pages.
+    let slots = MAX_PROMOTED_SLOTS.min(limit - 1);
+    let promoted = if cursor.is_none() && slots > 0 {
+        select_promoted_products(&state.db, &filters_json, slots)
+            .await
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let promoted_ids: Vec<Uuid> = promoted.iter().map(|(_, product_id)| *product_id).collect();
+    let excluded: Vec<Uuid> = cursor.as_ref().map(|c| c.exclude.clone()).unwrap_or_default();
 
-    // For now, use a simpler approach without dynamic SQL
+    // For now, use a simpler approach without dynamic SQL.
+    // These are the predicates of `listing_matches_filters`, kept inline so the planner can use
+    // the indexes; a category filter matches the category and all of its descendants.
     let products = sqlx::query!(
         r#"
+        WITH RECURSIVE category_tree AS (
+            SELECT category_id FROM product_schema.categories WHERE category_id = $3
+            UNION
+            SELECT c.category_id FROM product_schema.categories c
+            JOIN category_tree ct ON c.parent_category_id = ct.category_id
+        )
         SELECT p.*, 
                array_agg(DISTINCT t.name) FILTER (WHERE t.name IS NOT NULL) as tags,
                array_agg(DISTINCT pi.image_url) FILTER (WHERE pi.image_url IS NOT NULL) as images
@@ -314,12 +486,40 @@ pub async fn list_products(
         LEFT JOIN product_schema.tags t ON pt.tag_id = t.tag_id
         LEFT JOIN product_schema.product_images pi ON p.product_id = pi.product_id
         WHERE p.status = 'active'
+        AND ($3::uuid IS NULL OR p.category_id IN (SELECT category_id FROM category_tree))
+        AND ($4::float8 IS NULL OR p.daily_price >= $4::numeric)
+        AND ($5::float8 IS NULL OR p.daily_price <= $5::numeric)
+        AND ($6::text IS NULL
+             OR strpos(lower(p.name), lower($6)) > 0
+             OR strpos(lower(COALESCE(p.description, '')), lower($6)) > 0)
+        AND ($7::jsonb IS NULL OR p.specifications @> $7)
+        AND ($8::float8 IS NULL OR (
+            jsonb_typeof(p.address->'lat') = 'number' AND jsonb_typeof(p.address->'lng') = 'number'
+            AND 2 * 6371 * asin(sqrt(
+                power(sin(radians(((p.address->>'lat')::float8 - $8) / 2)), 2)
+                + cos(radians($8)) * cos(radians((p.address->>'lat')::float8))
+                * power(sin(radians(((p.address->>'lng')::float8 - $9::float8) / 2)), 2)
+            )) <= $10::float8
+        ))
+        AND ($2::timestamptz IS NULL OR (p.created_at, p.product_id) < ($2, $12))
+        AND NOT (p.product_id = ANY($13))
         GROUP BY p.product_id
-        ORDER BY p.created_at DESC
-        LIMIT $1 OFFSET $2
+        ORDER BY (p.product_id = ANY($11)) DESC, p.created_at DESC, p.product_id DESC
+        LIMIT $1
         "#,
-        per_page,
-        offset
+        limit + 1,
+        cursor.as_ref().map(|c| c.sort_key),
+        filters.category_id,
+        filters.min_price,
+        filters.max_price,
+        search,
+        spec_filters.clone(),
+        geo.map(|(lat, _, _)| lat),
+        geo.map(|(_, lng, _)| lng),
+        geo.map(|(_, _, radius)| radius),
+        &promoted_ids,
+        cursor.as_ref().map(|c| c.id),
+        &excluded
     )
     .fetch_all(&state.db)
     .await;
@@ -329,15 +529,76 @@ pub async fn list_products(
         Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch products"),
     };
 
-    let total = sqlx::query!(
-        "SELECT COUNT(*) as count FROM product_schema.products WHERE status = 'active'"
-    )
-    .fetch_one(&state.db)
-    .await;
+    // A promoted listing can change between selecting it and fetching the page
+    let promoted: Vec<(Uuid, Uuid)> = promoted
+        .into_iter()
+        .filter(|(_, product_id)| products.iter().any(|p| p.product_id == *product_id))
+        .collect();
+
+    // Impressions are counted per signed-in viewer and day, off the request path
+    let viewer = extract_user_id_from_token(&headers).ok();
+    if let Some(viewer_id) = viewer.filter(|_| !promoted.is_empty()) {
+        let db = state.db.clone();
+        let promotion_ids: Vec<Uuid> = promoted.iter().map(|(promotion_id, _)| *promotion_id).collect();
+        tokio::spawn(async move {
+            let _ = record_promotion_impressions(&db, viewer_id, &promotion_ids).await;
+        });
+    }
+
+    // Counting every match is only done on request
+    let total = if filters.include_total.unwrap_or(false) {
+        let total = sqlx::query!(
+            r#"
+            WITH RECURSIVE category_tree AS (
+                SELECT category_id FROM product_schema.categories WHERE category_id = $1
+                UNION
+                SELECT c.category_id FROM product_schema.categories c
+                JOIN category_tree ct ON c.parent_category_id = ct.category_id
+            )
+            SELECT COUNT(*) as count FROM product_schema.products p
+            WHERE p.status = 'active'
+            AND ($1::uuid IS NULL OR p.category_id IN (SELECT category_id FROM category_tree))
+            AND ($2::float8 IS NULL OR p.daily_price >= $2::numeric)
+            AND ($3::float8 IS NULL OR p.daily_price <= $3::numeric)
+            AND ($4::text IS NULL
+                 OR strpos(lower(p.name), lower($4)) > 0
+                 OR strpos(lower(COALESCE(p.description, '')), lower($4)) > 0)
+            AND ($5::jsonb IS NULL OR p.specifications @> $5)
+            AND ($6::float8 IS NULL OR (
+                jsonb_typeof(p.address->'lat') = 'number' AND jsonb_typeof(p.address->'lng') = 'number'
+                AND 2 * 6371 * asin(sqrt(
+                    power(sin(radians(((p.address->>'lat')::float8 - $6) / 2)), 2)
+                    + cos(radians($6)) * cos(radians((p.address->>'lat')::float8))
+                    * power(sin(radians(((p.address->>'lng')::float8 - $7::float8) / 2)), 2)
+                )) <= $8::float8
+            ))
+            "#,
+            filters.category_id,
+            filters.min_price,
+            filters.max_price,
+            search,
+            spec_filters,
+            geo.map(|(lat, _, _)| lat),
+            geo.map(|(_, lng, _)| lng),
+            geo.map(|(_, _, radius)| radius)
+        )
+        .fetch_one(&state.db)
+        .await;
+
+        match total {
+            Ok(t) => Some(t.count.unwrap_or(0)),
+            Err(_) => None,
+        }
+    } else {
+        None
+    };
 
-    let total = match total {
-        Ok(t) => t.count.unwrap_or(0),
-        Err(_) => 0,
+    let wishlisted = match viewer {
+        Some(user_id) => {
+            let ids: Vec<Uuid> = products.iter().map(|p| p.product_id).collect();
+            wishlisted_product_ids(&state.db, user_id, &ids).await.unwrap_or_default()
+        }
+        None => Vec::new(),
     };
 
     let product_responses: Vec<ProductResponse> = products
@@ -356,18 +617,30 @@ pub async fn list_products(
             avg_rating: p.avg_rating,
             total_reviews: p.total_reviews,
             status: p.status,
+            cancellation_policy: p.cancellation_policy,
+            instant_book: p.instant_book,
+            instant_book_requirements: serde_json::from_value(p.instant_book_requirements).unwrap_or_default(),
+            late_fee_policy: serde_json::from_value(p.late_fee_policy).unwrap_or_default(),
             created_at: p.created_at,
             tags: p.tags.unwrap_or_default(),
             images: p.images.unwrap_or_default(),
+            is_wishlisted: wishlisted.contains(&p.product_id),
+            promotion_id: promoted
+                .iter()
+                .find(|(_, product_id)| *product_id == p.product_id)
+                .map(|(promotion_id, _)| *promotion_id),
         })
         .collect();
 
-    let response = ProductListResponse {
-        products: product_responses,
-        total,
-        page,
-        per_page,
+    let exclude: Vec<Uuid> = if cursor.is_none() {
+        promoted.iter().map(|(_, product_id)| *product_id).collect()
+    } else {
+        excluded
     };
+    let response = CursorPage::from_rows_with(product_responses, limit, |p| {
+        Cursor::new("products", p.created_at, p.product_id).excluding(exclude.clone())
+    })
+    .with_total(total);
 
     ok(response)
 }
@@ -383,12 +656,27 @@ pub async fn update_product(
         Err(status) => return err(status, "Unauthorized"),
     };
 
-    // Verify product ownership
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update product"),
+    };
+
+    // Verify product ownership; the lock keeps the status we check against until commit
     let product = sqlx::query!(
-        "SELECT owner_id FROM product_schema.products WHERE product_id = $1",
+        r#"
+        SELECT p.owner_id, p.category_id, p.specifications, p.status, p.name, p.description, p.version,
+               ARRAY(
+                   SELECT t.name FROM product_schema.product_tags pt
+                   JOIN product_schema.tags t ON t.tag_id = pt.tag_id
+                   WHERE pt.product_id = p.product_id
+               ) as "tags!"
+        FROM product_schema.products p
+        WHERE p.product_id = $1
+        FOR UPDATE OF p
+        "#,
         product_id
     )
-    .fetch_one(&state.db)
+    .fetch_one(&mut tx)
     .await;
 
     let product = match product {
@@ -400,6 +688,61 @@ pub async fn update_product(
         return err(StatusCode::FORBIDDEN, "Not authorized to update this product");
     }
 
+    if let Err(e) = check_if_match(&headers, product.version) {
+        return e.into_response();
+    }
+
+    let current_status = match product.status.parse::<ListingStatus>() {
+        Ok(s) => s,
+        Err(message) => return err(StatusCode::INTERNAL_SERVER_ERROR, &message),
+    };
+
+    // Re-validate when either the category or the specifications change
+    if req.category_id.is_some() || req.specifications.is_some() {
+        let category_id = req.category_id.unwrap_or(product.category_id);
+        let specifications = req.specifications.as_ref().or(product.specifications.as_ref());
+        if let Err(e) = validate_product_specs(&state.db, category_id, specifications).await {
+            return e.into_response();
+        }
+    }
+
+    let tags = match req.tags.as_deref().map(normalize_tags).transpose() {
+        Ok(t) => t,
+        Err(e) => return err(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
+
+    if let Some(Err(e)) = req.instant_book_requirements.as_ref().map(InstantBookRequirements::validate) {
+        return err(StatusCode::BAD_REQUEST, &e);
+    }
+
+    if let Some(Err(e)) = req.late_fee_policy.as_ref().map(LateFeePolicy::validate) {
+        return err(StatusCode::BAD_REQUEST, &e);
+    }
+
+    // Only text changes need another moderation pass
+    let moderation = if req.name.is_some() || req.description.is_some() || tags.is_some() {
+        let content = ListingContent {
+            name: req.name.clone().unwrap_or(product.name),
+            description: req.description.clone().or(product.description),
+            tags: tags.clone().unwrap_or(product.tags),
+        };
+        match moderate_listing(&content) {
+            Ok(outcome) => Some((outcome, content)),
+            Err(e) => return e.into_response(),
+        }
+    } else {
+        None
+    };
+    let flagged = moderation.as_ref().filter(|(o, _)| o.decision == ModerationDecision::Flag);
+
+    // Flagged text for a published listing waits on the case; the approved text stays live
+    let held_back = flagged.is_some() && matches!(current_status, ListingStatus::Active | ListingStatus::Paused);
+    let (name, description, tags) = if held_back {
+        (None, None, None)
+    } else {
+        (req.name.as_ref(), req.description.as_ref(), tags)
+    };
+
     // Update product
     let result = sqlx::query!(
         r#"
@@ -413,29 +756,81 @@ pub async fn update_product(
             insurance_required = COALESCE($7, insurance_required),
             specifications = COALESCE($8, specifications),
             address = COALESCE($9, address),
-            status = COALESCE($10, status),
+            cancellation_policy = COALESCE($10, cancellation_policy),
+            instant_book = COALESCE($11, instant_book),
+            instant_book_requirements = COALESCE($12, instant_book_requirements),
+            late_fee_policy = COALESCE($13, late_fee_policy),
             updated_at = NOW()
-        WHERE product_id = $1
+        WHERE product_id = $1 AND version = $14
         "#,
         product_id,
-        req.name,
-        req.description,
+        name,
+        description,
         req.category_id,
         req.daily_price,
         req.deposit_amount,
         req.insurance_required,
         req.specifications,
         req.address,
-        req.status
+        req.cancellation_policy.map(|c| c.as_str()),
+        req.instant_book,
+        req.instant_book_requirements.as_ref().and_then(|r| serde_json::to_value(r).ok()),
+        req.late_fee_policy.as_ref().and_then(|p| serde_json::to_value(p).ok()),
+        product.version
     )
-    .execute(&state.db)
+    .execute(&mut tx)
     .await;
 
-    if result.is_err() {
+    // Someone else saved between our read and this write
+    match result {
+        Ok(r) if r.rows_affected() == 0 => return PreconditionError::Stale.into_response(),
+        Ok(_) => {}
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update product"),
+    }
+
+    // Omitted tags are left alone; an empty list clears them
+    if let Some(tags) = tags {
+        if set_product_tags(&mut tx, product_id, &tags).await.is_err() {
+            return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save product tags");
+        }
+    }
+
+    if let Some((outcome, content)) = flagged {
+        let pending = held_back.then_some(content);
+        if open_moderation_case(&mut tx, product_id, outcome, pending).await.is_err() {
+            return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update product");
+        }
+    }
+
+    // Status goes through the lifecycle rules after the other fields, so publishing sees the new values
+    if let Some(next_status) = req.status {
+        if let Err(e) = transition_listing(&mut tx, product_id, current_status, next_status, false).await {
+            return transition_error_response(e);
+        }
+    }
+
+    let version = sqlx::query!(
+        "SELECT version FROM product_schema.products WHERE product_id = $1",
+        product_id
+    )
+    .fetch_one(&mut tx)
+    .await;
+
+    let version = match version {
+        Ok(v) => v.version,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update product"),
+    };
+
+    if tx.commit().await.is_err() {
         return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update product");
     }
 
-    ok(serde_json::json!({ "message": "Product updated successfully" }))
+    let message = if held_back {
+        "Product updated; text changes are waiting for moderator review"
+    } else {
+        "Product updated successfully"
+    };
+    with_etag(ok(serde_json::json!({ "message": message, "held_for_review": held_back })), version)
 }
 
 pub async fn delete_product(
@@ -448,12 +843,17 @@ pub async fn delete_product(
         Err(status) => return err(status, "Unauthorized"),
     };
 
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete product"),
+    };
+
     // Verify product ownership
     let product = sqlx::query!(
-        "SELECT owner_id FROM product_schema.products WHERE product_id = $1",
+        "SELECT owner_id, status FROM product_schema.products WHERE product_id = $1 FOR UPDATE",
         product_id
     )
-    .fetch_one(&state.db)
+    .fetch_one(&mut tx)
     .await;
 
     let product = match product {
@@ -465,79 +865,19 @@ pub async fn delete_product(
         return err(StatusCode::FORBIDDEN, "Not authorized to delete this product");
     }
 
-    // Soft delete by setting status to 'deleted'
-    let result = sqlx::query!(
-        "UPDATE product_schema.products SET status = 'deleted', updated_at = NOW() WHERE product_id = $1",
-        product_id
-    )
-    .execute(&state.db)
-    .await;
+    let current_status = match product.status.parse::<ListingStatus>() {
+        Ok(s) => s,
+        Err(message) => return err(StatusCode::INTERNAL_SERVER_ERROR, &message),
+    };
 
-    if result.is_err() {
-        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete product");
+    // Soft delete by archiving the listing
+    if let Err(e) = transition_listing(&mut tx, product_id, current_status, ListingStatus::Archived, false).await {
+        return transition_error_response(e);
     }
 
-    ok(serde_json::json!({ "message": "Product deleted successfully" }))
-}
-
-pub async fn create_category(
-    State(state): State<AppState>,
-    Json(req): Json<CreateCategoryRequest>,
-) -> impl IntoResponse {
-    let category_id = Uuid::new_v4();
-    let result = sqlx::query!(
-        r#"
-        INSERT INTO product_schema.categories (category_id, name, description, parent_category_id)
-        VALUES ($1, $2, $3, $4)
-        "#,
-        category_id,
-        req.name,
-        req.description,
-        req.parent_category_id
-    )
-    .execute(&state.db)
-    .await;
-
-    if result.is_err() {
-        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create category");
+    if tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete product");
     }
 
-    let response = serde_json::json!({
-        "category_id": category_id,
-        "message": "Category created successfully"
-    });
-
-    ok(response)
-}
-
-pub async fn list_categories(
-    State(state): State<AppState>,
-) -> impl IntoResponse {
-    let categories = sqlx::query!(
-        r#"
-        SELECT category_id, name, description, parent_category_id, created_at
-        FROM product_schema.categories
-        ORDER BY name
-        "#
-    )
-    .fetch_all(&state.db)
-    .await;
-
-    let categories = match categories {
-        Ok(c) => c,
-        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch categories"),
-    };
-
-    let category_responses: Vec<CategoryResponse> = categories
-        .into_iter()
-        .map(|c| CategoryResponse {
-            category_id: c.category_id,
-            name: c.name,
-            description: c.description,
-            parent_category_id: c.parent_category_id,
-            created_at: c.created_at,
-        })
-        .collect();
-
-    ok(category_responses)
+    ok(serde_json::json!({ "message": "Product deleted successfully" }))
 }
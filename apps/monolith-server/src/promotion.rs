@@ -0,0 +1,418 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::pricing::DEFAULT_CURRENCY;
+use crate::product::DEFAULT_RADIUS_KM;
+
+pub const PROMOTION_DAILY_RATE_CENTS: i32 = 500;
+pub const MAX_PROMOTION_DAYS: i64 = 30;
+pub const MAX_PROMOTION_LEAD_DAYS: i64 = 90;
+pub const MAX_ACTIVE_PROMOTIONS_PER_OWNER: i64 = 5;
+/// Most promoted listings shown ahead of organic results in one listing response; they count
+/// towards the page size.
+pub const MAX_PROMOTED_SLOTS: i64 = 3;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatePromotionRequest {
+    pub starts_on: NaiveDate,
+    pub ends_on: NaiveDate,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PromotionResponse {
+    pub promotion_id: Uuid,
+    pub product_id: Uuid,
+    pub product_name: String,
+    pub starts_on: NaiveDate,
+    pub ends_on: NaiveDate,
+    pub amount_cents: i32,
+    pub currency: String,
+    pub status: String,
+    pub impressions: i64,
+    pub clicks: i64,
+    pub click_through_rate: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum PromotionError {
+    #[error("ends_on must not be before starts_on")]
+    InvalidRange,
+    #[error("Promotions cannot start in the past")]
+    StartsInPast,
+    #[error("Promotions can be booked at most {} days ahead", MAX_PROMOTION_LEAD_DAYS)]
+    TooFarAhead,
+    #[error("A promotion can run for at most {} days", MAX_PROMOTION_DAYS)]
+    TooLong,
+}
+
+/// Validates the requested window and returns the price in cents for the whole range, both
+/// dates inclusive.
+pub fn promotion_price_cents(starts_on: NaiveDate, ends_on: NaiveDate, today: NaiveDate) -> Result<i32, PromotionError> {
+    if ends_on < starts_on {
+        return Err(PromotionError::InvalidRange);
+    }
+    if starts_on < today {
+        return Err(PromotionError::StartsInPast);
+    }
+    if (starts_on - today).num_days() > MAX_PROMOTION_LEAD_DAYS {
+        return Err(PromotionError::TooFarAhead);
+    }
+    let days = (ends_on - starts_on).num_days() + 1;
+    if days > MAX_PROMOTION_DAYS {
+        return Err(PromotionError::TooLong);
+    }
+    Ok(days as i32 * PROMOTION_DAILY_RATE_CENTS)
+}
+
+pub fn click_through_rate(impressions: i64, clicks: i64) -> f64 {
+    if impressions <= 0 {
+        return 0.0;
+    }
+    (clicks as f64 / impressions as f64 * 10000.0).round() / 10000.0
+}
+
+/// Picks the promotions to boost for a listing query, matching `filters` (the JSON form of
+/// `ProductFilters`) like the organic results. Fair-use rules: at most `slots`
+/// listings (capped at `MAX_PROMOTED_SLOTS`), one per owner, rotating towards the promotions
+/// that have been shown least. Selecting does not count impressions; see
+/// `record_promotion_impressions`.
+pub async fn select_promoted_products(
+    db: &PgPool,
+    filters: &serde_json::Value,
+    slots: i64,
+) -> Result<Vec<(Uuid, Uuid)>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        WITH candidates AS (
+            SELECT DISTINCT ON (p.owner_id) pr.promotion_id, pr.product_id, pr.impressions
+            FROM product_schema.promotions pr
+            JOIN product_schema.products p ON p.product_id = pr.product_id
+            WHERE pr.status = 'paid'
+            AND CURRENT_DATE BETWEEN pr.starts_on AND pr.ends_on
+            AND p.status = 'active'
+            AND product_schema.listing_matches_filters(
+                p, $1, (SELECT product_schema.category_subtree(($1->>'category_id')::uuid)), $2
+            )
+            ORDER BY p.owner_id, pr.impressions, random()
+        )
+        SELECT promotion_id as "promotion_id!", product_id as "product_id!"
+        FROM candidates
+        ORDER BY impressions, random()
+        LIMIT $3
+        "#,
+        filters,
+        DEFAULT_RADIUS_KM,
+        slots.min(MAX_PROMOTED_SLOTS)
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.promotion_id, r.product_id)).collect())
+}
+
+/// Counts one impression per signed-in viewer, promotion and day. Owners seeing their own
+/// promotions are not counted, and neither are anonymous viewers, who cannot be told apart.
+pub async fn record_promotion_impressions(db: &PgPool, viewer_id: Uuid, promotion_ids: &[Uuid]) -> Result<(), sqlx::Error> {
+    if promotion_ids.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query!(
+        r#"
+        WITH fresh AS (
+            INSERT INTO product_schema.promotion_interactions (promotion_id, viewer_id, day, kind)
+            SELECT promotion_id, $2, $3, 'impression'
+            FROM product_schema.promotions
+            WHERE promotion_id = ANY($1) AND owner_id <> $2
+            ON CONFLICT DO NOTHING
+            RETURNING promotion_id
+        )
+        UPDATE product_schema.promotions pr SET impressions = pr.impressions + 1
+        FROM fresh
+        WHERE pr.promotion_id = fresh.promotion_id
+        "#,
+        promotion_ids,
+        viewer_id,
+        Utc::now().date_naive()
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// Books a promotion slot. The slot stays `pending_payment` until a payment intent created
+/// with its `promotion_id` is confirmed.
+pub async fn create_promotion(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(product_id): Path<Uuid>,
+    Json(req): Json<CreatePromotionRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let amount_cents = match promotion_price_cents(req.starts_on, req.ends_on, Utc::now().date_naive()) {
+        Ok(a) => a,
+        Err(e) => return err(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create promotion"),
+    };
+
+    // Locking the listing, then its owner, serializes bookings so the overlap and open-count
+    // checks below still hold at insert time
+    let product = sqlx::query!(
+        "SELECT owner_id, status FROM product_schema.products WHERE product_id = $1 FOR UPDATE",
+        product_id
+    )
+    .fetch_optional(&mut tx)
+    .await;
+
+    let product = match product {
+        Ok(Some(p)) => p,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Product not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create promotion"),
+    };
+
+    if product.owner_id != user_id {
+        return err(StatusCode::FORBIDDEN, "Not authorized to promote this product");
+    }
+
+    if product.status != "active" {
+        return err(StatusCode::BAD_REQUEST, "Only active listings can be promoted");
+    }
+
+    if sqlx::query!("SELECT user_id FROM user_schema.users WHERE user_id = $1 FOR UPDATE", user_id)
+        .fetch_one(&mut tx)
+        .await
+        .is_err()
+    {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create promotion");
+    }
+
+    let existing = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE product_id = $2 AND starts_on <= $4 AND ends_on >= $3) as "overlapping!",
+            COUNT(*) FILTER (WHERE ends_on >= CURRENT_DATE) as "open!"
+        FROM product_schema.promotions
+        WHERE owner_id = $1 AND status IN ('pending_payment', 'paid')
+        "#,
+        user_id,
+        product_id,
+        req.starts_on,
+        req.ends_on
+    )
+    .fetch_one(&mut tx)
+    .await;
+
+    match existing {
+        Ok(e) if e.overlapping > 0 => {
+            return err(StatusCode::CONFLICT, "This listing already has a promotion in that period")
+        }
+        Ok(e) if e.open >= MAX_ACTIVE_PROMOTIONS_PER_OWNER => {
+            let message = format!("You can have at most {} open promotions", MAX_ACTIVE_PROMOTIONS_PER_OWNER);
+            return err(StatusCode::CONFLICT, &message);
+        }
+        Ok(_) => {}
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create promotion"),
+    }
+
+    let promotion = sqlx::query!(
+        r#"
+        INSERT INTO product_schema.promotions (product_id, owner_id, starts_on, ends_on, amount_cents, currency)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING promotion_id
+        "#,
+        product_id,
+        user_id,
+        req.starts_on,
+        req.ends_on,
+        amount_cents,
+        DEFAULT_CURRENCY
+    )
+    .fetch_one(&mut tx)
+    .await;
+
+    let promotion = match promotion {
+        Ok(p) => p,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create promotion"),
+    };
+
+    match tx.commit().await {
+        Ok(_) => ok(serde_json::json!({
+            "promotion_id": promotion.promotion_id,
+            "amount_cents": amount_cents,
+            "currency": DEFAULT_CURRENCY,
+            "status": "pending_payment",
+            "message": "Promotion booked; create a payment intent with this promotion_id to pay for it"
+        })),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create promotion"),
+    }
+}
+
+pub async fn list_promotions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let promotions = sqlx::query!(
+        r#"
+        SELECT pr.promotion_id, pr.product_id, p.name as product_name, pr.starts_on, pr.ends_on,
+               pr.amount_cents, pr.currency, pr.status, pr.impressions, pr.clicks, pr.created_at
+        FROM product_schema.promotions pr
+        JOIN product_schema.products p ON p.product_id = pr.product_id
+        WHERE pr.owner_id = $1
+        ORDER BY pr.starts_on DESC
+        "#,
+        user_id
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    let promotions = match promotions {
+        Ok(p) => p,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch promotions"),
+    };
+
+    let response: Vec<PromotionResponse> = promotions
+        .into_iter()
+        .map(|p| PromotionResponse {
+            promotion_id: p.promotion_id,
+            product_id: p.product_id,
+            product_name: p.product_name,
+            starts_on: p.starts_on,
+            ends_on: p.ends_on,
+            amount_cents: p.amount_cents,
+            currency: p.currency,
+            status: p.status,
+            click_through_rate: click_through_rate(p.impressions, p.clicks),
+            impressions: p.impressions,
+            clicks: p.clicks,
+            created_at: p.created_at,
+        })
+        .collect();
+
+    ok(response)
+}
+
+/// Unpaid bookings can be released; paid promotions run to the end of their window.
+pub async fn cancel_promotion(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(promotion_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE product_schema.promotions SET status = 'cancelled'
+        WHERE promotion_id = $1 AND owner_id = $2 AND status = 'pending_payment'
+        "#,
+        promotion_id,
+        user_id
+    )
+    .execute(&state.db)
+    .await;
+
+    match result {
+        Ok(r) if r.rows_affected() > 0 => ok(serde_json::json!({ "message": "Promotion cancelled" })),
+        Ok(_) => err(StatusCode::NOT_FOUND, "No unpaid promotion found"),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to cancel promotion"),
+    }
+}
+
+/// Called by clients when a promoted listing is opened from a boosted slot. Each signed-in
+/// user counts at most once per promotion and day, and owners' own clicks never count, so
+/// repeating the call cannot inflate the click-through rate.
+pub async fn record_promotion_click(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(promotion_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let result = sqlx::query!(
+        r#"
+        WITH running AS (
+            SELECT promotion_id, owner_id FROM product_schema.promotions
+            WHERE promotion_id = $1 AND status = 'paid' AND $3 BETWEEN starts_on AND ends_on
+        ),
+        fresh AS (
+            INSERT INTO product_schema.promotion_interactions (promotion_id, viewer_id, day, kind)
+            SELECT promotion_id, $2, $3, 'click' FROM running WHERE owner_id <> $2
+            ON CONFLICT DO NOTHING
+            RETURNING promotion_id
+        ),
+        counted AS (
+            UPDATE product_schema.promotions pr SET clicks = pr.clicks + 1
+            FROM fresh
+            WHERE pr.promotion_id = fresh.promotion_id
+            RETURNING pr.promotion_id
+        )
+        SELECT EXISTS (SELECT 1 FROM running) as "running!", EXISTS (SELECT 1 FROM counted) as "recorded!"
+        "#,
+        promotion_id,
+        user_id,
+        Utc::now().date_naive()
+    )
+    .fetch_one(&state.db)
+    .await;
+
+    match result {
+        Ok(r) if r.running => ok(serde_json::json!({ "recorded": r.recorded })),
+        Ok(_) => err(StatusCode::NOT_FOUND, "Promotion is not running"),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to record click"),
+    }
+}
@@ -5,11 +5,22 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
 use crate::state::{ok, err, AppState};
 use crate::jwt::verify_token;
+use crate::pricing::{quote_for_product, PriceQuote};
+use crate::inventory::{assign_unit, blacked_out, busy_units, overlapping_rentals};
+use crate::instant_book::{complete_instant_booking, load_renter_standing, InstantBookRequirements};
+use crate::payment::{record_pending_charge, settle_charge, settle_rental_payments};
+use crate::insurance::{default_insurance_provider, record_policy, select_coverage, CoverageOption, InsuranceError};
+use crate::cancellation::settle_cancellation;
+use crate::analytics::{spawn_record_event, ProductEvent};
+use crate::rental_status::{record_rental_history, RentalRole, RentalStatus};
+use crate::etag::{check_if_match, with_etag, PreconditionError};
+use crate::pagination::{decode_cursor, page_size, CursorPage, DEFAULT_PAGE_SIZE};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateRentalRequest {
@@ -18,11 +29,14 @@ pub struct CreateRentalRequest {
     pub rental_period_end: DateTime<Utc>,
     pub pickup_notes: Option<String>,
     pub return_notes: Option<String>,
+    pub delivery_requested: Option<bool>,
+    /// Coverage option to buy with the booking, from `/quote/insurance`.
+    pub insurance_option: Option<String>,
 }
 
+/// Status changes go through the transition endpoints in `rental_status`.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateRentalRequest {
-    pub status: Option<String>,
     pub pickup_notes: Option<String>,
     pub return_notes: Option<String>,
 }
@@ -32,9 +46,12 @@ pub struct RentalResponse {
     pub rental_id: Uuid,
     pub product_id: Uuid,
     pub renter_id: Uuid,
+    pub unit_id: Option<Uuid>,
     pub rental_period_start: DateTime<Utc>,
     pub rental_period_end: DateTime<Utc>,
     pub status: String,
+    /// Policy snapshotted from the product when the rental was booked.
+    pub cancellation_policy: String,
     pub pickup_notes: Option<String>,
     pub return_notes: Option<String>,
     pub created_at: DateTime<Utc>,
@@ -50,19 +67,12 @@ pub struct ProductInfo {
     pub owner_id: Uuid,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct RentalListResponse {
-    pub rentals: Vec<RentalResponse>,
-    pub total: i64,
-    pub page: i64,
-    pub per_page: i64,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RentalFilters {
     pub status: Option<String>,
-    pub page: Option<i64>,
-    pub per_page: Option<i64>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+    pub include_total: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -75,12 +85,17 @@ pub struct AvailabilityRequest {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AvailabilityResponse {
     pub available: bool,
+    pub total_units: i64,
+    pub available_units: i64,
+    /// The owner blocked these dates, here or on another platform's calendar.
+    pub blacked_out: bool,
     pub conflicting_rentals: Vec<RentalConflict>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RentalConflict {
     pub rental_id: Uuid,
+    pub unit_id: Option<Uuid>,
     pub start_date: DateTime<Utc>,
     pub end_date: DateTime<Utc>,
     pub status: String,
@@ -111,93 +126,363 @@ fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
     }
 }
 
-pub async fn create_rental(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-    Json(req): Json<CreateRentalRequest>,
-) -> impl IntoResponse {
-    let user_id = match extract_user_id_from_token(&headers) {
-        Ok(id) => id,
-        Err(status) => return err(status, "Unauthorized"),
-    };
+/// Attempts before a booking that keeps hitting the overlap constraint is reported as a conflict.
+pub const MAX_BOOKING_ATTEMPTS: usize = 3;
+
+/// Postgres `exclusion_violation`, raised by `rentals_unit_period_excl` when two open bookings
+/// of the same unit overlap.
+pub const EXCLUSION_VIOLATION: &str = "23P01";
+
+#[derive(Debug, thiserror::Error)]
+pub enum BookingError {
+    #[error("Product not found")]
+    ProductNotFound,
+    #[error("User not found")]
+    RenterNotFound,
+    #[error("Product is not available for rental")]
+    ProductInactive,
+    #[error("Cannot rent your own product")]
+    OwnProduct,
+    #[error("This item can only be rented with insurance; choose a coverage option")]
+    InsuranceRequired,
+    #[error("{0}")]
+    Insurance(#[from] InsuranceError),
+    #[error("Product is not available for the selected dates")]
+    Unavailable,
+    /// A concurrent booking took the unit between the availability check and the insert.
+    #[error("Product was just booked for overlapping dates; please pick other dates")]
+    Overlap,
+    #[error("{1}")]
+    Quote(StatusCode, String),
+    /// Charging an instant booking failed; the booking was cancelled or never made.
+    #[error("{1}")]
+    Payment(StatusCode, String),
+    #[error("Failed to create rental")]
+    Database(sqlx::Error),
+}
+
+impl BookingError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            BookingError::ProductNotFound | BookingError::RenterNotFound => StatusCode::NOT_FOUND,
+            BookingError::ProductInactive | BookingError::OwnProduct | BookingError::InsuranceRequired => {
+                StatusCode::BAD_REQUEST
+            }
+            BookingError::Insurance(e) => e.status(),
+            BookingError::Unavailable | BookingError::Overlap => StatusCode::CONFLICT,
+            BookingError::Quote(status, _) | BookingError::Payment(status, _) => *status,
+            BookingError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
 
-    // Verify product exists and is available
+impl From<sqlx::Error> for BookingError {
+    fn from(e: sqlx::Error) -> Self {
+        match &e {
+            sqlx::Error::Database(db) if db.code().as_deref() == Some(EXCLUSION_VIOLATION) => BookingError::Overlap,
+            _ => BookingError::Database(e),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Booking {
+    pub rental_id: Uuid,
+    pub unit_id: Uuid,
+    pub quote: PriceQuote,
+    /// `Confirmed` for a paid instant booking, `PendingPayment` while its charge is still in
+    /// flight, otherwise `Requested`.
+    pub status: RentalStatus,
+    /// Instant-book requirements the renter missed, which turned the booking into a request.
+    pub unmet_requirements: Vec<&'static str>,
+    pub insurance_policy_id: Option<Uuid>,
+}
+
+/// Books a unit of the product for the renter. Unit assignment and the insert share one
+/// transaction, and the exclusion constraint on `rental_period` is the final word on overlaps:
+/// a violation rolls back and retries, so another free unit can still be used.
+///
+/// On an instant-book listing a renter who meets the owner's requirements is booked as pending
+/// payment and charged once the booking has committed, with the rental id as the idempotency
+/// key; the charge confirms it or, if declined, cancels it. Anyone else leaves a request for the
+/// owner to answer.
+///
+/// Listings that require insurance are only booked with a coverage option, whose premium is
+/// added to the quote. The insurer binds the policy once the booking has committed, before any
+/// charge is sent; if it refuses, the booking is withdrawn.
+pub async fn book_rental(db: &PgPool, renter_id: Uuid, req: &CreateRentalRequest) -> Result<Booking, BookingError> {
     let product = sqlx::query!(
-        "SELECT product_id, owner_id, status FROM product_schema.products WHERE product_id = $1",
+        r#"
+        SELECT product_id, owner_id, status, instant_book, instant_book_requirements, insurance_required
+        FROM product_schema.products WHERE product_id = $1
+        "#,
         req.product_id
     )
-    .fetch_one(&state.db)
-    .await;
-
-    let product = match product {
-        Ok(p) => p,
-        Err(_) => return err(StatusCode::NOT_FOUND, "Product not found"),
-    };
+    .fetch_optional(db)
+    .await?
+    .ok_or(BookingError::ProductNotFound)?;
 
     if product.status != "active" {
-        return err(StatusCode::BAD_REQUEST, "Product is not available for rental");
+        return Err(BookingError::ProductInactive);
     }
 
-    if product.owner_id == user_id {
-        return err(StatusCode::BAD_REQUEST, "Cannot rent your own product");
+    if product.owner_id == renter_id {
+        return Err(BookingError::OwnProduct);
     }
 
-    // Check availability
-    let conflicts = sqlx::query!(
-        r#"
-        SELECT rental_id, rental_period_start, rental_period_end, status
-        FROM rental_schema.rentals
-        WHERE product_id = $1 
-        AND status IN ('requested', 'confirmed', 'active')
-        AND (
-            (rental_period_start <= $2 AND rental_period_end >= $2) OR
-            (rental_period_start <= $3 AND rental_period_end >= $3) OR
-            (rental_period_start >= $2 AND rental_period_end <= $3)
-        )
-        "#,
+    // Price the booking and snapshot the quote on the rental
+    let quote = quote_for_product(
+        db,
         req.product_id,
         req.rental_period_start,
-        req.rental_period_end
+        req.rental_period_end,
+        req.delivery_requested.unwrap_or(false),
     )
-    .fetch_all(&state.db)
-    .await;
+    .await
+    .map_err(|(status, message)| BookingError::Quote(status, message))?;
 
-    let conflicts = match conflicts {
-        Ok(c) => c,
-        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to check availability"),
+    let coverage = match req.insurance_option.as_deref() {
+        Some(option_id) => Some(select_coverage(default_insurance_provider(), &quote, option_id)?),
+        None if product.insurance_required => return Err(BookingError::InsuranceRequired),
+        None => None,
+    };
+    let quote = quote.with_insurance_premium(coverage.as_ref().map_or(0.0, |c| c.premium));
+
+    let unmet_requirements = if product.instant_book {
+        let requirements: InstantBookRequirements = product
+            .instant_book_requirements
+            .and_then(|r| serde_json::from_value(r).ok())
+            .unwrap_or_default();
+        let standing = load_renter_standing(db, renter_id).await?.ok_or(BookingError::RenterNotFound)?;
+        requirements.unmet(&standing)
+    } else {
+        Vec::new()
+    };
+    let status = if product.instant_book && unmet_requirements.is_empty() {
+        RentalStatus::Confirmed
+    } else {
+        RentalStatus::Requested
     };
 
-    if !conflicts.is_empty() {
-        return err(StatusCode::CONFLICT, "Product is not available for the selected dates");
-    }
+    let mut attempt = 1;
+    let (rental_id, unit_id, payment_intent_id) = loop {
+        match try_book(db, renter_id, req, &quote, status).await {
+            Ok(booked) => break booked,
+            Err(BookingError::Overlap) if attempt < MAX_BOOKING_ATTEMPTS => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    };
+
+    // Called once, outside the retried transaction, so an attempt that rolls back never holds
+    // a policy
+    let insurance_policy_id = match coverage.as_ref() {
+        Some(option) => match bind_policy(db, rental_id, &quote, option).await {
+            Ok(policy_id) => Some(policy_id),
+            Err(e) => {
+                withdraw_booking(db, rental_id, "Insurance could not be issued").await?;
+                return Err(e);
+            }
+        },
+        None => None,
+    };
+
+    // If the charge cannot be settled now, the booking stays pending and the expiry job
+    // finishes it
+    let status = match payment_intent_id {
+        Some(payment_intent_id) => {
+            let charged = settle_charge(db, payment_intent_id).await;
+            match (charged, complete_instant_booking(db, rental_id).await?) {
+                (Err((status, message)), Some(RentalStatus::Cancelled)) => {
+                    return Err(BookingError::Payment(status, message))
+                }
+                (_, settled) => settled.unwrap_or(RentalStatus::PendingPayment),
+            }
+        }
+        None => status,
+    };
+
+    Ok(Booking { rental_id, unit_id, quote, status, unmet_requirements, insurance_policy_id })
+}
+
+async fn try_book(
+    db: &PgPool,
+    renter_id: Uuid,
+    req: &CreateRentalRequest,
+    quote: &PriceQuote,
+    status: RentalStatus,
+) -> Result<(Uuid, Uuid, Option<Uuid>), BookingError> {
+    let mut tx = db.begin().await?;
+
+    // The booking needs a unit that is free for the whole period
+    let unit_id = assign_unit(&mut tx, req.product_id, req.rental_period_start, req.rental_period_end)
+        .await?
+        .ok_or(BookingError::Unavailable)?;
+
+    // Instant bookings are paid up front and hold the unit as pending payment until then
+    let amount_cents = quote.total_due_cents();
+    let instant = status == RentalStatus::Confirmed;
+    let status = if instant && amount_cents > 0 { RentalStatus::PendingPayment } else { status };
 
-    // Create rental
     let rental_id = Uuid::new_v4();
-    let result = sqlx::query!(
+    sqlx::query!(
         r#"
         INSERT INTO rental_schema.rentals 
-        (rental_id, product_id, renter_id, rental_period_start, rental_period_end, 
-         pickup_notes, return_notes)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        (rental_id, product_id, renter_id, unit_id, rental_period_start, rental_period_end, 
+         pickup_notes, return_notes, price_quote, total_amount, status, cancellation_policy)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11,
+                (SELECT cancellation_policy FROM product_schema.products WHERE product_id = $2))
         "#,
         rental_id,
         req.product_id,
-        user_id,
+        renter_id,
+        unit_id,
         req.rental_period_start,
         req.rental_period_end,
         req.pickup_notes,
-        req.return_notes
+        req.return_notes,
+        serde_json::to_value(quote).unwrap_or_default(),
+        quote.total_due,
+        status.as_str()
     )
-    .execute(&state.db)
-    .await;
+    .execute(&mut tx)
+    .await?;
+
+    record_rental_history(
+        &mut tx,
+        rental_id,
+        None,
+        status,
+        Some(renter_id),
+        Some(RentalRole::Renter),
+        instant.then_some("Instant book"),
+    )
+    .await?;
+
+    // Only recorded here; the provider is called once the booking has committed
+    let payment_intent_id = if status == RentalStatus::PendingPayment {
+        let charge = record_pending_charge(
+            &mut tx,
+            renter_id,
+            Some(rental_id),
+            amount_cents,
+            &quote.currency,
+            &rental_id.to_string(),
+            "instant booking",
+        )
+        .await
+        .map_err(|(status, message)| BookingError::Payment(status, message))?;
+        Some(charge)
+    } else {
+        None
+    };
+
+    tx.commit().await?;
+
+    Ok((rental_id, unit_id, payment_intent_id))
+}
+
+/// Has the insurer bind `option` for a committed booking and records the policy.
+async fn bind_policy(
+    db: &PgPool,
+    rental_id: Uuid,
+    quote: &PriceQuote,
+    option: &CoverageOption,
+) -> Result<Uuid, BookingError> {
+    let provider = default_insurance_provider();
+    let issued = provider.issue_policy(rental_id, quote, option)?;
+
+    let mut tx = db.begin().await?;
+    let policy_id = record_policy(&mut tx, rental_id, provider.name(), quote, option, &issued).await?;
+    tx.commit().await?;
+    Ok(policy_id)
+}
+
+/// Cancels a committed booking that could not be completed. Its instant-book charge is dropped
+/// before it reaches the provider, and anything already paid is refunded in full.
+async fn withdraw_booking(db: &PgPool, rental_id: Uuid, reason: &str) -> Result<(), BookingError> {
+    let mut tx = db.begin().await?;
 
-    if result.is_err() {
-        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create rental");
+    let rental = sqlx::query!(
+        "SELECT status FROM rental_schema.rentals WHERE rental_id = $1 FOR UPDATE",
+        rental_id
+    )
+    .fetch_one(&mut tx)
+    .await?;
+
+    // Someone may have moved it on already
+    let from = match rental.status.parse::<RentalStatus>() {
+        Ok(from) if from.can_transition_to(RentalStatus::Cancelled) => from,
+        _ => return Ok(()),
+    };
+
+    sqlx::query!(
+        r#"
+        UPDATE payment_schema.payment_intents
+        SET status = 'failed', failure_reason = $2, updated_at = NOW()
+        WHERE rental_id = $1 AND status = 'pending' AND metadata->>'idempotency_key' = $1::text
+        "#,
+        rental_id,
+        reason
+    )
+    .execute(&mut tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE rental_schema.rentals SET status = $2, updated_at = NOW() WHERE rental_id = $1",
+        rental_id,
+        RentalStatus::Cancelled.as_str()
+    )
+    .execute(&mut tx)
+    .await?;
+
+    record_rental_history(&mut tx, rental_id, Some(from), RentalStatus::Cancelled, None, None, Some(reason)).await?;
+
+    settle_cancellation(&mut tx, rental_id, from, RentalRole::Admin, Some(reason))
+        .await
+        .map_err(|(status, message)| BookingError::Payment(status, message))?;
+
+    tx.commit().await?;
+
+    if let Err(e) = settle_rental_payments(db, Some(rental_id)).await {
+        tracing::warn!(%rental_id, error = %e, "settling rental payments failed");
     }
+    Ok(())
+}
+
+pub async fn create_rental(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateRentalRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let booking = match book_rental(&state.db, user_id, &req).await {
+        Ok(b) => b,
+        Err(e) => return err(e.status(), &e.to_string()),
+    };
+
+    spawn_record_event(&state.db, req.product_id, ProductEvent::Inquiry);
+
+    let message = match booking.status {
+        RentalStatus::Confirmed => {
+            spawn_record_event(&state.db, req.product_id, ProductEvent::Booking);
+            "Rental booked and confirmed"
+        }
+        RentalStatus::PendingPayment => "Rental booked; the payment is still processing",
+        _ => "Rental request created successfully",
+    };
 
     let response = serde_json::json!({
-        "rental_id": rental_id,
-        "message": "Rental request created successfully"
+        "rental_id": booking.rental_id,
+        "unit_id": booking.unit_id,
+        "status": booking.status,
+        "quote": booking.quote,
+        "unmet_requirements": booking.unmet_requirements,
+        "insurance_policy_id": booking.insurance_policy_id,
+        "message": message
     });
 
     ok(response)
@@ -231,13 +516,16 @@ pub async fn get_rental(
         Err(_) => return err(StatusCode::NOT_FOUND, "Rental not found"),
     };
 
+    let version = rental.version;
     let response = RentalResponse {
         rental_id: rental.rental_id,
         product_id: rental.product_id,
         renter_id: rental.renter_id,
+        unit_id: rental.unit_id,
         rental_period_start: rental.rental_period_start,
         rental_period_end: rental.rental_period_end,
         status: rental.status,
+        cancellation_policy: rental.cancellation_policy,
         pickup_notes: rental.pickup_notes,
         return_notes: rental.return_notes,
         created_at: rental.created_at,
@@ -250,7 +538,7 @@ pub async fn get_rental(
         },
     };
 
-    ok(response)
+    with_etag(ok(response), version)
 }
 
 pub async fn list_rentals(
@@ -263,22 +551,26 @@ pub async fn list_rentals(
         Err(status) => return err(status, "Unauthorized"),
     };
 
-    let page = filters.page.unwrap_or(1);
-    let per_page = filters.per_page.unwrap_or(20).min(100);
-    let offset = (page - 1) * per_page;
+    let limit = page_size(filters.limit, DEFAULT_PAGE_SIZE);
+    let cursor = match decode_cursor(filters.cursor.as_deref(), "rentals") {
+        Ok(c) => c,
+        Err(e) => return err(StatusCode::BAD_REQUEST, &e.to_string()),
+    };
 
     let rentals = sqlx::query!(
         r#"
         SELECT r.*, p.name, p.daily_price, p.owner_id
         FROM rental_schema.rentals r
         JOIN product_schema.products p ON r.product_id = p.product_id
-        WHERE r.renter_id = $1 OR p.owner_id = $1
-        ORDER BY r.created_at DESC
-        LIMIT $2 OFFSET $3
+        WHERE (r.renter_id = $1 OR p.owner_id = $1)
+        AND ($3::timestamptz IS NULL OR (r.created_at, r.rental_id) < ($3, $4))
+        ORDER BY r.created_at DESC, r.rental_id DESC
+        LIMIT $2
         "#,
         user_id,
-        per_page,
-        offset
+        limit + 1,
+        cursor.as_ref().map(|c| c.sort_key),
+        cursor.as_ref().map(|c| c.id)
     )
     .fetch_all(&state.db)
     .await;
@@ -288,21 +580,25 @@ pub async fn list_rentals(
         Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch rentals"),
     };
 
-    let total = sqlx::query!(
-        r#"
-        SELECT COUNT(*) as count
-        FROM rental_schema.rentals r
-        JOIN product_schema.products p ON r.product_id = p.product_id
-        WHERE r.renter_id = $1 OR p.owner_id = $1
-        "#,
-        user_id
-    )
-    .fetch_one(&state.db)
-    .await;
-
-    let total = match total {
-        Ok(t) => t.count.unwrap_or(0),
-        Err(_) => 0,
+    let total = if filters.include_total.unwrap_or(false) {
+        let total = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as count
+            FROM rental_schema.rentals r
+            JOIN product_schema.products p ON r.product_id = p.product_id
+            WHERE r.renter_id = $1 OR p.owner_id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(&state.db)
+        .await;
+
+        match total {
+            Ok(t) => Some(t.count.unwrap_or(0)),
+            Err(_) => None,
+        }
+    } else {
+        None
     };
 
     let rental_responses: Vec<RentalResponse> = rentals
@@ -311,9 +607,11 @@ pub async fn list_rentals(
             rental_id: r.rental_id,
             product_id: r.product_id,
             renter_id: r.renter_id,
+            unit_id: r.unit_id,
             rental_period_start: r.rental_period_start,
             rental_period_end: r.rental_period_end,
             status: r.status,
+            cancellation_policy: r.cancellation_policy,
             pickup_notes: r.pickup_notes,
             return_notes: r.return_notes,
             created_at: r.created_at,
@@ -327,12 +625,8 @@ pub async fn list_rentals(
         })
         .collect();
 
-    let response = RentalListResponse {
-        rentals: rental_responses,
-        total,
-        page,
-        per_page,
-    };
+    let response = CursorPage::from_rows(rental_responses, limit, "rentals", |r| (r.created_at, r.rental_id))
+        .with_total(total);
 
     ok(response)
 }
@@ -367,9 +661,8 @@ pub async fn update_rental(
         Err(_) => return err(StatusCode::NOT_FOUND, "Rental not found"),
     };
 
-    // Only product owner can update status
-    if req.status.is_some() && rental.owner_id != user_id {
-        return err(StatusCode::FORBIDDEN, "Only product owner can update rental status");
+    if let Err(e) = check_if_match(&headers, rental.version) {
+        return e.into_response();
     }
 
     // Update rental
@@ -377,31 +670,37 @@ pub async fn update_rental(
         r#"
         UPDATE rental_schema.rentals 
         SET 
-            status = COALESCE($2, status),
-            pickup_notes = COALESCE($3, pickup_notes),
-            return_notes = COALESCE($4, return_notes),
+            pickup_notes = COALESCE($2, pickup_notes),
+            return_notes = COALESCE($3, return_notes),
             updated_at = NOW()
-        WHERE rental_id = $1
+        WHERE rental_id = $1 AND version = $4
+        RETURNING version
         "#,
         rental_id,
-        req.status,
         req.pickup_notes,
-        req.return_notes
+        req.return_notes,
+        rental.version
     )
-    .execute(&state.db)
+    .fetch_optional(&state.db)
     .await;
 
-    if result.is_err() {
-        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update rental");
-    }
+    let version = match result {
+        Ok(Some(r)) => r.version,
+        Ok(None) => return PreconditionError::Stale.into_response(),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update rental"),
+    };
 
-    ok(serde_json::json!({ "message": "Rental updated successfully" }))
+    with_etag(ok(serde_json::json!({ "message": "Rental updated successfully" })), version)
 }
 
 pub async fn check_availability(
     State(state): State<AppState>,
     Json(req): Json<AvailabilityRequest>,
 ) -> impl IntoResponse {
+    if req.end_date <= req.start_date {
+        return err(StatusCode::BAD_REQUEST, "end_date must be after start_date");
+    }
+
     // Verify product exists
     let product = sqlx::query!(
         "SELECT product_id, status FROM product_schema.products WHERE product_id = $1",
@@ -418,32 +717,44 @@ pub async fn check_availability(
     if product.status != "active" {
         return ok(AvailabilityResponse {
             available: false,
+            total_units: 0,
+            available_units: 0,
+            blacked_out: false,
             conflicting_rentals: vec![],
         });
     }
 
-    // Check for conflicts
-    let conflicts = sqlx::query!(
+    let total_units = sqlx::query!(
         r#"
-        SELECT rental_id, rental_period_start, rental_period_end, status
-        FROM rental_schema.rentals
-        WHERE product_id = $1 
-        AND status IN ('requested', 'confirmed', 'active')
-        AND (
-            (rental_period_start <= $2 AND rental_period_end >= $2) OR
-            (rental_period_start <= $3 AND rental_period_end >= $3) OR
-            (rental_period_start >= $2 AND rental_period_end <= $3)
-        )
-        ORDER BY rental_period_start
+        SELECT COUNT(*) as "count!"
+        FROM product_schema.product_units
+        WHERE product_id = $1 AND status = 'active'
         "#,
-        req.product_id,
-        req.start_date,
-        req.end_date
+        req.product_id
     )
-    .fetch_all(&state.db)
+    .fetch_one(&state.db)
     .await;
 
-    let conflicts = match conflicts {
+    let total_units = match total_units {
+        Ok(t) => t.count,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to check availability"),
+    };
+
+    // Same rules the booking path uses, so a unit shown free here can be booked
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to check availability"),
+    };
+    let busy = match busy_units(&mut tx, req.product_id, req.start_date, req.end_date).await {
+        Ok(b) => b,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to check availability"),
+    };
+    let blacked_out = match blacked_out(&mut tx, req.product_id, req.start_date, req.end_date).await {
+        Ok(b) => b,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to check availability"),
+    };
+
+    let conflicts = match overlapping_rentals(&mut tx, req.product_id, req.start_date, req.end_date).await {
         Ok(c) => c,
         Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to check availability"),
     };
@@ -452,16 +763,21 @@ pub async fn check_availability(
         .into_iter()
         .map(|c| RentalConflict {
             rental_id: c.rental_id,
+            unit_id: c.unit_id,
             start_date: c.rental_period_start,
             end_date: c.rental_period_end,
             status: c.status,
         })
         .collect();
 
-    let available = conflicting_rentals.is_empty();
+    // Overlapping bookings only block the units they were assigned to; blackouts block them all
+    let available_units = if blacked_out { 0 } else { (total_units - busy.len() as i64).max(0) };
 
     let response = AvailabilityResponse {
-        available,
+        available: available_units > 0,
+        total_units,
+        available_units,
+        blacked_out,
         conflicting_rentals,
     };
 
@@ -0,0 +1,633 @@
+use std::fmt;
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::etag::with_etag;
+use crate::insurance::{active_coverage_option, default_insurance_provider, reschedule_policy, select_coverage};
+use crate::inventory::{assign_unit, unit_free_for};
+use crate::payment::{record_pending_charge, refund_rental_payments, rental_refundable_charges, settle_rental_payments};
+use crate::pricing::{quote_for_product, PriceQuote};
+use crate::rental::EXCLUSION_VIOLATION;
+use crate::rental_status::{RentalRole, RentalStatus};
+
+pub const MAX_MODIFICATION_MESSAGE_LEN: usize = 1000;
+
+/// Postgres `unique_violation`; raised when a rental already has a pending proposal.
+const UNIQUE_VIOLATION: &str = "23505";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModificationStatus {
+    Pending,
+    Accepted,
+    /// Turned down by the other party.
+    Declined,
+    /// Taken back by the party who proposed it.
+    Withdrawn,
+}
+
+impl ModificationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModificationStatus::Pending => "pending",
+            ModificationStatus::Accepted => "accepted",
+            ModificationStatus::Declined => "declined",
+            ModificationStatus::Withdrawn => "withdrawn",
+        }
+    }
+}
+
+impl fmt::Display for ModificationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ModificationStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(ModificationStatus::Pending),
+            "accepted" => Ok(ModificationStatus::Accepted),
+            "declined" => Ok(ModificationStatus::Declined),
+            "withdrawn" => Ok(ModificationStatus::Withdrawn),
+            other => Err(format!("Unknown modification status '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProposeModificationRequest {
+    pub rental_period_start: DateTime<Utc>,
+    pub rental_period_end: DateTime<Utc>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModificationResponse {
+    pub modification_id: Uuid,
+    pub rental_id: Uuid,
+    pub proposed_by: Uuid,
+    pub proposer_role: String,
+    pub previous_start: DateTime<Utc>,
+    pub previous_end: DateTime<Utc>,
+    pub rental_period_start: DateTime<Utc>,
+    pub rental_period_end: DateTime<Utc>,
+    pub price_quote: serde_json::Value,
+    /// New total due minus the current one; positive means the renter pays more.
+    pub price_difference_cents: i64,
+    pub message: Option<String>,
+    pub status: String,
+    pub responded_by: Option<Uuid>,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Money moved when a modification is accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "amount_cents", rename_all = "snake_case")]
+pub enum Settlement {
+    None,
+    Charge(i64),
+    Refund(i64),
+}
+
+/// Settles a price difference against what the renter has paid. Unpaid rentals only get the
+/// new price; refunds never exceed what was paid.
+pub fn settlement(price_difference_cents: i64, paid_cents: i64) -> Settlement {
+    if paid_cents <= 0 || price_difference_cents == 0 {
+        Settlement::None
+    } else if price_difference_cents > 0 {
+        Settlement::Charge(price_difference_cents)
+    } else {
+        Settlement::Refund((-price_difference_cents).min(paid_cents))
+    }
+}
+
+/// Only these rentals can change dates; anything earlier is still a request, anything later is over.
+pub fn modifiable(status: RentalStatus) -> bool {
+    matches!(status, RentalStatus::Confirmed | RentalStatus::Active)
+}
+
+/// Checks proposed dates against the rental as it stands. Once picked up, only the end can move.
+pub fn validate_new_period(
+    status: RentalStatus,
+    current: (DateTime<Utc>, DateTime<Utc>),
+    proposed: (DateTime<Utc>, DateTime<Utc>),
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let (start, end) = proposed;
+    if end <= start {
+        return Err("rental_period_end must be after rental_period_start".to_string());
+    }
+    if proposed == current {
+        return Err("The proposed dates match the current ones".to_string());
+    }
+    if status == RentalStatus::Active && start != current.0 {
+        return Err("The start of an active rental cannot change".to_string());
+    }
+    if status == RentalStatus::Confirmed && start < now {
+        return Err("The proposed dates cannot start in the past".to_string());
+    }
+    if end <= now {
+        return Err("The proposed dates must end in the future".to_string());
+    }
+    Ok(())
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// The rental as seen by one of its parties, locked for the rest of the transaction.
+struct RentalParty {
+    role: RentalRole,
+    status: RentalStatus,
+    product_id: Uuid,
+    renter_id: Uuid,
+    unit_id: Option<Uuid>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    quote: Option<PriceQuote>,
+}
+
+async fn lock_rental_for_party(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+    user_id: Uuid,
+) -> Result<RentalParty, (StatusCode, String)> {
+    let rental = sqlx::query!(
+        r#"
+        SELECT r.status, r.product_id, r.renter_id, r.unit_id, r.rental_period_start, r.rental_period_end,
+               r.price_quote, p.owner_id
+        FROM rental_schema.rentals r
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE r.rental_id = $1
+        FOR UPDATE OF r
+        "#,
+        rental_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load rental".to_string()))?
+    .ok_or((StatusCode::NOT_FOUND, "Rental not found".to_string()))?;
+
+    let role = if rental.renter_id == user_id {
+        RentalRole::Renter
+    } else if rental.owner_id == user_id {
+        RentalRole::Owner
+    } else {
+        return Err((StatusCode::NOT_FOUND, "Rental not found".to_string()));
+    };
+
+    let status = rental
+        .status
+        .parse::<RentalStatus>()
+        .map_err(|message| (StatusCode::INTERNAL_SERVER_ERROR, message))?;
+
+    Ok(RentalParty {
+        role,
+        status,
+        product_id: rental.product_id,
+        renter_id: rental.renter_id,
+        unit_id: rental.unit_id,
+        start: rental.rental_period_start,
+        end: rental.rental_period_end,
+        quote: rental.price_quote.and_then(|q| serde_json::from_value(q).ok()),
+    })
+}
+
+/// The unit that would hold the rental for new dates, or `None` if none is free. Rentals booked
+/// before units existed have no unit of their own, so any free unit of the product will do.
+async fn unit_for_new_period(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+    rental: &RentalParty,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    match rental.unit_id {
+        Some(unit_id) => Ok(unit_free_for(tx, unit_id, rental_id, start, end).await?.then_some(unit_id)),
+        None => assign_unit(tx, rental.product_id, start, end).await,
+    }
+}
+
+async fn fetch_modification(
+    tx: &mut Transaction<'_, Postgres>,
+    modification_id: Uuid,
+) -> Result<ModificationResponse, sqlx::Error> {
+    sqlx::query_as!(
+        ModificationResponse,
+        r#"
+        SELECT modification_id, rental_id, proposed_by, proposer_role, previous_start, previous_end,
+               rental_period_start, rental_period_end, price_quote, price_difference_cents, message,
+               status, responded_by, responded_at, created_at
+        FROM rental_schema.rental_modifications
+        WHERE modification_id = $1
+        "#,
+        modification_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+}
+
+pub async fn propose_modification(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+    Json(req): Json<ProposeModificationRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let message = req.message.as_deref().map(str::trim).filter(|m| !m.is_empty());
+    if message.is_some_and(|m| m.chars().count() > MAX_MODIFICATION_MESSAGE_LEN) {
+        return err(
+            StatusCode::BAD_REQUEST,
+            &format!("message must be at most {} characters", MAX_MODIFICATION_MESSAGE_LEN),
+        );
+    }
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to propose modification"),
+    };
+
+    let rental = match lock_rental_for_party(&mut tx, rental_id, user_id).await {
+        Ok(r) => r,
+        Err((status, message)) => return err(status, &message),
+    };
+
+    if !modifiable(rental.status) {
+        return err(StatusCode::CONFLICT, &format!("A {} rental cannot be modified", rental.status));
+    }
+
+    let proposed = (req.rental_period_start, req.rental_period_end);
+    if let Err(message) = validate_new_period(rental.status, (rental.start, rental.end), proposed, Utc::now()) {
+        return err(StatusCode::BAD_REQUEST, &message);
+    }
+
+    let current_quote = match rental.quote {
+        Some(q) => q,
+        None => return err(StatusCode::CONFLICT, "This rental has no price quote and cannot be modified"),
+    };
+
+    match unit_for_new_period(&mut tx, rental_id, &rental, proposed.0, proposed.1).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return err(StatusCode::CONFLICT, "The item is not available for the proposed dates"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to check availability"),
+    }
+
+    let coverage = match active_coverage_option(&mut tx, rental_id).await {
+        Ok(c) => c,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to propose modification"),
+    };
+
+    let quote = match quote_for_product(
+        &state.db,
+        rental.product_id,
+        proposed.0,
+        proposed.1,
+        current_quote.includes_delivery(),
+    )
+    .await
+    {
+        Ok(q) => q,
+        Err((status, message)) => return err(status, &message),
+    };
+
+    // Insurance bought at booking is priced again for the new dates
+    let premium = match coverage.as_deref() {
+        Some(option_id) => match select_coverage(default_insurance_provider(), &quote, option_id) {
+            Ok(option) => option.premium,
+            Err(e) => return err(e.status(), &e.to_string()),
+        },
+        None => current_quote.insurance_premium,
+    };
+    let quote = quote.with_insurance_premium(premium);
+    let price_difference_cents = quote.total_due_cents() - current_quote.total_due_cents();
+
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO rental_schema.rental_modifications
+        (rental_id, proposed_by, proposer_role, previous_start, previous_end, rental_period_start,
+         rental_period_end, price_quote, price_difference_cents, message)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING modification_id
+        "#,
+        rental_id,
+        user_id,
+        rental.role.as_str(),
+        rental.start,
+        rental.end,
+        proposed.0,
+        proposed.1,
+        serde_json::to_value(&quote).unwrap_or_default(),
+        price_difference_cents,
+        message
+    )
+    .fetch_one(&mut tx)
+    .await;
+
+    let modification_id = match inserted {
+        Ok(row) => row.modification_id,
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some(UNIQUE_VIOLATION) => {
+            return err(StatusCode::CONFLICT, "This rental already has a pending modification")
+        }
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to propose modification"),
+    };
+
+    let modification = match fetch_modification(&mut tx, modification_id).await {
+        Ok(m) => m,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to propose modification"),
+    };
+
+    if tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to propose modification");
+    }
+
+    ok(modification)
+}
+
+pub async fn list_modifications(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let modifications = sqlx::query_as!(
+        ModificationResponse,
+        r#"
+        SELECT m.modification_id, m.rental_id, m.proposed_by, m.proposer_role, m.previous_start, m.previous_end,
+               m.rental_period_start, m.rental_period_end, m.price_quote, m.price_difference_cents, m.message,
+               m.status, m.responded_by, m.responded_at, m.created_at
+        FROM rental_schema.rental_modifications m
+        JOIN rental_schema.rentals r ON m.rental_id = r.rental_id
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE m.rental_id = $1 AND (r.renter_id = $2 OR p.owner_id = $2)
+        ORDER BY m.created_at DESC
+        "#,
+        rental_id,
+        user_id
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    match modifications {
+        Ok(m) => ok(m),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch modifications"),
+    }
+}
+
+/// Applies an accepted proposal in the caller's transaction: new dates and quote on the rental,
+/// and the price difference recorded as a pending charge or refund for `settle_rental_payments`
+/// to send once the caller commits. Returns the rental's new version.
+async fn apply_modification(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+    rental: &RentalParty,
+    modification: &ModificationResponse,
+) -> Result<(i32, Settlement), (StatusCode, String)> {
+    let failed = || (StatusCode::INTERNAL_SERVER_ERROR, "Failed to apply modification".to_string());
+
+    let quote: PriceQuote = serde_json::from_value(modification.price_quote.clone()).map_err(|_| failed())?;
+
+    // Blackouts and unit-less rentals are not covered by the exclusion constraint
+    let (start, end) = (modification.rental_period_start, modification.rental_period_end);
+    let unit_id = unit_for_new_period(tx, rental_id, rental, start, end)
+        .await
+        .map_err(|_| failed())?
+        .ok_or((StatusCode::CONFLICT, "The item is no longer available for the proposed dates".to_string()))?;
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE rental_schema.rentals
+        SET rental_period_start = $2, rental_period_end = $3, price_quote = $4, total_amount = $5, unit_id = $6,
+            updated_at = NOW()
+        WHERE rental_id = $1
+        RETURNING version
+        "#,
+        rental_id,
+        modification.rental_period_start,
+        modification.rental_period_end,
+        modification.price_quote,
+        quote.total_due,
+        unit_id
+    )
+    .fetch_one(&mut *tx)
+    .await;
+
+    let version = match updated {
+        Ok(row) => row.version,
+        // Someone booked the unit for the new dates since the proposal was made
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some(EXCLUSION_VIOLATION) => {
+            return Err((StatusCode::CONFLICT, "The item is no longer available for the proposed dates".to_string()))
+        }
+        Err(_) => return Err(failed()),
+    };
+
+    reschedule_policy(tx, rental_id, start, end, quote.insurance_premium)
+        .await
+        .map_err(|_| failed())?;
+
+    let paid_cents: i64 = rental_refundable_charges(tx, rental_id)
+        .await
+        .map_err(|_| failed())?
+        .iter()
+        .map(|c| c.refundable_cents)
+        .sum();
+
+    let settled = settlement(modification.price_difference_cents, paid_cents);
+    match settled {
+        Settlement::Charge(cents) => {
+            // A proposal is accepted at most once, so its id keys the charge
+            record_pending_charge(
+                tx,
+                rental.renter_id,
+                Some(rental_id),
+                cents,
+                &quote.currency,
+                &format!("rental-modification-{}", modification.modification_id),
+                "rental dates changed",
+            )
+            .await?;
+        }
+        Settlement::Refund(cents) => {
+            refund_rental_payments(tx, rental_id, cents, "rental dates changed").await?;
+        }
+        Settlement::None => {}
+    }
+
+    Ok((version, settled))
+}
+
+/// Shared body of the accept and decline endpoints.
+async fn respond_to_modification(
+    state: AppState,
+    headers: HeaderMap,
+    rental_id: Uuid,
+    modification_id: Uuid,
+    accept: bool,
+) -> Response {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update modification"),
+    };
+
+    let rental = match lock_rental_for_party(&mut tx, rental_id, user_id).await {
+        Ok(r) => r,
+        Err((status, message)) => return err(status, &message),
+    };
+
+    let pending = sqlx::query!(
+        r#"
+        SELECT proposer_role FROM rental_schema.rental_modifications
+        WHERE modification_id = $1 AND rental_id = $2 AND status = 'pending'
+        FOR UPDATE
+        "#,
+        modification_id,
+        rental_id
+    )
+    .fetch_optional(&mut tx)
+    .await;
+
+    let proposer_role = match pending {
+        Ok(Some(m)) => m.proposer_role,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "No pending modification found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update modification"),
+    };
+    let proposed_by_caller = proposer_role == rental.role.as_str();
+
+    // Only the other side can accept; the proposer can still withdraw
+    if accept && proposed_by_caller {
+        return err(StatusCode::FORBIDDEN, "A modification must be accepted by the other party");
+    }
+
+    let status = match (accept, proposed_by_caller) {
+        (true, _) => ModificationStatus::Accepted,
+        (false, true) => ModificationStatus::Withdrawn,
+        (false, false) => ModificationStatus::Declined,
+    };
+
+    let mut outcome = None;
+    if accept {
+        if !modifiable(rental.status) {
+            return err(StatusCode::CONFLICT, &format!("A {} rental cannot be modified", rental.status));
+        }
+
+        let modification = match fetch_modification(&mut tx, modification_id).await {
+            Ok(m) => m,
+            Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update modification"),
+        };
+        if (modification.previous_start, modification.previous_end) != (rental.start, rental.end) {
+            return err(StatusCode::CONFLICT, "The rental dates changed since this was proposed");
+        }
+
+        outcome = match apply_modification(&mut tx, rental_id, &rental, &modification).await {
+            Ok(o) => Some(o),
+            Err((status, message)) => return err(status, &message),
+        };
+    }
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE rental_schema.rental_modifications
+        SET status = $2, responded_by = $3, responded_at = NOW()
+        WHERE modification_id = $1
+        "#,
+        modification_id,
+        status.as_str(),
+        user_id
+    )
+    .execute(&mut tx)
+    .await;
+
+    if updated.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update modification");
+    }
+
+    let modification = match fetch_modification(&mut tx, modification_id).await {
+        Ok(m) => m,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update modification"),
+    };
+
+    if tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update modification");
+    }
+
+    // The settlement job retries anything the provider does not take now
+    if outcome.as_ref().is_some_and(|(_, settled)| *settled != Settlement::None) {
+        if let Err(e) = settle_rental_payments(&state.db, Some(rental_id)).await {
+            tracing::warn!(%rental_id, error = %e, "settling rental payments failed");
+        }
+    }
+
+    match outcome {
+        Some((version, settlement)) => with_etag(
+            ok(serde_json::json!({ "modification": modification, "settlement": settlement })),
+            version,
+        ),
+        None => ok(serde_json::json!({ "modification": modification })),
+    }
+}
+
+pub async fn accept_modification(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((rental_id, modification_id)): Path<(Uuid, Uuid)>,
+) -> Response {
+    respond_to_modification(state, headers, rental_id, modification_id, true).await
+}
+
+pub async fn decline_modification(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((rental_id, modification_id)): Path<(Uuid, Uuid)>,
+) -> Response {
+    respond_to_modification(state, headers, rental_id, modification_id, false).await
+}
@@ -0,0 +1,551 @@
+use std::fmt;
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::roles::{has_any_role, ROLE_ADMIN};
+use crate::analytics::{spawn_record_event, ProductEvent};
+use crate::etag::with_etag;
+use crate::cancellation::settle_cancellation;
+use crate::damage_claim::has_unresolved_claim;
+use crate::escrow::settle_deposit;
+use crate::insurance::cancel_policy;
+use crate::payment::settle_rental_payments;
+
+pub const MAX_TRANSITION_REASON_LEN: usize = 1000;
+
+/// Lifecycle of a rental. Mirrors the CHECK constraint on `rental_schema.rentals.status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RentalStatus {
+    Requested,
+    Confirmed,
+    Declined,
+    Active,
+    Returned,
+    Completed,
+    Cancelled,
+    Disputed,
+    /// The owner did not answer a request in time.
+    Expired,
+    /// Past its end and not yet returned; late fees accrue.
+    Overdue,
+    /// An instant booking whose up-front charge has not gone through yet.
+    PendingPayment,
+}
+
+impl RentalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RentalStatus::Requested => "requested",
+            RentalStatus::Confirmed => "confirmed",
+            RentalStatus::Declined => "declined",
+            RentalStatus::Active => "active",
+            RentalStatus::Returned => "returned",
+            RentalStatus::Completed => "completed",
+            RentalStatus::Cancelled => "cancelled",
+            RentalStatus::Disputed => "disputed",
+            RentalStatus::Expired => "expired",
+            RentalStatus::Overdue => "overdue",
+            RentalStatus::PendingPayment => "pending_payment",
+        }
+    }
+
+    /// States reachable from `self` in one step. `Declined`, `Completed`, `Cancelled` and `Expired`
+    /// are terminal.
+    pub fn allowed_transitions(&self) -> &'static [RentalStatus] {
+        use RentalStatus::*;
+        match self {
+            Requested => &[Confirmed, Declined, Cancelled, Expired],
+            PendingPayment => &[Confirmed, Cancelled],
+            Confirmed => &[Active, Cancelled],
+            Active => &[Returned, Disputed, Overdue],
+            Overdue => &[Returned, Disputed],
+            Returned => &[Completed, Disputed],
+            Disputed => &[Completed, Cancelled],
+            Declined | Completed | Cancelled | Expired => &[],
+        }
+    }
+
+    pub fn can_transition_to(&self, next: RentalStatus) -> bool {
+        self.allowed_transitions().contains(&next)
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.allowed_transitions().is_empty()
+    }
+}
+
+impl fmt::Display for RentalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RentalStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "requested" => Ok(RentalStatus::Requested),
+            "confirmed" => Ok(RentalStatus::Confirmed),
+            "declined" => Ok(RentalStatus::Declined),
+            "active" => Ok(RentalStatus::Active),
+            "returned" => Ok(RentalStatus::Returned),
+            "completed" => Ok(RentalStatus::Completed),
+            "cancelled" => Ok(RentalStatus::Cancelled),
+            "disputed" => Ok(RentalStatus::Disputed),
+            "expired" => Ok(RentalStatus::Expired),
+            "overdue" => Ok(RentalStatus::Overdue),
+            "pending_payment" => Ok(RentalStatus::PendingPayment),
+            other => Err(format!("Unknown rental status '{}'", other)),
+        }
+    }
+}
+
+/// The capacity in which a user acts on a rental.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RentalRole {
+    Renter,
+    Owner,
+    Admin,
+}
+
+impl RentalRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RentalRole::Renter => "renter",
+            RentalRole::Owner => "owner",
+            RentalRole::Admin => "admin",
+        }
+    }
+}
+
+/// Who may move a rental from `from` to `to`. Admins may perform any allowed transition and
+/// are the only ones who resolve disputes.
+pub fn permitted_roles(from: RentalStatus, to: RentalStatus) -> &'static [RentalRole] {
+    use RentalRole::*;
+    use RentalStatus::*;
+    match (from, to) {
+        (Requested, Confirmed) | (Requested, Declined) => &[Owner, Admin],
+        (Requested, Cancelled) | (Confirmed, Cancelled) => &[Renter, Owner, Admin],
+        // Handover is recorded by whichever party is present
+        (Confirmed, Active) => &[Renter, Owner, Admin],
+        // Only the owner can confirm the item came back, late or not
+        (Active, Returned) | (Overdue, Returned) => &[Owner, Admin],
+        (Returned, Completed) => &[Owner, Admin],
+        (Returned, Disputed) => &[Renter, Owner, Admin],
+        // While the item is out, a renter could use a dispute to stop the overdue scan and late
+        // fees and free the unit, so only the owner or an admin opens one
+        (Active, Disputed) | (Overdue, Disputed) => &[Owner, Admin],
+        (Disputed, Completed) | (Disputed, Cancelled) => &[Admin],
+        // Normally applied by the request expiry job, which acts without a user
+        (Requested, Expired) => &[Admin],
+        // Normally applied by the overdue job
+        (Active, Overdue) => &[Admin],
+        // Applied once the instant booking's charge settles
+        (PendingPayment, Confirmed) | (PendingPayment, Cancelled) => &[Admin],
+        _ => &[],
+    }
+}
+
+/// The role used for a transition: the first of the user's roles that is permitted.
+pub fn acting_role(roles: &[RentalRole], from: RentalStatus, to: RentalStatus) -> Option<RentalRole> {
+    let permitted = permitted_roles(from, to);
+    roles.iter().copied().find(|role| permitted.contains(role))
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RentalTransitionRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RentalHistoryEntry {
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub actor_id: Option<Uuid>,
+    pub actor_role: Option<String>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Appends a status change to the rental's history. `from` is `None` for the initial request.
+pub async fn record_rental_history(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+    from: Option<RentalStatus>,
+    to: RentalStatus,
+    actor_id: Option<Uuid>,
+    actor_role: Option<RentalRole>,
+    reason: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO rental_schema.rental_status_history
+        (rental_id, from_status, to_status, actor_id, actor_role, reason)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        rental_id,
+        from.map(|s| s.as_str()),
+        to.as_str(),
+        actor_id,
+        actor_role.map(|r| r.as_str()),
+        reason
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Moves a rental from `from` to `to` inside the caller's transaction, enforcing the state
+/// machine and role permissions, and records the change. Returns the new row version.
+pub async fn transition_rental(
+    tx: &mut Transaction<'_, Postgres>,
+    rental_id: Uuid,
+    from: RentalStatus,
+    to: RentalStatus,
+    actor_id: Uuid,
+    roles: &[RentalRole],
+    reason: Option<&str>,
+) -> Result<i32, (StatusCode, String)> {
+    if !from.can_transition_to(to) {
+        return Err((StatusCode::CONFLICT, format!("Cannot move rental from {} to {}", from, to)));
+    }
+
+    let role = acting_role(roles, from, to).ok_or_else(|| {
+        (StatusCode::FORBIDDEN, format!("Not allowed to move this rental from {} to {}", from, to))
+    })?;
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE rental_schema.rentals SET status = $3, updated_at = NOW()
+        WHERE rental_id = $1 AND status = $2
+        RETURNING version
+        "#,
+        rental_id,
+        from.as_str(),
+        to.as_str()
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to update rental status".to_string()))?;
+
+    let version = match updated {
+        Some(r) => r.version,
+        None => return Err((StatusCode::CONFLICT, "Rental status changed; reload it and retry".to_string())),
+    };
+
+    record_rental_history(tx, rental_id, Some(from), to, Some(actor_id), Some(role), reason)
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to record rental history".to_string()))?;
+
+    Ok(version)
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// Shared body of the transition endpoints.
+async fn apply_transition(
+    state: AppState,
+    headers: HeaderMap,
+    rental_id: Uuid,
+    to: RentalStatus,
+    req: RentalTransitionRequest,
+) -> Response {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let reason = req.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    if reason.is_some_and(|r| r.chars().count() > MAX_TRANSITION_REASON_LEN) {
+        return err(
+            StatusCode::BAD_REQUEST,
+            &format!("reason must be at most {} characters", MAX_TRANSITION_REASON_LEN),
+        );
+    }
+    if to == RentalStatus::Disputed && reason.is_none() {
+        return err(StatusCode::BAD_REQUEST, "A reason is required to open a dispute");
+    }
+
+    let is_admin = match has_any_role(&state.db, user_id, &[ROLE_ADMIN]).await {
+        Ok(is_admin) => is_admin,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update rental status"),
+    };
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update rental status"),
+    };
+
+    // Lock the row so concurrent transitions see each other's result
+    let rental = sqlx::query!(
+        r#"
+        SELECT r.status, r.renter_id, r.product_id, p.owner_id
+        FROM rental_schema.rentals r
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE r.rental_id = $1
+        FOR UPDATE OF r
+        "#,
+        rental_id
+    )
+    .fetch_optional(&mut tx)
+    .await;
+
+    let rental = match rental {
+        Ok(Some(r)) => r,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Rental not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update rental status"),
+    };
+
+    let mut roles = Vec::new();
+    if rental.renter_id == user_id {
+        roles.push(RentalRole::Renter);
+    }
+    if rental.owner_id == user_id {
+        roles.push(RentalRole::Owner);
+    }
+    if is_admin {
+        roles.push(RentalRole::Admin);
+    }
+    if roles.is_empty() {
+        return err(StatusCode::NOT_FOUND, "Rental not found");
+    }
+
+    let current = match rental.status.parse::<RentalStatus>() {
+        Ok(s) => s,
+        Err(message) => return err(StatusCode::INTERNAL_SERVER_ERROR, &message),
+    };
+
+    if current == RentalStatus::Disputed {
+        match has_unresolved_claim(&mut tx, rental_id).await {
+            Ok(false) => {}
+            Ok(true) => return err(StatusCode::CONFLICT, "Resolve the damage claim on this rental instead"),
+            Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update rental status"),
+        }
+    }
+
+    let version = match transition_rental(&mut tx, rental_id, current, to, user_id, &roles, reason).await {
+        Ok(v) => v,
+        Err((status, message)) => return err(status, &message),
+    };
+
+    // Refunds and penalties are recorded with the transition and paid out once it commits
+    let cancellation = match (to, acting_role(&roles, current, to)) {
+        (RentalStatus::Cancelled | RentalStatus::Declined, Some(role)) => {
+            let terms = match settle_cancellation(&mut tx, rental_id, current, role, reason).await {
+                Ok(terms) => terms,
+                Err((status, message)) => return err(status, &message),
+            };
+            if cancel_policy(&mut tx, rental_id).await.is_err() {
+                return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update rental status");
+            }
+            Some(terms)
+        }
+        _ => None,
+    };
+
+    // A completed rental no longer needs its deposit held
+    let deposit = if to == RentalStatus::Completed {
+        match settle_deposit(&mut tx, rental_id, 0, None, "deposit released").await {
+            Ok(settlement) => Some(settlement),
+            Err((status, message)) => return err(status, &message),
+        }
+    } else {
+        None
+    };
+
+    if tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update rental status");
+    }
+
+    // The settlement job retries anything the provider does not take now
+    if cancellation.is_some() || deposit.is_some() {
+        if let Err(e) = settle_rental_payments(&state.db, Some(rental_id)).await {
+            tracing::warn!(%rental_id, error = %e, "settling rental payments failed");
+        }
+    }
+
+    if to == RentalStatus::Confirmed {
+        spawn_record_event(&state.db, rental.product_id, ProductEvent::Booking);
+    }
+
+    with_etag(
+        ok(serde_json::json!({
+            "rental_id": rental_id,
+            "status": to,
+            "cancellation": cancellation,
+            "deposit": deposit,
+            "message": format!("Rental {}", to)
+        })),
+        version,
+    )
+}
+
+pub async fn confirm_rental(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+    req: Option<Json<RentalTransitionRequest>>,
+) -> impl IntoResponse {
+    let req = req.map(|Json(r)| r).unwrap_or_default();
+    apply_transition(state, headers, rental_id, RentalStatus::Confirmed, req).await
+}
+
+pub async fn decline_rental(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+    req: Option<Json<RentalTransitionRequest>>,
+) -> impl IntoResponse {
+    let req = req.map(|Json(r)| r).unwrap_or_default();
+    apply_transition(state, headers, rental_id, RentalStatus::Declined, req).await
+}
+
+pub async fn cancel_rental(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+    req: Option<Json<RentalTransitionRequest>>,
+) -> impl IntoResponse {
+    let req = req.map(|Json(r)| r).unwrap_or_default();
+    apply_transition(state, headers, rental_id, RentalStatus::Cancelled, req).await
+}
+
+pub async fn pickup_rental(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+    req: Option<Json<RentalTransitionRequest>>,
+) -> impl IntoResponse {
+    let req = req.map(|Json(r)| r).unwrap_or_default();
+    apply_transition(state, headers, rental_id, RentalStatus::Active, req).await
+}
+
+pub async fn return_rental(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+    req: Option<Json<RentalTransitionRequest>>,
+) -> impl IntoResponse {
+    let req = req.map(|Json(r)| r).unwrap_or_default();
+    apply_transition(state, headers, rental_id, RentalStatus::Returned, req).await
+}
+
+pub async fn complete_rental(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+    req: Option<Json<RentalTransitionRequest>>,
+) -> impl IntoResponse {
+    let req = req.map(|Json(r)| r).unwrap_or_default();
+    apply_transition(state, headers, rental_id, RentalStatus::Completed, req).await
+}
+
+pub async fn dispute_rental(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+    req: Option<Json<RentalTransitionRequest>>,
+) -> impl IntoResponse {
+    let req = req.map(|Json(r)| r).unwrap_or_default();
+    apply_transition(state, headers, rental_id, RentalStatus::Disputed, req).await
+}
+
+pub async fn get_rental_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(rental_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let is_admin = match has_any_role(&state.db, user_id, &[ROLE_ADMIN]).await {
+        Ok(is_admin) => is_admin,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch rental history"),
+    };
+
+    let rental = sqlx::query!(
+        r#"
+        SELECT r.renter_id, p.owner_id
+        FROM rental_schema.rentals r
+        JOIN product_schema.products p ON r.product_id = p.product_id
+        WHERE r.rental_id = $1
+        "#,
+        rental_id
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    match rental {
+        Ok(Some(r)) if r.renter_id == user_id || r.owner_id == user_id || is_admin => {}
+        Ok(_) => return err(StatusCode::NOT_FOUND, "Rental not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch rental history"),
+    }
+
+    let history = sqlx::query!(
+        r#"
+        SELECT from_status, to_status, actor_id, actor_role, reason, created_at
+        FROM rental_schema.rental_status_history
+        WHERE rental_id = $1
+        ORDER BY created_at, history_id
+        "#,
+        rental_id
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    match history {
+        Ok(rows) => ok(rows
+            .into_iter()
+            .map(|h| RentalHistoryEntry {
+                from_status: h.from_status,
+                to_status: h.to_status,
+                actor_id: h.actor_id,
+                actor_role: h.actor_role,
+                reason: h.reason,
+                created_at: h.created_at,
+            })
+            .collect::<Vec<_>>()),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch rental history"),
+    }
+}
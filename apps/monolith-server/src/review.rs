@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::state::{err, ok, AppState};
+use crate::pagination::{decode_cursor, page_size, CursorPage, DEFAULT_PAGE_SIZE};
 
 // Request/Response Models
 #[derive(Debug, Deserialize)]
@@ -37,8 +38,9 @@ pub struct ReviewFilters {
     pub rating: Option<i32>,
     pub review_type: Option<String>,
     pub status: Option<String>,
+    pub cursor: Option<String>,
     pub limit: Option<i64>,
-    pub offset: Option<i64>,
+    pub include_total: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -74,14 +76,6 @@ pub struct UserReviewResponse {
     pub reviewed_user_name: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct ReviewListResponse {
-    pub reviews: Vec<serde_json::Value>,
-    pub total: i64,
-    pub limit: i64,
-    pub offset: i64,
-}
-
 #[derive(Debug, Serialize)]
 pub struct ReviewStatsResponse {
     pub average_rating: f64,
@@ -357,8 +351,11 @@ pub async fn list_product_reviews(
     State(state): State<AppState>,
     Query(filters): Query<ReviewFilters>,
 ) -> impl axum::response::IntoResponse {
-    let limit = filters.limit.unwrap_or(20).min(100);
-    let offset = filters.offset.unwrap_or(0);
+    let limit = page_size(filters.limit, DEFAULT_PAGE_SIZE);
+    let cursor = match decode_cursor(filters.cursor.as_deref(), "product_reviews") {
+        Ok(c) => c,
+        Err(e) => return err(axum::http::StatusCode::BAD_REQUEST, &e.to_string()),
+    };
     let status = filters.status.unwrap_or_else(|| "active".to_string().to_string());
 
     let mut query = String::from(
@@ -391,15 +388,10 @@ pub async fn list_product_reviews(
         params.push(Box::new(rating));
     }
 
-    query.push_str(" ORDER BY r.created_at DESC LIMIT $");
+    query.push_str(" ORDER BY r.created_at DESC, r.id DESC LIMIT $");
     param_count += 1;
     query.push_str(&param_count.to_string());
-    params.push(Box::new(limit));
-
-    query.push_str(" OFFSET $");
-    param_count += 1;
-    query.push_str(&param_count.to_string());
-    params.push(Box::new(offset));
+    params.push(Box::new(limit + 1));
 
     // For now, we'll use a simpler approach with raw SQL
     let reviews = sqlx::query_as!(
@@ -411,33 +403,35 @@ pub async fn list_product_reviews(
         FROM review_schema.product_reviews r
         LEFT JOIN user_schema.user_profiles p ON r.reviewer_id = p.user_id
         WHERE r.status = $1
-        ORDER BY r.created_at DESC
-        LIMIT $2 OFFSET $3
+        AND ($3::timestamptz IS NULL OR (r.created_at, r.id) < ($3, $4))
+        ORDER BY r.created_at DESC, r.id DESC
+        LIMIT $2
         "#,
         status,
-        limit,
-        offset
+        limit + 1,
+        cursor.as_ref().map(|c| c.sort_key),
+        cursor.as_ref().map(|c| c.id)
     )
     .fetch_all(&state.db)
     .await
     .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?;
 
-    let total = sqlx::query!(
-        "SELECT COUNT(*) as count FROM review_schema.product_reviews WHERE status = $1",
-        status
-    )
-    .fetch_one(&state.db)
-    .await
-    .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?
-    .count;
-
-    let response = ReviewListResponse {
-        reviews: reviews.into_iter().map(|r| serde_json::to_value(r).unwrap()).collect(),
-        total,
-        limit,
-        offset,
+    let total = if filters.include_total.unwrap_or(false) {
+        sqlx::query!(
+            "SELECT COUNT(*) as count FROM review_schema.product_reviews WHERE status = $1",
+            status
+        )
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e))?
+        .count
+    } else {
+        None
     };
 
+    let response = CursorPage::from_rows(reviews, limit, "product_reviews", |r| (r.created_at, r.id))
+        .with_total(total);
+
     ok(response)
 }
 
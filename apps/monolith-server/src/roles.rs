@@ -0,0 +1,22 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub const ROLE_USER: &str = "user";
+pub const ROLE_ADMIN: &str = "admin";
+pub const ROLE_MODERATOR: &str = "moderator";
+/// Customer support staff; they resolve damage claims.
+pub const ROLE_SUPPORT: &str = "support";
+
+/// Roles live on `user_schema.users.role`; tokens only carry the user id.
+pub async fn has_any_role(db: &PgPool, user_id: Uuid, roles: &[&str]) -> Result<bool, sqlx::Error> {
+    let roles: Vec<String> = roles.iter().map(|r| r.to_string()).collect();
+    let row = sqlx::query!(
+        "SELECT user_id FROM user_schema.users WHERE user_id = $1 AND role = ANY($2)",
+        user_id,
+        &roles
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.is_some())
+}
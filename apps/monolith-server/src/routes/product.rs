@@ -1,6 +1,24 @@
 use axum::{routing::{get, post, put, delete}, Router};
 use crate::state::{ok, AppState};
-use crate::product::{create_product, get_product, list_products, update_product, delete_product, create_category, list_categories};
+use crate::product::{create_product, get_product, list_products, update_product, delete_product};
+use crate::category::{create_category, list_categories, get_category_tree, update_category, move_category, delete_category};
+use crate::listing::change_listing_status;
+use crate::analytics::get_product_stats;
+use crate::similar::get_similar_products;
+use crate::catalog::{import_products, get_import_job, export_products};
+use crate::inventory::{list_units, add_unit, retire_unit};
+use crate::calendar::{
+    serve_calendar_feed, get_product_calendar_feed, rotate_product_calendar_feed, get_owner_calendar_feed,
+    rotate_owner_calendar_feed, list_calendar_imports, add_calendar_import, sync_calendar_import,
+    delete_calendar_import, list_blackouts, add_blackout, delete_blackout,
+};
+use crate::saved_search::{create_saved_search, list_saved_searches, update_saved_search, delete_saved_search};
+use crate::promotion::{create_promotion, list_promotions, cancel_promotion, record_promotion_click};
+use crate::moderation::{list_moderation_cases, approve_moderation_case, reject_moderation_case};
+use crate::specs::get_category_spec_schema;
+use crate::pricing::{get_pricing_rules, upsert_pricing_rules};
+use crate::tag::{autocomplete_tags, merge_tags};
+use crate::wishlist::{add_to_wishlist, remove_from_wishlist, list_wishlist, get_wishlist_stats};
 
 async fn ping() -> impl axum::response::IntoResponse {
 	ok(serde_json::json!({ "service": "product", "status": "ok" }))
@@ -11,11 +29,56 @@ pub fn router() -> Router<AppState> {
 		.route("/ping", get(ping))
 		.route("/products", post(create_product))
 		.route("/products", get(list_products))
+		.route("/import", post(import_products))
+		.route("/import/:job_id", get(get_import_job))
+		.route("/export", get(export_products))
 		.route("/products/:product_id", get(get_product))
 		.route("/products/:product_id", put(update_product))
 		.route("/products/:product_id", delete(delete_product))
+		.route("/products/:product_id/similar", get(get_similar_products))
+		.route("/products/:product_id/status", post(change_listing_status))
+		.route("/products/:product_id/stats", get(get_product_stats))
+		.route("/products/:product_id/units", get(list_units))
+		.route("/products/:product_id/units", post(add_unit))
+		.route("/products/:product_id/units/:unit_id", delete(retire_unit))
+		.route("/products/:product_id/blackouts", get(list_blackouts))
+		.route("/products/:product_id/blackouts", post(add_blackout))
+		.route("/products/:product_id/blackouts/:blackout_id", delete(delete_blackout))
+		.route("/products/:product_id/calendar-feed", get(get_product_calendar_feed))
+		.route("/products/:product_id/calendar-feed/rotate", post(rotate_product_calendar_feed))
+		.route("/products/:product_id/calendar-imports", get(list_calendar_imports))
+		.route("/products/:product_id/calendar-imports", post(add_calendar_import))
+		.route("/products/:product_id/calendar-imports/:import_id", delete(delete_calendar_import))
+		.route("/products/:product_id/calendar-imports/:import_id/sync", post(sync_calendar_import))
+		.route("/calendar-feed", get(get_owner_calendar_feed))
+		.route("/calendar-feed/rotate", post(rotate_owner_calendar_feed))
+		.route("/calendar/:token", get(serve_calendar_feed))
+		.route("/products/:product_id/promotions", post(create_promotion))
+		.route("/promotions", get(list_promotions))
+		.route("/promotions/:promotion_id/cancel", post(cancel_promotion))
+		.route("/promotions/:promotion_id/click", post(record_promotion_click))
+		.route("/products/:product_id/pricing", get(get_pricing_rules))
+		.route("/products/:product_id/pricing", put(upsert_pricing_rules))
 		.route("/categories", post(create_category))
 		.route("/categories", get(list_categories))
+		.route("/categories/tree", get(get_category_tree))
+		.route("/categories/:category_id", put(update_category))
+		.route("/categories/:category_id", delete(delete_category))
+		.route("/categories/:category_id/move", post(move_category))
+		.route("/categories/:category_id/spec-schema", get(get_category_spec_schema))
+		.route("/moderation/cases", get(list_moderation_cases))
+		.route("/moderation/cases/:case_id/approve", post(approve_moderation_case))
+		.route("/moderation/cases/:case_id/reject", post(reject_moderation_case))
+		.route("/saved-searches", post(create_saved_search))
+		.route("/saved-searches", get(list_saved_searches))
+		.route("/saved-searches/:search_id", put(update_saved_search))
+		.route("/saved-searches/:search_id", delete(delete_saved_search))
+		.route("/tags", get(autocomplete_tags))
+		.route("/tags/merge", post(merge_tags))
+		.route("/wishlist", post(add_to_wishlist))
+		.route("/wishlist", get(list_wishlist))
+		.route("/wishlist/stats", get(get_wishlist_stats))
+		.route("/wishlist/:product_id", delete(remove_from_wishlist))
 }
 
 
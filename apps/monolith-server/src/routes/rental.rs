@@ -1,6 +1,22 @@
-use axum::{routing::{get, post, put}, Router};
+use axum::{extract::DefaultBodyLimit, routing::{get, post, put}, Router};
 use crate::state::{ok, AppState};
 use crate::rental::{create_rental, get_rental, list_rentals, update_rental, check_availability};
+use crate::rental_status::{
+	confirm_rental, decline_rental, cancel_rental, pickup_rental, return_rental, complete_rental, dispute_rental,
+	get_rental_history,
+};
+use crate::cancellation::preview_cancellation;
+use crate::rental_modification::{accept_modification, decline_modification, list_modifications, propose_modification};
+use crate::condition_report::{
+	get_condition_photo, get_condition_reports, put_condition_report, sign_condition_report, upload_condition_photo,
+	MAX_PHOTO_BYTES,
+};
+use crate::damage_claim::{
+	get_damage_claim, list_damage_claims, open_damage_claim, resolve_damage_claim, respond_to_damage_claim,
+};
+use crate::overdue::list_late_fees;
+use crate::insurance::{get_rental_insurance, quote_insurance};
+use crate::pricing::quote_rental;
 
 async fn ping() -> impl axum::response::IntoResponse {
 	ok(serde_json::json!({ "service": "rental", "status": "ok" }))
@@ -13,7 +29,38 @@ pub fn router() -> Router<AppState> {
 		.route("/rentals", get(list_rentals))
 		.route("/rentals/:rental_id", get(get_rental))
 		.route("/rentals/:rental_id", put(update_rental))
+		.route("/rentals/:rental_id/confirm", post(confirm_rental))
+		.route("/rentals/:rental_id/decline", post(decline_rental))
+		.route("/rentals/:rental_id/cancel", post(cancel_rental))
+		.route("/rentals/:rental_id/cancellation-quote", get(preview_cancellation))
+		.route("/rentals/:rental_id/pickup", post(pickup_rental))
+		.route("/rentals/:rental_id/return", post(return_rental))
+		.route("/rentals/:rental_id/complete", post(complete_rental))
+		.route("/rentals/:rental_id/dispute", post(dispute_rental))
+		.route("/rentals/:rental_id/history", get(get_rental_history))
+		.route("/rentals/:rental_id/modifications", post(propose_modification))
+		.route("/rentals/:rental_id/modifications", get(list_modifications))
+		.route("/rentals/:rental_id/modifications/:modification_id/accept", post(accept_modification))
+		.route("/rentals/:rental_id/modifications/:modification_id/decline", post(decline_modification))
+		.route("/rentals/:rental_id/condition-reports", get(get_condition_reports))
+		.route("/rentals/:rental_id/condition-reports/photos/:photo_id", get(get_condition_photo))
+		.route("/rentals/:rental_id/condition-reports/:stage", put(put_condition_report))
+		.route(
+			"/rentals/:rental_id/condition-reports/:stage/photos",
+			// Room for the multipart framing and caption around a full-size photo
+			post(upload_condition_photo).layer(DefaultBodyLimit::max(MAX_PHOTO_BYTES + 64 * 1024)),
+		)
+		.route("/rentals/:rental_id/condition-reports/:stage/sign", post(sign_condition_report))
+		.route("/rentals/:rental_id/damage-claim", post(open_damage_claim))
+		.route("/rentals/:rental_id/damage-claim", get(get_damage_claim))
+		.route("/rentals/:rental_id/damage-claim/respond", post(respond_to_damage_claim))
+		.route("/rentals/:rental_id/damage-claim/resolve", post(resolve_damage_claim))
+		.route("/rentals/:rental_id/late-fees", get(list_late_fees))
+		.route("/rentals/:rental_id/insurance", get(get_rental_insurance))
+		.route("/damage-claims", get(list_damage_claims))
 		.route("/availability", post(check_availability))
+		.route("/quote", post(quote_rental))
+		.route("/quote/insurance", post(quote_insurance))
 }
 
 
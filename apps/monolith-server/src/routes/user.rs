@@ -1,12 +1,16 @@
-use axum::{routing::get, Router};
+use axum::{routing::{get, post}, Router};
 use crate::state::{ok, AppState};
+use crate::notification::{list_notifications, mark_notification_read};
 
 async fn ping() -> impl axum::response::IntoResponse {
 	ok(serde_json::json!({ "service": "user", "status": "ok" }))
 }
 
 pub fn router() -> Router<AppState> {
-	Router::new().route("/ping", get(ping))
+	Router::new()
+		.route("/ping", get(ping))
+		.route("/notifications", get(list_notifications))
+		.route("/notifications/:notification_id/read", post(mark_notification_read))
 }
 
 
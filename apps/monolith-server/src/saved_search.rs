@@ -0,0 +1,568 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::product::{geo_filter, ProductFilters, DEFAULT_RADIUS_KM};
+use crate::specs::{check_spec_filters, effective_spec_schema};
+use crate::notification::{send_notification, NewNotification, KIND_SAVED_SEARCH_DIGEST, KIND_SAVED_SEARCH_MATCH};
+
+pub const MAX_SAVED_SEARCHES_PER_USER: i64 = 20;
+/// How far back each matcher pass looks for activated listings. Matches are de-duplicated,
+/// so overlapping passes are harmless and a restart does not lose alerts.
+pub const MATCH_LOOKBACK_HOURS: i64 = 24;
+pub const MATCHER_INTERVAL_SECS: u64 = 60;
+pub const MAX_DIGEST_ITEMS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertFrequency {
+    Instant,
+    Daily,
+}
+
+impl AlertFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertFrequency::Instant => "instant",
+            AlertFrequency::Daily => "daily",
+        }
+    }
+}
+
+impl fmt::Display for AlertFrequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AlertFrequency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "instant" => Ok(AlertFrequency::Instant),
+            "daily" => Ok(AlertFrequency::Daily),
+            other => Err(format!("Unknown alert frequency: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSavedSearchRequest {
+    pub name: String,
+    pub filters: ProductFilters,
+    pub frequency: Option<AlertFrequency>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateSavedSearchRequest {
+    pub name: Option<String>,
+    pub frequency: Option<AlertFrequency>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedSearchResponse {
+    pub search_id: Uuid,
+    pub name: String,
+    pub filters: serde_json::Value,
+    pub frequency: String,
+    pub last_digest_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Great-circle distance in kilometres.
+pub fn haversine_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lng1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lng2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lng2 - lng1) / 2.0).sin().powi(2);
+    2.0 * 6371.0 * a.sqrt().asin()
+}
+
+/// Reads `lat`/`lng` from a listing address.
+pub fn address_location(address: Option<&serde_json::Value>) -> Option<(f64, f64)> {
+    let address = address?;
+    Some((address.get("lat")?.as_f64()?, address.get("lng")?.as_f64()?))
+}
+
+/// A daily digest goes out once a day at most.
+pub fn digest_due(last_digest_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    match last_digest_at {
+        Some(last) => now - last >= Duration::hours(24),
+        None => true,
+    }
+}
+
+/// Builds the alert for one or more new listings matching a saved search.
+pub fn alert_notification(
+    user_id: Uuid,
+    search_id: Uuid,
+    search_name: &str,
+    listings: &[(Uuid, String)],
+    frequency: AlertFrequency,
+) -> NewNotification {
+    let (kind, title) = match frequency {
+        AlertFrequency::Instant => (KIND_SAVED_SEARCH_MATCH, format!("New listing for \"{}\"", search_name)),
+        AlertFrequency::Daily => (
+            KIND_SAVED_SEARCH_DIGEST,
+            format!("{} new listings for \"{}\"", listings.len(), search_name),
+        ),
+    };
+
+    let mut names: Vec<&str> = listings.iter().take(MAX_DIGEST_ITEMS).map(|(_, name)| name.as_str()).collect();
+    if listings.len() > MAX_DIGEST_ITEMS {
+        names.push("…");
+    }
+
+    NewNotification {
+        user_id,
+        kind: kind.to_string(),
+        title,
+        body: names.join(", "),
+        data: serde_json::json!({
+            "search_id": search_id,
+            "product_ids": listings.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+        }),
+    }
+}
+
+/// Cursor and paging fields are meaningless for a stored search.
+fn stored_filters(mut filters: ProductFilters) -> ProductFilters {
+    filters.cursor = None;
+    filters.limit = None;
+    filters.include_total = None;
+    filters
+}
+
+/// One matcher pass: records matches for recently activated listings, sends instant alerts
+/// and any digests that are due.
+pub async fn run_matcher_pass(db: &PgPool) -> Result<(), sqlx::Error> {
+    // Selected once per pass; most passes find nothing new and go straight to the digests
+    let candidates: Vec<Uuid> = sqlx::query_scalar!(
+        r#"
+        SELECT product_id FROM product_schema.products
+        WHERE status = 'active' AND activated_at >= NOW() - make_interval(hours => $1::int)
+        "#,
+        MATCH_LOOKBACK_HOURS as i32
+    )
+    .fetch_all(db)
+    .await?;
+
+    if !candidates.is_empty() {
+        record_matches(db, &candidates).await?;
+    }
+
+    send_due_digests(db).await
+}
+
+/// Matches the candidates against every saved search with `listing_matches_filters`, the
+/// predicates `list_products` applies, and alerts instant searches about the new matches.
+async fn record_matches(db: &PgPool, candidates: &[Uuid]) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    // Only listings that went live after the search was saved, and never the user's own
+    let fresh = sqlx::query!(
+        r#"
+        WITH searches AS MATERIALIZED (
+            -- Each search's category tree is walked once, not once per candidate
+            SELECT search_id, user_id, created_at, filters,
+                   product_schema.category_subtree((filters->>'category_id')::uuid) AS category_ids
+            FROM product_schema.saved_searches
+        ),
+        inserted AS (
+            INSERT INTO product_schema.saved_search_matches (search_id, product_id, activated_at)
+            SELECT s.search_id, p.product_id, p.activated_at
+            FROM searches s
+            JOIN product_schema.products p ON p.product_id = ANY($1)
+            WHERE p.owner_id <> s.user_id
+            AND p.activated_at > s.created_at
+            AND product_schema.listing_matches_filters(p, s.filters, s.category_ids, $2)
+            ON CONFLICT DO NOTHING
+            RETURNING search_id, product_id, activated_at
+        )
+        SELECT i.search_id, i.product_id, i.activated_at, s.user_id, s.name as search_name,
+               p.name as product_name
+        FROM inserted i
+        JOIN product_schema.saved_searches s ON s.search_id = i.search_id
+        JOIN product_schema.products p ON p.product_id = i.product_id
+        WHERE s.frequency = 'instant'
+        "#,
+        candidates,
+        DEFAULT_RADIUS_KM
+    )
+    .fetch_all(&mut tx)
+    .await?;
+
+    for m in fresh {
+        let listing = [(m.product_id, m.product_name)];
+        let alert = alert_notification(m.user_id, m.search_id, &m.search_name, &listing, AlertFrequency::Instant);
+        send_notification(&mut tx, &alert).await?;
+        sqlx::query!(
+            r#"
+            UPDATE product_schema.saved_search_matches SET notified_at = NOW()
+            WHERE search_id = $1 AND product_id = $2 AND activated_at = $3
+            "#,
+            m.search_id,
+            m.product_id,
+            m.activated_at
+        )
+        .execute(&mut tx)
+        .await?;
+    }
+
+    tx.commit().await
+}
+
+/// Sends every pending match of a search in one digest and marks them notified.
+async fn flush_pending_matches(
+    tx: &mut Transaction<'_, Postgres>,
+    search_id: Uuid,
+    user_id: Uuid,
+    search_name: &str,
+) -> Result<(), sqlx::Error> {
+    let pending = sqlx::query!(
+        r#"
+        UPDATE product_schema.saved_search_matches m SET notified_at = NOW()
+        FROM product_schema.products p
+        WHERE m.search_id = $1 AND m.notified_at IS NULL AND p.product_id = m.product_id
+        RETURNING m.product_id, p.name
+        "#,
+        search_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    if !pending.is_empty() {
+        let listings: Vec<(Uuid, String)> = pending.into_iter().map(|p| (p.product_id, p.name)).collect();
+        let digest = alert_notification(user_id, search_id, search_name, &listings, AlertFrequency::Daily);
+        send_notification(tx, &digest).await?;
+    }
+
+    Ok(())
+}
+
+/// Daily searches get their digest once it is due. Instant searches only have pending
+/// matches left when they were switched from daily mid-pass; those go out right away.
+async fn send_due_digests(db: &PgPool) -> Result<(), sqlx::Error> {
+    let searches = sqlx::query!(
+        r#"
+        SELECT s.search_id, s.user_id, s.name, s.frequency, s.last_digest_at
+        FROM product_schema.saved_searches s
+        WHERE EXISTS (
+            SELECT 1 FROM product_schema.saved_search_matches m
+            WHERE m.search_id = s.search_id AND m.notified_at IS NULL
+        )
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let now = Utc::now();
+    for search in searches {
+        let instant = search.frequency == AlertFrequency::Instant.as_str();
+        if !instant && !digest_due(search.last_digest_at, now) {
+            continue;
+        }
+
+        let mut tx = db.begin().await?;
+        flush_pending_matches(&mut tx, search.search_id, search.user_id, &search.name).await?;
+
+        if !instant {
+            sqlx::query!(
+                "UPDATE product_schema.saved_searches SET last_digest_at = $2 WHERE search_id = $1",
+                search.search_id,
+                now
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+    }
+
+    Ok(())
+}
+
+/// Background loop started by the server; a failed pass is retried on the next tick.
+pub async fn run_matcher(db: PgPool) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(MATCHER_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        if let Err(e) = run_matcher_pass(&db).await {
+            tracing::warn!(error = %e, "saved search matcher pass failed");
+        }
+    }
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+pub async fn create_saved_search(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateSavedSearchRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let name = req.name.trim();
+    if name.is_empty() || name.chars().count() > 100 {
+        return err(StatusCode::BAD_REQUEST, "Name must be 1-100 characters");
+    }
+
+    let filters = stored_filters(req.filters);
+
+    if let Err(message) = geo_filter(&filters) {
+        return err(StatusCode::BAD_REQUEST, &message);
+    }
+
+    // Spec filters follow the same rules as on list_products
+    if let Some(raw) = filters.specs.as_deref() {
+        let category_id = match filters.category_id {
+            Some(c) => c,
+            None => return err(StatusCode::BAD_REQUEST, "specs filter requires category_id"),
+        };
+        let parsed: serde_json::Value = match serde_json::from_str(raw) {
+            Ok(v) => v,
+            Err(_) => return err(StatusCode::BAD_REQUEST, "specs must be a JSON object"),
+        };
+        let schema = match effective_spec_schema(&state.db, category_id).await {
+            Ok(s) => s.map(|(_, schema)| schema),
+            Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load spec schema"),
+        };
+        if let Err(message) = check_spec_filters(schema.as_ref(), &parsed) {
+            return err(StatusCode::BAD_REQUEST, &message);
+        }
+    }
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save search"),
+    };
+
+    // Locking the user row keeps concurrent saves from both passing the limit
+    if sqlx::query!("SELECT user_id FROM user_schema.users WHERE user_id = $1 FOR UPDATE", user_id)
+        .fetch_one(&mut tx)
+        .await
+        .is_err()
+    {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save search");
+    }
+
+    let existing = sqlx::query!(
+        r#"SELECT COUNT(*) as "count!" FROM product_schema.saved_searches WHERE user_id = $1"#,
+        user_id
+    )
+    .fetch_one(&mut tx)
+    .await;
+
+    match existing {
+        Ok(e) if e.count >= MAX_SAVED_SEARCHES_PER_USER => {
+            let message = format!("You can have at most {} saved searches", MAX_SAVED_SEARCHES_PER_USER);
+            return err(StatusCode::BAD_REQUEST, &message);
+        }
+        Ok(_) => {}
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save search"),
+    }
+
+    let frequency = req.frequency.unwrap_or(AlertFrequency::Instant);
+    let filters_json = serde_json::to_value(&filters).unwrap_or_default();
+
+    let search = sqlx::query!(
+        r#"
+        INSERT INTO product_schema.saved_searches (user_id, name, filters, frequency)
+        VALUES ($1, $2, $3, $4)
+        RETURNING search_id
+        "#,
+        user_id,
+        name,
+        filters_json,
+        frequency.as_str()
+    )
+    .fetch_one(&mut tx)
+    .await;
+
+    let search = match search {
+        Ok(s) => s,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save search"),
+    };
+
+    match tx.commit().await {
+        Ok(_) => ok(serde_json::json!({
+            "search_id": search.search_id,
+            "message": "Search saved successfully"
+        })),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save search"),
+    }
+}
+
+pub async fn list_saved_searches(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let searches = sqlx::query!(
+        r#"
+        SELECT search_id, name, filters, frequency, last_digest_at, created_at
+        FROM product_schema.saved_searches
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    let searches = match searches {
+        Ok(s) => s,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch saved searches"),
+    };
+
+    let response: Vec<SavedSearchResponse> = searches
+        .into_iter()
+        .map(|s| SavedSearchResponse {
+            search_id: s.search_id,
+            name: s.name,
+            filters: s.filters,
+            frequency: s.frequency,
+            last_digest_at: s.last_digest_at,
+            created_at: s.created_at,
+        })
+        .collect();
+
+    ok(response)
+}
+
+pub async fn update_saved_search(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(search_id): Path<Uuid>,
+    Json(req): Json<UpdateSavedSearchRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let name = req.name.as_deref().map(str::trim);
+    if name.is_some_and(|n| n.is_empty() || n.chars().count() > 100) {
+        return err(StatusCode::BAD_REQUEST, "Name must be 1-100 characters");
+    }
+
+    let failed = || err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to update saved search");
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return failed(),
+    };
+
+    let current = sqlx::query!(
+        "SELECT frequency FROM product_schema.saved_searches WHERE search_id = $1 AND user_id = $2 FOR UPDATE",
+        search_id,
+        user_id
+    )
+    .fetch_optional(&mut tx)
+    .await;
+
+    let current = match current {
+        Ok(Some(c)) => c,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Saved search not found"),
+        Err(_) => return failed(),
+    };
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE product_schema.saved_searches
+        SET name = COALESCE($2, name), frequency = COALESCE($3, frequency)
+        WHERE search_id = $1
+        RETURNING name, frequency
+        "#,
+        search_id,
+        name,
+        req.frequency.map(|f| f.as_str())
+    )
+    .fetch_one(&mut tx)
+    .await;
+
+    let updated = match updated {
+        Ok(u) => u,
+        Err(_) => return failed(),
+    };
+
+    // Matches held back for the digest go out now; instant alerts only cover new listings
+    if current.frequency == AlertFrequency::Daily.as_str()
+        && updated.frequency == AlertFrequency::Instant.as_str()
+        && flush_pending_matches(&mut tx, search_id, user_id, &updated.name).await.is_err()
+    {
+        return failed();
+    }
+
+    match tx.commit().await {
+        Ok(_) => ok(serde_json::json!({ "message": "Saved search updated" })),
+        Err(_) => failed(),
+    }
+}
+
+pub async fn delete_saved_search(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(search_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let result = sqlx::query!(
+        "DELETE FROM product_schema.saved_searches WHERE search_id = $1 AND user_id = $2",
+        search_id,
+        user_id
+    )
+    .execute(&state.db)
+    .await;
+
+    match result {
+        Ok(r) if r.rows_affected() > 0 => ok(serde_json::json!({ "message": "Saved search deleted" })),
+        Ok(_) => err(StatusCode::NOT_FOUND, "Saved search not found"),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete saved search"),
+    }
+}
@@ -0,0 +1,367 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::saved_search::{address_location, haversine_km};
+
+pub const DEFAULT_SIMILAR_LIMIT: i64 = 10;
+/// Number of neighbours stored per product; requests can ask for up to this many.
+pub const MAX_SIMILAR: i64 = 20;
+/// Upper bound on listings scored per refresh.
+pub const MAX_SIMILAR_CANDIDATES: i64 = 500;
+pub const SIMILAR_CACHE_TTL_HOURS: i64 = 6;
+/// Listings further apart than this get no distance credit.
+pub const SIMILAR_MAX_DISTANCE_KM: f64 = 100.0;
+/// Renters shared with the target at which the co-rental signal saturates.
+pub const CO_RENTAL_SATURATION: i64 = 3;
+
+/// Weights of the individual signals; they sum to 1 so scores stay in `0..=1`.
+pub const WEIGHT_CATEGORY: f64 = 0.30;
+pub const WEIGHT_TAGS: f64 = 0.20;
+pub const WEIGHT_TEXT: f64 = 0.15;
+pub const WEIGHT_PRICE: f64 = 0.15;
+pub const WEIGHT_DISTANCE: f64 = 0.10;
+pub const WEIGHT_CO_RENTAL: f64 = 0.10;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimilarFilters {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimilarProductResponse {
+    pub product_id: Uuid,
+    pub name: String,
+    pub daily_price: f64,
+    pub score: f64,
+}
+
+/// The parts of a listing used for similarity. `category_chain` runs from the listing's own
+/// category up to the root.
+#[derive(Debug, Clone)]
+pub struct ProductFeatures {
+    pub product_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub category_chain: Vec<Uuid>,
+    pub tags: Vec<String>,
+    pub daily_price: f64,
+    pub location: Option<(f64, f64)>,
+}
+
+/// 1 for the same category, halving for each level up to the closest shared ancestor
+/// (siblings 0.5, cousins 0.25); 0 when the trees are unrelated.
+pub fn category_proximity(a: &[Uuid], b: &[Uuid]) -> f64 {
+    a.iter()
+        .enumerate()
+        .filter_map(|(i, category)| b.iter().position(|c| c == category).map(|j| i.max(j)))
+        .min()
+        .map_or(0.0, |levels| 0.5f64.powi(levels as i32))
+}
+
+pub fn jaccard<T: Eq + std::hash::Hash>(a: &HashSet<T>, b: &HashSet<T>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Lowercased words of three or more letters or digits.
+pub fn text_tokens(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// 1 for equal prices, falling to 0 when one price is four times the other.
+pub fn price_similarity(a: f64, b: f64) -> f64 {
+    if a <= 0.0 || b <= 0.0 {
+        return if a == b { 1.0 } else { 0.0 };
+    }
+    (1.0 - (a / b).ln().abs() / 4f64.ln()).max(0.0)
+}
+
+/// Linear falloff to 0 at `SIMILAR_MAX_DISTANCE_KM`; no credit without both locations.
+pub fn distance_similarity(a: Option<(f64, f64)>, b: Option<(f64, f64)>) -> f64 {
+    match (a, b) {
+        (Some(a), Some(b)) => (1.0 - haversine_km(a, b) / SIMILAR_MAX_DISTANCE_KM).max(0.0),
+        _ => 0.0,
+    }
+}
+
+pub fn co_rental_signal(shared_renters: i64) -> f64 {
+    (shared_renters as f64 / CO_RENTAL_SATURATION as f64).min(1.0)
+}
+
+/// Weighted similarity of `candidate` to `target`, in `0..=1`.
+pub fn similarity_score(target: &ProductFeatures, candidate: &ProductFeatures, shared_renters: i64) -> f64 {
+    let tags = |p: &ProductFeatures| p.tags.iter().map(|t| t.to_lowercase()).collect::<HashSet<_>>();
+    let text = |p: &ProductFeatures| {
+        text_tokens(&format!("{} {}", p.name, p.description.as_deref().unwrap_or_default()))
+    };
+
+    WEIGHT_CATEGORY * category_proximity(&target.category_chain, &candidate.category_chain)
+        + WEIGHT_TAGS * jaccard(&tags(target), &tags(candidate))
+        + WEIGHT_TEXT * jaccard(&text(target), &text(candidate))
+        + WEIGHT_PRICE * price_similarity(target.daily_price, candidate.daily_price)
+        + WEIGHT_DISTANCE * distance_similarity(target.location, candidate.location)
+        + WEIGHT_CO_RENTAL * co_rental_signal(shared_renters)
+}
+
+/// Scores and orders candidates, best first, keeping at most `limit` with a positive score.
+pub fn rank_similar(
+    target: &ProductFeatures,
+    candidates: &[ProductFeatures],
+    co_rentals: &HashMap<Uuid, i64>,
+    limit: usize,
+) -> Vec<(Uuid, f64)> {
+    let mut scored: Vec<(Uuid, f64)> = candidates
+        .iter()
+        .filter(|c| c.product_id != target.product_id)
+        .map(|c| {
+            let shared = co_rentals.get(&c.product_id).copied().unwrap_or(0);
+            (c.product_id, similarity_score(target, c, shared))
+        })
+        .filter(|(_, score)| *score > 0.0)
+        .collect();
+
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    scored.truncate(limit);
+    scored
+}
+
+async fn load_features(db: &PgPool, product_ids: &[Uuid]) -> Result<Vec<ProductFeatures>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        WITH RECURSIVE chain AS (
+            SELECT p.product_id, p.category_id, 0 AS depth
+            FROM product_schema.products p
+            WHERE p.product_id = ANY($1)
+            UNION ALL
+            SELECT ch.product_id, c.parent_category_id, ch.depth + 1
+            FROM chain ch
+            JOIN product_schema.categories c ON c.category_id = ch.category_id
+            WHERE c.parent_category_id IS NOT NULL
+        )
+        SELECT p.product_id, p.name, p.description, p.daily_price, p.address,
+               ARRAY(
+                   SELECT ch.category_id FROM chain ch
+                   WHERE ch.product_id = p.product_id AND ch.category_id IS NOT NULL
+                   ORDER BY ch.depth
+               ) as "category_chain!",
+               ARRAY(
+                   SELECT t.name FROM product_schema.product_tags pt
+                   JOIN product_schema.tags t ON t.tag_id = pt.tag_id
+                   WHERE pt.product_id = p.product_id
+               ) as "tags!"
+        FROM product_schema.products p
+        WHERE p.product_id = ANY($1)
+        "#,
+        product_ids
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| ProductFeatures {
+            product_id: r.product_id,
+            name: r.name,
+            description: r.description,
+            category_chain: r.category_chain,
+            tags: r.tags,
+            daily_price: r.daily_price,
+            location: address_location(r.address.as_ref()),
+        })
+        .collect())
+}
+
+/// Recomputes and stores the neighbours of one listing. Candidates are active listings in the
+/// same category tree, sharing a tag, or rented by the same renters.
+pub async fn refresh_similar_products(db: &PgPool, product_id: Uuid) -> Result<(), sqlx::Error> {
+    let candidate_ids: Vec<Uuid> = sqlx::query_scalar!(
+        r#"
+        WITH RECURSIVE target_chain AS (
+            SELECT c.category_id, c.parent_category_id
+            FROM product_schema.categories c
+            JOIN product_schema.products p ON p.category_id = c.category_id
+            WHERE p.product_id = $1
+            UNION
+            SELECT c.category_id, c.parent_category_id
+            FROM product_schema.categories c
+            JOIN target_chain t ON c.category_id = t.parent_category_id
+        ),
+        root_tree AS (
+            SELECT category_id FROM target_chain WHERE parent_category_id IS NULL
+            UNION
+            SELECT c.category_id FROM product_schema.categories c
+            JOIN root_tree r ON c.parent_category_id = r.category_id
+        )
+        SELECT p.product_id
+        FROM product_schema.products p
+        WHERE p.status = 'active' AND p.product_id <> $1
+        AND (
+            p.category_id IN (SELECT category_id FROM root_tree)
+            OR EXISTS (
+                SELECT 1 FROM product_schema.product_tags a
+                JOIN product_schema.product_tags b ON b.tag_id = a.tag_id
+                WHERE a.product_id = $1 AND b.product_id = p.product_id
+            )
+            OR EXISTS (
+                SELECT 1 FROM rental_schema.rentals r1
+                JOIN rental_schema.rentals r2 ON r2.renter_id = r1.renter_id
+                WHERE r1.product_id = $1 AND r2.product_id = p.product_id
+                AND r1.status IN ('confirmed', 'active', 'completed')
+                AND r2.status IN ('confirmed', 'active', 'completed')
+            )
+        )
+        ORDER BY p.created_at DESC
+        LIMIT $2
+        "#,
+        product_id,
+        MAX_SIMILAR_CANDIDATES
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut ids = candidate_ids.clone();
+    ids.push(product_id);
+    let features = load_features(db, &ids).await?;
+
+    let co_rentals: HashMap<Uuid, i64> = sqlx::query!(
+        r#"
+        SELECT r2.product_id as "product_id!", COUNT(DISTINCT r2.renter_id) as "renters!"
+        FROM rental_schema.rentals r1
+        JOIN rental_schema.rentals r2 ON r2.renter_id = r1.renter_id AND r2.product_id <> r1.product_id
+        WHERE r1.product_id = $1 AND r2.product_id = ANY($2)
+        AND r1.status IN ('confirmed', 'active', 'completed')
+        AND r2.status IN ('confirmed', 'active', 'completed')
+        GROUP BY r2.product_id
+        "#,
+        product_id,
+        &candidate_ids
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .map(|r| (r.product_id, r.renters))
+    .collect();
+
+    let ranked = match features.iter().find(|f| f.product_id == product_id) {
+        Some(target) => rank_similar(target, &features, &co_rentals, MAX_SIMILAR as usize),
+        None => Vec::new(),
+    };
+
+    let mut tx = db.begin().await?;
+
+    // Two requests can find the same stale cache; the second one waits here instead of
+    // racing the first one's DELETE and INSERTs
+    sqlx::query!(
+        "SELECT 1 as \"locked!\" FROM pg_advisory_xact_lock(hashtextextended($1::text, 0))",
+        product_id.to_string()
+    )
+    .execute(&mut tx)
+    .await?;
+
+    sqlx::query!("DELETE FROM product_schema.similar_products WHERE product_id = $1", product_id)
+        .execute(&mut tx)
+        .await?;
+
+    for (similar_product_id, score) in &ranked {
+        sqlx::query!(
+            r#"
+            INSERT INTO product_schema.similar_products (product_id, similar_product_id, score)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#,
+            product_id,
+            similar_product_id,
+            score
+        )
+        .execute(&mut tx)
+        .await?;
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO product_schema.similar_product_refreshes (product_id, computed_at)
+        VALUES ($1, NOW())
+        ON CONFLICT (product_id) DO UPDATE SET computed_at = NOW()
+        "#,
+        product_id
+    )
+    .execute(&mut tx)
+    .await?;
+
+    tx.commit().await
+}
+
+pub async fn get_similar_products(
+    State(state): State<AppState>,
+    Path(product_id): Path<Uuid>,
+    Query(filters): Query<SimilarFilters>,
+) -> impl IntoResponse {
+    let limit = filters.limit.unwrap_or(DEFAULT_SIMILAR_LIMIT).clamp(1, MAX_SIMILAR);
+
+    let product = sqlx::query!(
+        r#"
+        SELECT p.status,
+               (r.computed_at > NOW() - make_interval(hours => $2::int)) as "fresh"
+        FROM product_schema.products p
+        LEFT JOIN product_schema.similar_product_refreshes r ON r.product_id = p.product_id
+        WHERE p.product_id = $1
+        "#,
+        product_id,
+        SIMILAR_CACHE_TTL_HOURS as i32
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    let product = match product {
+        Ok(Some(p)) if p.status == "active" => p,
+        Ok(_) => return err(StatusCode::NOT_FOUND, "Product not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch product"),
+    };
+
+    // Neighbours are cached per product and recomputed on the first request after they expire
+    if !product.fresh.unwrap_or(false) && refresh_similar_products(&state.db, product_id).await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to compute similar products");
+    }
+
+    let similar = sqlx::query!(
+        r#"
+        SELECT p.product_id, p.name, p.daily_price, s.score
+        FROM product_schema.similar_products s
+        JOIN product_schema.products p ON p.product_id = s.similar_product_id
+        WHERE s.product_id = $1 AND p.status = 'active'
+        ORDER BY s.score DESC, p.product_id
+        LIMIT $2
+        "#,
+        product_id,
+        limit
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    match similar {
+        Ok(rows) => ok(rows
+            .into_iter()
+            .map(|r| SimilarProductResponse {
+                product_id: r.product_id,
+                name: r.name,
+                daily_price: r.daily_price,
+                score: r.score,
+            })
+            .collect::<Vec<_>>()),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch similar products"),
+    }
+}
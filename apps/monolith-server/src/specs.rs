@@ -0,0 +1,163 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use jsonschema::{error::ValidationErrorKind, JSONSchema};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+
+/// Custom keyword on a property schema marking it usable in `list_products` spec filters.
+pub const FILTERABLE_KEYWORD: &str = "x-filterable";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpecFieldError {
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CategorySpecSchemaResponse {
+    pub category_id: Uuid,
+    /// Category the effective schema was inherited from, if any.
+    pub source_category_id: Option<Uuid>,
+    pub spec_schema: Option<serde_json::Value>,
+    pub filterable_fields: Vec<String>,
+}
+
+/// Rejects documents that are not valid JSON Schema before they are stored on a category.
+pub fn check_spec_schema(schema: &serde_json::Value) -> Result<(), String> {
+    if !schema.is_object() {
+        return Err("spec_schema must be a JSON object".to_string());
+    }
+    JSONSchema::compile(schema).map(|_| ()).map_err(|e| format!("Invalid spec_schema: {}", e))
+}
+
+/// Validates product specifications, returning one error per offending field.
+pub fn validate_specifications(
+    schema: &serde_json::Value,
+    specifications: &serde_json::Value,
+) -> Result<(), Vec<SpecFieldError>> {
+    let compiled = match JSONSchema::compile(schema) {
+        Ok(c) => c,
+        Err(e) => {
+            return Err(vec![SpecFieldError {
+                field: String::new(),
+                message: format!("Category spec_schema is invalid: {}", e),
+            }])
+        }
+    };
+
+    let errors: Vec<SpecFieldError> = match compiled.validate(specifications) {
+        Ok(()) => return Ok(()),
+        Err(errors) => errors
+            .map(|e| {
+                let field = match &e.kind {
+                    ValidationErrorKind::Required { property } => {
+                        let parent = e.instance_path.to_string();
+                        let property = property.as_str().unwrap_or_default();
+                        join_field(&parent, property)
+                    }
+                    _ => join_field(&e.instance_path.to_string(), ""),
+                };
+                SpecFieldError { field, message: e.to_string() }
+            })
+            .collect(),
+    };
+    Err(errors)
+}
+
+fn join_field(pointer: &str, leaf: &str) -> String {
+    pointer
+        .split('/')
+        .chain(std::iter::once(leaf))
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Top-level properties marked with `"x-filterable": true`.
+pub fn filterable_fields(schema: &serde_json::Value) -> Vec<String> {
+    let mut fields: Vec<String> = schema
+        .get("properties")
+        .and_then(|p| p.as_object())
+        .map(|props| {
+            props
+                .iter()
+                .filter(|(_, prop)| prop.get(FILTERABLE_KEYWORD).and_then(|f| f.as_bool()) == Some(true))
+                .map(|(name, _)| name.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    fields.sort();
+    fields
+}
+
+/// Spec filters must be a flat object whose keys are all filterable in the schema.
+pub fn check_spec_filters(schema: Option<&serde_json::Value>, filters: &serde_json::Value) -> Result<(), String> {
+    let filters = filters.as_object().ok_or("specs must be a JSON object")?;
+    let allowed = schema.map(filterable_fields).unwrap_or_default();
+    for (key, value) in filters {
+        if !allowed.contains(key) {
+            return Err(format!("'{}' is not a filterable specification", key));
+        }
+        if value.is_object() || value.is_array() {
+            return Err(format!("Filter value for '{}' must be a scalar", key));
+        }
+    }
+    Ok(())
+}
+
+/// Nearest schema walking up from `category_id`; subcategories inherit their parent's schema.
+pub async fn effective_spec_schema(
+    db: &PgPool,
+    category_id: Uuid,
+) -> Result<Option<(Uuid, serde_json::Value)>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        WITH RECURSIVE ancestors AS (
+            SELECT category_id, parent_category_id, spec_schema, 0 AS depth
+            FROM product_schema.categories WHERE category_id = $1
+            UNION ALL
+            SELECT c.category_id, c.parent_category_id, c.spec_schema, a.depth + 1
+            FROM product_schema.categories c
+            JOIN ancestors a ON c.category_id = a.parent_category_id
+            WHERE a.depth < 32
+        )
+        SELECT category_id as "category_id!", spec_schema as "spec_schema!"
+        FROM ancestors
+        WHERE spec_schema IS NOT NULL
+        ORDER BY depth
+        LIMIT 1
+        "#,
+        category_id
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|r| (r.category_id, r.spec_schema)))
+}
+
+pub async fn get_category_spec_schema(
+    State(state): State<AppState>,
+    Path(category_id): Path<Uuid>,
+) -> impl IntoResponse {
+    match effective_spec_schema(&state.db, category_id).await {
+        Ok(Some((source, schema))) => ok(CategorySpecSchemaResponse {
+            category_id,
+            source_category_id: Some(source),
+            filterable_fields: filterable_fields(&schema),
+            spec_schema: Some(schema),
+        }),
+        Ok(None) => ok(CategorySpecSchemaResponse {
+            category_id,
+            source_category_id: None,
+            spec_schema: None,
+            filterable_fields: Vec::new(),
+        }),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch spec schema"),
+    }
+}
@@ -20,3 +20,7 @@ pub fn ok<T: Serialize>(data: T) -> Response {
 pub fn err(status: StatusCode, message: &str) -> Response {
     (status, axum::Json(Envelope::<()> { data: None, error: Some(message.to_string()) })).into_response()
 }
+
+pub fn err_with_data<T: Serialize>(status: StatusCode, message: &str, data: T) -> Response {
+    (status, axum::Json(Envelope { data: Some(data), error: Some(message.to_string()) })).into_response()
+}
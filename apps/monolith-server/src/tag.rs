@@ -0,0 +1,286 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use unicode_normalization::UnicodeNormalization;
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::roles::{has_any_role, ROLE_ADMIN};
+
+// Matches product_schema.tags.name VARCHAR(50)
+pub const MAX_TAG_LENGTH: usize = 50;
+pub const MAX_TAGS_PER_PRODUCT: usize = 20;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagAutocompleteParams {
+    pub prefix: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagSuggestion {
+    pub tag_id: Uuid,
+    pub name: String,
+    pub usage_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeTagsRequest {
+    pub source_tag_ids: Vec<Uuid>,
+    pub target_tag_id: Uuid,
+}
+
+impl MergeTagsRequest {
+    /// The distinct tags to fold into the target, leaving out the target itself.
+    pub fn sources(&self) -> Result<Vec<Uuid>, String> {
+        let mut sources: Vec<Uuid> = Vec::new();
+        for id in &self.source_tag_ids {
+            if *id != self.target_tag_id && !sources.contains(id) {
+                sources.push(*id);
+            }
+        }
+        if sources.is_empty() {
+            return Err("No source tags to merge".to_string());
+        }
+        Ok(sources)
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum TagError {
+    #[error("Tag '{0}' is longer than {} characters", MAX_TAG_LENGTH)]
+    TooLong(String),
+    #[error("A product can have at most {} tags", MAX_TAGS_PER_PRODUCT)]
+    TooMany,
+}
+
+/// NFKC-folds, lowercases and collapses whitespace. Returns `None` for tags that end up empty.
+pub fn normalize_tag(raw: &str) -> Option<String> {
+    let folded: String = raw.nfkc().collect::<String>().to_lowercase();
+    let collapsed = folded
+        .trim()
+        .trim_start_matches('#')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if collapsed.is_empty() {
+        None
+    } else {
+        Some(collapsed)
+    }
+}
+
+/// Normalizes a tag list, dropping blanks and duplicates while keeping the caller's order.
+pub fn normalize_tags(raw: &[String]) -> Result<Vec<String>, TagError> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw.iter().filter_map(|t| normalize_tag(t)) {
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(TagError::TooLong(tag));
+        }
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    if tags.len() > MAX_TAGS_PER_PRODUCT {
+        return Err(TagError::TooMany);
+    }
+    Ok(tags)
+}
+
+/// Replaces a product's tags with `tags` (already normalized) inside the caller's transaction.
+pub async fn set_product_tags(
+    tx: &mut Transaction<'_, Postgres>,
+    product_id: Uuid,
+    tags: &[String],
+) -> Result<(), sqlx::Error> {
+    let tag_ids: Vec<Uuid> = sqlx::query!(
+        r#"
+        INSERT INTO product_schema.tags (name)
+        SELECT * FROM UNNEST($1::text[])
+        ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name
+        RETURNING tag_id
+        "#,
+        tags
+    )
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .map(|r| r.tag_id)
+    .collect();
+
+    sqlx::query!(
+        "DELETE FROM product_schema.product_tags WHERE product_id = $1 AND NOT (tag_id = ANY($2))",
+        product_id,
+        &tag_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO product_schema.product_tags (product_id, tag_id)
+        SELECT $1, * FROM UNNEST($2::uuid[])
+        ON CONFLICT DO NOTHING
+        "#,
+        product_id,
+        &tag_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    Ok(())
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+pub async fn autocomplete_tags(
+    State(state): State<AppState>,
+    Query(params): Query<TagAutocompleteParams>,
+) -> impl IntoResponse {
+    let prefix = params.prefix.as_deref().and_then(normalize_tag).unwrap_or_default();
+    let limit = params.limit.unwrap_or(10).clamp(1, 50);
+
+    // Ranked by how many active listings use the tag
+    let tags = sqlx::query!(
+        r#"
+        SELECT t.tag_id, t.name, COUNT(p.product_id) as usage_count
+        FROM product_schema.tags t
+        LEFT JOIN product_schema.product_tags pt ON pt.tag_id = t.tag_id
+        LEFT JOIN product_schema.products p ON p.product_id = pt.product_id AND p.status = 'active'
+        WHERE t.name LIKE $1 || '%'
+        GROUP BY t.tag_id, t.name
+        ORDER BY usage_count DESC, t.name
+        LIMIT $2
+        "#,
+        prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"),
+        limit
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    let tags = match tags {
+        Ok(t) => t,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch tags"),
+    };
+
+    let suggestions: Vec<TagSuggestion> = tags
+        .into_iter()
+        .map(|t| TagSuggestion {
+            tag_id: t.tag_id,
+            name: t.name,
+            usage_count: t.usage_count.unwrap_or(0),
+        })
+        .collect();
+
+    ok(suggestions)
+}
+
+pub async fn merge_tags(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<MergeTagsRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    match has_any_role(&state.db, user_id, &[ROLE_ADMIN]).await {
+        Ok(true) => {}
+        Ok(false) => return err(StatusCode::FORBIDDEN, "Only admins can merge tags"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to merge tags"),
+    }
+
+    let sources = match req.sources() {
+        Ok(sources) => sources,
+        Err(message) => return err(StatusCode::BAD_REQUEST, &message),
+    };
+
+    let mut tx = match state.db.begin().await {
+        Ok(tx) => tx,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to merge tags"),
+    };
+
+    let target = sqlx::query!(
+        "SELECT tag_id FROM product_schema.tags WHERE tag_id = $1",
+        req.target_tag_id
+    )
+    .fetch_optional(&mut tx)
+    .await;
+
+    match target {
+        Ok(Some(_)) => {}
+        Ok(None) => return err(StatusCode::NOT_FOUND, "Target tag not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to merge tags"),
+    }
+
+    let relinked = sqlx::query!(
+        r#"
+        INSERT INTO product_schema.product_tags (product_id, tag_id)
+        SELECT product_id, $2 FROM product_schema.product_tags WHERE tag_id = ANY($1)
+        ON CONFLICT DO NOTHING
+        "#,
+        &sources,
+        req.target_tag_id
+    )
+    .execute(&mut tx)
+    .await;
+
+    let unlinked = sqlx::query!(
+        "DELETE FROM product_schema.product_tags WHERE tag_id = ANY($1)",
+        &sources
+    )
+    .execute(&mut tx)
+    .await;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM product_schema.tags WHERE tag_id = ANY($1)",
+        &sources
+    )
+    .execute(&mut tx)
+    .await;
+
+    let merged = match (relinked, unlinked, deleted) {
+        (Ok(_), Ok(_), Ok(d)) => d.rows_affected(),
+        _ => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to merge tags"),
+    };
+
+    if tx.commit().await.is_err() {
+        return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to merge tags");
+    }
+
+    ok(serde_json::json!({
+        "target_tag_id": req.target_tag_id,
+        "merged_tags": merged,
+        "message": "Tags merged successfully"
+    }))
+}
@@ -0,0 +1,307 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::state::{ok, err, AppState};
+use crate::jwt::verify_token;
+use crate::analytics::{spawn_record_event, ProductEvent};
+use crate::pagination::{page_size, DEFAULT_PAGE_SIZE};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddWishlistRequest {
+    pub product_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WishlistFilters {
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProductSummary {
+    pub product_id: Uuid,
+    pub name: String,
+    pub daily_price: f64,
+    pub status: String,
+    pub avg_rating: Option<f64>,
+    pub primary_image: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WishlistItemResponse {
+    pub wishlist_id: Uuid,
+    pub added_at: chrono::DateTime<chrono::Utc>,
+    pub product: ProductSummary,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WishlistListResponse {
+    pub items: Vec<WishlistItemResponse>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProductWishlistCount {
+    pub product_id: Uuid,
+    pub name: String,
+    pub wishlist_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WishlistStatsResponse {
+    pub total_wishlists: i64,
+    pub products: Vec<ProductWishlistCount>,
+}
+
+fn extract_user_id_from_token(headers: &HeaderMap) -> Result<Uuid, StatusCode> {
+    let auth_header = headers.get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let token = match auth_header {
+        Some(token) => token,
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    let claims = match verify_token(token) {
+        Ok(claims) => claims,
+        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    if claims.token_type != "access" {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    match Uuid::parse_str(&claims.sub) {
+        Ok(id) => Ok(id),
+        Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// Returns the subset of `product_ids` the user has saved. Used to fill `is_wishlisted`.
+pub async fn wishlisted_product_ids(
+    db: &PgPool,
+    user_id: Uuid,
+    product_ids: &[Uuid],
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    let rows = sqlx::query!(
+        "SELECT product_id FROM product_schema.wishlists WHERE user_id = $1 AND product_id = ANY($2)",
+        user_id,
+        product_ids
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.product_id).collect())
+}
+
+pub async fn add_to_wishlist(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<AddWishlistRequest>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let product = sqlx::query!(
+        "SELECT status FROM product_schema.products WHERE product_id = $1",
+        req.product_id
+    )
+    .fetch_optional(&state.db)
+    .await;
+
+    match product {
+        Ok(Some(p)) if p.status != "archived" => {}
+        Ok(_) => return err(StatusCode::NOT_FOUND, "Product not found"),
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch product"),
+    }
+
+    // Saving twice is a no-op
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO product_schema.wishlists (user_id, product_id)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id, product_id) DO NOTHING
+        "#,
+        user_id,
+        req.product_id
+    )
+    .execute(&state.db)
+    .await;
+
+    match result {
+        Ok(r) if r.rows_affected() > 0 => {
+            spawn_record_event(&state.db, req.product_id, ProductEvent::WishlistAdd);
+        }
+        Ok(_) => {}
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to add to wishlist"),
+    }
+
+    ok(serde_json::json!({
+        "product_id": req.product_id,
+        "message": "Product added to wishlist"
+    }))
+}
+
+pub async fn remove_from_wishlist(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(product_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let result = sqlx::query!(
+        "DELETE FROM product_schema.wishlists WHERE user_id = $1 AND product_id = $2",
+        user_id,
+        product_id
+    )
+    .execute(&state.db)
+    .await;
+
+    match result {
+        Ok(r) if r.rows_affected() == 0 => err(StatusCode::NOT_FOUND, "Product not in wishlist"),
+        Ok(_) => ok(serde_json::json!({ "message": "Product removed from wishlist" })),
+        Err(_) => err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to remove from wishlist"),
+    }
+}
+
+pub async fn list_wishlist(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(filters): Query<WishlistFilters>,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    let page = filters.page.unwrap_or(1).max(1);
+    let per_page = page_size(filters.per_page, DEFAULT_PAGE_SIZE);
+    let offset = (page - 1) * per_page;
+
+    let items = sqlx::query!(
+        r#"
+        SELECT w.wishlist_id, w.created_at as added_at,
+               p.product_id, p.name, p.daily_price, p.status, p.avg_rating,
+               (SELECT pi.image_url FROM product_schema.product_images pi
+                WHERE pi.product_id = p.product_id
+                ORDER BY pi.is_primary DESC, pi.created_at
+                LIMIT 1) as primary_image
+        FROM product_schema.wishlists w
+        JOIN product_schema.products p ON w.product_id = p.product_id
+        WHERE w.user_id = $1 AND p.status <> 'archived'
+        ORDER BY w.created_at DESC
+        LIMIT $2 OFFSET $3
+        "#,
+        user_id,
+        per_page,
+        offset
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    let items = match items {
+        Ok(i) => i,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch wishlist"),
+    };
+
+    let total = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as count
+        FROM product_schema.wishlists w
+        JOIN product_schema.products p ON w.product_id = p.product_id
+        WHERE w.user_id = $1 AND p.status <> 'archived'
+        "#,
+        user_id
+    )
+    .fetch_one(&state.db)
+    .await;
+
+    let total = match total {
+        Ok(t) => t.count.unwrap_or(0),
+        Err(_) => 0,
+    };
+
+    let items: Vec<WishlistItemResponse> = items
+        .into_iter()
+        .map(|i| WishlistItemResponse {
+            wishlist_id: i.wishlist_id,
+            added_at: i.added_at,
+            product: ProductSummary {
+                product_id: i.product_id,
+                name: i.name,
+                daily_price: i.daily_price,
+                status: i.status,
+                avg_rating: i.avg_rating,
+                primary_image: i.primary_image,
+            },
+        })
+        .collect();
+
+    ok(WishlistListResponse {
+        items,
+        total,
+        page,
+        per_page,
+    })
+}
+
+pub async fn get_wishlist_stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let user_id = match extract_user_id_from_token(&headers) {
+        Ok(id) => id,
+        Err(status) => return err(status, "Unauthorized"),
+    };
+
+    // Counts for every listing owned by the caller
+    let counts = sqlx::query!(
+        r#"
+        SELECT p.product_id, p.name, COUNT(w.wishlist_id) as wishlist_count
+        FROM product_schema.products p
+        LEFT JOIN product_schema.wishlists w ON w.product_id = p.product_id
+        WHERE p.owner_id = $1 AND p.status <> 'archived'
+        GROUP BY p.product_id, p.name
+        ORDER BY wishlist_count DESC, p.name
+        "#,
+        user_id
+    )
+    .fetch_all(&state.db)
+    .await;
+
+    let counts = match counts {
+        Ok(c) => c,
+        Err(_) => return err(StatusCode::INTERNAL_SERVER_ERROR, "Failed to fetch wishlist stats"),
+    };
+
+    let products: Vec<ProductWishlistCount> = counts
+        .into_iter()
+        .map(|c| ProductWishlistCount {
+            product_id: c.product_id,
+            name: c.name,
+            wishlist_count: c.wishlist_count.unwrap_or(0),
+        })
+        .collect();
+
+    let total_wishlists = products.iter().map(|p| p.wishlist_count).sum();
+
+    ok(WishlistStatsResponse {
+        total_wishlists,
+        products,
+    })
+}
@@ -0,0 +1,59 @@
+use monolith_server::analytics::{
+    conversion_rate, fill_daily_series, DailyStats, EngagementCounts, FunnelConversion, ProductEvent,
+};
+use chrono::NaiveDate;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[tokio::test]
+async fn test_each_event_increments_one_counter() {
+    assert_eq!(ProductEvent::View.increments(), (1, 0, 0, 0));
+    assert_eq!(ProductEvent::WishlistAdd.increments(), (0, 1, 0, 0));
+    assert_eq!(ProductEvent::Inquiry.increments(), (0, 0, 1, 0));
+    assert_eq!(ProductEvent::Booking.increments(), (0, 0, 0, 1));
+}
+
+#[tokio::test]
+async fn test_fill_daily_series_adds_empty_days() {
+    let rows = vec![DailyStats {
+        day: date(2026, 10, 17),
+        counts: EngagementCounts { views: 12, wishlist_adds: 2, inquiries: 1, bookings: 0 },
+    }];
+
+    let series = fill_daily_series(date(2026, 10, 16), date(2026, 10, 18), &rows);
+
+    assert_eq!(series.len(), 3);
+    assert_eq!(series[0].day, date(2026, 10, 16));
+    assert_eq!(series[0].counts, EngagementCounts::default());
+    assert_eq!(series[1].counts.views, 12);
+    assert_eq!(series[2].day, date(2026, 10, 18));
+}
+
+#[tokio::test]
+async fn test_funnel_conversion() {
+    let counts = EngagementCounts { views: 200, wishlist_adds: 20, inquiries: 8, bookings: 2 };
+    let funnel = FunnelConversion::from_counts(&counts);
+
+    assert_eq!(funnel.view_to_wishlist, 0.1);
+    assert_eq!(funnel.view_to_inquiry, 0.04);
+    assert_eq!(funnel.inquiry_to_booking, 0.25);
+    assert_eq!(funnel.view_to_booking, 0.01);
+
+    assert_eq!(conversion_rate(5, 0), 0.0);
+    assert_eq!(conversion_rate(1, 3), 0.3333);
+}
+
+#[tokio::test]
+async fn test_daily_stats_serialize_flat() {
+    let stats = DailyStats {
+        day: date(2026, 10, 18),
+        counts: EngagementCounts { views: 3, wishlist_adds: 1, inquiries: 0, bookings: 0 },
+    };
+
+    let value = serde_json::to_value(&stats).unwrap();
+    assert_eq!(value["day"], "2026-10-18");
+    assert_eq!(value["views"], 3);
+    assert_eq!(value["wishlist_adds"], 1);
+}
@@ -0,0 +1,213 @@
+use axum::{http::header, routing::get, Router};
+use chrono::{Duration, TimeZone, Utc};
+use monolith_server::calendar::{
+    calendar_http_client, fetch_calendar, is_public_ip, normalize_import_url, parse_calendar, render_calendar,
+    CalendarError, CalendarEvent,
+};
+
+const AIRBNB_STYLE: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+PRODID:-//Example//Hosting Calendar//EN\r\n\
+BEGIN:VTIMEZONE\r\n\
+TZID:Europe/Berlin\r\n\
+BEGIN:STANDARD\r\n\
+DTSTART:19701025T030000\r\n\
+END:STANDARD\r\n\
+END:VTIMEZONE\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART;VALUE=DATE:20300610\r\n\
+DTEND;VALUE=DATE:20300613\r\n\
+UID:abc-1@example.com\r\n\
+SUMMARY:Reserved\\, guest\r\n\
+\x20\x20from the other site\r\n\
+BEGIN:VALARM\r\n\
+TRIGGER:-PT15M\r\n\
+DESCRIPTION:Not an event\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART;TZID=Europe/Berlin:20300620T090000\r\n\
+DTEND;TZID=Europe/Berlin:20300620T170000\r\n\
+UID:abc-2@example.com\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART:20300701T100000Z\r\n\
+DTEND:20300702T100000Z\r\n\
+STATUS:CANCELLED\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART;VALUE=DATE:20300801\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+#[tokio::test]
+async fn test_parse_reads_busy_periods() {
+    let events = parse_calendar(AIRBNB_STYLE).unwrap();
+    let periods: Vec<_> = events.iter().map(|e| (e.start, e.end)).collect();
+
+    assert_eq!(
+        periods,
+        vec![
+            (Utc.with_ymd_and_hms(2030, 6, 10, 0, 0, 0).unwrap(), Utc.with_ymd_and_hms(2030, 6, 13, 0, 0, 0).unwrap()),
+            // Berlin is on summer time (UTC+2) in June
+            (Utc.with_ymd_and_hms(2030, 6, 20, 7, 0, 0).unwrap(), Utc.with_ymd_and_hms(2030, 6, 20, 15, 0, 0).unwrap()),
+            // An all-day event without an end lasts the day
+            (Utc.with_ymd_and_hms(2030, 8, 1, 0, 0, 0).unwrap(), Utc.with_ymd_and_hms(2030, 8, 2, 0, 0, 0).unwrap()),
+        ]
+    );
+    assert_eq!(events[0].uid.as_deref(), Some("abc-1@example.com"));
+    assert_eq!(events[0].summary.as_deref(), Some("Reserved, guest from the other site"));
+    assert_eq!(events[1].summary, None);
+}
+
+#[tokio::test]
+async fn test_parse_converts_zoned_times_and_keeps_floating_ones() {
+    let ics = "BEGIN:VCALENDAR\n\
+BEGIN:VEVENT\nDTSTART;TZID=America/New_York:20300115T090000\nDTEND;TZID=\"/America/New_York\":20300115T120000\nEND:VEVENT\n\
+BEGIN:VEVENT\nDTSTART:20300115T090000\nDTEND;TZID=Mars/Olympus_Mons:20300115T120000\nEND:VEVENT\n\
+BEGIN:VEVENT\nDTSTART;TZID=Europe/Berlin:20300331T023000\nDTEND;TZID=Europe/Berlin:20300331T040000\nEND:VEVENT\n\
+END:VCALENDAR\n";
+    let periods: Vec<_> = parse_calendar(ics).unwrap().iter().map(|e| (e.start, e.end)).collect();
+
+    assert_eq!(
+        periods,
+        vec![
+            (Utc.with_ymd_and_hms(2030, 1, 15, 14, 0, 0).unwrap(), Utc.with_ymd_and_hms(2030, 1, 15, 17, 0, 0).unwrap()),
+            // Floating times and unknown zones stay as written
+            (Utc.with_ymd_and_hms(2030, 1, 15, 9, 0, 0).unwrap(), Utc.with_ymd_and_hms(2030, 1, 15, 12, 0, 0).unwrap()),
+            // 02:30 does not exist on the night clocks go forward
+            (Utc.with_ymd_and_hms(2030, 3, 31, 1, 30, 0).unwrap(), Utc.with_ymd_and_hms(2030, 3, 31, 2, 0, 0).unwrap()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_parse_rejects_non_calendars_and_skips_broken_events() {
+    assert!(matches!(parse_calendar("<html>Not found</html>"), Err(CalendarError::Parse(_))));
+
+    let broken = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART:20300610T100000Z\nEND:VEVENT\n\
+BEGIN:VEVENT\nDTSTART:20300610T100000Z\nDTEND:20300609T100000Z\nEND:VEVENT\n\
+BEGIN:VEVENT\nDTSTART:tomorrow\nDTEND:20300609T100000Z\nEND:VEVENT\nEND:VCALENDAR\n";
+    assert_eq!(parse_calendar(broken).unwrap(), vec![]);
+}
+
+#[tokio::test]
+async fn test_render_escapes_and_folds_lines() {
+    let start = Utc.with_ymd_and_hms(2030, 6, 10, 9, 0, 0).unwrap();
+    let event = CalendarEvent {
+        uid: "rental-1@onesociety".to_string(),
+        start,
+        end: start + Duration::days(2),
+        summary: format!("Bike; red, fast\n{}", "é".repeat(60)),
+    };
+    let ics = render_calendar("Owner's rentals", &[event], start);
+
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    assert!(!ics.replace("\r\n", "").contains('\n'));
+    assert!(ics.contains("DTSTART:20300610T090000Z\r\nDTEND:20300612T090000Z\r\n"));
+    assert!(ics.contains("SUMMARY:Bike\\; red\\, fast\\n"));
+    assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+}
+
+#[tokio::test]
+async fn test_rendered_feeds_parse_back() {
+    let start = Utc.with_ymd_and_hms(2030, 6, 10, 9, 0, 0).unwrap();
+    let events: Vec<_> = (0..3)
+        .map(|i| CalendarEvent {
+            uid: format!("rental-{}@onesociety", i),
+            start: start + Duration::days(i * 7),
+            end: start + Duration::days(i * 7 + 2),
+            summary: format!("Kayak \\ paddle, {}: booked {}", i, "x".repeat(80)),
+        })
+        .collect();
+
+    let parsed = parse_calendar(&render_calendar("Kayak", &events, start)).unwrap();
+    assert_eq!(parsed.len(), 3);
+    for (event, back) in events.iter().zip(&parsed) {
+        assert_eq!(back.uid.as_deref(), Some(event.uid.as_str()));
+        assert_eq!((back.start, back.end), (event.start, event.end));
+        assert_eq!(back.summary.as_deref(), Some(event.summary.as_str()));
+    }
+}
+
+#[tokio::test]
+async fn test_import_urls_must_be_public_http() {
+    assert_eq!(
+        normalize_import_url(" webcal://calendar.example.com/ical/abc.ics ").unwrap(),
+        "https://calendar.example.com/ical/abc.ics"
+    );
+    assert!(normalize_import_url("https://93.184.216.34/cal.ics").is_ok());
+
+    for url in [
+        "",
+        "not a url",
+        "ftp://example.com/cal.ics",
+        "file:///etc/passwd",
+        "http://localhost:8080/cal.ics",
+        "http://printer.local/cal.ics",
+        "http://127.0.0.1/cal.ics",
+        "http://10.1.2.3/cal.ics",
+        "http://192.168.0.10/cal.ics",
+        "http://169.254.169.254/latest/meta-data",
+        "http://[::1]/cal.ics",
+        "http://[fd00::1]/cal.ics",
+        "http://[::ffff:127.0.0.1]/cal.ics",
+        "http://[::ffff:10.0.0.1]/cal.ics",
+        "http://100.64.0.1/cal.ics",
+    ] {
+        assert!(
+            matches!(normalize_import_url(url), Err(CalendarError::InvalidUrl(_))),
+            "{} should be rejected",
+            url
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_fetch_reads_calendar_from_http_fixture() {
+    let app = Router::new()
+        .route("/cal.ics", get(|| async { ([(header::CONTENT_TYPE, "text/calendar")], AIRBNB_STYLE) }))
+        .route("/huge.ics", get(|| async { "x".repeat(3 * 1024 * 1024) }));
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    let client = reqwest::Client::new();
+    let body = fetch_calendar(&client, &format!("{}/cal.ics", base)).await.unwrap();
+    assert_eq!(parse_calendar(&body).unwrap().len(), 3);
+
+    let missing = fetch_calendar(&client, &format!("{}/gone.ics", base)).await;
+    assert!(matches!(missing, Err(CalendarError::Fetch(_))));
+
+    let huge = fetch_calendar(&client, &format!("{}/huge.ics", base)).await;
+    assert!(matches!(huge, Err(CalendarError::TooLarge)));
+
+    // The import client checks what a name resolves to before connecting
+    let by_name = base.replace("127.0.0.1", "localhost");
+    assert!(fetch_calendar(&client, &format!("{}/cal.ics", by_name)).await.is_ok());
+    let refused = fetch_calendar(calendar_http_client(), &format!("{}/cal.ics", by_name)).await;
+    assert!(matches!(refused, Err(CalendarError::Fetch(_))));
+}
+
+#[tokio::test]
+async fn test_public_ip_check_sees_through_mapped_and_shared_addresses() {
+    for ip in ["93.184.216.34", "2606:2800:220:1::1", "100.128.0.1", "::ffff:93.184.216.34"] {
+        assert!(is_public_ip(ip.parse().unwrap()), "{} should be public", ip);
+    }
+    for ip in [
+        "127.0.0.1",
+        "0.0.0.0",
+        "100.64.0.1",
+        "100.127.255.254",
+        "224.0.0.1",
+        "::ffff:127.0.0.1",
+        "::ffff:169.254.169.254",
+        "fe80::1",
+    ] {
+        assert!(!is_public_ip(ip.parse().unwrap()), "{} should be internal", ip);
+    }
+}
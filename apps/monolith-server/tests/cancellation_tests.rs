@@ -0,0 +1,122 @@
+use chrono::{Duration, TimeZone, Utc};
+use monolith_server::cancellation::{
+    cancellation_terms, hours_before_start, owner_penalty_percent, renter_refund_percent, CancellationPolicy,
+    RentalCharges,
+};
+use monolith_server::rental_status::{RentalRole, RentalStatus};
+
+/// $120 paid: a $90 subtotal, $10 of fees and a $20 deposit.
+const CHARGES: RentalCharges =
+    RentalCharges { paid_cents: 12000, deposit_cents: 2000, subtotal_cents: 9000, insurance_cents: 0 };
+
+#[tokio::test]
+async fn test_policy_round_trips_and_defaults_to_moderate() {
+    for policy in [CancellationPolicy::Flexible, CancellationPolicy::Moderate, CancellationPolicy::Strict] {
+        assert_eq!(policy.to_string().parse::<CancellationPolicy>(), Ok(policy));
+    }
+    assert_eq!(CancellationPolicy::default(), CancellationPolicy::Moderate);
+    assert!("lenient".parse::<CancellationPolicy>().is_err());
+
+    let parsed: CancellationPolicy = serde_json::from_str("\"strict\"").unwrap();
+    assert_eq!(parsed, CancellationPolicy::Strict);
+}
+
+#[tokio::test]
+async fn test_renter_refund_tiers() {
+    use CancellationPolicy::*;
+
+    assert_eq!(renter_refund_percent(Flexible, 24), 100);
+    assert_eq!(renter_refund_percent(Flexible, 23), 50);
+    assert_eq!(renter_refund_percent(Flexible, 0), 50);
+    assert_eq!(renter_refund_percent(Flexible, -1), 0);
+
+    assert_eq!(renter_refund_percent(Moderate, 120), 100);
+    assert_eq!(renter_refund_percent(Moderate, 119), 50);
+    assert_eq!(renter_refund_percent(Moderate, 24), 50);
+    assert_eq!(renter_refund_percent(Moderate, 23), 0);
+
+    assert_eq!(renter_refund_percent(Strict, 1000), 50);
+    assert_eq!(renter_refund_percent(Strict, 168), 50);
+    assert_eq!(renter_refund_percent(Strict, 167), 0);
+}
+
+#[tokio::test]
+async fn test_owner_penalty_grows_closer_to_start() {
+    assert_eq!(owner_penalty_percent(168), 0);
+    assert_eq!(owner_penalty_percent(167), 10);
+    assert_eq!(owner_penalty_percent(24), 10);
+    assert_eq!(owner_penalty_percent(23), 25);
+    assert_eq!(owner_penalty_percent(-5), 25);
+}
+
+#[tokio::test]
+async fn test_hours_before_start_rounds_down() {
+    let start = Utc.with_ymd_and_hms(2030, 6, 10, 10, 0, 0).unwrap();
+
+    assert_eq!(hours_before_start(start, start - Duration::minutes(90)), 1);
+    assert_eq!(hours_before_start(start, start), 0);
+    // Half an hour in is already after the start
+    assert_eq!(hours_before_start(start, start + Duration::minutes(30)), -1);
+}
+
+#[tokio::test]
+async fn test_renter_cancellation_refunds_tier_plus_deposit() {
+    let terms = cancellation_terms(CancellationPolicy::Moderate, RentalRole::Renter, RentalStatus::Confirmed, 48, &CHARGES);
+    assert_eq!(terms.refund_percent, 50);
+    assert_eq!(terms.refund_cents, 2000 + 5000);
+    assert_eq!(terms.owner_penalty_cents, 0);
+
+    let late = cancellation_terms(CancellationPolicy::Strict, RentalRole::Renter, RentalStatus::Confirmed, 2, &CHARGES);
+    assert_eq!(late.refund_cents, 2000);
+
+    // Nothing is owed before the owner accepts
+    let unconfirmed = cancellation_terms(CancellationPolicy::Strict, RentalRole::Renter, RentalStatus::Requested, 2, &CHARGES);
+    assert_eq!(unconfirmed.refund_percent, 100);
+    assert_eq!(unconfirmed.refund_cents, 12000);
+}
+
+#[tokio::test]
+async fn test_owner_cancellation_refunds_in_full_with_penalty() {
+    let terms = cancellation_terms(CancellationPolicy::Strict, RentalRole::Owner, RentalStatus::Confirmed, 48, &CHARGES);
+    assert_eq!(terms.refund_cents, 12000);
+    assert_eq!(terms.owner_penalty_cents, 900);
+
+    let declined = cancellation_terms(CancellationPolicy::Strict, RentalRole::Owner, RentalStatus::Requested, 2, &CHARGES);
+    assert_eq!(declined.refund_cents, 12000);
+    assert_eq!(declined.owner_penalty_cents, 0);
+
+    let admin = cancellation_terms(CancellationPolicy::Strict, RentalRole::Admin, RentalStatus::Confirmed, 2, &CHARGES);
+    assert_eq!(admin.refund_cents, 12000);
+    assert_eq!(admin.owner_penalty_cents, 0);
+}
+
+#[tokio::test]
+async fn test_refund_never_exceeds_what_was_paid() {
+    let unpaid = RentalCharges { paid_cents: 0, ..CHARGES };
+    let terms = cancellation_terms(CancellationPolicy::Flexible, RentalRole::Owner, RentalStatus::Confirmed, 48, &unpaid);
+    assert_eq!(terms.refund_cents, 0);
+    assert_eq!(terms.owner_penalty_cents, 900);
+
+    // Only part of the deposit was captured
+    let partial = RentalCharges { paid_cents: 1500, ..CHARGES };
+    let terms = cancellation_terms(CancellationPolicy::Strict, RentalRole::Renter, RentalStatus::Confirmed, 2, &partial);
+    assert_eq!(terms.refund_cents, 1500);
+}
+
+#[tokio::test]
+async fn test_insurance_premium_is_refunded_in_full() {
+    // $8 of insurance on top of the usual charges
+    let insured = RentalCharges { paid_cents: 12800, insurance_cents: 800, ..CHARGES };
+
+    let late = cancellation_terms(CancellationPolicy::Strict, RentalRole::Renter, RentalStatus::Confirmed, 2, &insured);
+    assert_eq!(late.refund_percent, 0);
+    assert_eq!(late.refund_cents, 2800);
+
+    let half = cancellation_terms(CancellationPolicy::Moderate, RentalRole::Renter, RentalStatus::Confirmed, 48, &insured);
+    assert_eq!(half.refund_cents, 2000 + 800 + 5000);
+
+    // Only part of the deposit and premium was captured
+    let partial = RentalCharges { paid_cents: 2500, ..insured };
+    let terms = cancellation_terms(CancellationPolicy::Strict, RentalRole::Renter, RentalStatus::Confirmed, 2, &partial);
+    assert_eq!(terms.refund_cents, 2500);
+}
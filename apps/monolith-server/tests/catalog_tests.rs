@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use monolith_server::catalog::{
+    parse_row, read_csv, read_xlsx, resolve_columns, write_csv, write_xlsx, CatalogFormat, ExportRow, CATALOG_COLUMNS,
+};
+use uuid::Uuid;
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+fn kayak() -> ExportRow {
+    ExportRow {
+        name: "Sea kayak".to_string(),
+        description: Some("Two-seater, paddles included".to_string()),
+        category: "water-sports".to_string(),
+        daily_price: 35.5,
+        deposit_amount: Some(200.0),
+        insurance_required: true,
+        quantity: 5,
+        tags: strings(&["kayak", "outdoor"]),
+        city: Some("Hamburg".to_string()),
+        country: Some("DE".to_string()),
+        specifications: Some(serde_json::json!({ "seats": 2 })),
+    }
+}
+
+#[tokio::test]
+async fn test_catalog_format_from_file_name() {
+    assert_eq!(CatalogFormat::from_file_name("Inventory.CSV"), Some(CatalogFormat::Csv));
+    assert_eq!(CatalogFormat::from_file_name("shop.xlsx"), Some(CatalogFormat::Xlsx));
+    assert_eq!(CatalogFormat::from_file_name("shop.xls"), None);
+}
+
+#[tokio::test]
+async fn test_resolve_columns_applies_mapping() {
+    let headers = strings(&["Item", "Price per day", "Category", "Stock"]);
+    let mapping: HashMap<String, String> = [
+        ("name".to_string(), "item".to_string()),
+        ("daily_price".to_string(), "Price per day".to_string()),
+        ("quantity".to_string(), "Stock".to_string()),
+    ]
+    .into_iter()
+    .collect();
+
+    let columns = resolve_columns(&headers, &mapping).unwrap();
+    assert_eq!(columns["name"], 0);
+    assert_eq!(columns["daily_price"], 1);
+    assert_eq!(columns["category"], 2);
+    assert_eq!(columns["quantity"], 3);
+    assert!(!columns.contains_key("tags"));
+}
+
+#[tokio::test]
+async fn test_resolve_columns_reports_missing_and_unknown_columns() {
+    let headers = strings(&["name", "category"]);
+
+    let error = resolve_columns(&headers, &HashMap::new()).unwrap_err();
+    assert!(error.contains("daily_price"));
+
+    let mapping: HashMap<String, String> = [("colour".to_string(), "Colour".to_string())].into_iter().collect();
+    assert!(resolve_columns(&headers, &mapping).unwrap_err().contains("colour"));
+}
+
+#[tokio::test]
+async fn test_parse_row_collects_every_error() {
+    let headers = strings(CATALOG_COLUMNS);
+    let columns = resolve_columns(&headers, &HashMap::new()).unwrap();
+    let cells = strings(&["", "", "tools", "-3", "", "maybe", "0", "", "", "", "[1]"]);
+
+    let errors = parse_row(7, &cells, &columns).unwrap_err();
+    let failed: Vec<&str> = errors.iter().filter_map(|e| e.column.as_deref()).collect();
+    assert_eq!(failed, vec!["name", "daily_price", "insurance_required", "quantity", "specifications"]);
+    assert!(errors.iter().all(|e| e.row == 7));
+}
+
+#[tokio::test]
+async fn test_parse_row_builds_product_request() {
+    let headers = strings(CATALOG_COLUMNS);
+    let columns = resolve_columns(&headers, &HashMap::new()).unwrap();
+    let cells = strings(&["Drill", "", "tools", "12", "", "no", "", "power; diy", "Berlin", "DE", ""]);
+
+    let row = parse_row(2, &cells, &columns).unwrap();
+    assert_eq!(row.tags, strings(&["power", "diy"]));
+    assert_eq!(row.insurance_required, Some(false));
+
+    let category_id = Uuid::new_v4();
+    let request = row.into_request(category_id);
+    assert_eq!(request.category_id, category_id);
+    assert_eq!(request.daily_price, 12.0);
+    assert_eq!(request.address.unwrap()["city"], "Berlin");
+}
+
+#[tokio::test]
+async fn test_csv_export_round_trips_through_import() {
+    let bytes = write_csv(&[kayak()]).unwrap();
+    let rows = read_csv(&bytes).unwrap();
+    assert_eq!(rows[0], strings(CATALOG_COLUMNS));
+
+    let columns = resolve_columns(&rows[0], &HashMap::new()).unwrap();
+    let row = parse_row(2, &rows[1], &columns).unwrap();
+    assert_eq!(row.name, "Sea kayak");
+    assert_eq!(row.daily_price, 35.5);
+    assert_eq!(row.quantity, Some(5));
+    assert_eq!(row.tags, strings(&["kayak", "outdoor"]));
+    assert_eq!(row.specifications, Some(serde_json::json!({ "seats": 2 })));
+}
+
+#[tokio::test]
+async fn test_csv_export_escapes_formula_cells() {
+    let mut row = kayak();
+    row.name = "=HYPERLINK(\"http://evil.example\")".to_string();
+    row.description = Some("-2+3".to_string());
+    row.city = Some("@SUM(A1)".to_string());
+
+    let rows = read_csv(&write_csv(&[row]).unwrap()).unwrap();
+    assert_eq!(rows[1][0], "'=HYPERLINK(\"http://evil.example\")");
+    assert_eq!(rows[1][1], "'-2+3");
+    assert_eq!(rows[1][2], "water-sports");
+    assert_eq!(rows[1][8], "'@SUM(A1)");
+}
+
+#[tokio::test]
+async fn test_xlsx_export_round_trips_through_import() {
+    let bytes = write_xlsx(&[kayak()]).unwrap();
+    let rows = read_xlsx(&bytes).unwrap();
+
+    let columns = resolve_columns(&rows[0], &HashMap::new()).unwrap();
+    let row = parse_row(2, &rows[1], &columns).unwrap();
+    assert_eq!(row.category, "water-sports");
+    assert_eq!(row.deposit_amount, Some(200.0));
+    assert_eq!(row.insurance_required, Some(true));
+
+    assert!(read_xlsx(b"not a workbook").is_err());
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use monolith_server::category::{
+    build_category_tree, is_in_subtree, slugify, unique_slug, CategoryResponse, MoveCategoryRequest,
+};
+use uuid::Uuid;
+use chrono::Utc;
+
+fn category(name: &str, parent: Option<Uuid>, sort_order: i32) -> CategoryResponse {
+    CategoryResponse {
+        category_id: Uuid::new_v4(),
+        name: name.to_string(),
+        slug: slugify(name),
+        icon: None,
+        description: None,
+        parent_category_id: parent,
+        sort_order,
+        created_at: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn test_slugify_normalizes_names() {
+    assert_eq!(slugify("Power Tools"), "power-tools");
+    assert_eq!(slugify("  Cameras & Lenses!! "), "cameras-lenses");
+    assert_eq!(slugify("***"), "category");
+}
+
+#[tokio::test]
+async fn test_unique_slug_appends_suffix() {
+    let existing = vec!["tools".to_string(), "tools-2".to_string()];
+
+    assert_eq!(unique_slug("tools", &existing), "tools-3");
+    assert_eq!(unique_slug("cameras", &existing), "cameras");
+}
+
+#[tokio::test]
+async fn test_build_category_tree_nests_and_orders() {
+    let outdoor = category("Outdoor", None, 1);
+    let electronics = category("Electronics", None, 0);
+    let tents = category("Tents", Some(outdoor.category_id), 1);
+    let kayaks = category("Kayaks", Some(outdoor.category_id), 0);
+    let outdoor_id = outdoor.category_id;
+
+    let tree = build_category_tree(vec![tents, outdoor, kayaks, electronics]);
+
+    assert_eq!(tree.len(), 2);
+    assert_eq!(tree[0].name, "Electronics");
+    assert_eq!(tree[1].category_id, outdoor_id);
+    assert_eq!(tree[1].children.len(), 2);
+    assert_eq!(tree[1].children[0].name, "Kayaks");
+}
+
+#[tokio::test]
+async fn test_move_into_own_subtree_is_detected() {
+    let root = Uuid::new_v4();
+    let child = Uuid::new_v4();
+    let grandchild = Uuid::new_v4();
+    let other = Uuid::new_v4();
+    let parents: HashMap<Uuid, Option<Uuid>> = HashMap::from([
+        (root, None),
+        (child, Some(root)),
+        (grandchild, Some(child)),
+        (other, None),
+    ]);
+
+    assert!(is_in_subtree(&parents, root, grandchild));
+    assert!(is_in_subtree(&parents, child, child));
+    assert!(!is_in_subtree(&parents, child, root));
+    assert!(!is_in_subtree(&parents, root, other));
+}
+
+#[tokio::test]
+async fn test_move_category_request_validation() {
+    let request = MoveCategoryRequest {
+        parent_category_id: None,
+        sort_order: Some(3),
+    };
+
+    assert!(request.parent_category_id.is_none());
+    assert_eq!(request.sort_order.unwrap(), 3);
+}
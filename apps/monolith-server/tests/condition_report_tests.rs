@@ -0,0 +1,130 @@
+use monolith_server::blob_store::{validate_key, BlobError, BlobStore, FileBlobStore, MemoryBlobStore};
+use monolith_server::condition_report::{
+    compare_reports, photo_blob_key, photo_extension, ChecklistItem, ConditionReportContent, HandoverStage,
+    ItemCondition,
+};
+use monolith_server::rental_status::RentalStatus;
+use uuid::Uuid;
+
+fn item(name: &str, condition: ItemCondition) -> ChecklistItem {
+    ChecklistItem { item: name.to_string(), condition, note: None }
+}
+
+fn report(checklist: Vec<ChecklistItem>, meters: &[(&str, f64)]) -> ConditionReportContent {
+    ConditionReportContent {
+        checklist,
+        notes: None,
+        meter_readings: meters.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+    }
+}
+
+#[tokio::test]
+async fn test_report_validation() {
+    assert!(report(vec![item("Lens", ItemCondition::Good)], &[("shutter_count", 1200.0)]).validate().is_ok());
+
+    let duplicate = report(vec![item("Lens", ItemCondition::Good), item(" lens", ItemCondition::Fair)], &[]);
+    assert!(duplicate.validate().is_err());
+
+    assert!(report(vec![item("  ", ItemCondition::Good)], &[]).validate().is_err());
+    assert!(report(vec![], &[("fuel_percent", -1.0)]).validate().is_err());
+    assert!(report(vec![], &[("fuel_percent", f64::NAN)]).validate().is_err());
+
+    let too_many = (0..51).map(|i| item(&format!("item {}", i), ItemCondition::Good)).collect();
+    assert!(report(too_many, &[]).validate().is_err());
+}
+
+#[tokio::test]
+async fn test_stage_is_open_only_around_its_handover() {
+    assert!(HandoverStage::Pickup.open_during().contains(&RentalStatus::Confirmed));
+    assert!(HandoverStage::Pickup.open_during().contains(&RentalStatus::Active));
+    assert!(!HandoverStage::Pickup.open_during().contains(&RentalStatus::Returned));
+
+    assert!(HandoverStage::Return.open_during().contains(&RentalStatus::Returned));
+    assert!(HandoverStage::Return.open_during().contains(&RentalStatus::Overdue));
+    assert!(!HandoverStage::Return.open_during().contains(&RentalStatus::Confirmed));
+    assert!(!HandoverStage::Return.open_during().contains(&RentalStatus::Completed));
+
+    assert_eq!("return".parse::<HandoverStage>(), Ok(HandoverStage::Return));
+    assert!("midway".parse::<HandoverStage>().is_err());
+}
+
+#[tokio::test]
+async fn test_comparison_lines_up_items_and_meters() {
+    let pickup = report(
+        vec![item("Body", ItemCondition::Excellent), item("Lens cap", ItemCondition::Good), item("Strap", ItemCondition::Fair)],
+        &[("shutter_count", 1200.0), ("battery_percent", 100.0)],
+    );
+    let returned = report(
+        vec![item("body", ItemCondition::Damaged), item("Strap", ItemCondition::Good), item("Charger", ItemCondition::Good)],
+        &[("shutter_count", 1650.0)],
+    );
+
+    let comparison = compare_reports(Some(&pickup), Some(&returned));
+
+    let rows: Vec<_> = comparison
+        .checklist
+        .iter()
+        .map(|c| (c.item.as_str(), c.pickup, c.return_condition, c.worsened))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            ("Body", Some(ItemCondition::Excellent), Some(ItemCondition::Damaged), true),
+            ("Lens cap", Some(ItemCondition::Good), None, false),
+            ("Strap", Some(ItemCondition::Fair), Some(ItemCondition::Good), false),
+            ("Charger", None, Some(ItemCondition::Good), false),
+        ]
+    );
+
+    let shutter = comparison.meter_readings.iter().find(|m| m.name == "shutter_count").unwrap();
+    assert_eq!(shutter.delta, Some(450.0));
+    let battery = comparison.meter_readings.iter().find(|m| m.name == "battery_percent").unwrap();
+    assert_eq!((battery.pickup, battery.return_value, battery.delta), (Some(100.0), None, None));
+
+    // Before the return handover there is only one side to show
+    let pickup_only = compare_reports(Some(&pickup), None);
+    assert!(pickup_only.checklist.iter().all(|c| c.return_condition.is_none() && !c.worsened));
+    assert_eq!(compare_reports(None, None).checklist.len(), 0);
+}
+
+#[tokio::test]
+async fn test_photo_keys_and_types() {
+    assert_eq!(photo_extension("image/jpeg"), Some("jpg"));
+    assert_eq!(photo_extension("image/gif"), None);
+
+    let (rental_id, report_id, photo_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let key = photo_blob_key(rental_id, report_id, photo_id, "png");
+    assert!(validate_key(&key).is_ok());
+    assert!(key.ends_with(&format!("{}.png", photo_id)));
+}
+
+#[tokio::test]
+async fn test_blob_store_rejects_escaping_keys() {
+    for key in ["", "../etc/passwd", "a//b", "a/./b", "/absolute", "a/b c"] {
+        assert!(matches!(validate_key(key), Err(BlobError::InvalidKey(_))), "{key}");
+    }
+
+    let store = MemoryBlobStore::default();
+    store.put("reports/a.jpg", b"jpeg").unwrap();
+    assert_eq!(store.get("reports/a.jpg").unwrap(), b"jpeg");
+    assert!(store.put("../a.jpg", b"jpeg").is_err());
+
+    store.delete("reports/a.jpg").unwrap();
+    assert!(matches!(store.get("reports/a.jpg"), Err(BlobError::NotFound)));
+}
+
+#[tokio::test]
+async fn test_file_blob_store_round_trip() {
+    let root = std::env::temp_dir().join(format!("blob-store-{}", Uuid::new_v4()));
+    let store = FileBlobStore::new(&root);
+
+    store.put("condition-reports/r/1.jpg", b"photo").unwrap();
+    assert_eq!(store.get("condition-reports/r/1.jpg").unwrap(), b"photo");
+
+    store.delete("condition-reports/r/1.jpg").unwrap();
+    assert!(matches!(store.get("condition-reports/r/1.jpg"), Err(BlobError::NotFound)));
+    // Deleting twice is fine
+    store.delete("condition-reports/r/1.jpg").unwrap();
+
+    std::fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,80 @@
+use chrono::{Duration, TimeZone, Utc};
+use monolith_server::damage_claim::{
+    capture_for, claim_window_open, resolvable, ClaimOutcome, ClaimStatus, OpenClaimRequest, CLAIM_WINDOW_HOURS,
+    RESPONSE_WINDOW_HOURS,
+};
+use monolith_server::escrow::{split_deposit, DepositSettlement};
+use uuid::Uuid;
+
+fn claim(amount_cents: i64) -> OpenClaimRequest {
+    OpenClaimRequest {
+        amount_cents,
+        description: "Cracked lens hood".to_string(),
+        evidence_photo_ids: vec![Uuid::new_v4()],
+    }
+}
+
+#[tokio::test]
+async fn test_claim_status_round_trips() {
+    for status in [ClaimStatus::Open, ClaimStatus::Responded, ClaimStatus::Resolved] {
+        assert_eq!(status.to_string().parse::<ClaimStatus>(), Ok(status));
+    }
+    assert!("pending".parse::<ClaimStatus>().is_err());
+
+    let outcome: ClaimOutcome = serde_json::from_str("\"partial\"").unwrap();
+    assert_eq!(outcome, ClaimOutcome::Partial);
+}
+
+#[tokio::test]
+async fn test_claims_are_limited_to_the_held_deposit() {
+    assert!(claim(5000).validate(5000).is_ok());
+    assert!(claim(5001).validate(5000).is_err());
+    assert!(claim(0).validate(5000).is_err());
+    assert!(claim(100).validate(0).is_err());
+
+    let blank = OpenClaimRequest { description: "  ".to_string(), ..claim(100) };
+    assert!(blank.validate(5000).is_err());
+
+    let photo = Uuid::new_v4();
+    let repeated = OpenClaimRequest { evidence_photo_ids: vec![photo, photo], ..claim(100) };
+    assert!(repeated.validate(5000).is_err());
+}
+
+#[tokio::test]
+async fn test_claim_and_response_windows() {
+    let returned_at = Utc.with_ymd_and_hms(2030, 6, 10, 10, 0, 0).unwrap();
+    assert!(claim_window_open(returned_at, returned_at + Duration::hours(CLAIM_WINDOW_HOURS - 1)));
+    assert!(!claim_window_open(returned_at, returned_at + Duration::hours(CLAIM_WINDOW_HOURS)));
+
+    let opened_at = returned_at + Duration::hours(5);
+    assert!(resolvable(ClaimStatus::Responded, opened_at, opened_at));
+    assert!(!resolvable(ClaimStatus::Open, opened_at, opened_at + Duration::hours(1)));
+    // Renters who stay silent do not block the claim forever
+    assert!(resolvable(ClaimStatus::Open, opened_at, opened_at + Duration::hours(RESPONSE_WINDOW_HOURS)));
+    assert!(!resolvable(ClaimStatus::Resolved, opened_at, opened_at + Duration::days(30)));
+}
+
+#[tokio::test]
+async fn test_capture_for_each_outcome() {
+    assert_eq!(capture_for(ClaimOutcome::Full, None, 3000, 5000), Ok(3000));
+    assert_eq!(capture_for(ClaimOutcome::Denied, Some(1000), 3000, 5000), Ok(0));
+    assert_eq!(capture_for(ClaimOutcome::Partial, Some(1000), 3000, 5000), Ok(1000));
+
+    assert!(capture_for(ClaimOutcome::Partial, None, 3000, 5000).is_err());
+    assert!(capture_for(ClaimOutcome::Partial, Some(3000), 3000, 5000).is_err());
+    assert!(capture_for(ClaimOutcome::Partial, Some(0), 3000, 5000).is_err());
+
+    // Part of the deposit was refunded since the claim was opened
+    assert_eq!(capture_for(ClaimOutcome::Full, None, 3000, 2000), Ok(2000));
+}
+
+#[tokio::test]
+async fn test_split_deposit_releases_what_is_not_captured() {
+    assert_eq!(
+        split_deposit(5000, 1200),
+        DepositSettlement { held_cents: 5000, captured_cents: 1200, released_cents: 3800 }
+    );
+    assert_eq!(split_deposit(5000, 0).released_cents, 5000);
+    assert_eq!(split_deposit(5000, 9000).captured_cents, 5000);
+    assert_eq!(split_deposit(0, 100), DepositSettlement { held_cents: 0, captured_cents: 0, released_cents: 0 });
+}
@@ -0,0 +1,44 @@
+use monolith_server::etag::{check_if_match, etag, if_match_allows, with_etag, PreconditionError};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::IntoResponse;
+
+fn if_match(value: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::IF_MATCH, HeaderValue::from_str(value).unwrap());
+    headers
+}
+
+#[tokio::test]
+async fn test_etag_is_quoted_version() {
+    assert_eq!(etag(1), "\"1\"");
+    assert_eq!(etag(42), "\"42\"");
+}
+
+#[tokio::test]
+async fn test_if_match_values() {
+    assert_eq!(if_match_allows("\"3\"", 3), Ok(true));
+    assert_eq!(if_match_allows("\"2\"", 3), Ok(false));
+    assert_eq!(if_match_allows("\"1\", \"3\"", 3), Ok(true));
+    assert_eq!(if_match_allows("*", 7), Ok(true));
+    // If-Match uses strong comparison
+    assert_eq!(if_match_allows("W/\"3\"", 3), Ok(false));
+    assert_eq!(if_match_allows("3", 3), Err(PreconditionError::Malformed));
+}
+
+#[tokio::test]
+async fn test_check_if_match_statuses() {
+    assert_eq!(check_if_match(&if_match("\"5\""), 5), Ok(()));
+
+    let stale = check_if_match(&if_match("\"4\""), 5).unwrap_err();
+    assert_eq!(stale, PreconditionError::Stale);
+    assert_eq!(stale.status(), StatusCode::PRECONDITION_FAILED);
+
+    let missing = check_if_match(&HeaderMap::new(), 5).unwrap_err();
+    assert_eq!(missing.status(), StatusCode::PRECONDITION_REQUIRED);
+}
+
+#[tokio::test]
+async fn test_with_etag_sets_header() {
+    let response = with_etag(StatusCode::OK.into_response(), 9);
+    assert_eq!(response.headers().get(header::ETAG).unwrap(), "\"9\"");
+}
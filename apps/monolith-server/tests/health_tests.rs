@@ -9,7 +9,11 @@ async fn healthz_returns_ok() {
     let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
 
     // Minimal state: we don't need DB for healthz
-    let config = AppConfig { bind_addr: addr.to_string(), database_url: std::env::var("DATABASE_URL").unwrap_or_default() };
+    let config = AppConfig {
+        bind_addr: addr.to_string(),
+        database_url: std::env::var("DATABASE_URL").unwrap_or_default(),
+        rental_request_ttl_hours: 48,
+    };
     let pool = if !config.database_url.is_empty() {
         Some(config.make_db_pool().await.unwrap())
     } else {
@@ -0,0 +1,55 @@
+use chrono::{Duration, TimeZone, Utc};
+use monolith_server::instant_book::{request_expired, InstantBookRequirements, RenterStanding};
+
+fn standing(identity_verified: bool, avg_rating: Option<f64>, completed_rentals: i64) -> RenterStanding {
+    RenterStanding { identity_verified, avg_rating, completed_rentals }
+}
+
+#[tokio::test]
+async fn test_no_requirements_admit_everyone() {
+    let requirements: InstantBookRequirements = serde_json::from_str("{}").unwrap();
+    assert_eq!(requirements, InstantBookRequirements::default());
+    assert!(requirements.unmet(&standing(false, None, 0)).is_empty());
+}
+
+#[tokio::test]
+async fn test_unmet_lists_each_missing_requirement() {
+    let requirements = InstantBookRequirements {
+        require_verified_id: true,
+        min_rating: Some(4.5),
+        min_completed_rentals: Some(3),
+    };
+
+    assert!(requirements.unmet(&standing(true, Some(4.5), 3)).is_empty());
+    assert_eq!(requirements.unmet(&standing(false, Some(4.8), 5)), vec!["verified_id"]);
+    assert_eq!(requirements.unmet(&standing(true, Some(4.4), 2)), vec!["min_rating", "min_completed_rentals"]);
+    // New renters have no rating to meet the bar with
+    assert_eq!(requirements.unmet(&standing(true, None, 3)), vec!["min_rating"]);
+}
+
+#[tokio::test]
+async fn test_requirement_validation() {
+    let valid = InstantBookRequirements { min_rating: Some(4.0), min_completed_rentals: Some(0), ..Default::default() };
+    assert!(valid.validate().is_ok());
+
+    let rating = InstantBookRequirements { min_rating: Some(6.0), ..Default::default() };
+    assert!(rating.validate().is_err());
+
+    let rentals = InstantBookRequirements { min_completed_rentals: Some(-1), ..Default::default() };
+    assert!(rentals.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_requests_expire_after_ttl_or_at_start() {
+    let created = Utc.with_ymd_and_hms(2030, 6, 1, 9, 0, 0).unwrap();
+    let starts = created + Duration::days(7);
+    let ttl = Duration::hours(48);
+
+    assert!(!request_expired(created, starts, created + Duration::hours(47), ttl));
+    assert!(request_expired(created, starts, created + Duration::hours(48), ttl));
+
+    // A request for tomorrow lapses when tomorrow comes, even within the window
+    let soon = created + Duration::hours(12);
+    assert!(!request_expired(created, soon, created + Duration::hours(11), ttl));
+    assert!(request_expired(created, soon, soon, ttl));
+}
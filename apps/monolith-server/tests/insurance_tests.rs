@@ -0,0 +1,77 @@
+use axum::http::StatusCode;
+use chrono::{Duration, TimeZone, Utc};
+use monolith_server::insurance::{select_coverage, InsuranceError, InsuranceProvider, MockInsuranceProvider};
+use monolith_server::pricing::{compute_quote, PriceQuote, PricingRules};
+use uuid::Uuid;
+
+/// Five days at 20.00 a day with a 100.00 deposit.
+fn quote() -> PriceQuote {
+    let start = Utc.with_ymd_and_hms(2024, 6, 3, 10, 0, 0).unwrap();
+    compute_quote(
+        Uuid::new_v4(),
+        20.0,
+        Some(100.0),
+        &PricingRules::default(),
+        start,
+        start + Duration::days(5),
+        false,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_mock_provider_prices_coverage_from_the_quote() {
+    let options = MockInsuranceProvider.coverage_options(&quote()).unwrap();
+
+    let rows: Vec<_> = options
+        .iter()
+        .map(|o| (o.option_id.as_str(), o.coverage_amount, o.deductible, o.premium))
+        .collect();
+    assert_eq!(rows, vec![("basic", 600.0, 60.0, 5.0), ("premium", 1200.0, 0.0, 12.0)]);
+}
+
+#[tokio::test]
+async fn test_select_coverage_rejects_unknown_options() {
+    let basic = select_coverage(&MockInsuranceProvider, &quote(), "basic").unwrap();
+    assert_eq!(basic.name, "Basic damage cover");
+
+    let unknown = select_coverage(&MockInsuranceProvider, &quote(), "platinum").unwrap_err();
+    assert_eq!(unknown, InsuranceError::UnknownOption("platinum".to_string()));
+    assert_eq!(unknown.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_issued_policy_references_rental_and_option() {
+    let quote = quote();
+    let option = select_coverage(&MockInsuranceProvider, &quote, "premium").unwrap();
+    let rental_id = Uuid::new_v4();
+
+    let issued = MockInsuranceProvider.issue_policy(rental_id, &quote, &option).unwrap();
+    let reference = rental_id.simple().to_string()[..12].to_uppercase();
+    assert_eq!(issued.policy_number, format!("MOCK-PREMIUM-{}", reference));
+    assert_eq!(issued.terms["option"]["premium"], 12.0);
+}
+
+#[tokio::test]
+async fn test_premium_is_added_to_the_amount_due() {
+    let quote = quote();
+    let due = quote.total_due;
+    assert_eq!(quote.insurance_premium, 0.0);
+
+    let insured = quote.with_insurance_premium(5.0);
+    assert_eq!((insured.insurance_premium, insured.total_due), (5.0, due + 5.0));
+
+    // Replacing the premium does not count the old one twice
+    let upgraded = insured.with_insurance_premium(12.0);
+    assert_eq!(upgraded.total_due, due + 12.0);
+    assert_eq!(upgraded.with_insurance_premium(0.0).total_due, due);
+}
+
+#[tokio::test]
+async fn test_quotes_saved_without_a_premium_still_load() {
+    let mut saved = serde_json::to_value(quote()).unwrap();
+    saved.as_object_mut().unwrap().remove("insurance_premium");
+
+    let loaded: PriceQuote = serde_json::from_value(saved).unwrap();
+    assert_eq!(loaded.insurance_premium, 0.0);
+}
@@ -0,0 +1,28 @@
+use monolith_server::inventory::{default_unit_labels, pick_free_unit, AddUnitRequest};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_default_unit_labels_continue_numbering() {
+    assert_eq!(default_unit_labels(0, 2), vec!["Unit 1".to_string(), "Unit 2".to_string()]);
+    assert_eq!(default_unit_labels(3, 1), vec!["Unit 4".to_string()]);
+    assert!(default_unit_labels(3, 0).is_empty());
+}
+
+#[tokio::test]
+async fn test_pick_free_unit_skips_busy_units() {
+    let units = vec![Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+
+    assert_eq!(pick_free_unit(&units, &[]), Some(units[0]));
+    assert_eq!(pick_free_unit(&units, &[units[0], units[2]]), Some(units[1]));
+    assert_eq!(pick_free_unit(&units, &units), None);
+    assert_eq!(pick_free_unit(&[], &[]), None);
+}
+
+#[tokio::test]
+async fn test_add_unit_request_label_is_optional() {
+    let request: AddUnitRequest = serde_json::from_str("{}").unwrap();
+    assert!(request.label.is_none());
+
+    let request: AddUnitRequest = serde_json::from_str(r#"{ "label": "Red kayak" }"#).unwrap();
+    assert_eq!(request.label.as_deref(), Some("Red kayak"));
+}
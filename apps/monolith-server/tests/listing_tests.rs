@@ -0,0 +1,50 @@
+use monolith_server::listing::{publish_blockers, ChangeListingStatusRequest, ListingStatus};
+
+#[tokio::test]
+async fn test_listing_status_round_trips_through_strings() {
+    for status in [
+        ListingStatus::Draft,
+        ListingStatus::PendingReview,
+        ListingStatus::Active,
+        ListingStatus::Paused,
+        ListingStatus::Archived,
+    ] {
+        assert_eq!(status.as_str().parse::<ListingStatus>(), Ok(status));
+        assert_eq!(serde_json::to_value(status).unwrap(), serde_json::json!(status.as_str()));
+    }
+
+    assert!("deleted".parse::<ListingStatus>().is_err());
+}
+
+#[tokio::test]
+async fn test_listing_transitions() {
+    assert!(ListingStatus::Draft.can_transition_to(ListingStatus::PendingReview));
+    assert!(ListingStatus::PendingReview.can_transition_to(ListingStatus::Active));
+    assert!(ListingStatus::Active.can_transition_to(ListingStatus::Paused));
+    assert!(ListingStatus::Paused.can_transition_to(ListingStatus::Active));
+    assert!(ListingStatus::Paused.can_transition_to(ListingStatus::Archived));
+
+    // Drafts cannot skip review, and archived listings stay archived
+    assert!(!ListingStatus::Draft.can_transition_to(ListingStatus::Active));
+    assert!(!ListingStatus::Paused.can_transition_to(ListingStatus::Draft));
+    assert!(ListingStatus::Archived.allowed_transitions().is_empty());
+}
+
+#[tokio::test]
+async fn test_publish_blockers() {
+    let address = serde_json::json!({ "city": "Berlin", "country": "DE" });
+    assert!(publish_blockers(2, 25.0, Some(&address)).is_empty());
+
+    let missing = publish_blockers(0, 0.0, Some(&serde_json::json!({ "city": "Berlin" })));
+    assert_eq!(missing.len(), 3);
+    assert_eq!(publish_blockers(1, 10.0, None).len(), 1);
+}
+
+#[tokio::test]
+async fn test_change_listing_status_request_deserializes() {
+    let request: ChangeListingStatusRequest =
+        serde_json::from_str(r#"{ "status": "pending_review" }"#).unwrap();
+    assert_eq!(request.status, ListingStatus::PendingReview);
+
+    assert!(serde_json::from_str::<ChangeListingStatusRequest>(r#"{ "status": "deleted" }"#).is_err());
+}
@@ -44,26 +44,30 @@ async fn test_send_message_request_default_type() {
 async fn test_conversation_filters_validation() {
     let filters = ConversationFilters {
         status: Some("active".to_string()),
+        cursor: Some("opaque".to_string()),
         limit: Some(20),
-        offset: Some(0),
+        include_total: Some(false),
     };
 
     assert_eq!(filters.status, Some("active".to_string()));
+    assert_eq!(filters.cursor.as_deref(), Some("opaque"));
     assert_eq!(filters.limit, Some(20));
-    assert_eq!(filters.offset, Some(0));
+    assert_eq!(filters.include_total, Some(false));
 }
 
 #[tokio::test]
 async fn test_conversation_filters_defaults() {
     let filters = ConversationFilters {
         status: None,
+        cursor: None,
         limit: None,
-        offset: None,
+        include_total: None,
     };
 
     assert_eq!(filters.status, None);
+    assert_eq!(filters.cursor, None);
     assert_eq!(filters.limit, None);
-    assert_eq!(filters.offset, None);
+    assert_eq!(filters.include_total, None);
 }
 
 #[tokio::test]
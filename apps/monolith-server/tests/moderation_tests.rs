@@ -0,0 +1,112 @@
+use monolith_server::moderation::{
+    default_pipeline, ClassifierStub, ContactInfoScanner, ContentScanner, KeywordRule, KeywordScanner,
+    ListingContent, ModerationDecision, ModerationPipeline, RegexScanner, ScanFinding,
+};
+
+fn listing(name: &str, description: &str) -> ListingContent {
+    ListingContent {
+        name: name.to_string(),
+        description: Some(description.to_string()),
+        tags: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn test_default_pipeline_approves_clean_listing() {
+    let outcome = default_pipeline().evaluate(&listing("Canon EOS R6", "Full-frame camera with two batteries."));
+
+    assert_eq!(outcome.decision, ModerationDecision::Approve);
+    assert!(outcome.findings.is_empty());
+}
+
+#[tokio::test]
+async fn test_default_pipeline_rejects_prohibited_items() {
+    let outcome = default_pipeline().evaluate(&listing("Hunting handgun", "Comes with case"));
+    assert_eq!(outcome.decision, ModerationDecision::Reject);
+
+    let outcome = default_pipeline().evaluate(&listing("Costume kit", "Includes a fake ID for the party"));
+    assert_eq!(outcome.decision, ModerationDecision::Reject);
+    assert_eq!(outcome.findings[0].rule, "fake_documents");
+}
+
+#[tokio::test]
+async fn test_keyword_scanner_matches_whole_words_only() {
+    let scanner = KeywordScanner::new(vec![KeywordRule {
+        keyword: "Pepper Spray".to_string(),
+        decision: ModerationDecision::Flag,
+    }]);
+
+    assert_eq!(scanner.scan(&listing("Self defence", "pepper-spray included")).len(), 1);
+    assert!(scanner.scan(&listing("Salt and pepper sprayer", "")).is_empty());
+}
+
+#[tokio::test]
+async fn test_contact_info_is_flagged() {
+    let scanner = ContactInfoScanner::new();
+
+    let findings = scanner.scan(&listing("Tent", "Email me at camper@example.com or call +49 170 1234567"));
+    let rules: Vec<&str> = findings.iter().map(|f| f.rule.as_str()).collect();
+    assert_eq!(rules, vec!["email", "phone"]);
+    assert!(findings.iter().all(|f| f.decision == ModerationDecision::Flag));
+
+    assert!(scanner.scan(&listing("Tent", "Sleeps 4, weighs 3.5 kg")).is_empty());
+}
+
+#[tokio::test]
+async fn test_phone_numbers_are_told_apart_from_specs() {
+    let scanner = ContactInfoScanner::new();
+
+    for text in ["Call +49 170 1234567", "Mobile 0170 1234567", "Ring (555) 123-4567"] {
+        assert_eq!(scanner.scan(&listing("Tent", text)).len(), 1, "{} should be flagged", text);
+    }
+    for text in [
+        "Model WX-2000-4500-B",
+        "Measures 120 x 60 x 75 cm",
+        "EAN 4006381333931",
+        "Fits the 2019 2020 2021 models",
+    ] {
+        assert!(scanner.scan(&listing("Tent", text)).is_empty(), "{} should not be flagged", text);
+    }
+}
+
+#[tokio::test]
+async fn test_classifier_stub_scores_scam_signals() {
+    let classifier = ClassifierStub::default();
+
+    assert_eq!(classifier.score(&listing("Drill", "Cordless drill, two bits")), 0.0);
+
+    let scam = listing("CHEAP CAMERA BEST DEAL EVER", "Pay by western union or gift card, deposit first!!!!!");
+    assert!(classifier.score(&scam) >= classifier.reject_threshold);
+    assert_eq!(classifier.scan(&scam)[0].decision, ModerationDecision::Reject);
+}
+
+struct AlwaysFlag;
+
+impl ContentScanner for AlwaysFlag {
+    fn name(&self) -> &'static str {
+        "always_flag"
+    }
+
+    fn scan(&self, _content: &ListingContent) -> Vec<ScanFinding> {
+        vec![ScanFinding {
+            scanner: self.name().to_string(),
+            rule: "test".to_string(),
+            decision: ModerationDecision::Flag,
+            excerpt: String::new(),
+        }]
+    }
+}
+
+#[tokio::test]
+async fn test_pipeline_takes_strictest_decision_from_custom_scanners() {
+    let regex = RegexScanner::new(&[("forbidden", r"(?i)\bforbidden\b", ModerationDecision::Reject)]).unwrap();
+    let pipeline = ModerationPipeline::new().with_scanner(AlwaysFlag).with_scanner(regex);
+
+    assert_eq!(pipeline.evaluate(&listing("Bike", "")).decision, ModerationDecision::Flag);
+
+    let outcome = pipeline.evaluate(&listing("Forbidden bike", ""));
+    assert_eq!(outcome.decision, ModerationDecision::Reject);
+    assert_eq!(outcome.findings.len(), 2);
+
+    assert!(RegexScanner::new(&[("broken", "(", ModerationDecision::Flag)]).is_err());
+}
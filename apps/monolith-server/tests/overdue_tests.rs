@@ -0,0 +1,54 @@
+use chrono::{Duration, TimeZone, Utc};
+use monolith_server::overdue::{fee_days_due, plan_late_fees, LateFeeCharge, LateFeePolicy};
+
+#[tokio::test]
+async fn test_policy_defaults_and_validation() {
+    let policy: LateFeePolicy = serde_json::from_str("{}").unwrap();
+    assert_eq!(policy, LateFeePolicy::default());
+
+    let partial: LateFeePolicy = serde_json::from_str(r#"{"daily_fee": 12.5}"#).unwrap();
+    assert_eq!((partial.grace_hours, partial.daily_fee, partial.max_fee_days), (2, Some(12.5), 14));
+    assert!(partial.validate().is_ok());
+
+    assert!(LateFeePolicy { grace_hours: 73, ..LateFeePolicy::default() }.validate().is_err());
+    assert!(LateFeePolicy { daily_fee: Some(-1.0), ..LateFeePolicy::default() }.validate().is_err());
+    assert!(LateFeePolicy { daily_fee: Some(f64::INFINITY), ..LateFeePolicy::default() }.validate().is_err());
+    assert!(LateFeePolicy { max_fee_days: 0, ..LateFeePolicy::default() }.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_daily_fee_falls_back_to_daily_price() {
+    assert_eq!(LateFeePolicy::default().daily_fee_cents(39.99), 3999);
+    let policy = LateFeePolicy { daily_fee: Some(15.0), ..LateFeePolicy::default() };
+    assert_eq!(policy.daily_fee_cents(39.99), 1500);
+}
+
+#[tokio::test]
+async fn test_fee_days_start_after_grace_and_are_capped() {
+    let end = Utc.with_ymd_and_hms(2030, 6, 10, 10, 0, 0).unwrap();
+    let policy = LateFeePolicy::default();
+
+    assert_eq!(fee_days_due(end, &policy, end + Duration::hours(2)), 0);
+    assert_eq!(fee_days_due(end, &policy, end + Duration::hours(2) + Duration::seconds(1)), 1);
+    assert_eq!(fee_days_due(end, &policy, end + Duration::hours(26)), 1);
+    assert_eq!(fee_days_due(end, &policy, end + Duration::hours(27)), 2);
+    assert_eq!(fee_days_due(end, &policy, end + Duration::days(90)), 14);
+}
+
+#[tokio::test]
+async fn test_late_fees_come_from_the_deposit_first() {
+    let plan = plan_late_fees(0, 3, 2000, 3000);
+    assert_eq!(
+        plan,
+        vec![
+            LateFeeCharge { fee_day: 1, amount_cents: 2000, from_deposit_cents: 2000, from_payment_method_cents: 0 },
+            LateFeeCharge { fee_day: 2, amount_cents: 2000, from_deposit_cents: 1000, from_payment_method_cents: 1000 },
+            LateFeeCharge { fee_day: 3, amount_cents: 2000, from_deposit_cents: 0, from_payment_method_cents: 2000 },
+        ]
+    );
+
+    // Days already charged on an earlier run are skipped
+    let rest = plan_late_fees(2, 4, 2000, 0);
+    assert_eq!(rest.iter().map(|c| c.fee_day).collect::<Vec<_>>(), vec![3, 4]);
+    assert!(plan_late_fees(4, 4, 2000, 0).is_empty());
+}
@@ -0,0 +1,75 @@
+use monolith_server::pagination::{decode_cursor, page_size, Cursor, CursorError, CursorPage, MAX_PAGE_SIZE};
+use chrono::{Duration, TimeZone, Utc};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_cursor_round_trip() {
+    let cursor = Cursor::new("rentals", Utc.with_ymd_and_hms(2026, 10, 18, 9, 30, 0).unwrap(), Uuid::new_v4());
+
+    let token = cursor.encode();
+    assert!(!token.contains("rentals"));
+    assert_eq!(Cursor::decode(&token, "rentals"), Ok(cursor));
+}
+
+#[tokio::test]
+async fn test_cursor_rejects_tampering_and_other_lists() {
+    let token = Cursor::new("rentals", Utc::now(), Uuid::new_v4()).encode();
+
+    assert_eq!(Cursor::decode(&token, "messages"), Err(CursorError::WrongScope));
+    assert_eq!(Cursor::decode("not-a-cursor", "rentals"), Err(CursorError::Invalid));
+
+    // Editing the payload invalidates the signature
+    let mut parts: Vec<String> = token.split('.').map(str::to_string).collect();
+    let replacement = if parts[1].starts_with('x') { "y" } else { "x" };
+    parts[1].replace_range(0..1, replacement);
+    assert_eq!(Cursor::decode(&parts.join("."), "rentals"), Err(CursorError::Invalid));
+
+    assert_eq!(decode_cursor(None, "rentals"), Ok(None));
+}
+
+#[tokio::test]
+async fn test_cursor_carries_excluded_rows() {
+    let promoted = vec![Uuid::new_v4(), Uuid::new_v4()];
+    let cursor = Cursor::new("products", Utc::now(), Uuid::new_v4()).excluding(promoted.clone());
+
+    let decoded = Cursor::decode(&cursor.encode(), "products").unwrap();
+    assert_eq!(decoded.exclude, promoted);
+
+    let plain = Cursor::decode(&Cursor::new("products", Utc::now(), Uuid::new_v4()).encode(), "products").unwrap();
+    assert!(plain.exclude.is_empty());
+}
+
+#[tokio::test]
+async fn test_page_size_is_clamped() {
+    assert_eq!(page_size(None, 20), 20);
+    assert_eq!(page_size(Some(0), 20), 1);
+    assert_eq!(page_size(Some(5000), 20), MAX_PAGE_SIZE);
+    assert_eq!(page_size(Some(-10), 20), 1);
+}
+
+#[tokio::test]
+async fn test_from_rows_sets_next_cursor_only_when_more_rows_exist() {
+    let start = Utc::now();
+    let rows: Vec<(chrono::DateTime<Utc>, Uuid)> =
+        (0..3).map(|i| (start - Duration::minutes(i), Uuid::new_v4())).collect();
+
+    let page = CursorPage::from_rows(rows.clone(), 2, "messages", |r| *r);
+    assert_eq!(page.items.len(), 2);
+    let next = Cursor::decode(page.next_cursor.as_deref().unwrap(), "messages").unwrap();
+    assert_eq!((next.sort_key, next.id), rows[1]);
+
+    let last_page = CursorPage::from_rows(rows, 3, "messages", |r| *r).with_total(Some(3));
+    assert_eq!(last_page.items.len(), 3);
+    assert!(last_page.next_cursor.is_none());
+    assert_eq!(last_page.total, Some(3));
+}
+
+#[tokio::test]
+async fn test_total_is_omitted_unless_requested() {
+    let page: CursorPage<i32> = CursorPage::from_rows(vec![1], 20, "products", |_| (Utc::now(), Uuid::nil()));
+    let value = serde_json::to_value(&page).unwrap();
+
+    assert_eq!(value["items"], serde_json::json!([1]));
+    assert!(value["next_cursor"].is_null());
+    assert!(value.get("total").is_none());
+}
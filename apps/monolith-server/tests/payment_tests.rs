@@ -1,5 +1,6 @@
 use monolith_server::payment::{
-    CreatePaymentMethodRequest, CreatePaymentIntentRequest, ConfirmPaymentIntentRequest, PaymentFilters,
+    allocate_refund, CreatePaymentMethodRequest, CreatePaymentIntentRequest, ConfirmPaymentIntentRequest,
+    PaymentFilters, RefundableCharge,
 };
 use uuid::Uuid;
 
@@ -25,6 +26,7 @@ async fn test_create_payment_intent_request_validation() {
     let request = CreatePaymentIntentRequest {
         rental_id: Some(Uuid::new_v4()),
         subscription_id: None,
+        promotion_id: None,
         amount_cents: 5000,
         currency: Some("USD".to_string()),
         payment_method_id: Some(Uuid::new_v4()),
@@ -55,6 +57,8 @@ async fn test_payment_filters_validation() {
         payment_type: Some("card".to_string()),
         limit: Some(20),
         offset: Some(0),
+        cursor: None,
+        include_total: None,
     };
 
     assert_eq!(filters.status, Some("succeeded".to_string()));
@@ -68,6 +72,7 @@ async fn test_payment_amount_validation() {
     let request = CreatePaymentIntentRequest {
         rental_id: Some(Uuid::new_v4()),
         subscription_id: None,
+        promotion_id: None,
         amount_cents: 100,
         currency: Some("USD".to_string()),
         payment_method_id: None,
@@ -84,6 +89,7 @@ async fn test_payment_currency_validation() {
     let request = CreatePaymentIntentRequest {
         rental_id: Some(Uuid::new_v4()),
         subscription_id: None,
+        promotion_id: None,
         amount_cents: 1000,
         currency: Some("USD".to_string()),
         payment_method_id: None,
@@ -92,3 +98,26 @@ async fn test_payment_currency_validation() {
 
     assert!(valid_currencies.contains(&request.currency.as_ref().unwrap().as_str()));
 }
+
+#[tokio::test]
+async fn test_allocate_refund_spreads_newest_first() {
+    let charge = |amount_cents: i64, refundable_cents: i64| RefundableCharge {
+        payment_intent_id: Uuid::new_v4(),
+        amount_cents,
+        refundable_cents,
+        currency: "USD".to_string(),
+        provider_charge_id: "ch_test".to_string(),
+    };
+    let charges = vec![charge(10000, 10000), charge(3000, 1000)];
+
+    assert_eq!(allocate_refund(&charges, 500), vec![(charges[1].payment_intent_id, 500)]);
+    assert_eq!(
+        allocate_refund(&charges, 4000),
+        vec![(charges[1].payment_intent_id, 1000), (charges[0].payment_intent_id, 3000)]
+    );
+
+    // More than was paid only refunds what is left
+    let all: i64 = allocate_refund(&charges, 50000).iter().map(|(_, cents)| cents).sum();
+    assert_eq!(all, 11000);
+    assert!(allocate_refund(&charges, 0).is_empty());
+}
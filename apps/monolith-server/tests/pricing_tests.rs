@@ -0,0 +1,178 @@
+use monolith_server::pricing::{
+    billable_days, compute_quote, LengthOfStayDiscount, PricingError, PricingRules, QuoteRequest,
+    SeasonalOverride,
+};
+use uuid::Uuid;
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+
+#[tokio::test]
+async fn test_quote_request_validation() {
+    let start = Utc::now() + Duration::days(1);
+    let request = QuoteRequest {
+        product_id: Uuid::new_v4(),
+        rental_period_start: start,
+        rental_period_end: start + Duration::days(3),
+        delivery_requested: Some(true),
+    };
+
+    assert!(request.rental_period_start < request.rental_period_end);
+    assert_eq!(request.delivery_requested, Some(true));
+}
+
+#[tokio::test]
+async fn test_billable_days_rounds_partial_days_up() {
+    // 2024-06-03 is a Monday
+    let start = Utc.with_ymd_and_hms(2024, 6, 3, 10, 0, 0).unwrap();
+
+    assert_eq!(billable_days(start, start + Duration::days(2)).unwrap(), 2);
+    assert_eq!(billable_days(start, start + Duration::hours(50)).unwrap(), 3);
+    assert_eq!(billable_days(start, start), Err(PricingError::InvalidPeriod));
+}
+
+#[tokio::test]
+async fn test_flat_daily_quote_breakdown() {
+    let start = Utc.with_ymd_and_hms(2024, 6, 3, 10, 0, 0).unwrap();
+    let quote = compute_quote(
+        Uuid::new_v4(),
+        20.0,
+        Some(100.0),
+        &PricingRules::default(),
+        start,
+        start + Duration::days(3),
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(quote.days, 3);
+    assert_eq!(quote.base_amount, 60.0);
+    assert!(quote.discounts.is_empty());
+    assert_eq!(quote.subtotal, 60.0);
+    assert_eq!(quote.platform_commission, 6.0);
+    assert_eq!(quote.tax, 12.54);
+    assert_eq!(quote.total, 78.54);
+    assert_eq!(quote.deposit, 100.0);
+    assert_eq!(quote.total_due, 178.54);
+}
+
+#[tokio::test]
+async fn test_weekly_rate_and_length_of_stay_discount() {
+    let start = Utc.with_ymd_and_hms(2024, 6, 3, 10, 0, 0).unwrap();
+    let rules = PricingRules {
+        weekly_price: Some(100.0),
+        length_of_stay_discounts: vec![
+            LengthOfStayDiscount { min_days: 3, percent: 5.0 },
+            LengthOfStayDiscount { min_days: 7, percent: 10.0 },
+        ],
+        ..PricingRules::default()
+    };
+
+    let quote = compute_quote(Uuid::new_v4(), 20.0, None, &rules, start, start + Duration::days(8), false).unwrap();
+
+    // 8 nights at 20 = 160; one week package + 1 day = 120; then 10% off 120
+    assert_eq!(quote.base_amount, 160.0);
+    assert_eq!(quote.discounts.len(), 2);
+    assert_eq!(quote.discounts[0].amount, 40.0);
+    assert_eq!(quote.discounts[1].amount, 12.0);
+    assert_eq!(quote.subtotal, 108.0);
+}
+
+#[tokio::test]
+async fn test_weekend_and_seasonal_overrides() {
+    // Friday to Monday: Fri, Sat, Sun
+    let start = Utc.with_ymd_and_hms(2024, 6, 7, 10, 0, 0).unwrap();
+    let rules = PricingRules {
+        weekend_daily_price: Some(30.0),
+        seasonal_overrides: vec![SeasonalOverride {
+            label: Some("Summer festival".to_string()),
+            start_date: NaiveDate::from_ymd_opt(2024, 6, 9).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2024, 6, 9).unwrap(),
+            daily_price: 50.0,
+        }],
+        ..PricingRules::default()
+    };
+
+    let quote = compute_quote(Uuid::new_v4(), 20.0, None, &rules, start, start + Duration::days(3), false).unwrap();
+
+    assert_eq!(quote.base_amount, 20.0 + 30.0 + 50.0);
+}
+
+#[tokio::test]
+async fn test_fees_are_itemized() {
+    let start = Utc.with_ymd_and_hms(2024, 6, 3, 10, 0, 0).unwrap();
+    let rules = PricingRules {
+        cleaning_fee: Some(15.0),
+        delivery_fee: Some(25.0),
+        ..PricingRules::default()
+    };
+
+    let without_delivery = compute_quote(Uuid::new_v4(), 20.0, None, &rules, start, start + Duration::days(1), false).unwrap();
+    let with_delivery = compute_quote(Uuid::new_v4(), 20.0, None, &rules, start, start + Duration::days(1), true).unwrap();
+
+    assert_eq!(without_delivery.fees.len(), 1);
+    assert_eq!(without_delivery.subtotal, 35.0);
+    assert_eq!(with_delivery.fees.len(), 2);
+    assert_eq!(with_delivery.subtotal, 60.0);
+}
+
+#[tokio::test]
+async fn test_pricing_rules_validation() {
+    let invalid = PricingRules {
+        length_of_stay_discounts: vec![LengthOfStayDiscount { min_days: 0, percent: 150.0 }],
+        ..PricingRules::default()
+    };
+
+    assert!(PricingRules::default().validate().is_ok());
+    assert!(invalid.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_package_prices_must_be_above_zero() {
+    let free_week = PricingRules { weekly_price: Some(0.0), ..PricingRules::default() };
+    let free_month = PricingRules { monthly_price: Some(0.0), ..PricingRules::default() };
+    assert!(free_week.validate().is_err());
+    assert!(free_month.validate().is_err());
+
+    // Rules that skipped validation are priced nightly
+    let start = Utc.with_ymd_and_hms(2024, 6, 3, 10, 0, 0).unwrap();
+    let quote = compute_quote(Uuid::new_v4(), 20.0, None, &free_week, start, start + Duration::days(7), false).unwrap();
+    assert_eq!(quote.base_amount, 140.0);
+    assert!(quote.discounts.is_empty());
+}
+
+#[tokio::test]
+async fn test_days_after_a_package_keep_weekend_rates() {
+    // 2024-06-01 is a Saturday
+    let start = Utc.with_ymd_and_hms(2024, 6, 1, 10, 0, 0).unwrap();
+    let rules = PricingRules {
+        weekly_price: Some(100.0),
+        weekend_daily_price: Some(30.0),
+        ..PricingRules::default()
+    };
+
+    // The package covers Saturday to Friday; the 8th day is a Saturday again
+    let quote = compute_quote(Uuid::new_v4(), 20.0, None, &rules, start, start + Duration::days(8), false).unwrap();
+
+    // Nightly: 3 weekend days at 30 and 5 weekdays at 20 = 190; package 100 + 30
+    assert_eq!(quote.base_amount, 190.0);
+    assert_eq!(quote.discounts[0].amount, 60.0);
+}
+
+#[tokio::test]
+async fn test_rules_saved_without_lists_still_load() {
+    let rules: PricingRules = serde_json::from_str(r#"{"weekly_price": 100.0}"#).unwrap();
+    assert!(rules.seasonal_overrides.is_empty());
+    assert!(rules.length_of_stay_discounts.is_empty());
+
+    let season = |daily_price| PricingRules {
+        seasonal_overrides: vec![SeasonalOverride {
+            label: None,
+            start_date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2024, 6, 30).unwrap(),
+            daily_price,
+        }],
+        ..PricingRules::default()
+    };
+    assert!(season(25.0).validate().is_ok());
+    assert!(season(0.0).validate().is_err());
+    assert!(season(f64::NAN).validate().is_err());
+}
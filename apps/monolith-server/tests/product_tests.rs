@@ -1,4 +1,7 @@
-use monolith_server::product::{CreateProductRequest, UpdateProductRequest, ProductFilters, CreateCategoryRequest};
+use monolith_server::product::{CreateProductRequest, UpdateProductRequest, ProductFilters};
+use monolith_server::category::CreateCategoryRequest;
+use monolith_server::listing::ListingStatus;
+use monolith_server::cancellation::CancellationPolicy;
 use uuid::Uuid;
 use chrono::Utc;
 
@@ -20,11 +23,17 @@ async fn test_create_product_request_validation() {
             "country": "Test Country"
         })),
         tags: Some(vec!["electronics".to_string(), "test".to_string()]),
+        quantity: Some(5),
+        cancellation_policy: None,
+        instant_book: None,
+        instant_book_requirements: None,
+        late_fee_policy: None,
     };
 
     assert_eq!(request.name, "Test Product");
     assert_eq!(request.daily_price, 25.0);
     assert_eq!(request.tags.as_ref().unwrap().len(), 2);
+    assert_eq!(request.quantity, Some(5));
 }
 
 #[tokio::test]
@@ -38,12 +47,18 @@ async fn test_update_product_request_validation() {
         insurance_required: Some(true),
         specifications: None,
         address: None,
-        status: Some("active".to_string()),
+        status: Some(ListingStatus::Active),
+        tags: None,
+        cancellation_policy: Some(CancellationPolicy::Strict),
+        instant_book: Some(true),
+        instant_book_requirements: None,
+        late_fee_policy: None,
     };
 
     assert_eq!(request.name.as_ref().unwrap(), "Updated Product");
     assert_eq!(request.daily_price.unwrap(), 30.0);
     assert_eq!(request.insurance_required.unwrap(), true);
+    assert_eq!(request.cancellation_policy, Some(CancellationPolicy::Strict));
 }
 
 #[tokio::test]
@@ -53,8 +68,13 @@ async fn test_product_filters_validation() {
         min_price: Some(10.0),
         max_price: Some(100.0),
         search: Some("test".to_string()),
-        page: Some(1),
-        per_page: Some(20),
+        specs: None,
+        lat: None,
+        lng: None,
+        radius_km: None,
+        cursor: None,
+        limit: Some(20),
+        include_total: None,
     };
 
     assert!(filters.category_id.is_some());
@@ -69,6 +89,10 @@ async fn test_create_category_request_validation() {
         name: "Electronics".to_string(),
         description: Some("Electronic devices and gadgets".to_string()),
         parent_category_id: None,
+        slug: None,
+        icon: None,
+        sort_order: None,
+        spec_schema: None,
     };
 
     assert_eq!(request.name, "Electronics");
@@ -88,6 +112,11 @@ async fn test_product_price_validation() {
         specifications: None,
         address: None,
         tags: None,
+        quantity: None,
+        cancellation_policy: None,
+        instant_book: None,
+        instant_book_requirements: None,
+        late_fee_policy: None,
     };
 
     assert!(valid_request.daily_price > 0.0);
@@ -110,6 +139,11 @@ async fn test_product_tags_validation() {
             "gaming".to_string(),
             "portable".to_string(),
         ]),
+        quantity: None,
+        cancellation_policy: None,
+        instant_book: None,
+        instant_book_requirements: None,
+        late_fee_policy: None,
     };
 
     let tags = request.tags.unwrap();
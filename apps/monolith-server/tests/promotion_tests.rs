@@ -0,0 +1,61 @@
+use monolith_server::promotion::{
+    click_through_rate, promotion_price_cents, CreatePromotionRequest, PromotionError,
+    MAX_PROMOTION_DAYS, PROMOTION_DAILY_RATE_CENTS,
+};
+use chrono::{Duration, NaiveDate};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[tokio::test]
+async fn test_promotion_price_counts_both_end_dates() {
+    let today = date(2026, 10, 18);
+
+    assert_eq!(promotion_price_cents(today, today, today), Ok(PROMOTION_DAILY_RATE_CENTS));
+    assert_eq!(
+        promotion_price_cents(date(2026, 10, 20), date(2026, 10, 26), today),
+        Ok(7 * PROMOTION_DAILY_RATE_CENTS)
+    );
+}
+
+#[tokio::test]
+async fn test_promotion_window_validation() {
+    let today = date(2026, 10, 18);
+
+    assert_eq!(
+        promotion_price_cents(date(2026, 10, 20), date(2026, 10, 19), today),
+        Err(PromotionError::InvalidRange)
+    );
+    assert_eq!(
+        promotion_price_cents(date(2026, 10, 17), date(2026, 10, 19), today),
+        Err(PromotionError::StartsInPast)
+    );
+    assert_eq!(
+        promotion_price_cents(today + Duration::days(91), today + Duration::days(92), today),
+        Err(PromotionError::TooFarAhead)
+    );
+
+    let last_allowed = today + Duration::days(MAX_PROMOTION_DAYS - 1);
+    assert!(promotion_price_cents(today, last_allowed, today).is_ok());
+    assert_eq!(
+        promotion_price_cents(today, last_allowed + Duration::days(1), today),
+        Err(PromotionError::TooLong)
+    );
+}
+
+#[tokio::test]
+async fn test_click_through_rate() {
+    assert_eq!(click_through_rate(0, 0), 0.0);
+    assert_eq!(click_through_rate(200, 3), 0.015);
+    assert_eq!(click_through_rate(3, 1), 0.3333);
+}
+
+#[tokio::test]
+async fn test_create_promotion_request_parses_dates() {
+    let request: CreatePromotionRequest =
+        serde_json::from_str(r#"{ "starts_on": "2026-11-01", "ends_on": "2026-11-07" }"#).unwrap();
+
+    assert_eq!(request.starts_on, date(2026, 11, 1));
+    assert_eq!(request.ends_on, date(2026, 11, 7));
+}
@@ -0,0 +1,231 @@
+//! Double-booking tests against a real database. They run when `DATABASE_URL` points at a
+//! migrated database and are skipped otherwise.
+
+use chrono::{Duration, TimeZone, Utc};
+use monolith_server::rental::{book_rental, BookingError, CreateRentalRequest, EXCLUSION_VIOLATION};
+use monolith_server::rental_status::RentalStatus;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const PARALLEL_BOOKINGS: usize = 8;
+
+async fn test_pool() -> Option<PgPool> {
+    let url = std::env::var("DATABASE_URL").ok().filter(|u| !u.is_empty())?;
+    Some(PgPool::connect(&url).await.expect("DATABASE_URL is set but unreachable"))
+}
+
+async fn insert_user(db: &PgPool) -> Uuid {
+    let user_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO user_schema.users (user_id, email, password_hash) VALUES ($1, $2, 'x')")
+        .bind(user_id)
+        .bind(format!("{}@booking.test", user_id))
+        .execute(db)
+        .await
+        .unwrap();
+    user_id
+}
+
+/// An active single-unit product; returns `(product_id, unit_id)`.
+async fn insert_product(db: &PgPool, owner_id: Uuid) -> (Uuid, Uuid) {
+    insert_listing(db, owner_id, false, serde_json::json!({})).await
+}
+
+async fn insert_listing(db: &PgPool, owner_id: Uuid, instant_book: bool, requirements: serde_json::Value) -> (Uuid, Uuid) {
+    let product_id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO product_schema.products
+        (product_id, owner_id, name, daily_price, status, instant_book, instant_book_requirements)
+        VALUES ($1, $2, 'Tent', 20, 'active', $3, $4)
+        "#,
+    )
+    .bind(product_id)
+    .bind(owner_id)
+    .bind(instant_book)
+    .bind(requirements)
+    .execute(db)
+    .await
+    .unwrap();
+
+    let unit_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO product_schema.product_units (unit_id, product_id, label) VALUES ($1, $2, 'Unit 1')")
+        .bind(unit_id)
+        .bind(product_id)
+        .execute(db)
+        .await
+        .unwrap();
+
+    (product_id, unit_id)
+}
+
+fn request(product_id: Uuid, start_day: u32, days: i64) -> CreateRentalRequest {
+    let start = Utc.with_ymd_and_hms(2030, 6, start_day, 10, 0, 0).unwrap();
+    CreateRentalRequest {
+        product_id,
+        rental_period_start: start,
+        rental_period_end: start + Duration::days(days),
+        pickup_notes: None,
+        return_notes: None,
+        delivery_requested: None,
+        insurance_option: None,
+    }
+}
+
+#[tokio::test]
+async fn test_parallel_bookings_of_one_unit_yield_one_rental() {
+    let Some(db) = test_pool().await else { return };
+    let owner_id = insert_user(&db).await;
+    let (product_id, _) = insert_product(&db, owner_id).await;
+
+    let mut handles = Vec::new();
+    for i in 0..PARALLEL_BOOKINGS {
+        let db = db.clone();
+        let renter_id = insert_user(&db).await;
+        // Every request overlaps every other one
+        let req = request(product_id, 10 + (i % 3) as u32, 3);
+        handles.push(tokio::spawn(async move { book_rental(&db, renter_id, &req).await }));
+    }
+
+    let mut booked = 0;
+    for handle in handles {
+        match handle.await.unwrap() {
+            Ok(_) => booked += 1,
+            Err(e @ (BookingError::Unavailable | BookingError::Overlap)) => {
+                assert_eq!(e.status(), axum::http::StatusCode::CONFLICT)
+            }
+            Err(e) => panic!("unexpected booking error: {:?}", e),
+        }
+    }
+    assert_eq!(booked, 1);
+}
+
+#[tokio::test]
+async fn test_back_to_back_bookings_do_not_conflict() {
+    let Some(db) = test_pool().await else { return };
+    let owner_id = insert_user(&db).await;
+    let (product_id, _) = insert_product(&db, owner_id).await;
+
+    let first = request(product_id, 1, 3);
+    let mut second = request(product_id, 4, 2);
+    second.rental_period_start = first.rental_period_end;
+
+    assert!(book_rental(&db, insert_user(&db).await, &first).await.is_ok());
+    assert!(book_rental(&db, insert_user(&db).await, &second).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_exclusion_constraint_rejects_parallel_raw_inserts() {
+    let Some(db) = test_pool().await else { return };
+    let owner_id = insert_user(&db).await;
+    let renter_id = insert_user(&db).await;
+    let (product_id, unit_id) = insert_product(&db, owner_id).await;
+
+    // Bypass unit assignment entirely: only the constraint stands between these inserts
+    let mut handles = Vec::new();
+    for i in 0..PARALLEL_BOOKINGS {
+        let db = db.clone();
+        let req = request(product_id, 20, 1 + (i % 4) as i64);
+        handles.push(tokio::spawn(async move {
+            sqlx::query(
+                r#"
+                INSERT INTO rental_schema.rentals (product_id, renter_id, unit_id, rental_period_start, rental_period_end)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(product_id)
+            .bind(renter_id)
+            .bind(unit_id)
+            .bind(req.rental_period_start)
+            .bind(req.rental_period_end)
+            .execute(&db)
+            .await
+        }));
+    }
+
+    let mut inserted = 0;
+    for handle in handles {
+        match handle.await.unwrap() {
+            Ok(_) => inserted += 1,
+            Err(sqlx::Error::Database(e)) => assert_eq!(e.code().as_deref(), Some(EXCLUSION_VIOLATION)),
+            Err(e) => panic!("unexpected error: {:?}", e),
+        }
+    }
+    assert_eq!(inserted, 1);
+}
+
+#[tokio::test]
+async fn test_bookings_are_requests_unless_instant_book() {
+    let Some(db) = test_pool().await else { return };
+    let owner_id = insert_user(&db).await;
+    let (product_id, _) = insert_product(&db, owner_id).await;
+
+    let booking = book_rental(&db, insert_user(&db).await, &request(product_id, 1, 2)).await.unwrap();
+    assert_eq!(booking.status, RentalStatus::Requested);
+    assert!(booking.unmet_requirements.is_empty());
+}
+
+#[tokio::test]
+async fn test_instant_book_falls_back_to_request_for_unqualified_renter() {
+    let Some(db) = test_pool().await else { return };
+    let owner_id = insert_user(&db).await;
+    let (product_id, _) = insert_listing(&db, owner_id, true, serde_json::json!({ "require_verified_id": true })).await;
+
+    let booking = book_rental(&db, insert_user(&db).await, &request(product_id, 1, 2)).await.unwrap();
+    assert_eq!(booking.status, RentalStatus::Requested);
+    assert_eq!(booking.unmet_requirements, vec!["verified_id"]);
+}
+
+#[tokio::test]
+async fn test_instant_book_without_payment_method_books_nothing() {
+    let Some(db) = test_pool().await else { return };
+    let owner_id = insert_user(&db).await;
+    let (product_id, _) = insert_listing(&db, owner_id, true, serde_json::json!({})).await;
+
+    match book_rental(&db, insert_user(&db).await, &request(product_id, 1, 2)).await {
+        Err(e @ BookingError::Payment(..)) => assert_eq!(e.status(), axum::http::StatusCode::PAYMENT_REQUIRED),
+        other => panic!("expected a payment error, got {:?}", other),
+    }
+
+    let rentals: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM rental_schema.rentals WHERE product_id = $1")
+        .bind(product_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+    assert_eq!(rentals, 0);
+}
+
+#[tokio::test]
+async fn test_listing_that_requires_insurance_books_only_with_a_policy() {
+    let Some(db) = test_pool().await else { return };
+    let owner_id = insert_user(&db).await;
+    let (product_id, _) = insert_product(&db, owner_id).await;
+    sqlx::query("UPDATE product_schema.products SET insurance_required = true WHERE product_id = $1")
+        .bind(product_id)
+        .execute(&db)
+        .await
+        .unwrap();
+    let renter_id = insert_user(&db).await;
+
+    match book_rental(&db, renter_id, &request(product_id, 1, 2)).await {
+        Err(e @ BookingError::InsuranceRequired) => assert_eq!(e.status(), axum::http::StatusCode::BAD_REQUEST),
+        other => panic!("expected insurance to be required, got {:?}", other),
+    }
+
+    let mut unknown = request(product_id, 1, 2);
+    unknown.insurance_option = Some("platinum".to_string());
+    assert!(matches!(book_rental(&db, renter_id, &unknown).await, Err(BookingError::Insurance(_))));
+
+    let mut insured = request(product_id, 1, 2);
+    insured.insurance_option = Some("basic".to_string());
+    let booking = book_rental(&db, renter_id, &insured).await.unwrap();
+    assert!(booking.quote.insurance_premium > 0.0);
+
+    let linked: Option<Uuid> =
+        sqlx::query_scalar("SELECT insurance_policy_id FROM rental_schema.rentals WHERE rental_id = $1")
+            .bind(booking.rental_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+    assert_eq!(linked, booking.insurance_policy_id);
+    assert!(linked.is_some());
+}
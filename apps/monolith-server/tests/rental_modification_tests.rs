@@ -0,0 +1,69 @@
+use chrono::{Duration, TimeZone, Utc};
+use monolith_server::rental_modification::{
+    modifiable, settlement, validate_new_period, ModificationStatus, Settlement,
+};
+use monolith_server::rental_status::RentalStatus;
+
+#[tokio::test]
+async fn test_settlement_charges_or_refunds_the_difference() {
+    assert_eq!(settlement(2500, 10000), Settlement::Charge(2500));
+    assert_eq!(settlement(-2500, 10000), Settlement::Refund(2500));
+    assert_eq!(settlement(0, 10000), Settlement::None);
+
+    // Unpaid rentals just take the new price, and refunds stop at what was paid
+    assert_eq!(settlement(2500, 0), Settlement::None);
+    assert_eq!(settlement(-8000, 5000), Settlement::Refund(5000));
+
+    assert_eq!(
+        serde_json::to_value(Settlement::Charge(2500)).unwrap(),
+        serde_json::json!({ "kind": "charge", "amount_cents": 2500 })
+    );
+}
+
+#[tokio::test]
+async fn test_only_confirmed_and_active_rentals_are_modifiable() {
+    use RentalStatus::*;
+    assert!(modifiable(Confirmed) && modifiable(Active));
+    for status in [Requested, Declined, Returned, Completed, Cancelled, Disputed, Expired] {
+        assert!(!modifiable(status), "{}", status);
+    }
+}
+
+#[tokio::test]
+async fn test_validate_new_period() {
+    let now = Utc.with_ymd_and_hms(2030, 6, 1, 12, 0, 0).unwrap();
+    let start = now + Duration::days(2);
+    let current = (start, start + Duration::days(3));
+
+    // Extending a confirmed rental, or moving it entirely
+    assert!(validate_new_period(RentalStatus::Confirmed, current, (start, start + Duration::days(5)), now).is_ok());
+    let moved = (start + Duration::days(1), start + Duration::days(4));
+    assert!(validate_new_period(RentalStatus::Confirmed, current, moved, now).is_ok());
+
+    assert!(validate_new_period(RentalStatus::Confirmed, current, current, now).is_err());
+    assert!(validate_new_period(RentalStatus::Confirmed, current, (start, start), now).is_err());
+    let past = (now - Duration::days(1), start + Duration::days(3));
+    assert!(validate_new_period(RentalStatus::Confirmed, current, past, now).is_err());
+}
+
+#[tokio::test]
+async fn test_active_rentals_can_only_move_their_end() {
+    let start = Utc.with_ymd_and_hms(2030, 6, 1, 9, 0, 0).unwrap();
+    let now = start + Duration::days(1);
+    let current = (start, start + Duration::days(3));
+
+    assert!(validate_new_period(RentalStatus::Active, current, (start, start + Duration::days(6)), now).is_ok());
+    assert!(validate_new_period(RentalStatus::Active, current, (start, now + Duration::hours(2)), now).is_ok());
+    assert!(validate_new_period(RentalStatus::Active, current, (start, now), now).is_err());
+    let shifted = (start + Duration::hours(1), start + Duration::days(6));
+    assert!(validate_new_period(RentalStatus::Active, current, shifted, now).is_err());
+}
+
+#[tokio::test]
+async fn test_modification_status_round_trips() {
+    use ModificationStatus::*;
+    for status in [Pending, Accepted, Declined, Withdrawn] {
+        assert_eq!(status.as_str().parse::<ModificationStatus>(), Ok(status));
+    }
+    assert!("cancelled".parse::<ModificationStatus>().is_err());
+}
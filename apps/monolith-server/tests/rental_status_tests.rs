@@ -0,0 +1,104 @@
+use monolith_server::rental_status::{acting_role, permitted_roles, RentalRole, RentalStatus, RentalTransitionRequest};
+
+#[tokio::test]
+async fn test_happy_path_transitions() {
+    use RentalStatus::*;
+    let path = [Requested, Confirmed, Active, Returned, Completed];
+    for pair in path.windows(2) {
+        assert!(pair[0].can_transition_to(pair[1]), "{} -> {}", pair[0], pair[1]);
+    }
+    assert!(Completed.is_terminal());
+}
+
+#[tokio::test]
+async fn test_invalid_transitions_are_rejected() {
+    use RentalStatus::*;
+    assert!(!Requested.can_transition_to(Active));
+    assert!(!Active.can_transition_to(Cancelled));
+    assert!(!Declined.can_transition_to(Confirmed));
+    assert!(!Cancelled.can_transition_to(Requested));
+    assert!(Declined.is_terminal() && Cancelled.is_terminal() && Expired.is_terminal());
+}
+
+#[tokio::test]
+async fn test_status_round_trips_through_strings() {
+    use RentalStatus::*;
+    for status in [Requested, Confirmed, Declined, Active, Returned, Completed, Cancelled, Disputed, Expired, Overdue, PendingPayment] {
+        assert_eq!(status.as_str().parse::<RentalStatus>(), Ok(status));
+        assert_eq!(serde_json::to_value(status).unwrap(), serde_json::json!(status.as_str()));
+    }
+    assert!("canceled".parse::<RentalStatus>().is_err());
+}
+
+#[tokio::test]
+async fn test_only_owner_confirms_and_declines() {
+    use RentalStatus::*;
+    assert_eq!(acting_role(&[RentalRole::Renter], Requested, Confirmed), None);
+    assert_eq!(acting_role(&[RentalRole::Owner], Requested, Confirmed), Some(RentalRole::Owner));
+    assert_eq!(acting_role(&[RentalRole::Renter], Requested, Declined), None);
+    assert_eq!(acting_role(&[RentalRole::Owner], Returned, Completed), Some(RentalRole::Owner));
+    assert_eq!(acting_role(&[RentalRole::Renter], Returned, Completed), None);
+    assert_eq!(acting_role(&[RentalRole::Renter], Active, Returned), None);
+    assert_eq!(acting_role(&[RentalRole::Renter, RentalRole::Owner], Active, Returned), Some(RentalRole::Owner));
+}
+
+#[tokio::test]
+async fn test_both_parties_can_cancel_before_pickup() {
+    use RentalStatus::*;
+    for from in [Requested, Confirmed] {
+        assert_eq!(acting_role(&[RentalRole::Renter], from, Cancelled), Some(RentalRole::Renter));
+        assert_eq!(acting_role(&[RentalRole::Owner], from, Cancelled), Some(RentalRole::Owner));
+    }
+}
+
+#[tokio::test]
+async fn test_disputes_are_resolved_by_admins() {
+    use RentalStatus::*;
+    assert_eq!(acting_role(&[RentalRole::Renter], Returned, Disputed), Some(RentalRole::Renter));
+    // A renter still holding the item cannot open one
+    assert_eq!(acting_role(&[RentalRole::Renter], Active, Disputed), None);
+    assert_eq!(acting_role(&[RentalRole::Renter, RentalRole::Owner], Active, Disputed), Some(RentalRole::Owner));
+    assert_eq!(permitted_roles(Disputed, Completed), &[RentalRole::Admin]);
+    assert_eq!(acting_role(&[RentalRole::Owner, RentalRole::Renter], Disputed, Cancelled), None);
+    assert_eq!(acting_role(&[RentalRole::Owner, RentalRole::Admin], Disputed, Cancelled), Some(RentalRole::Admin));
+}
+
+#[tokio::test]
+async fn test_every_allowed_transition_has_a_role() {
+    use RentalStatus::*;
+    for from in [Requested, Confirmed, Declined, Active, Returned, Completed, Cancelled, Disputed, Expired, Overdue, PendingPayment] {
+        for to in from.allowed_transitions() {
+            assert!(permitted_roles(from, *to).contains(&RentalRole::Admin), "{} -> {}", from, to);
+        }
+    }
+    assert!(permitted_roles(Requested, Completed).is_empty());
+}
+
+#[tokio::test]
+async fn test_overdue_rentals_can_still_be_returned() {
+    use RentalStatus::*;
+    assert!(Active.can_transition_to(Overdue));
+    assert!(Overdue.can_transition_to(Returned) && Overdue.can_transition_to(Disputed));
+    assert!(!Overdue.can_transition_to(Completed) && !Overdue.is_terminal());
+    // Only the job (acting as admin) marks a rental overdue
+    assert_eq!(acting_role(&[RentalRole::Owner, RentalRole::Renter], Active, Overdue), None);
+    assert_eq!(acting_role(&[RentalRole::Renter], Overdue, Returned), None);
+    assert_eq!(acting_role(&[RentalRole::Renter, RentalRole::Owner], Overdue, Returned), Some(RentalRole::Owner));
+    assert_eq!(acting_role(&[RentalRole::Renter], Overdue, Disputed), None);
+    assert_eq!(acting_role(&[RentalRole::Owner], Overdue, Disputed), Some(RentalRole::Owner));
+}
+
+#[tokio::test]
+async fn test_pending_payment_is_settled_by_the_system() {
+    use RentalStatus::*;
+    assert!(PendingPayment.can_transition_to(Confirmed) && PendingPayment.can_transition_to(Cancelled));
+    assert!(!PendingPayment.can_transition_to(Active));
+    assert_eq!(acting_role(&[RentalRole::Renter, RentalRole::Owner], PendingPayment, Cancelled), None);
+    assert_eq!("pending_payment".parse::<RentalStatus>(), Ok(PendingPayment));
+}
+
+#[tokio::test]
+async fn test_transition_request_reason_is_optional() {
+    let req: RentalTransitionRequest = serde_json::from_str("{}").unwrap();
+    assert!(req.reason.is_none());
+}
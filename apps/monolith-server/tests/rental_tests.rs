@@ -13,6 +13,8 @@ async fn test_create_rental_request_validation() {
         rental_period_end: end_date,
         pickup_notes: Some("Please call before pickup".to_string()),
         return_notes: Some("Return to same location".to_string()),
+        delivery_requested: None,
+        insurance_option: None,
     };
 
     assert!(request.rental_period_start < request.rental_period_end);
@@ -23,12 +25,10 @@ async fn test_create_rental_request_validation() {
 #[tokio::test]
 async fn test_update_rental_request_validation() {
     let request = UpdateRentalRequest {
-        status: Some("confirmed".to_string()),
         pickup_notes: Some("Updated pickup instructions".to_string()),
         return_notes: None,
     };
 
-    assert_eq!(request.status.as_ref().unwrap(), "confirmed");
     assert_eq!(request.pickup_notes.as_ref().unwrap(), "Updated pickup instructions");
     assert!(request.return_notes.is_none());
 }
@@ -52,13 +52,15 @@ async fn test_availability_request_validation() {
 async fn test_rental_filters_validation() {
     let filters = RentalFilters {
         status: Some("active".to_string()),
-        page: Some(1),
-        per_page: Some(10),
+        cursor: None,
+        limit: Some(10),
+        include_total: Some(true),
     };
 
     assert_eq!(filters.status.as_ref().unwrap(), "active");
-    assert_eq!(filters.page.unwrap(), 1);
-    assert_eq!(filters.per_page.unwrap(), 10);
+    assert!(filters.cursor.is_none());
+    assert_eq!(filters.limit.unwrap(), 10);
+    assert_eq!(filters.include_total, Some(true));
 }
 
 #[tokio::test]
@@ -47,15 +47,16 @@ async fn test_review_filters_validation() {
         rating: Some(5),
         review_type: Some("renter".to_string()),
         status: Some("active".to_string()),
+        cursor: None,
         limit: Some(20),
-        offset: Some(0),
+        include_total: Some(true),
     };
 
     assert!(filters.rating.unwrap() >= 1 && filters.rating.unwrap() <= 5);
     assert_eq!(filters.review_type, Some("renter".to_string()));
     assert_eq!(filters.status, Some("active".to_string()));
     assert_eq!(filters.limit, Some(20));
-    assert_eq!(filters.offset, Some(0));
+    assert_eq!(filters.include_total, Some(true));
 }
 
 #[tokio::test]
@@ -67,8 +68,9 @@ async fn test_review_filters_defaults() {
         rating: None,
         review_type: None,
         status: None,
+        cursor: None,
         limit: None,
-        offset: None,
+        include_total: None,
     };
 
     assert_eq!(filters.product_id, None);
@@ -77,8 +79,9 @@ async fn test_review_filters_defaults() {
     assert_eq!(filters.rating, None);
     assert_eq!(filters.review_type, None);
     assert_eq!(filters.status, None);
+    assert_eq!(filters.cursor, None);
     assert_eq!(filters.limit, None);
-    assert_eq!(filters.offset, None);
+    assert_eq!(filters.include_total, None);
 }
 
 #[tokio::test]
@@ -0,0 +1,106 @@
+use monolith_server::saved_search::{
+    address_location, alert_notification, digest_due, haversine_km, AlertFrequency, CreateSavedSearchRequest,
+    MAX_DIGEST_ITEMS,
+};
+use monolith_server::product::{geo_filter, ProductFilters};
+use monolith_server::notification::{KIND_SAVED_SEARCH_DIGEST, KIND_SAVED_SEARCH_MATCH};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+fn filters() -> ProductFilters {
+    ProductFilters {
+        category_id: None,
+        min_price: None,
+        max_price: None,
+        search: None,
+        specs: None,
+        lat: None,
+        lng: None,
+        radius_km: None,
+        cursor: None,
+        limit: None,
+        include_total: None,
+    }
+}
+
+#[tokio::test]
+async fn test_haversine_distance() {
+    // Berlin to Potsdam is roughly 27 km
+    let d = haversine_km((52.52, 13.405), (52.39, 13.065));
+    assert!((26.0..28.0).contains(&d), "got {}", d);
+    assert_eq!(haversine_km((10.0, 10.0), (10.0, 10.0)), 0.0);
+}
+
+#[tokio::test]
+async fn test_address_location_requires_numeric_coordinates() {
+    let address = serde_json::json!({ "city": "Berlin", "lat": 52.52, "lng": 13.405 });
+    assert_eq!(address_location(Some(&address)), Some((52.52, 13.405)));
+    assert_eq!(address_location(Some(&serde_json::json!({ "lat": "52.5", "lng": 13.4 }))), None);
+    assert_eq!(address_location(None), None);
+}
+
+#[tokio::test]
+async fn test_geo_filter_validation() {
+    assert_eq!(geo_filter(&filters()), Ok(None));
+
+    let mut f = filters();
+    f.lat = Some(52.52);
+    f.lng = Some(13.4);
+    assert_eq!(geo_filter(&f), Ok(Some((52.52, 13.4, 25.0))));
+
+    f.radius_km = Some(1000.0);
+    assert!(geo_filter(&f).is_err());
+
+    let mut lat_only = filters();
+    lat_only.lat = Some(52.52);
+    assert!(geo_filter(&lat_only).is_err());
+}
+
+#[tokio::test]
+async fn test_digest_due_once_a_day() {
+    let now = Utc::now();
+    assert!(digest_due(None, now));
+    assert!(!digest_due(Some(now - Duration::hours(3)), now));
+    assert!(digest_due(Some(now - Duration::hours(24)), now));
+}
+
+#[tokio::test]
+async fn test_alert_notifications() {
+    let user_id = Uuid::new_v4();
+    let search_id = Uuid::new_v4();
+    let product_id = Uuid::new_v4();
+
+    let instant = alert_notification(
+        user_id,
+        search_id,
+        "Drills",
+        &[(product_id, "Cordless Drill".to_string())],
+        AlertFrequency::Instant,
+    );
+    assert_eq!(instant.kind, KIND_SAVED_SEARCH_MATCH);
+    assert_eq!(instant.body, "Cordless Drill");
+    assert_eq!(instant.data["product_ids"], serde_json::json!([product_id]));
+
+    let listings: Vec<(Uuid, String)> =
+        (0..12).map(|i| (Uuid::new_v4(), format!("Drill {}", i))).collect();
+    let digest = alert_notification(user_id, search_id, "Drills", &listings, AlertFrequency::Daily);
+    assert_eq!(digest.kind, KIND_SAVED_SEARCH_DIGEST);
+    assert_eq!(digest.title, "12 new listings for \"Drills\"");
+    assert_eq!(digest.body.matches("Drill ").count(), MAX_DIGEST_ITEMS);
+    assert_eq!(digest.data["product_ids"].as_array().unwrap().len(), 12);
+}
+
+#[tokio::test]
+async fn test_create_request_deserializes_frequency() {
+    let req: CreateSavedSearchRequest = serde_json::from_value(serde_json::json!({
+        "name": "Drills near me",
+        "filters": { "search": "drill", "lat": 52.52, "lng": 13.4, "radius_km": 10.0 },
+        "frequency": "daily"
+    }))
+    .unwrap();
+
+    assert_eq!(req.frequency, Some(AlertFrequency::Daily));
+    assert_eq!(req.filters.radius_km, Some(10.0));
+    assert_eq!("instant".parse::<AlertFrequency>(), Ok(AlertFrequency::Instant));
+    assert!("weekly".parse::<AlertFrequency>().is_err());
+}
@@ -0,0 +1,88 @@
+use monolith_server::similar::{
+    category_proximity, co_rental_signal, distance_similarity, jaccard, price_similarity, rank_similar,
+    similarity_score, text_tokens, ProductFeatures,
+};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+fn features(name: &str, category_chain: Vec<Uuid>, tags: &[&str], daily_price: f64) -> ProductFeatures {
+    ProductFeatures {
+        product_id: Uuid::new_v4(),
+        name: name.to_string(),
+        description: None,
+        category_chain,
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        daily_price,
+        location: Some((52.52, 13.405)),
+    }
+}
+
+#[tokio::test]
+async fn test_category_proximity_halves_per_level() {
+    let (root, drills, saws, cordless) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+    assert_eq!(category_proximity(&[drills, root], &[drills, root]), 1.0);
+    assert_eq!(category_proximity(&[drills, root], &[saws, root]), 0.5);
+    assert_eq!(category_proximity(&[cordless, drills, root], &[saws, root]), 0.25);
+    assert_eq!(category_proximity(&[drills, root], &[Uuid::new_v4()]), 0.0);
+    assert_eq!(category_proximity(&[], &[drills]), 0.0);
+}
+
+#[tokio::test]
+async fn test_set_and_text_similarity() {
+    let a: HashSet<&str> = ["camping", "tent"].into_iter().collect();
+    let b: HashSet<&str> = ["tent", "outdoor"].into_iter().collect();
+    assert!((jaccard(&a, &b) - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(jaccard::<&str>(&HashSet::new(), &HashSet::new()), 0.0);
+
+    let tokens = text_tokens("Bosch GSR-18V drill, 2 batteries");
+    assert!(tokens.contains("bosch") && tokens.contains("gsr") && tokens.contains("18v"));
+    assert!(!tokens.contains("2"));
+}
+
+#[tokio::test]
+async fn test_price_distance_and_co_rental_signals() {
+    assert_eq!(price_similarity(20.0, 20.0), 1.0);
+    assert!((price_similarity(10.0, 20.0) - 0.5).abs() < 1e-9);
+    assert_eq!(price_similarity(10.0, 50.0), 0.0);
+
+    assert_eq!(distance_similarity(Some((52.52, 13.405)), Some((52.52, 13.405))), 1.0);
+    assert_eq!(distance_similarity(Some((52.52, 13.405)), Some((48.14, 11.58))), 0.0);
+    assert_eq!(distance_similarity(None, Some((52.52, 13.405))), 0.0);
+
+    assert_eq!(co_rental_signal(0), 0.0);
+    assert_eq!(co_rental_signal(10), 1.0);
+}
+
+#[tokio::test]
+async fn test_identical_listing_scores_one() {
+    let root = Uuid::new_v4();
+    let target = features("Cordless drill", vec![root], &["diy"], 15.0);
+    let mut twin = target.clone();
+    twin.product_id = Uuid::new_v4();
+
+    assert!((similarity_score(&target, &twin, 5) - 1.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_rank_similar_orders_and_excludes_target() {
+    let (root, drills, saws) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let target = features("Cordless drill", vec![drills, root], &["diy", "drill"], 15.0);
+    let drill = features("Impact drill", vec![drills, root], &["drill"], 18.0);
+    let saw = features("Circular saw", vec![saws, root], &["diy"], 25.0);
+    let kayak = ProductFeatures { location: None, ..features("Kayak", vec![Uuid::new_v4()], &[], 200.0) };
+
+    let candidates = vec![target.clone(), saw.clone(), kayak.clone(), drill.clone()];
+    let ranked = rank_similar(&target, &candidates, &HashMap::new(), 10);
+
+    let ids: Vec<Uuid> = ranked.iter().map(|(id, _)| *id).collect();
+    assert_eq!(ids, vec![drill.product_id, saw.product_id]);
+
+    // Shared renters add to the score
+    let co_rentals = HashMap::from([(saw.product_id, 3)]);
+    let boosted = rank_similar(&target, &candidates, &co_rentals, 10);
+    assert_eq!(boosted[1].0, saw.product_id);
+    assert!(boosted[1].1 > ranked[1].1);
+
+    assert_eq!(rank_similar(&target, &candidates, &co_rentals, 1).len(), 1);
+}
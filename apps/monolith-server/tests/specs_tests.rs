@@ -0,0 +1,63 @@
+use monolith_server::specs::{check_spec_filters, check_spec_schema, filterable_fields, validate_specifications};
+use serde_json::json;
+
+fn camera_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "megapixels": { "type": "number", "minimum": 1, "x-filterable": true },
+            "mount": { "type": "string", "enum": ["EF", "RF", "E"], "x-filterable": true },
+            "notes": { "type": "string" }
+        },
+        "required": ["megapixels", "mount"]
+    })
+}
+
+#[tokio::test]
+async fn test_check_spec_schema() {
+    assert!(check_spec_schema(&camera_schema()).is_ok());
+    assert!(check_spec_schema(&json!(["not", "an", "object"])).is_err());
+    assert!(check_spec_schema(&json!({ "type": "no-such-type" })).is_err());
+}
+
+#[tokio::test]
+async fn test_validate_specifications_accepts_matching_specs() {
+    let specs = json!({ "megapixels": 24, "mount": "RF", "notes": "Lightly used" });
+    assert!(validate_specifications(&camera_schema(), &specs).is_ok());
+}
+
+#[tokio::test]
+async fn test_validate_specifications_reports_field_errors() {
+    let specs = json!({ "megapixels": 0, "mount": "F" });
+    let errors = validate_specifications(&camera_schema(), &specs).unwrap_err();
+
+    let mut fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+    fields.sort();
+    assert_eq!(fields, vec!["megapixels", "mount"]);
+}
+
+#[tokio::test]
+async fn test_validate_specifications_names_missing_required_field() {
+    let specs = json!({ "megapixels": 24 });
+    let errors = validate_specifications(&camera_schema(), &specs).unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].field, "mount");
+}
+
+#[tokio::test]
+async fn test_filterable_fields() {
+    assert_eq!(filterable_fields(&camera_schema()), vec!["megapixels".to_string(), "mount".to_string()]);
+    assert!(filterable_fields(&json!({})).is_empty());
+}
+
+#[tokio::test]
+async fn test_check_spec_filters() {
+    let schema = camera_schema();
+
+    assert!(check_spec_filters(Some(&schema), &json!({ "mount": "EF" })).is_ok());
+    assert!(check_spec_filters(Some(&schema), &json!({ "notes": "x" })).is_err());
+    assert!(check_spec_filters(Some(&schema), &json!({ "mount": ["EF"] })).is_err());
+    assert!(check_spec_filters(None, &json!({ "mount": "EF" })).is_err());
+    assert!(check_spec_filters(Some(&schema), &json!("mount")).is_err());
+}
@@ -0,0 +1,48 @@
+use monolith_server::tag::{normalize_tag, normalize_tags, MergeTagsRequest, TagError};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_normalize_tag_folds_case_and_whitespace() {
+    assert_eq!(normalize_tag("  Power   Tools "), Some("power tools".to_string()));
+    assert_eq!(normalize_tag("#Camping"), Some("camping".to_string()));
+    assert_eq!(normalize_tag("   "), None);
+}
+
+#[tokio::test]
+async fn test_normalize_tag_applies_unicode_compatibility_folding() {
+    // Full-width letters and the "ﬁ" ligature fold to plain ASCII
+    assert_eq!(normalize_tag("ＤＳＬＲ"), Some("dslr".to_string()));
+    assert_eq!(normalize_tag("ﬁshing"), Some("fishing".to_string()));
+}
+
+#[tokio::test]
+async fn test_normalize_tags_deduplicates_in_order() {
+    let raw = vec![
+        "Camera".to_string(),
+        "tripod".to_string(),
+        " CAMERA ".to_string(),
+        "".to_string(),
+    ];
+
+    assert_eq!(normalize_tags(&raw).unwrap(), vec!["camera".to_string(), "tripod".to_string()]);
+}
+
+#[tokio::test]
+async fn test_normalize_tags_rejects_long_and_excess_tags() {
+    let long = vec!["x".repeat(51)];
+    let many: Vec<String> = (0..21).map(|i| format!("tag{}", i)).collect();
+
+    assert!(matches!(normalize_tags(&long), Err(TagError::TooLong(_))));
+    assert_eq!(normalize_tags(&many), Err(TagError::TooMany));
+}
+
+#[tokio::test]
+async fn test_merge_tags_request_validation() {
+    let (target, a, b) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let request = |source_tag_ids: Vec<Uuid>| MergeTagsRequest { source_tag_ids, target_tag_id: target };
+
+    // The target and repeated ids are dropped
+    assert_eq!(request(vec![a, target, b, a]).sources(), Ok(vec![a, b]));
+    assert!(request(vec![target]).sources().is_err());
+    assert!(request(vec![]).sources().is_err());
+}
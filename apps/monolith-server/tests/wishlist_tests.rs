@@ -0,0 +1,44 @@
+use monolith_server::wishlist::{AddWishlistRequest, ProductSummary, WishlistFilters, WishlistItemResponse};
+use uuid::Uuid;
+use chrono::Utc;
+
+#[tokio::test]
+async fn test_add_wishlist_request_validation() {
+    let product_id = Uuid::new_v4();
+    let request: AddWishlistRequest =
+        serde_json::from_value(serde_json::json!({ "product_id": product_id })).unwrap();
+
+    assert_eq!(request.product_id, product_id);
+}
+
+#[tokio::test]
+async fn test_wishlist_filters_validation() {
+    let filters = WishlistFilters {
+        page: Some(2),
+        per_page: Some(10),
+    };
+
+    assert_eq!(filters.page.unwrap(), 2);
+    assert_eq!(filters.per_page.unwrap(), 10);
+}
+
+#[tokio::test]
+async fn test_wishlist_item_includes_product_summary() {
+    let item = WishlistItemResponse {
+        wishlist_id: Uuid::new_v4(),
+        added_at: Utc::now(),
+        product: ProductSummary {
+            product_id: Uuid::new_v4(),
+            name: "Camping Tent".to_string(),
+            daily_price: 12.5,
+            status: "active".to_string(),
+            avg_rating: Some(4.5),
+            primary_image: None,
+        },
+    };
+
+    let json = serde_json::to_value(&item).unwrap();
+    assert_eq!(json["product"]["name"], "Camping Tent");
+    assert_eq!(json["product"]["daily_price"], 12.5);
+    assert!(json["product"]["primary_image"].is_null());
+}
@@ -0,0 +1,3 @@
+-- Remove promotion link from payment intents
+DROP INDEX IF EXISTS payment_schema.idx_payment_intents_promotion_id;
+ALTER TABLE payment_schema.payment_intents DROP COLUMN IF EXISTS promotion_id;
@@ -0,0 +1,26 @@
+-- Migration: add_payment_intent_promotion
+-- Service: payment
+-- Created at: 2026-10-18 00:00:14 UTC
+
+BEGIN;
+
+-- Payment intents can pay for a listing promotion. Payment migrations run before product
+-- ones, so the key to promotions is added here only when that table already exists; the
+-- product migration creating promotions adds it otherwise.
+ALTER TABLE payment_schema.payment_intents ADD COLUMN IF NOT EXISTS promotion_id UUID;
+
+DO $$
+BEGIN
+    IF to_regclass('product_schema.promotions') IS NOT NULL AND NOT EXISTS (
+        SELECT 1 FROM pg_constraint WHERE conname = 'payment_intents_promotion_id_fkey'
+    ) THEN
+        ALTER TABLE payment_schema.payment_intents
+            ADD CONSTRAINT payment_intents_promotion_id_fkey FOREIGN KEY (promotion_id)
+                REFERENCES product_schema.promotions(promotion_id) ON DELETE SET NULL;
+    END IF;
+END $$;
+
+CREATE INDEX IF NOT EXISTS idx_payment_intents_promotion_id
+    ON payment_schema.payment_intents(promotion_id);
+
+COMMIT;
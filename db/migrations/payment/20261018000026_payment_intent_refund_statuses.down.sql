@@ -0,0 +1,6 @@
+-- Restore payment intent statuses without refunds
+UPDATE payment_schema.payment_intents SET status = 'succeeded' WHERE status IN ('refunded', 'partially_refunded');
+ALTER TABLE payment_schema.payment_intents DROP CONSTRAINT IF EXISTS payment_intents_status_check;
+ALTER TABLE payment_schema.payment_intents
+    ADD CONSTRAINT payment_intents_status_check
+        CHECK (status IN ('pending', 'processing', 'succeeded', 'failed', 'canceled'));
@@ -0,0 +1,13 @@
+-- Migration: payment_intent_refund_statuses
+-- Service: payment
+-- Created at: 2026-10-18 00:00:26 UTC
+
+BEGIN;
+
+-- Cancellation refunds move a succeeded intent to refunded or partially_refunded
+ALTER TABLE payment_schema.payment_intents DROP CONSTRAINT IF EXISTS payment_intents_status_check;
+ALTER TABLE payment_schema.payment_intents
+    ADD CONSTRAINT payment_intents_status_check
+        CHECK (status IN ('pending', 'processing', 'succeeded', 'failed', 'canceled', 'refunded', 'partially_refunded'));
+
+COMMIT;
@@ -0,0 +1,2 @@
+-- Drop the pending refunds index
+DROP INDEX IF EXISTS payment_schema.idx_payment_transactions_pending_refunds;
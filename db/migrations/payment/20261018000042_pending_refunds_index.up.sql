@@ -0,0 +1,13 @@
+-- Migration: pending_refunds_index
+-- Service: payment
+-- Created at: 2026-10-18 00:00:42 UTC
+
+BEGIN;
+
+-- Refunds are recorded as pending and sent to the provider after commit; the settlement job
+-- scans for the ones left behind
+CREATE INDEX IF NOT EXISTS idx_payment_transactions_pending_refunds
+    ON payment_schema.payment_transactions(created_at)
+    WHERE transaction_type = 'refund' AND status = 'pending';
+
+COMMIT;
@@ -0,0 +1,21 @@
+-- Migration: create_pricing_rules
+-- Service: product
+-- Created at: 2026-10-18 00:00:01 UTC
+
+BEGIN;
+
+-- Per-product pricing rules used by the quote engine
+CREATE TABLE IF NOT EXISTS product_schema.pricing_rules (
+    product_id UUID PRIMARY KEY REFERENCES product_schema.products(product_id) ON DELETE CASCADE,
+    weekly_price NUMERIC(10,2) CHECK (weekly_price >= 0),
+    monthly_price NUMERIC(10,2) CHECK (monthly_price >= 0),
+    weekend_daily_price NUMERIC(10,2) CHECK (weekend_daily_price >= 0),
+    seasonal_overrides JSONB NOT NULL DEFAULT '[]'::jsonb,
+    length_of_stay_discounts JSONB NOT NULL DEFAULT '[]'::jsonb,
+    cleaning_fee NUMERIC(10,2) CHECK (cleaning_fee >= 0),
+    delivery_fee NUMERIC(10,2) CHECK (delivery_fee >= 0),
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMIT;
@@ -0,0 +1,3 @@
+-- Drop indexes
+DROP INDEX IF EXISTS product_schema.idx_wishlists_user_created_at;
+DROP INDEX IF EXISTS product_schema.idx_wishlists_product_id;
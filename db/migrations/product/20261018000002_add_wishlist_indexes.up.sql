@@ -0,0 +1,11 @@
+-- Migration: add_wishlist_indexes
+-- Service: product
+-- Created at: 2026-10-18 00:00:02 UTC
+
+BEGIN;
+
+-- Owner stats count wishlists per product; the unique key only covers (user_id, product_id)
+CREATE INDEX IF NOT EXISTS idx_wishlists_product_id ON product_schema.wishlists(product_id);
+CREATE INDEX IF NOT EXISTS idx_wishlists_user_created_at ON product_schema.wishlists(user_id, created_at DESC);
+
+COMMIT;
@@ -0,0 +1,12 @@
+-- Drop constraints and indexes
+ALTER TABLE product_schema.categories DROP CONSTRAINT IF EXISTS categories_parent_not_self;
+DROP INDEX IF EXISTS product_schema.idx_products_category_id;
+DROP INDEX IF EXISTS product_schema.idx_categories_parent_sort;
+DROP INDEX IF EXISTS product_schema.idx_categories_slug;
+
+-- Drop columns
+ALTER TABLE product_schema.categories
+    DROP COLUMN IF EXISTS updated_at,
+    DROP COLUMN IF EXISTS sort_order,
+    DROP COLUMN IF EXISTS icon,
+    DROP COLUMN IF EXISTS slug;
@@ -0,0 +1,39 @@
+-- Migration: category_tree
+-- Service: product
+-- Created at: 2026-10-18 00:00:03 UTC
+
+BEGIN;
+
+ALTER TABLE product_schema.categories
+    ADD COLUMN IF NOT EXISTS slug VARCHAR(120),
+    ADD COLUMN IF NOT EXISTS icon VARCHAR(255),
+    ADD COLUMN IF NOT EXISTS sort_order INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP;
+
+-- Backfill slugs from names, suffixing duplicates in creation order
+WITH slugs AS (
+    SELECT category_id,
+           COALESCE(NULLIF(trim(BOTH '-' FROM regexp_replace(lower(name), '[^a-z0-9]+', '-', 'g')), ''), 'category') AS base,
+           ROW_NUMBER() OVER (
+               PARTITION BY COALESCE(NULLIF(trim(BOTH '-' FROM regexp_replace(lower(name), '[^a-z0-9]+', '-', 'g')), ''), 'category')
+               ORDER BY created_at, category_id
+           ) AS n
+    FROM product_schema.categories
+    WHERE slug IS NULL
+)
+UPDATE product_schema.categories c
+SET slug = CASE WHEN s.n = 1 THEN s.base ELSE s.base || '-' || s.n END
+FROM slugs s
+WHERE c.category_id = s.category_id;
+
+ALTER TABLE product_schema.categories ALTER COLUMN slug SET NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_categories_slug ON product_schema.categories(slug);
+CREATE INDEX IF NOT EXISTS idx_categories_parent_sort ON product_schema.categories(parent_category_id, sort_order);
+
+-- A category can never be its own parent; deeper cycles are rejected by the API
+ALTER TABLE product_schema.categories
+    ADD CONSTRAINT categories_parent_not_self CHECK (parent_category_id IS DISTINCT FROM category_id);
+
+CREATE INDEX IF NOT EXISTS idx_products_category_id ON product_schema.products(category_id);
+
+COMMIT;
@@ -0,0 +1,3 @@
+-- Drop indexes (tag normalization itself is not reversible)
+DROP INDEX IF EXISTS product_schema.idx_product_tags_tag_id;
+DROP INDEX IF EXISTS product_schema.idx_tags_name_prefix;
@@ -0,0 +1,44 @@
+-- Migration: normalize_tags
+-- Service: product
+-- Created at: 2026-10-18 00:00:05 UTC
+
+BEGIN;
+
+-- Same folding as tag::normalize_tag: NFKC, lowercase, collapsed whitespace, no leading '#'
+CREATE TEMP TABLE tag_normalization ON COMMIT DROP AS
+SELECT tag_id,
+       normalized,
+       FIRST_VALUE(tag_id) OVER (PARTITION BY normalized ORDER BY created_at, tag_id) AS canonical_tag_id
+FROM (
+    SELECT tag_id, created_at,
+           btrim(regexp_replace(ltrim(btrim(lower(normalize(name, NFKC))), '#'), '\s+', ' ', 'g')) AS normalized
+    FROM product_schema.tags
+) t
+WHERE normalized <> '';
+
+-- Point products at the surviving tag, then drop the duplicates
+INSERT INTO product_schema.product_tags (product_id, tag_id)
+SELECT pt.product_id, n.canonical_tag_id
+FROM product_schema.product_tags pt
+JOIN tag_normalization n ON n.tag_id = pt.tag_id
+WHERE n.tag_id <> n.canonical_tag_id
+ON CONFLICT DO NOTHING;
+
+DELETE FROM product_schema.product_tags pt
+USING tag_normalization n
+WHERE n.tag_id = pt.tag_id AND n.tag_id <> n.canonical_tag_id;
+
+DELETE FROM product_schema.tags t
+USING tag_normalization n
+WHERE n.tag_id = t.tag_id AND n.tag_id <> n.canonical_tag_id;
+
+UPDATE product_schema.tags t
+SET name = n.normalized
+FROM tag_normalization n
+WHERE n.tag_id = t.tag_id AND t.name <> n.normalized;
+
+-- Prefix lookups for autocomplete
+CREATE INDEX IF NOT EXISTS idx_tags_name_prefix ON product_schema.tags(name text_pattern_ops);
+CREATE INDEX IF NOT EXISTS idx_product_tags_tag_id ON product_schema.product_tags(tag_id);
+
+COMMIT;
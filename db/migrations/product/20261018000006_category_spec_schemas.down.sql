@@ -0,0 +1,3 @@
+-- Drop spec schema support
+DROP INDEX IF EXISTS product_schema.idx_products_specifications;
+ALTER TABLE product_schema.categories DROP COLUMN IF EXISTS spec_schema;
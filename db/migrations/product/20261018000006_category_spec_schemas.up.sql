@@ -0,0 +1,16 @@
+-- Migration: category_spec_schemas
+-- Service: product
+-- Created at: 2026-10-18 00:00:06 UTC
+
+BEGIN;
+
+-- JSON Schema for product specifications; subcategories inherit the nearest ancestor's schema
+ALTER TABLE product_schema.categories
+    ADD COLUMN IF NOT EXISTS spec_schema JSONB
+        CHECK (spec_schema IS NULL OR jsonb_typeof(spec_schema) = 'object');
+
+-- Containment lookups for spec filters
+CREATE INDEX IF NOT EXISTS idx_products_specifications
+    ON product_schema.products USING GIN (specifications jsonb_path_ops);
+
+COMMIT;
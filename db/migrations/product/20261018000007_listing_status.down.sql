@@ -0,0 +1,5 @@
+-- Restore free-form listing status
+ALTER TABLE product_schema.products DROP CONSTRAINT IF EXISTS products_status_check;
+ALTER TABLE product_schema.products ALTER COLUMN status DROP NOT NULL;
+ALTER TABLE product_schema.products ALTER COLUMN status SET DEFAULT 'active';
+UPDATE product_schema.products SET status = 'deleted' WHERE status = 'archived';
@@ -0,0 +1,21 @@
+-- Migration: listing_status
+-- Service: product
+-- Created at: 2026-10-18 00:00:07 UTC
+
+BEGIN;
+
+-- Map legacy free-form values onto the listing lifecycle
+UPDATE product_schema.products SET status = 'archived' WHERE status = 'deleted';
+UPDATE product_schema.products SET status = 'paused' WHERE status = 'inactive';
+UPDATE product_schema.products
+SET status = 'draft'
+WHERE status IS NULL
+   OR status NOT IN ('draft', 'pending_review', 'active', 'paused', 'archived');
+
+ALTER TABLE product_schema.products
+    ALTER COLUMN status SET DEFAULT 'draft',
+    ALTER COLUMN status SET NOT NULL,
+    ADD CONSTRAINT products_status_check
+        CHECK (status IN ('draft', 'pending_review', 'active', 'paused', 'archived'));
+
+COMMIT;
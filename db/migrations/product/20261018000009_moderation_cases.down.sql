@@ -0,0 +1,2 @@
+-- Drop moderation queue
+DROP TABLE IF EXISTS product_schema.moderation_cases;
@@ -0,0 +1,27 @@
+-- Migration: moderation_cases
+-- Service: product
+-- Created at: 2026-10-18 00:00:09 UTC
+
+BEGIN;
+
+-- Listings flagged by the moderation pipeline, waiting on or resolved by a moderator
+CREATE TABLE IF NOT EXISTS product_schema.moderation_cases (
+    case_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    product_id UUID NOT NULL REFERENCES product_schema.products(product_id),
+    status VARCHAR(20) NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'approved', 'rejected')),
+    findings JSONB NOT NULL DEFAULT '[]',
+    resolved_by UUID REFERENCES user_schema.users(user_id),
+    resolution_note TEXT,
+    resolved_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- At most one open case per listing; re-flagging refreshes it
+CREATE UNIQUE INDEX IF NOT EXISTS idx_moderation_cases_open_product
+    ON product_schema.moderation_cases(product_id) WHERE status = 'open';
+CREATE INDEX IF NOT EXISTS idx_moderation_cases_status_created
+    ON product_schema.moderation_cases(status, created_at);
+
+COMMIT;
@@ -0,0 +1,2 @@
+-- Drop product units
+DROP TABLE IF EXISTS product_schema.product_units;
@@ -0,0 +1,29 @@
+-- Migration: product_units
+-- Service: product
+-- Created at: 2026-10-18 00:00:10 UTC
+
+BEGIN;
+
+-- Individually bookable units of a listing (e.g. five identical kayaks)
+CREATE TABLE IF NOT EXISTS product_schema.product_units (
+    unit_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    product_id UUID NOT NULL REFERENCES product_schema.products(product_id),
+    label VARCHAR(100) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'active'
+        CHECK (status IN ('active', 'retired')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (product_id, label)
+);
+
+CREATE INDEX IF NOT EXISTS idx_product_units_product_status
+    ON product_schema.product_units(product_id, status);
+
+-- Every existing listing becomes a single unit
+INSERT INTO product_schema.product_units (product_id, label)
+SELECT p.product_id, 'Unit 1'
+FROM product_schema.products p
+WHERE NOT EXISTS (
+    SELECT 1 FROM product_schema.product_units u WHERE u.product_id = p.product_id
+);
+
+COMMIT;
@@ -0,0 +1,2 @@
+-- Drop import jobs
+DROP TABLE IF EXISTS product_schema.import_jobs;
@@ -0,0 +1,29 @@
+-- Migration: import_jobs
+-- Service: product
+-- Created at: 2026-10-18 00:00:12 UTC
+
+BEGIN;
+
+-- Bulk listing imports processed in the background, with row-level errors
+CREATE TABLE IF NOT EXISTS product_schema.import_jobs (
+    job_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    owner_id UUID NOT NULL REFERENCES user_schema.users(user_id),
+    status VARCHAR(20) NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'completed', 'failed')),
+    file_name TEXT,
+    format VARCHAR(10) NOT NULL CHECK (format IN ('csv', 'xlsx')),
+    total_rows INTEGER NOT NULL DEFAULT 0,
+    processed_rows INTEGER NOT NULL DEFAULT 0,
+    created_count INTEGER NOT NULL DEFAULT 0,
+    error_count INTEGER NOT NULL DEFAULT 0,
+    errors JSONB NOT NULL DEFAULT '[]',
+    failure_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_import_jobs_owner_created
+    ON product_schema.import_jobs(owner_id, created_at DESC);
+
+COMMIT;
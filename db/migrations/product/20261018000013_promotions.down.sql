@@ -0,0 +1,3 @@
+-- Drop promotions
+ALTER TABLE IF EXISTS payment_schema.payment_intents DROP CONSTRAINT IF EXISTS payment_intents_promotion_id_fkey;
+DROP TABLE IF EXISTS product_schema.promotions;
@@ -0,0 +1,48 @@
+-- Migration: promotions
+-- Service: product
+-- Created at: 2026-10-18 00:00:13 UTC
+
+BEGIN;
+
+-- Paid placement slots; a promotion is boosted in listings once its payment succeeds
+CREATE TABLE IF NOT EXISTS product_schema.promotions (
+    promotion_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    product_id UUID NOT NULL REFERENCES product_schema.products(product_id) ON DELETE CASCADE,
+    owner_id UUID NOT NULL REFERENCES user_schema.users(user_id),
+    starts_on DATE NOT NULL,
+    ends_on DATE NOT NULL,
+    amount_cents INTEGER NOT NULL CHECK (amount_cents > 0),
+    currency VARCHAR(3) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending_payment'
+        CHECK (status IN ('pending_payment', 'paid', 'cancelled')),
+    impressions BIGINT NOT NULL DEFAULT 0,
+    clicks BIGINT NOT NULL DEFAULT 0,
+    paid_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT promotions_valid_range CHECK (ends_on >= starts_on)
+);
+
+CREATE INDEX IF NOT EXISTS idx_promotions_running
+    ON product_schema.promotions(starts_on, ends_on) WHERE status = 'paid';
+CREATE INDEX IF NOT EXISTS idx_promotions_owner
+    ON product_schema.promotions(owner_id, starts_on DESC);
+CREATE INDEX IF NOT EXISTS idx_promotions_product
+    ON product_schema.promotions(product_id);
+
+-- Payment intents paying for a promotion; the column comes from the payment service, and
+-- whichever of the two migrations runs second adds the key
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_schema = 'payment_schema' AND table_name = 'payment_intents' AND column_name = 'promotion_id'
+    ) AND NOT EXISTS (
+        SELECT 1 FROM pg_constraint WHERE conname = 'payment_intents_promotion_id_fkey'
+    ) THEN
+        ALTER TABLE payment_schema.payment_intents
+            ADD CONSTRAINT payment_intents_promotion_id_fkey FOREIGN KEY (promotion_id)
+                REFERENCES product_schema.promotions(promotion_id) ON DELETE SET NULL;
+    END IF;
+END $$;
+
+COMMIT;
@@ -0,0 +1,2 @@
+-- Drop engagement rollups
+DROP TABLE IF EXISTS product_schema.product_daily_stats;
@@ -0,0 +1,18 @@
+-- Migration: product_daily_stats
+-- Service: product
+-- Created at: 2026-10-18 00:00:15 UTC
+
+BEGIN;
+
+-- Per-day engagement rollups, incremented as events happen
+CREATE TABLE IF NOT EXISTS product_schema.product_daily_stats (
+    product_id UUID NOT NULL REFERENCES product_schema.products(product_id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    views BIGINT NOT NULL DEFAULT 0,
+    wishlist_adds BIGINT NOT NULL DEFAULT 0,
+    inquiries BIGINT NOT NULL DEFAULT 0,
+    bookings BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (product_id, day)
+);
+
+COMMIT;
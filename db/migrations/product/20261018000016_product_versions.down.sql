@@ -0,0 +1,4 @@
+-- Remove products row versions
+DROP TRIGGER IF EXISTS products_bump_version ON product_schema.products;
+DROP FUNCTION IF EXISTS product_schema.bump_row_version();
+ALTER TABLE product_schema.products DROP COLUMN IF EXISTS version;
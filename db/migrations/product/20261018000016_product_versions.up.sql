@@ -0,0 +1,23 @@
+-- Migration: product_versions
+-- Service: product
+-- Created at: 2026-10-18 00:00:16 UTC
+
+BEGIN;
+
+-- Row version for optimistic concurrency (ETag / If-Match); every update bumps it
+ALTER TABLE product_schema.products ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+
+CREATE OR REPLACE FUNCTION product_schema.bump_row_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS products_bump_version ON product_schema.products;
+CREATE TRIGGER products_bump_version
+    BEFORE UPDATE ON product_schema.products
+    FOR EACH ROW EXECUTE FUNCTION product_schema.bump_row_version();
+
+COMMIT;
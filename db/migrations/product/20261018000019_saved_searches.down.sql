@@ -0,0 +1,5 @@
+-- Drop saved searches and listing activation time
+DROP TABLE IF EXISTS product_schema.saved_search_matches;
+DROP TABLE IF EXISTS product_schema.saved_searches;
+DROP INDEX IF EXISTS product_schema.idx_products_activated_at;
+ALTER TABLE product_schema.products DROP COLUMN IF EXISTS activated_at;
@@ -0,0 +1,37 @@
+-- Migration: saved_searches
+-- Service: product
+-- Created at: 2026-10-18 00:00:19 UTC
+
+BEGIN;
+
+-- When a listing last went live; set on every transition to 'active'
+ALTER TABLE product_schema.products ADD COLUMN IF NOT EXISTS activated_at TIMESTAMPTZ;
+UPDATE product_schema.products SET activated_at = created_at WHERE status = 'active' AND activated_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_products_activated_at ON product_schema.products(activated_at) WHERE status = 'active';
+
+CREATE TABLE IF NOT EXISTS product_schema.saved_searches (
+    search_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES user_schema.users(user_id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    filters JSONB NOT NULL,
+    frequency VARCHAR(10) NOT NULL DEFAULT 'instant' CHECK (frequency IN ('instant', 'daily')),
+    last_digest_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_saved_searches_user ON product_schema.saved_searches(user_id, created_at DESC);
+
+-- One row per listing activation matched by a search; notified_at is set once alerted
+CREATE TABLE IF NOT EXISTS product_schema.saved_search_matches (
+    search_id UUID NOT NULL REFERENCES product_schema.saved_searches(search_id) ON DELETE CASCADE,
+    product_id UUID NOT NULL REFERENCES product_schema.products(product_id) ON DELETE CASCADE,
+    activated_at TIMESTAMPTZ NOT NULL,
+    matched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    notified_at TIMESTAMPTZ,
+    PRIMARY KEY (search_id, product_id, activated_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_saved_search_matches_pending
+    ON product_schema.saved_search_matches(search_id) WHERE notified_at IS NULL;
+
+COMMIT;
@@ -0,0 +1,3 @@
+-- Drop precomputed similar products
+DROP TABLE IF EXISTS product_schema.similar_product_refreshes;
+DROP TABLE IF EXISTS product_schema.similar_products;
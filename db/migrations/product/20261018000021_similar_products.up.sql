@@ -0,0 +1,21 @@
+-- Migration: similar_products
+-- Service: product
+-- Created at: 2026-10-18 00:00:21 UTC
+
+BEGIN;
+
+-- Precomputed neighbours for product detail pages, best first by score
+CREATE TABLE IF NOT EXISTS product_schema.similar_products (
+    product_id UUID NOT NULL REFERENCES product_schema.products(product_id) ON DELETE CASCADE,
+    similar_product_id UUID NOT NULL REFERENCES product_schema.products(product_id) ON DELETE CASCADE,
+    score DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (product_id, similar_product_id)
+);
+
+-- When each product's neighbours were last computed, including products with none
+CREATE TABLE IF NOT EXISTS product_schema.similar_product_refreshes (
+    product_id UUID PRIMARY KEY REFERENCES product_schema.products(product_id) ON DELETE CASCADE,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMIT;
@@ -0,0 +1,2 @@
+-- Drop product cancellation policy
+ALTER TABLE product_schema.products DROP COLUMN IF EXISTS cancellation_policy;
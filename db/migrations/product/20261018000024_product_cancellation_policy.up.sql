@@ -0,0 +1,13 @@
+-- Migration: product_cancellation_policy
+-- Service: product
+-- Created at: 2026-10-18 00:00:24 UTC
+
+BEGIN;
+
+-- Terms the owner offers renters who cancel; see cancellation.rs for the refund tiers
+ALTER TABLE product_schema.products
+    ADD COLUMN IF NOT EXISTS cancellation_policy VARCHAR(20) NOT NULL DEFAULT 'moderate'
+        CONSTRAINT products_cancellation_policy_check
+        CHECK (cancellation_policy IN ('flexible', 'moderate', 'strict'));
+
+COMMIT;
@@ -0,0 +1,3 @@
+-- Drop instant book settings
+ALTER TABLE product_schema.products DROP COLUMN IF EXISTS instant_book_requirements;
+ALTER TABLE product_schema.products DROP COLUMN IF EXISTS instant_book;
@@ -0,0 +1,13 @@
+-- Migration: product_instant_book
+-- Service: product
+-- Created at: 2026-10-18 00:00:27 UTC
+
+BEGIN;
+
+-- Instant-book listings confirm qualifying renters without waiting for the owner.
+-- Requirements: {"require_verified_id": bool, "min_rating": number, "min_completed_rentals": int}
+ALTER TABLE product_schema.products
+    ADD COLUMN IF NOT EXISTS instant_book BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN IF NOT EXISTS instant_book_requirements JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+COMMIT;
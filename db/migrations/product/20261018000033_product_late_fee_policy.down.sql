@@ -0,0 +1,2 @@
+-- Drop late fee policies
+ALTER TABLE product_schema.products DROP COLUMN IF EXISTS late_fee_policy;
@@ -0,0 +1,11 @@
+-- Migration: product_late_fee_policy
+-- Service: product
+-- Created at: 2026-10-18 00:00:33 UTC
+
+BEGIN;
+
+-- {"grace_hours": int, "daily_fee": number, "max_fee_days": int}; missing keys use the defaults
+ALTER TABLE product_schema.products
+    ADD COLUMN IF NOT EXISTS late_fee_policy JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+COMMIT;
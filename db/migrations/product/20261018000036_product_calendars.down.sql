@@ -0,0 +1,4 @@
+-- Drop calendar feeds, imports and blackouts
+DROP TABLE IF EXISTS product_schema.product_blackouts;
+DROP TABLE IF EXISTS product_schema.calendar_imports;
+DROP TABLE IF EXISTS product_schema.calendar_feeds;
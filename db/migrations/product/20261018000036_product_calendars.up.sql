@@ -0,0 +1,55 @@
+-- Migration: product_calendars
+-- Service: product
+-- Created at: 2026-10-18 00:00:36 UTC
+
+BEGIN;
+
+CREATE EXTENSION IF NOT EXISTS btree_gist;
+
+-- Secret iCalendar feed tokens: one per product, plus one per owner (product_id NULL)
+-- covering all of their listings
+CREATE TABLE IF NOT EXISTS product_schema.calendar_feeds (
+    feed_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    owner_id UUID NOT NULL REFERENCES user_schema.users(user_id) ON DELETE CASCADE,
+    product_id UUID REFERENCES product_schema.products(product_id) ON DELETE CASCADE,
+    token VARCHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_calendar_feeds_product
+    ON product_schema.calendar_feeds(product_id) WHERE product_id IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_calendar_feeds_owner
+    ON product_schema.calendar_feeds(owner_id) WHERE product_id IS NULL;
+
+-- External calendars polled for blackout ranges
+CREATE TABLE IF NOT EXISTS product_schema.calendar_imports (
+    import_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product_id UUID NOT NULL REFERENCES product_schema.products(product_id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    label VARCHAR(100),
+    last_attempted_at TIMESTAMPTZ,
+    last_synced_at TIMESTAMPTZ,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (product_id, url)
+);
+
+-- Periods the product cannot be booked: entered by the owner, or copied from an import
+-- (replaced on every successful sync)
+CREATE TABLE IF NOT EXISTS product_schema.product_blackouts (
+    blackout_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product_id UUID NOT NULL REFERENCES product_schema.products(product_id) ON DELETE CASCADE,
+    import_id UUID REFERENCES product_schema.calendar_imports(import_id) ON DELETE CASCADE,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    summary VARCHAR(255),
+    external_uid VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (ends_at > starts_at),
+    period TSTZRANGE GENERATED ALWAYS AS (tstzrange(starts_at, ends_at, '[)')) STORED
+);
+
+CREATE INDEX IF NOT EXISTS idx_product_blackouts_period
+    ON product_schema.product_blackouts USING gist (product_id, period);
+
+COMMIT;
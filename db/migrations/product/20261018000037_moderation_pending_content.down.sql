@@ -0,0 +1,2 @@
+-- Drop held-back listing edits
+ALTER TABLE product_schema.moderation_cases DROP COLUMN IF EXISTS pending_content;
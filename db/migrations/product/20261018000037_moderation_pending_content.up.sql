@@ -0,0 +1,10 @@
+-- Migration: moderation_pending_content
+-- Service: product
+-- Created at: 2026-10-18 00:00:37 UTC
+
+BEGIN;
+
+-- Flagged edits to a live listing are held here until a moderator resolves the case
+ALTER TABLE product_schema.moderation_cases ADD COLUMN IF NOT EXISTS pending_content JSONB;
+
+COMMIT;
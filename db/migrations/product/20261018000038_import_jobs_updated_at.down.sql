@@ -0,0 +1,3 @@
+-- Drop the import job heartbeat
+DROP INDEX IF EXISTS product_schema.idx_import_jobs_unfinished;
+ALTER TABLE product_schema.import_jobs DROP COLUMN IF EXISTS updated_at;
@@ -0,0 +1,14 @@
+-- Migration: import_jobs_updated_at
+-- Service: product
+-- Created at: 2026-10-18 00:00:38 UTC
+
+BEGIN;
+
+-- Heartbeat for import workers; jobs that stop updating are failed by the reclaim job
+ALTER TABLE product_schema.import_jobs
+    ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE INDEX IF NOT EXISTS idx_import_jobs_unfinished
+    ON product_schema.import_jobs(updated_at) WHERE status IN ('queued', 'running');
+
+COMMIT;
@@ -0,0 +1,2 @@
+-- Drop promotion interactions
+DROP TABLE IF EXISTS product_schema.promotion_interactions;
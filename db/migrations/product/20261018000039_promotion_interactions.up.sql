@@ -0,0 +1,17 @@
+-- Migration: promotion_interactions
+-- Service: product
+-- Created at: 2026-10-18 00:00:39 UTC
+
+BEGIN;
+
+-- Signed-in viewers who saw or clicked a promotion on a given day. A promotion's counters
+-- only move when a row is added here, so reloads and repeated clicks are not counted.
+CREATE TABLE IF NOT EXISTS product_schema.promotion_interactions (
+    promotion_id UUID NOT NULL REFERENCES product_schema.promotions(promotion_id) ON DELETE CASCADE,
+    viewer_id UUID NOT NULL REFERENCES user_schema.users(user_id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    kind VARCHAR(10) NOT NULL CHECK (kind IN ('impression', 'click')),
+    PRIMARY KEY (promotion_id, viewer_id, day, kind)
+);
+
+COMMIT;
@@ -0,0 +1,2 @@
+-- Drop the product view log
+DROP TABLE IF EXISTS product_schema.product_view_log;
@@ -0,0 +1,15 @@
+-- Migration: product_view_log
+-- Service: product
+-- Created at: 2026-10-18 00:00:40 UTC
+
+BEGIN;
+
+-- Viewers already counted in a product's daily views; one row per viewer, product and day
+CREATE TABLE IF NOT EXISTS product_schema.product_view_log (
+    product_id UUID NOT NULL REFERENCES product_schema.products(product_id) ON DELETE CASCADE,
+    viewer_id UUID NOT NULL REFERENCES user_schema.users(user_id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    PRIMARY KEY (product_id, viewer_id, day)
+);
+
+COMMIT;
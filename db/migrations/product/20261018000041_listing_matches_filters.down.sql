@@ -0,0 +1,2 @@
+-- Drop the shared listing filter predicate
+DROP FUNCTION IF EXISTS product_schema.listing_matches_filters(product_schema.products, JSONB, FLOAT8);
@@ -0,0 +1,48 @@
+-- Migration: listing_matches_filters
+-- Service: product
+-- Created at: 2026-10-18 00:00:41 UTC
+
+BEGIN;
+
+-- The one definition of whether a listing matches a set of listing filters (the JSON form of
+-- the API's ProductFilters), shared by listing queries and saved search alerts. A category
+-- includes its descendants, text is a case-insensitive substring of name or description,
+-- specs must be contained in the listing's specifications, and a geo filter needs numeric
+-- coordinates within the radius.
+CREATE OR REPLACE FUNCTION product_schema.listing_matches_filters(
+    p product_schema.products,
+    filters JSONB,
+    default_radius_km FLOAT8
+) RETURNS BOOLEAN
+LANGUAGE sql STABLE AS $$
+    SELECT COALESCE(
+        (filters->>'category_id' IS NULL OR p.category_id IN (
+            WITH RECURSIVE category_tree AS (
+                SELECT category_id FROM product_schema.categories
+                WHERE category_id = (filters->>'category_id')::uuid
+                UNION
+                SELECT c.category_id FROM product_schema.categories c
+                JOIN category_tree ct ON c.parent_category_id = ct.category_id
+            )
+            SELECT category_id FROM category_tree
+        ))
+        AND (filters->>'min_price' IS NULL OR p.daily_price >= (filters->>'min_price')::float8)
+        AND (filters->>'max_price' IS NULL OR p.daily_price <= (filters->>'max_price')::float8)
+        AND (COALESCE(btrim(filters->>'search'), '') = ''
+            OR strpos(lower(p.name), lower(btrim(filters->>'search'))) > 0
+            OR strpos(lower(COALESCE(p.description, '')), lower(btrim(filters->>'search'))) > 0)
+        AND (filters->>'specs' IS NULL OR p.specifications @> (filters->>'specs')::jsonb)
+        AND (filters->>'lat' IS NULL OR (
+            jsonb_typeof(p.address->'lat') = 'number' AND jsonb_typeof(p.address->'lng') = 'number'
+            AND 2 * 6371 * asin(sqrt(
+                power(sin(radians(((p.address->>'lat')::float8 - (filters->>'lat')::float8) / 2)), 2)
+                + cos(radians((filters->>'lat')::float8)) * cos(radians((p.address->>'lat')::float8))
+                * power(sin(radians(((p.address->>'lng')::float8 - (filters->>'lng')::float8) / 2)), 2)
+            )) <= COALESCE((filters->>'radius_km')::float8, default_radius_km)
+        )),
+        -- Missing specifications or address mean no match
+        FALSE
+    )
+$$;
+
+COMMIT;
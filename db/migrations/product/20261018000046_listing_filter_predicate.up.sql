@@ -0,0 +1,57 @@
+-- Migration: listing_filter_predicate
+-- Service: product
+-- Created at: 2026-10-18 00:00:46 UTC
+
+BEGIN;
+
+-- The category tree was walked for every listing the predicate was applied to. Callers now
+-- resolve it once and pass the ids in.
+DROP FUNCTION IF EXISTS product_schema.listing_matches_filters(product_schema.products, JSONB, FLOAT8);
+
+-- A category and all of its descendants; empty when the category does not exist
+CREATE OR REPLACE FUNCTION product_schema.category_subtree(root UUID) RETURNS UUID[]
+LANGUAGE sql STABLE AS $$
+    WITH RECURSIVE category_tree AS (
+        SELECT category_id FROM product_schema.categories WHERE category_id = root
+        UNION
+        SELECT c.category_id FROM product_schema.categories c
+        JOIN category_tree ct ON c.parent_category_id = ct.category_id
+    )
+    SELECT COALESCE(array_agg(category_id), '{}') FROM category_tree
+$$;
+
+-- Whether a listing matches a set of listing filters (the JSON form of the API's
+-- ProductFilters), for saved search alerts and promoted listings. `list_products` applies the
+-- same predicates inline. `category_ids` is the filter category's subtree. Text is a
+-- case-insensitive substring of name or description, specs must be contained in the listing's
+-- specifications, and a geo filter needs numeric coordinates within the radius. A single
+-- expression, so the planner inlines it into the calling query.
+CREATE OR REPLACE FUNCTION product_schema.listing_matches_filters(
+    p product_schema.products,
+    filters JSONB,
+    category_ids UUID[],
+    default_radius_km FLOAT8
+) RETURNS BOOLEAN
+LANGUAGE sql STABLE AS $$
+    SELECT COALESCE(
+        (filters->>'category_id' IS NULL OR p.category_id = ANY(category_ids))
+        AND (filters->>'min_price' IS NULL OR p.daily_price >= (filters->>'min_price')::numeric)
+        AND (filters->>'max_price' IS NULL OR p.daily_price <= (filters->>'max_price')::numeric)
+        AND (COALESCE(btrim(filters->>'search'), '') = ''
+            OR strpos(lower(p.name), lower(btrim(filters->>'search'))) > 0
+            OR strpos(lower(COALESCE(p.description, '')), lower(btrim(filters->>'search'))) > 0)
+        AND (filters->>'specs' IS NULL OR p.specifications @> (filters->>'specs')::jsonb)
+        AND (filters->>'lat' IS NULL OR (
+            jsonb_typeof(p.address->'lat') = 'number' AND jsonb_typeof(p.address->'lng') = 'number'
+            AND 2 * 6371 * asin(sqrt(
+                power(sin(radians(((p.address->>'lat')::float8 - (filters->>'lat')::float8) / 2)), 2)
+                + cos(radians((filters->>'lat')::float8)) * cos(radians((p.address->>'lat')::float8))
+                * power(sin(radians(((p.address->>'lng')::float8 - (filters->>'lng')::float8) / 2)), 2)
+            )) <= COALESCE((filters->>'radius_km')::float8, default_radius_km)
+        )),
+        -- Missing specifications or address mean no match
+        FALSE
+    )
+$$;
+
+COMMIT;
@@ -0,0 +1,7 @@
+-- Allow zero package prices again
+ALTER TABLE product_schema.pricing_rules
+    DROP CONSTRAINT IF EXISTS pricing_rules_weekly_price_check,
+    DROP CONSTRAINT IF EXISTS pricing_rules_monthly_price_check;
+ALTER TABLE product_schema.pricing_rules
+    ADD CONSTRAINT pricing_rules_weekly_price_check CHECK (weekly_price >= 0),
+    ADD CONSTRAINT pricing_rules_monthly_price_check CHECK (monthly_price >= 0);
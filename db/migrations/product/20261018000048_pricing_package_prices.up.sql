@@ -0,0 +1,18 @@
+-- Migration: pricing_package_prices
+-- Service: product
+-- Created at: 2026-10-18 00:00:48 UTC
+
+BEGIN;
+
+-- A zero weekly or monthly price made whole weeks or months free; such packages are dropped
+UPDATE product_schema.pricing_rules SET weekly_price = NULL WHERE weekly_price = 0;
+UPDATE product_schema.pricing_rules SET monthly_price = NULL WHERE monthly_price = 0;
+
+ALTER TABLE product_schema.pricing_rules
+    DROP CONSTRAINT IF EXISTS pricing_rules_weekly_price_check,
+    DROP CONSTRAINT IF EXISTS pricing_rules_monthly_price_check;
+ALTER TABLE product_schema.pricing_rules
+    ADD CONSTRAINT pricing_rules_weekly_price_check CHECK (weekly_price > 0),
+    ADD CONSTRAINT pricing_rules_monthly_price_check CHECK (monthly_price > 0);
+
+COMMIT;
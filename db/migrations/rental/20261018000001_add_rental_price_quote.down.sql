@@ -0,0 +1,4 @@
+-- Drop columns
+ALTER TABLE rental_schema.rentals
+    DROP COLUMN IF EXISTS total_amount,
+    DROP COLUMN IF EXISTS price_quote;
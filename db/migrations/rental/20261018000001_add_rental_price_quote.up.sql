@@ -0,0 +1,12 @@
+-- Migration: add_rental_price_quote
+-- Service: rental
+-- Created at: 2026-10-18 00:00:01 UTC
+
+BEGIN;
+
+-- Snapshot of the price quote at booking time
+ALTER TABLE rental_schema.rentals
+    ADD COLUMN IF NOT EXISTS price_quote JSONB,
+    ADD COLUMN IF NOT EXISTS total_amount NUMERIC(12,2);
+
+COMMIT;
@@ -0,0 +1,3 @@
+-- Drop unit assignment from rentals
+DROP INDEX IF EXISTS rental_schema.idx_rentals_unit_period;
+ALTER TABLE rental_schema.rentals DROP COLUMN IF EXISTS unit_id;
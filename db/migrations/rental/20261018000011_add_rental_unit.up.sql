@@ -0,0 +1,19 @@
+-- Migration: add_rental_unit
+-- Service: rental
+-- Created at: 2026-10-18 00:00:11 UTC
+
+BEGIN;
+
+ALTER TABLE rental_schema.rentals
+    ADD COLUMN IF NOT EXISTS unit_id UUID REFERENCES product_schema.product_units(unit_id);
+
+-- Existing bookings belong to the product's only unit
+UPDATE rental_schema.rentals r
+SET unit_id = u.unit_id
+FROM product_schema.product_units u
+WHERE u.product_id = r.product_id AND r.unit_id IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_rentals_unit_period
+    ON rental_schema.rentals(unit_id, rental_period_start, rental_period_end);
+
+COMMIT;
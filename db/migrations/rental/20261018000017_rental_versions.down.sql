@@ -0,0 +1,4 @@
+-- Remove rentals row versions
+DROP TRIGGER IF EXISTS rentals_bump_version ON rental_schema.rentals;
+DROP FUNCTION IF EXISTS rental_schema.bump_row_version();
+ALTER TABLE rental_schema.rentals DROP COLUMN IF EXISTS version;
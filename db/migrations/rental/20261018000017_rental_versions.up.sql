@@ -0,0 +1,23 @@
+-- Migration: rental_versions
+-- Service: rental
+-- Created at: 2026-10-18 00:00:17 UTC
+
+BEGIN;
+
+-- Row version for optimistic concurrency (ETag / If-Match); every update bumps it
+ALTER TABLE rental_schema.rentals ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+
+CREATE OR REPLACE FUNCTION rental_schema.bump_row_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS rentals_bump_version ON rental_schema.rentals;
+CREATE TRIGGER rentals_bump_version
+    BEFORE UPDATE ON rental_schema.rentals
+    FOR EACH ROW EXECUTE FUNCTION rental_schema.bump_row_version();
+
+COMMIT;
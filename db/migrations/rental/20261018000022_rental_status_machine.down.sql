@@ -0,0 +1,4 @@
+-- Drop rental history and restore free-form rental status
+DROP TABLE IF EXISTS rental_schema.rental_status_history;
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_status_check;
+ALTER TABLE rental_schema.rentals ALTER COLUMN status DROP NOT NULL;
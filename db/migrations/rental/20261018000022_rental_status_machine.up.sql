@@ -0,0 +1,37 @@
+-- Migration: rental_status_machine
+-- Service: rental
+-- Created at: 2026-10-18 00:00:22 UTC
+
+BEGIN;
+
+-- Map legacy free-form values onto the rental lifecycle
+UPDATE rental_schema.rentals SET status = 'requested' WHERE status = 'pending';
+UPDATE rental_schema.rentals SET status = 'declined' WHERE status = 'rejected';
+UPDATE rental_schema.rentals SET status = 'cancelled' WHERE status = 'canceled';
+UPDATE rental_schema.rentals
+SET status = 'cancelled'
+WHERE status IS NULL
+   OR status NOT IN ('requested', 'confirmed', 'declined', 'active', 'returned', 'completed', 'cancelled', 'disputed');
+
+ALTER TABLE rental_schema.rentals
+    ALTER COLUMN status SET DEFAULT 'requested',
+    ALTER COLUMN status SET NOT NULL,
+    ADD CONSTRAINT rentals_status_check
+        CHECK (status IN ('requested', 'confirmed', 'declined', 'active', 'returned', 'completed', 'cancelled', 'disputed'));
+
+-- Every status change, including the initial request (from_status NULL)
+CREATE TABLE IF NOT EXISTS rental_schema.rental_status_history (
+    history_id BIGSERIAL PRIMARY KEY,
+    rental_id UUID NOT NULL REFERENCES rental_schema.rentals(rental_id) ON DELETE CASCADE,
+    from_status VARCHAR(20),
+    to_status VARCHAR(20) NOT NULL,
+    actor_id UUID REFERENCES user_schema.users(user_id),
+    actor_role VARCHAR(20) CHECK (actor_role IN ('renter', 'owner', 'admin')),
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_rental_status_history_rental
+    ON rental_schema.rental_status_history(rental_id, created_at);
+
+COMMIT;
@@ -0,0 +1,6 @@
+-- Drop rental period exclusion constraint
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_unit_period_excl;
+ALTER TABLE rental_schema.rentals DROP COLUMN IF EXISTS rental_period;
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_period_check;
+CREATE INDEX IF NOT EXISTS idx_rentals_unit_period
+    ON rental_schema.rentals(unit_id, rental_period_start, rental_period_end);
@@ -0,0 +1,26 @@
+-- Migration: rental_period_exclusion
+-- Service: rental
+-- Created at: 2026-10-18 00:00:23 UTC
+
+BEGIN;
+
+CREATE EXTENSION IF NOT EXISTS btree_gist;
+
+ALTER TABLE rental_schema.rentals
+    ADD CONSTRAINT rentals_period_check CHECK (rental_period_end > rental_period_start);
+
+-- Half-open [start, end): a booking ending when the next one starts does not overlap it
+ALTER TABLE rental_schema.rentals
+    ADD COLUMN IF NOT EXISTS rental_period TSTZRANGE
+        GENERATED ALWAYS AS (tstzrange(rental_period_start, rental_period_end, '[)')) STORED;
+
+-- A unit can hold only one open booking for any instant. Fails if existing data already
+-- double-books a unit; resolve those rentals before migrating.
+ALTER TABLE rental_schema.rentals
+    ADD CONSTRAINT rentals_unit_period_excl
+        EXCLUDE USING gist (unit_id WITH =, rental_period WITH &&)
+        WHERE (status IN ('requested', 'confirmed', 'active'));
+
+DROP INDEX IF EXISTS rental_schema.idx_rentals_unit_period;
+
+COMMIT;
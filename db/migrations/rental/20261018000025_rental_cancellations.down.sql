@@ -0,0 +1,3 @@
+-- Drop rental cancellations and policy snapshot
+DROP TABLE IF EXISTS rental_schema.rental_cancellations;
+ALTER TABLE rental_schema.rentals DROP COLUMN IF EXISTS cancellation_policy;
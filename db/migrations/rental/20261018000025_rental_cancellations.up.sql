@@ -0,0 +1,40 @@
+-- Migration: rental_cancellations
+-- Service: rental
+-- Created at: 2026-10-18 00:00:25 UTC
+
+BEGIN;
+
+-- Rentals keep the policy they were booked under, even if the owner changes it later
+ALTER TABLE rental_schema.rentals
+    ADD COLUMN IF NOT EXISTS cancellation_policy VARCHAR(20);
+
+UPDATE rental_schema.rentals r
+SET cancellation_policy = p.cancellation_policy
+FROM product_schema.products p
+WHERE r.product_id = p.product_id AND r.cancellation_policy IS NULL;
+
+UPDATE rental_schema.rentals SET cancellation_policy = 'moderate' WHERE cancellation_policy IS NULL;
+
+ALTER TABLE rental_schema.rentals
+    ALTER COLUMN cancellation_policy SET DEFAULT 'moderate',
+    ALTER COLUMN cancellation_policy SET NOT NULL,
+    ADD CONSTRAINT rentals_cancellation_policy_check
+        CHECK (cancellation_policy IN ('flexible', 'moderate', 'strict'));
+
+-- Terms applied when a rental was cancelled or declined
+CREATE TABLE IF NOT EXISTS rental_schema.rental_cancellations (
+    rental_id UUID PRIMARY KEY REFERENCES rental_schema.rentals(rental_id) ON DELETE CASCADE,
+    cancelled_by VARCHAR(20) NOT NULL CHECK (cancelled_by IN ('renter', 'owner', 'admin')),
+    policy VARCHAR(20) NOT NULL CHECK (policy IN ('flexible', 'moderate', 'strict')),
+    hours_before_start BIGINT NOT NULL,
+    refund_percent BIGINT NOT NULL CHECK (refund_percent BETWEEN 0 AND 100),
+    refund_cents BIGINT NOT NULL CHECK (refund_cents >= 0),
+    owner_penalty_cents BIGINT NOT NULL DEFAULT 0 CHECK (owner_penalty_cents >= 0),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_rental_cancellations_owner_penalty
+    ON rental_schema.rental_cancellations(created_at)
+    WHERE owner_penalty_cents > 0;
+
+COMMIT;
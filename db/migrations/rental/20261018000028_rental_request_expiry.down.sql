@@ -0,0 +1,7 @@
+-- Drop request expiry; expired requests revert to declined
+DROP INDEX IF EXISTS rental_schema.idx_rentals_open_requests;
+UPDATE rental_schema.rentals SET status = 'declined' WHERE status = 'expired';
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_status_check;
+ALTER TABLE rental_schema.rentals
+    ADD CONSTRAINT rentals_status_check
+        CHECK (status IN ('requested', 'confirmed', 'declined', 'active', 'returned', 'completed', 'cancelled', 'disputed'));
@@ -0,0 +1,18 @@
+-- Migration: rental_request_expiry
+-- Service: rental
+-- Created at: 2026-10-18 00:00:28 UTC
+
+BEGIN;
+
+-- Requests the owner never answered end as 'expired'
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_status_check;
+ALTER TABLE rental_schema.rentals
+    ADD CONSTRAINT rentals_status_check
+        CHECK (status IN ('requested', 'confirmed', 'declined', 'active', 'returned', 'completed', 'cancelled', 'disputed', 'expired'));
+
+-- The expiry job scans open requests by age
+CREATE INDEX IF NOT EXISTS idx_rentals_open_requests
+    ON rental_schema.rentals(created_at)
+    WHERE status = 'requested';
+
+COMMIT;
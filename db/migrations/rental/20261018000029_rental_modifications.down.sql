@@ -0,0 +1,2 @@
+-- Drop rental modifications
+DROP TABLE IF EXISTS rental_schema.rental_modifications;
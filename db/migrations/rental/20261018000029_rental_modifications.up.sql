@@ -0,0 +1,36 @@
+-- Migration: rental_modifications
+-- Service: rental
+-- Created at: 2026-10-18 00:00:29 UTC
+
+BEGIN;
+
+-- Date changes proposed by one party and answered by the other
+CREATE TABLE IF NOT EXISTS rental_schema.rental_modifications (
+    modification_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    rental_id UUID NOT NULL REFERENCES rental_schema.rentals(rental_id) ON DELETE CASCADE,
+    proposed_by UUID NOT NULL REFERENCES user_schema.users(user_id),
+    proposer_role VARCHAR(20) NOT NULL CHECK (proposer_role IN ('renter', 'owner')),
+    previous_start TIMESTAMPTZ NOT NULL,
+    previous_end TIMESTAMPTZ NOT NULL,
+    rental_period_start TIMESTAMPTZ NOT NULL,
+    rental_period_end TIMESTAMPTZ NOT NULL,
+    price_quote JSONB NOT NULL,
+    price_difference_cents BIGINT NOT NULL,
+    message TEXT,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'accepted', 'declined', 'withdrawn')),
+    responded_by UUID REFERENCES user_schema.users(user_id),
+    responded_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (rental_period_end > rental_period_start)
+);
+
+-- At most one open proposal per rental
+CREATE UNIQUE INDEX IF NOT EXISTS idx_rental_modifications_one_pending
+    ON rental_schema.rental_modifications(rental_id)
+    WHERE status = 'pending';
+
+CREATE INDEX IF NOT EXISTS idx_rental_modifications_rental
+    ON rental_schema.rental_modifications(rental_id, created_at);
+
+COMMIT;
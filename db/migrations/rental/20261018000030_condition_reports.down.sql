@@ -0,0 +1,3 @@
+-- Drop condition reports
+DROP TABLE IF EXISTS rental_schema.condition_report_photos;
+DROP TABLE IF EXISTS rental_schema.condition_reports;
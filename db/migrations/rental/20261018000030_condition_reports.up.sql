@@ -0,0 +1,39 @@
+-- Migration: condition_reports
+-- Service: rental
+-- Created at: 2026-10-18 00:00:30 UTC
+
+BEGIN;
+
+-- Handover reports at pickup and return, signed off by both parties
+CREATE TABLE IF NOT EXISTS rental_schema.condition_reports (
+    report_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    rental_id UUID NOT NULL REFERENCES rental_schema.rentals(rental_id) ON DELETE CASCADE,
+    stage VARCHAR(20) NOT NULL CHECK (stage IN ('pickup', 'return')),
+    checklist JSONB NOT NULL DEFAULT '[]'::jsonb,
+    notes TEXT,
+    meter_readings JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_by UUID NOT NULL REFERENCES user_schema.users(user_id),
+    updated_by UUID NOT NULL REFERENCES user_schema.users(user_id),
+    renter_signed_at TIMESTAMPTZ,
+    owner_signed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (rental_id, stage)
+);
+
+-- Photo bytes live in the blob store under blob_key
+CREATE TABLE IF NOT EXISTS rental_schema.condition_report_photos (
+    photo_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    report_id UUID NOT NULL REFERENCES rental_schema.condition_reports(report_id) ON DELETE CASCADE,
+    blob_key TEXT NOT NULL UNIQUE,
+    content_type VARCHAR(50) NOT NULL,
+    size_bytes BIGINT NOT NULL CHECK (size_bytes > 0),
+    caption VARCHAR(200),
+    uploaded_by UUID NOT NULL REFERENCES user_schema.users(user_id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_condition_report_photos_report
+    ON rental_schema.condition_report_photos(report_id, created_at);
+
+COMMIT;
@@ -0,0 +1,3 @@
+-- Drop damage claims and deposit settlements
+DROP TABLE IF EXISTS rental_schema.rental_deposit_settlements;
+DROP TABLE IF EXISTS rental_schema.damage_claims;
@@ -0,0 +1,45 @@
+-- Migration: damage_claims
+-- Service: rental
+-- Created at: 2026-10-18 00:00:32 UTC
+
+BEGIN;
+
+-- One claim per rental, opened by the owner after the return and resolved by support
+CREATE TABLE IF NOT EXISTS rental_schema.damage_claims (
+    claim_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    rental_id UUID NOT NULL UNIQUE REFERENCES rental_schema.rentals(rental_id) ON DELETE CASCADE,
+    opened_by UUID NOT NULL REFERENCES user_schema.users(user_id),
+    amount_cents BIGINT NOT NULL CHECK (amount_cents > 0),
+    description TEXT NOT NULL,
+    -- Photos from the rental's condition reports
+    evidence_photo_ids UUID[] NOT NULL DEFAULT '{}',
+    status VARCHAR(20) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'responded', 'resolved')),
+    renter_accepts BOOLEAN,
+    renter_response TEXT,
+    responded_at TIMESTAMPTZ,
+    outcome VARCHAR(20) CHECK (outcome IN ('full', 'partial', 'denied')),
+    captured_cents BIGINT,
+    released_cents BIGINT,
+    resolution_note TEXT,
+    resolved_by UUID REFERENCES user_schema.users(user_id),
+    resolved_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((status = 'resolved') = (outcome IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_damage_claims_unresolved
+    ON rental_schema.damage_claims(created_at)
+    WHERE status <> 'resolved';
+
+-- How each rental's deposit left escrow; a deposit is settled once
+CREATE TABLE IF NOT EXISTS rental_schema.rental_deposit_settlements (
+    rental_id UUID PRIMARY KEY REFERENCES rental_schema.rentals(rental_id) ON DELETE CASCADE,
+    claim_id UUID REFERENCES rental_schema.damage_claims(claim_id),
+    held_cents BIGINT NOT NULL CHECK (held_cents >= 0),
+    captured_cents BIGINT NOT NULL CHECK (captured_cents >= 0),
+    released_cents BIGINT NOT NULL CHECK (released_cents >= 0),
+    settled_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (captured_cents + released_cents = held_cents)
+);
+
+COMMIT;
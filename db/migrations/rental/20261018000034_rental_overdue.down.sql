@@ -0,0 +1,8 @@
+-- Drop late fees; overdue rentals revert to active
+DROP TABLE IF EXISTS rental_schema.rental_late_fees;
+DROP INDEX IF EXISTS rental_schema.idx_rentals_running_end;
+UPDATE rental_schema.rentals SET status = 'active' WHERE status = 'overdue';
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_status_check;
+ALTER TABLE rental_schema.rentals
+    ADD CONSTRAINT rentals_status_check
+        CHECK (status IN ('requested', 'confirmed', 'declined', 'active', 'returned', 'completed', 'cancelled', 'disputed', 'expired'));
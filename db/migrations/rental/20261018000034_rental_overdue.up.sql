@@ -0,0 +1,33 @@
+-- Migration: rental_overdue
+-- Service: rental
+-- Created at: 2026-10-18 00:00:34 UTC
+
+BEGIN;
+
+-- Rentals not returned by their end become 'overdue'
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_status_check;
+ALTER TABLE rental_schema.rentals
+    ADD CONSTRAINT rentals_status_check
+        CHECK (status IN ('requested', 'confirmed', 'declined', 'active', 'returned', 'completed', 'cancelled',
+                          'disputed', 'expired', 'overdue'));
+
+-- The overdue job scans running rentals by end
+CREATE INDEX IF NOT EXISTS idx_rentals_running_end
+    ON rental_schema.rentals(rental_period_end)
+    WHERE status IN ('active', 'overdue');
+
+-- One row per started day late; the key keeps a day from being charged twice
+CREATE TABLE IF NOT EXISTS rental_schema.rental_late_fees (
+    rental_id UUID NOT NULL REFERENCES rental_schema.rentals(rental_id) ON DELETE CASCADE,
+    fee_day INTEGER NOT NULL CHECK (fee_day > 0),
+    amount_cents BIGINT NOT NULL CHECK (amount_cents >= 0),
+    from_deposit_cents BIGINT NOT NULL CHECK (from_deposit_cents >= 0),
+    payment_method_cents BIGINT NOT NULL CHECK (payment_method_cents >= 0),
+    unpaid_cents BIGINT NOT NULL CHECK (unpaid_cents >= 0),
+    payment_intent_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (rental_id, fee_day),
+    CHECK (from_deposit_cents + payment_method_cents + unpaid_cents = amount_cents)
+);
+
+COMMIT;
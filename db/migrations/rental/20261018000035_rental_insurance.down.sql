@@ -0,0 +1,11 @@
+-- Unlink policies from rentals and claims and drop the booking columns
+ALTER TABLE rental_schema.damage_claims DROP COLUMN IF EXISTS insurance_policy_id;
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_insurance_policy_fkey;
+DROP INDEX IF EXISTS rental_schema.idx_insurance_policies_rental;
+ALTER TABLE rental_schema.insurance_policies DROP CONSTRAINT IF EXISTS insurance_policies_status_check;
+ALTER TABLE rental_schema.insurance_policies
+    ALTER COLUMN status DROP NOT NULL,
+    ALTER COLUMN status DROP DEFAULT,
+    DROP COLUMN IF EXISTS deductible,
+    DROP COLUMN IF EXISTS premium,
+    DROP COLUMN IF EXISTS coverage_option;
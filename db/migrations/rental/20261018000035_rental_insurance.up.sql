@@ -0,0 +1,76 @@
+-- Migration: rental_insurance
+-- Service: rental
+-- Created at: 2026-10-18 00:00:35 UTC
+
+BEGIN;
+
+-- Policies bought at booking from the configured insurer, at most one per rental
+ALTER TABLE rental_schema.insurance_policies
+    ADD COLUMN IF NOT EXISTS coverage_option VARCHAR(50),
+    ADD COLUMN IF NOT EXISTS premium NUMERIC(12,2) NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS deductible NUMERIC(12,2) NOT NULL DEFAULT 0;
+
+-- Legacy rows: a missing status means the policy is in force, anything unknown is treated as
+-- no longer covering the rental
+UPDATE rental_schema.insurance_policies
+SET status = CASE
+        WHEN status IS NULL THEN 'active'
+        WHEN lower(status) IN ('active', 'cancelled') THEN lower(status)
+        ELSE 'cancelled'
+    END
+WHERE status IS NULL OR status NOT IN ('active', 'cancelled');
+
+-- Legacy rentals with several policies keep the one they point at, or else the newest; the
+-- others are detached rather than deleted
+UPDATE rental_schema.insurance_policies ip
+SET rental_id = NULL, status = 'cancelled'
+FROM (
+    SELECT p.policy_id,
+           ROW_NUMBER() OVER (
+               PARTITION BY p.rental_id
+               ORDER BY (r.insurance_policy_id = p.policy_id) DESC NULLS LAST, p.created_at DESC NULLS LAST
+           ) AS rank
+    FROM rental_schema.insurance_policies p
+    LEFT JOIN rental_schema.rentals r ON r.rental_id = p.rental_id
+    WHERE p.rental_id IS NOT NULL
+) ranked
+WHERE ip.policy_id = ranked.policy_id AND ranked.rank > 1;
+
+-- Rentals pointing at a policy that does not exist or belongs to another rental
+UPDATE rental_schema.rentals r
+SET insurance_policy_id = NULL
+WHERE r.insurance_policy_id IS NOT NULL
+  AND NOT EXISTS (
+      SELECT 1 FROM rental_schema.insurance_policies p
+      WHERE p.policy_id = r.insurance_policy_id AND p.rental_id = r.rental_id
+  );
+
+ALTER TABLE rental_schema.insurance_policies
+    ALTER COLUMN status SET DEFAULT 'active',
+    ALTER COLUMN status SET NOT NULL;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'insurance_policies_status_check') THEN
+        ALTER TABLE rental_schema.insurance_policies
+            ADD CONSTRAINT insurance_policies_status_check CHECK (status IN ('active', 'cancelled'));
+    END IF;
+END $$;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_insurance_policies_rental
+    ON rental_schema.insurance_policies(rental_id);
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'rentals_insurance_policy_fkey') THEN
+        ALTER TABLE rental_schema.rentals
+            ADD CONSTRAINT rentals_insurance_policy_fkey
+                FOREIGN KEY (insurance_policy_id) REFERENCES rental_schema.insurance_policies(policy_id);
+    END IF;
+END $$;
+
+-- Claims point at the policy the rental was booked with, if any
+ALTER TABLE rental_schema.damage_claims
+    ADD COLUMN IF NOT EXISTS insurance_policy_id UUID REFERENCES rental_schema.insurance_policies(policy_id);
+
+COMMIT;
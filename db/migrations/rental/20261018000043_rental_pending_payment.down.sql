@@ -0,0 +1,13 @@
+-- Cancel bookings still waiting on payment and drop the status
+DROP INDEX IF EXISTS rental_schema.idx_rentals_pending_payment;
+UPDATE rental_schema.rentals SET status = 'cancelled' WHERE status = 'pending_payment';
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_unit_period_excl;
+ALTER TABLE rental_schema.rentals
+    ADD CONSTRAINT rentals_unit_period_excl
+        EXCLUDE USING gist (unit_id WITH =, rental_period WITH &&)
+        WHERE (status IN ('requested', 'confirmed', 'active'));
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_status_check;
+ALTER TABLE rental_schema.rentals
+    ADD CONSTRAINT rentals_status_check
+        CHECK (status IN ('requested', 'confirmed', 'declined', 'active', 'returned', 'completed', 'cancelled',
+                          'disputed', 'expired', 'overdue'));
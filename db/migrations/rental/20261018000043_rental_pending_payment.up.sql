@@ -0,0 +1,26 @@
+-- Migration: rental_pending_payment
+-- Service: rental
+-- Created at: 2026-10-18 00:00:43 UTC
+
+BEGIN;
+
+-- Instant bookings are committed as 'pending_payment' and confirmed once the charge settles
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_status_check;
+ALTER TABLE rental_schema.rentals
+    ADD CONSTRAINT rentals_status_check
+        CHECK (status IN ('requested', 'confirmed', 'declined', 'active', 'returned', 'completed', 'cancelled',
+                          'disputed', 'expired', 'overdue', 'pending_payment'));
+
+-- The unit is held while the charge is in flight
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_unit_period_excl;
+ALTER TABLE rental_schema.rentals
+    ADD CONSTRAINT rentals_unit_period_excl
+        EXCLUDE USING gist (unit_id WITH =, rental_period WITH &&)
+        WHERE (status IN ('pending_payment', 'requested', 'confirmed', 'active'));
+
+-- The booking recovery pass looks for charges left in flight
+CREATE INDEX IF NOT EXISTS idx_rentals_pending_payment
+    ON rental_schema.rentals(updated_at)
+    WHERE status = 'pending_payment';
+
+COMMIT;
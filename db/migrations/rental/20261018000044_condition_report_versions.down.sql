@@ -0,0 +1,2 @@
+-- Remove condition report versions
+ALTER TABLE rental_schema.condition_reports DROP COLUMN IF EXISTS version;
@@ -0,0 +1,10 @@
+-- Migration: condition_report_versions
+-- Service: rental
+-- Created at: 2026-10-18 00:00:44 UTC
+
+BEGIN;
+
+-- Bumped on content and photo changes only, not on signatures; signing sends it as If-Match
+ALTER TABLE rental_schema.condition_reports ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+
+COMMIT;
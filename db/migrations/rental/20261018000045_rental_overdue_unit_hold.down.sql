@@ -0,0 +1,6 @@
+-- Stop holding units for overdue rentals
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_unit_period_excl;
+ALTER TABLE rental_schema.rentals
+    ADD CONSTRAINT rentals_unit_period_excl
+        EXCLUDE USING gist (unit_id WITH =, rental_period WITH &&)
+        WHERE (status IN ('pending_payment', 'requested', 'confirmed', 'active'));
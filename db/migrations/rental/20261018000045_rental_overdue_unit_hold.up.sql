@@ -0,0 +1,14 @@
+-- Migration: rental_overdue_unit_hold
+-- Service: rental
+-- Created at: 2026-10-18 00:00:45 UTC
+
+BEGIN;
+
+-- An overdue rental keeps its unit until the item is back
+ALTER TABLE rental_schema.rentals DROP CONSTRAINT IF EXISTS rentals_unit_period_excl;
+ALTER TABLE rental_schema.rentals
+    ADD CONSTRAINT rentals_unit_period_excl
+        EXCLUDE USING gist (unit_id WITH =, rental_period WITH &&)
+        WHERE (status IN ('pending_payment', 'requested', 'confirmed', 'active', 'overdue'));
+
+COMMIT;
@@ -0,0 +1,3 @@
+-- Forget owed owner penalties
+DROP INDEX IF EXISTS rental_schema.idx_rental_cancellations_owner_penalty_owed;
+ALTER TABLE rental_schema.rental_cancellations DROP COLUMN IF EXISTS owner_penalty_owed_cents;
@@ -0,0 +1,25 @@
+-- Migration: owner_penalty_owed
+-- Service: rental
+-- Created at: 2026-10-18 00:00:47 UTC
+
+BEGIN;
+
+-- Part of an owner's cancellation penalty that could not be charged because the owner had no
+-- saved payment method, still owed by the owner
+ALTER TABLE rental_schema.rental_cancellations
+    ADD COLUMN IF NOT EXISTS owner_penalty_owed_cents BIGINT NOT NULL DEFAULT 0;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'rental_cancellations_owner_penalty_owed_check') THEN
+        ALTER TABLE rental_schema.rental_cancellations
+            ADD CONSTRAINT rental_cancellations_owner_penalty_owed_check
+                CHECK (owner_penalty_owed_cents BETWEEN 0 AND owner_penalty_cents);
+    END IF;
+END $$;
+
+CREATE INDEX IF NOT EXISTS idx_rental_cancellations_owner_penalty_owed
+    ON rental_schema.rental_cancellations(created_at)
+    WHERE owner_penalty_owed_cents > 0;
+
+COMMIT;
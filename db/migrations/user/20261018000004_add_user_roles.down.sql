@@ -0,0 +1,6 @@
+-- Drop indexes
+DROP INDEX IF EXISTS user_schema.idx_users_role;
+
+-- Drop columns
+ALTER TABLE user_schema.users DROP CONSTRAINT IF EXISTS users_role_check;
+ALTER TABLE user_schema.users DROP COLUMN IF EXISTS role;
@@ -0,0 +1,15 @@
+-- Migration: add_user_roles
+-- Service: user
+-- Created at: 2026-10-18 00:00:04 UTC
+
+BEGIN;
+
+ALTER TABLE user_schema.users
+    ADD COLUMN IF NOT EXISTS role VARCHAR(20) NOT NULL DEFAULT 'user';
+
+ALTER TABLE user_schema.users
+    ADD CONSTRAINT users_role_check CHECK (role IN ('user', 'admin'));
+
+CREATE INDEX IF NOT EXISTS idx_users_role ON user_schema.users(role) WHERE role <> 'user';
+
+COMMIT;
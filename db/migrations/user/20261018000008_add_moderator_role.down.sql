@@ -0,0 +1,4 @@
+-- Demote moderators and restore the previous role set
+UPDATE user_schema.users SET role = 'user' WHERE role = 'moderator';
+ALTER TABLE user_schema.users DROP CONSTRAINT IF EXISTS users_role_check;
+ALTER TABLE user_schema.users ADD CONSTRAINT users_role_check CHECK (role IN ('user', 'admin'));
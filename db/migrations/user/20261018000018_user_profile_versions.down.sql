@@ -0,0 +1,4 @@
+-- Remove user_profiles row versions
+DROP TRIGGER IF EXISTS user_profiles_bump_version ON user_schema.user_profiles;
+DROP FUNCTION IF EXISTS user_schema.bump_row_version();
+ALTER TABLE user_schema.user_profiles DROP COLUMN IF EXISTS version;
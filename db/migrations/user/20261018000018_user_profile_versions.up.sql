@@ -0,0 +1,23 @@
+-- Migration: user_profile_versions
+-- Service: user
+-- Created at: 2026-10-18 00:00:18 UTC
+
+BEGIN;
+
+-- Row version for optimistic concurrency (ETag / If-Match); every update bumps it
+ALTER TABLE user_schema.user_profiles ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+
+CREATE OR REPLACE FUNCTION user_schema.bump_row_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS user_profiles_bump_version ON user_schema.user_profiles;
+CREATE TRIGGER user_profiles_bump_version
+    BEFORE UPDATE ON user_schema.user_profiles
+    FOR EACH ROW EXECUTE FUNCTION user_schema.bump_row_version();
+
+COMMIT;
@@ -0,0 +1,2 @@
+-- Drop notification inbox
+DROP TABLE IF EXISTS user_schema.notifications;
@@ -0,0 +1,22 @@
+-- Migration: notifications
+-- Service: user
+-- Created at: 2026-10-18 00:00:20 UTC
+
+BEGIN;
+
+-- In-app notification inbox; other delivery channels read from here
+CREATE TABLE IF NOT EXISTS user_schema.notifications (
+    notification_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES user_schema.users(user_id) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    data JSONB NOT NULL DEFAULT '{}',
+    read_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user_created
+    ON user_schema.notifications(user_id, created_at DESC, notification_id DESC);
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- Migration: add_support_role
+-- Service: user
+-- Created at: 2026-10-18 00:00:31 UTC
+
+BEGIN;
+
+ALTER TABLE user_schema.users DROP CONSTRAINT IF EXISTS users_role_check;
+ALTER TABLE user_schema.users
+    ADD CONSTRAINT users_role_check CHECK (role IN ('user', 'admin', 'moderator', 'support'));
+
+COMMIT;
@@ -3,12 +3,12 @@
 BEGIN;
 
 -- Insert some test users
-INSERT INTO user_schema.users (user_id, email, phone_number, password_hash, status)
+INSERT INTO user_schema.users (user_id, email, phone_number, password_hash, status, role)
 VALUES
     ('11111111-1111-1111-1111-111111111111', 'admin@example.com', '+1234567890', 
-     crypt('admin123', gen_salt('bf')), 'active'),
+     crypt('admin123', gen_salt('bf')), 'active', 'admin'),
     ('22222222-2222-2222-2222-222222222222', 'user@example.com', '+1234567891',
-     crypt('user123', gen_salt('bf')), 'active');
+     crypt('user123', gen_salt('bf')), 'active', 'user');
 
 -- Insert user profiles
 INSERT INTO user_schema.user_profiles (profile_id, user_id, first_name, last_name, verification_status)
@@ -19,11 +19,11 @@ VALUES
      'Test', 'User', 'verified');
 
 -- Insert product categories
-INSERT INTO product_schema.categories (category_id, name, description)
+INSERT INTO product_schema.categories (category_id, name, slug, description)
 VALUES
-    ('cccccccc-cccc-cccc-cccc-cccccccccccc', 'Electronics', 'Electronic devices and gadgets'),
-    ('dddddddd-dddd-dddd-dddd-dddddddddddd', 'Tools', 'Hand and power tools'),
-    ('eeeeeeee-eeee-eeee-eeee-eeeeeeeeeeee', 'Sports', 'Sports and outdoor equipment');
+    ('cccccccc-cccc-cccc-cccc-cccccccccccc', 'Electronics', 'electronics', 'Electronic devices and gadgets'),
+    ('dddddddd-dddd-dddd-dddd-dddddddddddd', 'Tools', 'tools', 'Hand and power tools'),
+    ('eeeeeeee-eeee-eeee-eeee-eeeeeeeeeeee', 'Sports', 'sports', 'Sports and outdoor equipment');
 
 -- Insert some tags
 INSERT INTO product_schema.tags (tag_id, name)